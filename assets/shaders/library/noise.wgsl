@@ -0,0 +1,65 @@
+// Noise functions, imported as nodes by the WGSL Library panel.
+// The first `///` line is the node title, `Category:` picks the panel section.
+
+const HASH_SCALE: vec2<f32> = vec2<f32>(127.1, 311.7);
+
+/// Hash
+/// Category: Noise
+/// Pseudo-random value in [0, 1) for a 2D point.
+fn hash21(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, HASH_SCALE)) * 43758.5453);
+}
+
+/// Value Noise
+/// Category: Noise
+/// Smooth noise in [0, 1], one lattice cell per unit of `p`.
+fn value_noise(p: vec2<f32>) -> f32 {
+    let cell = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let a = hash21(cell);
+    let b = hash21(cell + vec2<f32>(1.0, 0.0));
+    let c = hash21(cell + vec2<f32>(0.0, 1.0));
+    let d = hash21(cell + vec2<f32>(1.0, 1.0));
+    return mix(mix(a, b, u.x), mix(c, d, u.x), u.y);
+}
+
+/// Fractal Noise
+/// Category: Noise
+/// Value noise summed over `octaves` octaves, each at twice the frequency and half the
+/// amplitude of the last.
+fn fbm(p: vec2<f32>, octaves: f32) -> f32 {
+    var sum = 0.0;
+    var amplitude = 0.5;
+    var q = p;
+    for (var i = 0; i < i32(octaves); i++) {
+        sum += amplitude * value_noise(q);
+        q *= 2.0;
+        amplitude *= 0.5;
+    }
+    return sum;
+}
+
+struct Cells {
+    distance: f32,
+    id: vec2<f32>,
+}
+
+/// Voronoi
+/// Category: Noise
+/// Distance to the nearest of one jittered point per cell, and that cell's id.
+fn voronoi(p: vec2<f32>) -> Cells {
+    let cell = floor(p);
+    var result = Cells(8.0, cell);
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let neighbour = cell + vec2<f32>(f32(x), f32(y));
+            let point = neighbour + vec2<f32>(hash21(neighbour), hash21(neighbour + 17.0));
+            let d = distance(p, point);
+            if d < result.distance {
+                result = Cells(d, neighbour);
+            }
+        }
+    }
+    return result;
+}
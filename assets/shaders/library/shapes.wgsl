@@ -0,0 +1,20 @@
+// Signed distance shapes. Without a `Category:` line they land under "shapes".
+
+/// Circle
+/// Signed distance from `p` to a circle of `radius` around the origin.
+fn sd_circle(p: vec2<f32>, radius: f32) -> f32 {
+    return length(p) - radius;
+}
+
+/// Box
+/// Signed distance from `p` to a box with half extents `size` around the origin.
+fn sd_box(p: vec2<f32>, size: vec2<f32>) -> f32 {
+    let d = abs(p) - size;
+    return length(max(d, vec2<f32>(0.0))) + min(max(d.x, d.y), 0.0);
+}
+
+/// Fill
+/// 1 inside a distance field, 0 outside, with a soft edge `softness` wide.
+fn sdf_fill(distance: f32, softness: f32) -> f32 {
+    return 1.0 - smoothstep(-softness, softness, distance);
+}
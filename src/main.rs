@@ -1,14 +1,26 @@
+use crate::node_graph::canvas::{CanvasAnimation, CanvasSettings};
+use crate::node_graph::history::UndoHistory;
+use crate::node_graph::layout::{LayoutSettings, NodeAnimation};
+use crate::node_graph::minimap::Minimap;
 use crate::node_graph::model::NodeGraph;
 use crate::node_graph::pin_manager::PinPositionManager;
 use crate::node_graph::ui_state::GraphUiState;
+use crate::shader_graph::nodes::library::WgslLibrary;
+use crate::shader_graph::nodes::subgraph::SubgraphStack;
+use crate::shader_graph::nodes::texture::ImageAssets;
 use crate::shader_view::{
-    ShaderView, apply_shader, 
-    hot_reload_shaders, setup_shader_view,
+    GraphFullscreenPlugin, GraphPbrMaterial, GraphPreviewMaterial, GraphSpriteMaterial,
+    GraphUiMaterial, PreviewTime, ShaderView, advance_preview_time_system, apply_shader,
+    hot_reload_shaders, preview_error_ui_system, setup_shader_view, sync_preview_globals_system,
+    sync_preview_lights_system, transport_bar_ui_system, update_graph_preview_system,
 };
+use bevy::pbr::MaterialPlugin;
 use bevy::prelude::*;
+use bevy::sprite_render::Material2dPlugin;
 use bevy_egui::EguiPlugin;
 
 mod node_graph;
+mod shader_graph;
 mod shader_view;
 mod systems;
 
@@ -31,35 +43,88 @@ fn main() {
     app.add_plugins(bevy_brp_extras::BrpExtrasPlugin::default());
 
     app.add_plugins(EguiPlugin::default())
+        .add_plugins(MaterialPlugin::<GraphPreviewMaterial>::default())
+        .add_plugins(MaterialPlugin::<GraphPbrMaterial>::default())
+        .add_plugins(Material2dPlugin::<GraphSpriteMaterial>::default())
+        .add_plugins(UiMaterialPlugin::<GraphUiMaterial>::default())
+        .add_plugins(GraphFullscreenPlugin)
         // .add_plugins(ShadPlayPlugin)
         // Node graph resources
         .init_resource::<NodeGraph>()
         .init_resource::<GraphUiState>()
+        .init_resource::<CanvasSettings>()
+        .init_resource::<CanvasAnimation>()
+        .init_resource::<Minimap>()
+        .init_resource::<LayoutSettings>()
+        .init_resource::<NodeAnimation>()
+        .init_resource::<UndoHistory>()
         .init_resource::<PinPositionManager>()
+        .init_resource::<WgslLibrary>()
+        .init_resource::<SubgraphStack>()
+        .init_resource::<ImageAssets>()
         // Shader view resources
         .init_resource::<ShaderView>()
+        .init_resource::<PreviewTime>()
         // Systems
         .add_systems(Startup, setup_shader_view)
         // Canvas systems
         .add_systems(Update, node_graph::canvas::update_canvas_system)
+        // Frame All / Frame Selection and the animation towards the framed view
+        .add_systems(Update, systems::canvas_view::canvas_view_system)
+        .add_systems(Update, node_graph::canvas::animate_canvas_system)
+        // Corner minimap (B), click or drag in it to move the view
+        .add_systems(Update, node_graph::minimap::minimap_system)
         // Cache invalidation (run first)
         .add_systems(Update, invalidate_pin_cache_system)
-        .add_systems(Update, node_graph::render::render_canvas_background_system)
+        // Grid, comment frames and sticky notes go down before the wires drawn over them
+        .add_systems(
+            Update,
+            node_graph::render::render_canvas_background_system
+                .before(node_graph::render::render_connections_system),
+        )
         // Node rendering systems
         .add_systems(Update, node_graph::render::render_nodes_system)
         .add_systems(Update, node_graph::render::render_connections_system)
         .add_systems(Update, node_graph::render::render_pending_connection_system)
+        .add_systems(Update, node_graph::render::render_box_select_system)
         // Node interaction systems
         .add_systems(Update, node_graph::interactions::handle_node_drag_system)
         .add_systems(
             Update,
             node_graph::interactions::handle_pin_interactions_system,
         )
+        // Selection shortcuts, duplicate/delete/align
+        .add_systems(Update, systems::selection::selection_ui_system)
+        // Comment frames and sticky notes: add them, edit the one last clicked
+        .add_systems(Update, systems::annotations::annotations_ui_system)
+        // Auto layout with animated moves, and undo/redo of layouts
+        .add_systems(Update, systems::layout::auto_layout_system)
+        .add_systems(Update, node_graph::layout::animate_nodes_system)
         // Node creation system
         .add_systems(Update, systems::spawn_node::spawn_test_node_system)
+        // Node search palette: Space/Tab, right-click, or a wire dropped on empty canvas
+        .add_systems(Update, systems::node_palette::node_palette_system)
+        // WGSL library: watch the directory, list its functions
+        .add_systems(Update, systems::wgsl_library::sync_wgsl_library_system)
+        .add_systems(Update, systems::wgsl_library::wgsl_library_ui_system)
+        // Subgraphs: collapse the selection, open and leave subgraph nodes, saved assets
+        .add_systems(Update, systems::subgraph::subgraph_ui_system)
         // Shader view systems
         .add_systems(Update, apply_shader)
-        .add_systems(Update, hot_reload_shaders);
+        .add_systems(Update, hot_reload_shaders)
+        // Graph preview material and its clock
+        .add_systems(
+            Update,
+            (
+                advance_preview_time_system,
+                transport_bar_ui_system,
+                sync_preview_globals_system,
+                sync_preview_lights_system,
+                update_graph_preview_system,
+                preview_error_ui_system,
+            )
+                .chain(),
+        );
 
     app.run();
 }
@@ -0,0 +1,420 @@
+use crate::node_graph::canvas::{canvas_to_screen, nodes_bounds};
+use crate::node_graph::model::{AnnotationId, CommentFrame, NodeGraph, NodeId, StickyNote};
+use crate::node_graph::render::SELECTION_COLOR;
+use crate::node_graph::selection::node_rect;
+use crate::node_graph::ui_state::{AnnotationDrag, AnnotationPart};
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// Height of a comment frame's title bar, in canvas units
+pub const FRAME_HEADER_HEIGHT: f32 = 32.0;
+/// Room left around the nodes a new frame is put around
+const FRAME_PADDING: f32 = 32.0;
+/// Screen pixels of the corner that resizes a frame or note
+const RESIZE_HANDLE: f32 = 16.0;
+/// Nothing can be resized smaller than this
+const MIN_ANNOTATION_SIZE: Vec2 = Vec2::new(96.0, 64.0);
+pub const DEFAULT_FRAME_SIZE: Vec2 = Vec2::new(480.0, 320.0);
+pub const DEFAULT_NOTE_SIZE: Vec2 = Vec2::new(240.0, 160.0);
+
+/// Colours offered for frames and notes; frames start with the first, notes with the last
+pub const ANNOTATION_COLORS: [[u8; 3]; 6] = [
+    [70, 110, 170],
+    [70, 140, 90],
+    [120, 90, 160],
+    [170, 70, 70],
+    [110, 110, 110],
+    [245, 220, 120],
+];
+
+/// One past the highest frame or note id
+pub fn next_annotation_id(graph: &NodeGraph) -> AnnotationId {
+    let frames = graph.frames.iter().map(|frame| frame.id.0);
+    let notes = graph.notes.iter().map(|note| note.id.0);
+    AnnotationId(frames.chain(notes).max().map_or(0, |id| id + 1))
+}
+
+pub fn add_frame(graph: &mut NodeGraph, title: &str, rect: Rect) -> AnnotationId {
+    let id = next_annotation_id(graph);
+    graph.frames.push(CommentFrame {
+        id,
+        title: title.to_string(),
+        position: rect.min,
+        size: rect.size().max(MIN_ANNOTATION_SIZE),
+        color: ANNOTATION_COLORS[0],
+    });
+    id
+}
+
+pub fn add_note(graph: &mut NodeGraph, position: Vec2, text: &str) -> AnnotationId {
+    let id = next_annotation_id(graph);
+    graph.notes.push(StickyNote {
+        id,
+        text: text.to_string(),
+        position,
+        size: DEFAULT_NOTE_SIZE,
+        color: ANNOTATION_COLORS[ANNOTATION_COLORS.len() - 1],
+    });
+    id
+}
+
+/// A frame's rectangle around the nodes, with padding and room for the title bar, or
+/// `None` when there are no nodes
+pub fn frame_around(graph: &NodeGraph, node_ids: &[NodeId]) -> Option<Rect> {
+    let bounds = nodes_bounds(node_ids.iter().filter_map(|id| graph.nodes.get(id)))?;
+    let mut rect = bounds.inflate(FRAME_PADDING);
+    rect.min.y -= FRAME_HEADER_HEIGHT;
+    Some(rect)
+}
+
+pub fn remove_annotation(graph: &mut NodeGraph, id: AnnotationId) {
+    graph.frames.retain(|frame| frame.id != id);
+    graph.notes.retain(|note| note.id != id);
+}
+
+/// Canvas-space rectangle of a frame or note
+pub fn annotation_rect(graph: &NodeGraph, id: AnnotationId) -> Option<Rect> {
+    let frame = graph.frames.iter().find(|frame| frame.id == id);
+    let note = graph.notes.iter().find(|note| note.id == id);
+    frame
+        .map(|frame| (frame.position, frame.size))
+        .or(note.map(|note| (note.position, note.size)))
+        .map(|(position, size)| Rect::from_corners(position, position + size))
+}
+
+/// The frame or note the pointer would grab at `canvas_pos`. Notes are drawn over frames and
+/// later ones over earlier ones, so those win. A frame is only grabbed by its title bar,
+/// leaving the inside for box selection
+pub fn annotation_at(
+    graph: &NodeGraph,
+    canvas_pos: Vec2,
+    zoom: f32,
+) -> Option<(AnnotationId, AnnotationPart)> {
+    let handle = RESIZE_HANDLE / zoom;
+    let part = |position: Vec2, size: Vec2| {
+        let rect = Rect::from_corners(position, position + size);
+        let corner = Rect::from_corners(rect.max - Vec2::splat(handle), rect.max);
+        corner
+            .contains(canvas_pos)
+            .then_some(AnnotationPart::Resize)
+            .or(rect.contains(canvas_pos).then_some(AnnotationPart::Move))
+    };
+    let note = graph
+        .notes
+        .iter()
+        .rev()
+        .find_map(|note| part(note.position, note.size).map(|part| (note.id, part)));
+    note.or_else(|| {
+        graph.frames.iter().rev().find_map(|frame| {
+            let header = Rect::from_corners(
+                frame.position,
+                frame.position + Vec2::new(frame.size.x, FRAME_HEADER_HEIGHT),
+            );
+            match part(frame.position, frame.size)? {
+                AnnotationPart::Move if !header.contains(canvas_pos) => None,
+                part => Some((frame.id, part)),
+            }
+        })
+    })
+}
+
+/// Start dragging a frame or note; moving a frame picks up the nodes and notes lying
+/// entirely inside it
+pub fn start_annotation_drag(
+    graph: &NodeGraph,
+    id: AnnotationId,
+    part: AnnotationPart,
+) -> AnnotationDrag {
+    let mut drag = AnnotationDrag {
+        id,
+        part,
+        carried_nodes: Vec::new(),
+        carried_notes: Vec::new(),
+    };
+    let is_frame = graph.frames.iter().any(|frame| frame.id == id);
+    if part == AnnotationPart::Resize || !is_frame {
+        return drag;
+    }
+    let Some(rect) = annotation_rect(graph, id) else {
+        return drag;
+    };
+    let inside = |other: Rect| rect.contains(other.min) && rect.contains(other.max);
+    drag.carried_nodes = graph
+        .nodes
+        .values()
+        .filter(|node| inside(node_rect(node)))
+        .map(|node| node.node_id)
+        .collect();
+    drag.carried_nodes.sort_by_key(|id| id.0);
+    drag.carried_notes = graph
+        .notes
+        .iter()
+        .filter(|note| inside(Rect::from_corners(note.position, note.position + note.size)))
+        .map(|note| note.id)
+        .collect();
+    drag
+}
+
+/// Move or resize the dragged frame or note by `delta` canvas units
+pub fn drag_annotation(graph: &mut NodeGraph, drag: &AnnotationDrag, delta: Vec2) {
+    let frame = graph.frames.iter_mut().find(|frame| frame.id == drag.id);
+    let (position, size) = match frame {
+        Some(frame) => (&mut frame.position, &mut frame.size),
+        None => match graph.notes.iter_mut().find(|note| note.id == drag.id) {
+            Some(note) => (&mut note.position, &mut note.size),
+            None => return,
+        },
+    };
+    match drag.part {
+        AnnotationPart::Resize => *size = (*size + delta).max(MIN_ANNOTATION_SIZE),
+        AnnotationPart::Move => *position += delta,
+    }
+    if drag.part == AnnotationPart::Move {
+        for node_id in &drag.carried_nodes {
+            if let Some(node) = graph.nodes.get_mut(node_id) {
+                node.position += delta;
+            }
+        }
+        for note in &mut graph.notes {
+            if drag.carried_notes.contains(&note.id) {
+                note.position += delta;
+            }
+        }
+    }
+}
+
+/// How a line of a sticky note is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteLineKind {
+    /// Starts with `#`
+    Heading,
+    /// Starts with `- ` or `* `
+    Bullet,
+    Text,
+}
+
+/// A run of note text in one style
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NoteSpan {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteLine {
+    pub kind: NoteLineKind,
+    pub spans: Vec<NoteSpan>,
+}
+
+/// Split sticky note text into styled lines. Markers without a closing partner are kept as
+/// typed
+pub fn parse_note(text: &str) -> Vec<NoteLine> {
+    text.lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            let heading = trimmed.trim_start_matches('#');
+            if heading.len() < trimmed.len() && heading.starts_with(' ') {
+                return NoteLine {
+                    kind: NoteLineKind::Heading,
+                    spans: parse_spans(heading.trim_start()),
+                };
+            }
+            if let Some(item) = trimmed
+                .strip_prefix("- ")
+                .or_else(|| trimmed.strip_prefix("* "))
+            {
+                return NoteLine {
+                    kind: NoteLineKind::Bullet,
+                    spans: parse_spans(item),
+                };
+            }
+            NoteLine {
+                kind: NoteLineKind::Text,
+                spans: parse_spans(line),
+            }
+        })
+        .collect()
+}
+
+fn parse_spans(line: &str) -> Vec<NoteSpan> {
+    let mut spans = Vec::new();
+    let mut current = NoteSpan::default();
+    let flush = |spans: &mut Vec<NoteSpan>, current: &mut NoteSpan| {
+        if !current.text.is_empty() {
+            spans.push(current.clone());
+            current.text.clear();
+        }
+    };
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if c == '`'
+            && let Some(end) = rest[1..].find('`')
+        {
+            flush(&mut spans, &mut current);
+            spans.push(NoteSpan {
+                text: rest[1..1 + end].to_string(),
+                code: true,
+                ..current.clone()
+            });
+            rest = &rest[end + 2..];
+        } else if rest.starts_with("**") && (current.bold || rest[2..].contains("**")) {
+            flush(&mut spans, &mut current);
+            current.bold = !current.bold;
+            rest = &rest[2..];
+        } else if c == '*' && (current.italic || rest[1..].contains('*')) {
+            flush(&mut spans, &mut current);
+            current.italic = !current.italic;
+            rest = &rest[1..];
+        } else {
+            current.text.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    flush(&mut spans, &mut current);
+    spans
+}
+
+fn note_layout_job(text: &str, zoom: f32, wrap_width: f32) -> egui::text::LayoutJob {
+    let mut job = egui::text::LayoutJob::default();
+    job.wrap.max_width = wrap_width;
+    for (i, line) in parse_note(text).iter().enumerate() {
+        if i > 0 {
+            job.append("\n", 0.0, egui::TextFormat::default());
+        }
+        let size = match line.kind {
+            NoteLineKind::Heading => 17.0,
+            _ => 13.0,
+        } * zoom;
+        let plain = egui::TextFormat {
+            font_id: egui::FontId::proportional(size),
+            color: egui::Color32::from_gray(40),
+            ..Default::default()
+        };
+        if line.kind == NoteLineKind::Bullet {
+            job.append("•  ", 4.0 * zoom, plain.clone());
+        }
+        for span in &line.spans {
+            let mut format = plain.clone();
+            format.italics = span.italic;
+            if span.bold || line.kind == NoteLineKind::Heading {
+                format.color = egui::Color32::BLACK;
+            }
+            if span.code {
+                format.font_id = egui::FontId::monospace(size * 0.9);
+                format.background = egui::Color32::from_black_alpha(30);
+            }
+            job.append(&span.text, 0.0, format);
+        }
+    }
+    job
+}
+
+fn to_pos2(v: Vec2) -> egui::Pos2 {
+    egui::pos2(v.x, v.y)
+}
+
+fn screen_rect(graph: &NodeGraph, position: Vec2, size: Vec2) -> egui::Rect {
+    let canvas_state = &graph.canvas_state;
+    egui::Rect::from_min_max(
+        to_pos2(canvas_to_screen(position, canvas_state)),
+        to_pos2(canvas_to_screen(position + size, canvas_state)),
+    )
+}
+
+fn paint_resize_grip(painter: &egui::Painter, rect: egui::Rect, color: egui::Color32) {
+    let corner = rect.max - egui::vec2(3.0, 3.0);
+    for offset in [6.0, 11.0] {
+        painter.line_segment(
+            [
+                corner - egui::vec2(offset, 0.0),
+                corner - egui::vec2(0.0, offset),
+            ],
+            egui::Stroke::new(1.0, color),
+        );
+    }
+}
+
+/// Draw the comment frames, then the sticky notes, on the canvas background below the nodes.
+/// The active one is outlined
+pub fn paint_annotations(painter: &egui::Painter, graph: &NodeGraph, active: Option<AnnotationId>) {
+    let zoom = graph.canvas_state.zoom;
+    let visible = painter.clip_rect();
+    let outline = |id: AnnotationId, color: egui::Color32| {
+        if active == Some(id) {
+            egui::Stroke::new(2.0, SELECTION_COLOR)
+        } else {
+            egui::Stroke::new(1.0, color)
+        }
+    };
+
+    for frame in &graph.frames {
+        let rect = screen_rect(graph, frame.position, frame.size);
+        if !visible.intersects(rect) {
+            continue;
+        }
+        let [r, g, b] = frame.color;
+        let radius = 4.0 * zoom;
+        painter.rect(
+            rect,
+            radius,
+            egui::Color32::from_rgba_unmultiplied(r, g, b, 40),
+            outline(
+                frame.id,
+                egui::Color32::from_rgba_unmultiplied(r, g, b, 160),
+            ),
+            egui::StrokeKind::Inside,
+        );
+        let header = egui::Rect::from_min_size(
+            rect.min,
+            egui::vec2(rect.width(), FRAME_HEADER_HEIGHT * zoom),
+        );
+        painter.rect_filled(
+            header,
+            egui::CornerRadius {
+                nw: radius as u8,
+                ne: radius as u8,
+                sw: 0,
+                se: 0,
+            },
+            egui::Color32::from_rgba_unmultiplied(r, g, b, 200),
+        );
+        painter.with_clip_rect(header.intersect(visible)).text(
+            header.left_center() + egui::vec2(8.0 * zoom, 0.0),
+            egui::Align2::LEFT_CENTER,
+            &frame.title,
+            egui::FontId::proportional(15.0 * zoom),
+            egui::Color32::WHITE,
+        );
+        paint_resize_grip(
+            painter,
+            rect,
+            egui::Color32::from_rgba_unmultiplied(r, g, b, 220),
+        );
+    }
+
+    for note in &graph.notes {
+        let rect = screen_rect(graph, note.position, note.size);
+        if !visible.intersects(rect) {
+            continue;
+        }
+        let [r, g, b] = note.color;
+        painter.rect(
+            rect,
+            3.0 * zoom,
+            egui::Color32::from_rgb(r, g, b),
+            outline(note.id, egui::Color32::from_black_alpha(60)),
+            egui::StrokeKind::Inside,
+        );
+        let padding = 8.0 * zoom;
+        let inner = rect.shrink(padding);
+        let galley = painter.layout_job(note_layout_job(&note.text, zoom, inner.width()));
+        painter.with_clip_rect(inner.intersect(visible)).galley(
+            inner.min,
+            galley,
+            egui::Color32::from_gray(40),
+        );
+        paint_resize_grip(painter, rect, egui::Color32::from_black_alpha(90));
+    }
+}
@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::annotations::{
+        FRAME_HEADER_HEIGHT, NoteLineKind, NoteSpan, add_frame, add_note, annotation_at,
+        drag_annotation, frame_around, parse_note, remove_annotation, start_annotation_drag,
+    };
+    use crate::node_graph::model::{AnnotationId, NodeGraph};
+    use crate::node_graph::test_utils::add_node_at;
+    use crate::node_graph::ui_state::AnnotationPart;
+    use crate::shader_graph::node_kind::NodeKind;
+    use bevy::prelude::*;
+
+    fn span(text: &str) -> NoteSpan {
+        NoteSpan {
+            text: text.to_string(),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn test_frame_around_nodes() {
+        let mut graph = NodeGraph::new();
+        let a = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::new(100.0, 100.0));
+        let b = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::new(400.0, 300.0));
+        assert!(frame_around(&graph, &[]).is_none());
+
+        let rect = frame_around(&graph, &[a, b]).unwrap();
+        let size = graph.nodes[&b].size;
+        assert_eq!(rect.min, Vec2::new(68.0, 68.0 - FRAME_HEADER_HEIGHT));
+        assert_eq!(rect.max, Vec2::new(432.0, 332.0) + size);
+
+        // Frames and notes share one run of ids
+        let frame = add_frame(&mut graph, "Inputs", rect);
+        let note = add_note(&mut graph, Vec2::ZERO, "todo");
+        assert_eq!((frame, note), (AnnotationId(0), AnnotationId(1)));
+        remove_annotation(&mut graph, frame);
+        assert!(graph.frames.is_empty());
+        assert_eq!(add_frame(&mut graph, "Again", rect), AnnotationId(2));
+    }
+
+    #[test]
+    fn test_grabbing_title_bars_notes_and_corners() {
+        let mut graph = NodeGraph::new();
+        let frame = add_frame(&mut graph, "Frame", Rect::new(0.0, 0.0, 400.0, 300.0));
+        let note = add_note(&mut graph, Vec2::new(200.0, 100.0), "");
+
+        let at = |x: f32, y: f32, zoom: f32| annotation_at(&graph, Vec2::new(x, y), zoom);
+        assert_eq!(at(10.0, 10.0, 1.0), Some((frame, AnnotationPart::Move)));
+        assert_eq!(at(395.0, 295.0, 1.0), Some((frame, AnnotationPart::Resize)));
+        // The inside of a frame is left for box selection
+        assert_eq!(at(50.0, 200.0, 1.0), None);
+        // The note lies over the frame
+        assert_eq!(at(250.0, 150.0, 1.0), Some((note, AnnotationPart::Move)));
+        assert_eq!(at(435.0, 255.0, 1.0), Some((note, AnnotationPart::Resize)));
+        // The corner is a fixed size on screen, so it covers more canvas zoomed out
+        assert_eq!(at(370.0, 270.0, 1.0), None);
+        assert_eq!(at(370.0, 270.0, 0.5), Some((frame, AnnotationPart::Resize)));
+        assert_eq!(at(500.0, 500.0, 1.0), None);
+    }
+
+    #[test]
+    fn test_moving_a_frame_carries_what_is_inside() {
+        let mut graph = NodeGraph::new();
+        let inside = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::new(50.0, 50.0));
+        let across = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::new(350.0, 50.0));
+        let frame = add_frame(&mut graph, "Frame", Rect::new(0.0, 0.0, 400.0, 300.0));
+        let note = add_note(&mut graph, Vec2::new(20.0, 120.0), "");
+
+        let drag = start_annotation_drag(&graph, frame, AnnotationPart::Move);
+        assert_eq!(drag.carried_nodes, vec![inside]);
+        assert_eq!(drag.carried_notes, vec![note]);
+        drag_annotation(&mut graph, &drag, Vec2::new(10.0, 20.0));
+        assert_eq!(graph.frames[0].position, Vec2::new(10.0, 20.0));
+        assert_eq!(graph.nodes[&inside].position, Vec2::new(60.0, 70.0));
+        assert_eq!(graph.notes[0].position, Vec2::new(30.0, 140.0));
+        assert_eq!(graph.nodes[&across].position, Vec2::new(350.0, 50.0));
+
+        // Resizing moves nothing and stops at the smallest size
+        let drag = start_annotation_drag(&graph, frame, AnnotationPart::Resize);
+        assert!(drag.carried_nodes.is_empty());
+        drag_annotation(&mut graph, &drag, Vec2::new(-1000.0, 50.0));
+        assert_eq!(graph.frames[0].size, Vec2::new(96.0, 350.0));
+        assert_eq!(graph.nodes[&inside].position, Vec2::new(60.0, 70.0));
+    }
+
+    #[test]
+    fn test_parse_note() {
+        let lines = parse_note("# Blend *notes*\n- uses **two** inputs\nsee `mix()` or 2 * 3");
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].kind, NoteLineKind::Heading);
+        assert_eq!(
+            lines[0].spans,
+            vec![
+                span("Blend "),
+                NoteSpan {
+                    italic: true,
+                    ..span("notes")
+                }
+            ]
+        );
+        assert_eq!(lines[1].kind, NoteLineKind::Bullet);
+        assert_eq!(
+            lines[1].spans,
+            vec![
+                span("uses "),
+                NoteSpan {
+                    bold: true,
+                    ..span("two")
+                },
+                span(" inputs")
+            ]
+        );
+        // A lone `*` is just an asterisk
+        assert_eq!(lines[2].kind, NoteLineKind::Text);
+        assert_eq!(
+            lines[2].spans,
+            vec![
+                span("see "),
+                NoteSpan {
+                    code: true,
+                    ..span("mix()")
+                },
+                span(" or 2 * 3")
+            ]
+        );
+        assert_eq!(parse_note("#hashtag")[0].kind, NoteLineKind::Text);
+    }
+
+    #[test]
+    fn test_frames_and_notes_are_saved_with_the_graph() {
+        let mut graph = NodeGraph::new();
+        add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::ZERO);
+        add_frame(&mut graph, "Frame", Rect::new(0.0, 0.0, 400.0, 300.0));
+        add_note(&mut graph, Vec2::new(500.0, 0.0), "# Note\n- item");
+
+        let text = ron::to_string(&graph).unwrap();
+        let loaded: NodeGraph = ron::from_str(&text).unwrap();
+        assert_eq!(loaded, graph);
+
+        // Graphs saved before frames and notes existed still load
+        let old = ron::to_string(&NodeGraph::new()).unwrap();
+        let old = old.replace(",frames:[],notes:[]", "");
+        assert!(!old.contains("frames"), "{}", old);
+        let loaded: NodeGraph = ron::from_str(&old).unwrap();
+        assert!(loaded.frames.is_empty() && loaded.notes.is_empty());
+    }
+}
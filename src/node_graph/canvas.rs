@@ -1,15 +1,70 @@
-use crate::node_graph::model::{CanvasState, NodeGraph};
+use crate::node_graph::model::{CanvasState, NodeGraph, NodeInstance};
+use crate::node_graph::selection::node_rect;
 use bevy::input::mouse::{MouseMotion, MouseWheel};
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
 
+/// Canvas units between the background grid lines, and the step nodes snap to
+pub const GRID_SPACING: f32 = 48.0;
+
+/// Zoom limits, how framing behaves and whether dragged nodes snap to the grid, adjustable from the View window
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub struct CanvasSettings {
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    /// Zoom change per line scrolled
+    pub zoom_step: f32,
+    /// Screen pixels left free around framed nodes
+    pub frame_margin: f32,
+    /// How quickly a framing animation closes in on its target, per second
+    pub animation_rate: f32,
+    /// Dragged nodes land with their top-left corner on a grid point
+    pub snap_to_grid: bool,
+}
+
+impl Default for CanvasSettings {
+    fn default() -> Self {
+        Self {
+            min_zoom: 0.1,
+            max_zoom: 4.0,
+            zoom_step: 0.1,
+            frame_margin: 48.0,
+            animation_rate: 12.0,
+            snap_to_grid: false,
+        }
+    }
+}
+
+impl CanvasSettings {
+    pub fn clamp_zoom(&self, zoom: f32) -> f32 {
+        zoom.clamp(self.min_zoom, self.max_zoom.max(self.min_zoom))
+    }
+}
+
+/// Where the canvas is gliding to after Frame All or Frame Selection; scrolling or panning
+/// stops it
+#[derive(Debug, Clone, Default, Resource)]
+pub struct CanvasAnimation {
+    pub target: Option<CanvasState>,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn update_canvas_system(
     mut node_graph: ResMut<NodeGraph>,
     mut mouse_wheel_events: MessageReader<MouseWheel>,
     mut mouse_motion_events: MessageReader<MouseMotion>,
     mouse_button_input: Res<ButtonInput<MouseButton>>,
     key_input: Res<ButtonInput<KeyCode>>,
+    settings: Option<Res<CanvasSettings>>,
+    animation: Option<ResMut<CanvasAnimation>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
 ) {
-    let canvas_state = &mut node_graph.canvas_state;
+    let settings = settings.map(|s| s.clone()).unwrap_or_default();
+    let cursor = windows.single().ok().and_then(|w| w.cursor_position());
+    // The view is not part of what the graph computes, so panning and zooming (or sitting
+    // idle) must not flag the graph as changed and recompile the preview
+    let canvas_state = &mut node_graph.bypass_change_detection().canvas_state;
+    let mut moved = false;
 
     // Pan: RMB or MMB drag.
     let pan_pressed = mouse_button_input.pressed(MouseButton::Right)
@@ -26,6 +81,7 @@ pub fn update_canvas_system(
             // Because offset is in *canvas* space, we divide by zoom.
             let canvas_delta = delta / canvas_state.zoom;
             canvas_state.offset -= canvas_delta;
+            moved = true;
             debug!(
                 "Canvas pan: mouse_delta={:?} canvas_delta={:?} zoom={} new_offset={:?}",
                 delta, canvas_delta, canvas_state.zoom, canvas_state.offset
@@ -37,7 +93,7 @@ pub fn update_canvas_system(
     }
 
     // Zooming: mouse wheel. Hold Ctrl to zoom (matches the README), but allow without Ctrl too.
-    // NOTE: we don't currently zoom around cursor; we just adjust zoom.
+    // The point under the cursor stays put.
     let ctrl = key_input.pressed(KeyCode::ControlLeft) || key_input.pressed(KeyCode::ControlRight);
 
     for event in mouse_wheel_events.read() {
@@ -47,18 +103,91 @@ pub fn update_canvas_system(
         // If you *want* Ctrl-only zoom, uncomment the guard below.
         // if !ctrl { continue; }
 
-        let zoom_delta = scroll * 0.1;
+        let zoom_delta = scroll * settings.zoom_step;
         let old_zoom = canvas_state.zoom;
-        let new_zoom = (old_zoom + zoom_delta).clamp(0.1, 4.0);
-        canvas_state.zoom = new_zoom;
+        let new_zoom = settings.clamp_zoom(old_zoom + zoom_delta);
+        match cursor {
+            Some(cursor) => zoom_around(canvas_state, cursor, new_zoom),
+            None => canvas_state.zoom = new_zoom,
+        }
+        moved = true;
 
         debug!(
             "Canvas zoom: scroll_y={} ctrl={} old_zoom={} new_zoom={} offset={:?}",
             scroll, ctrl, old_zoom, new_zoom, canvas_state.offset
         );
     }
+
+    // Taking over the view cancels a framing animation
+    if moved && let Some(mut animation) = animation {
+        animation.target = None;
+    }
+}
+
+/// Glide the canvas towards the framing target
+pub fn animate_canvas_system(
+    time: Res<Time>,
+    settings: Res<CanvasSettings>,
+    mut animation: ResMut<CanvasAnimation>,
+    mut node_graph: ResMut<NodeGraph>,
+) {
+    let Some(target) = animation.target.clone() else {
+        return;
+    };
+    let t = 1.0 - (-settings.animation_rate * time.delta_secs()).exp();
+    let canvas_state = &mut node_graph.bypass_change_detection().canvas_state;
+    if step_towards(canvas_state, &target, t) {
+        animation.target = None;
+    }
+}
+
+/// Move `canvas_state` the fraction `t` of the way to `target`, landing on it once close.
+/// Returns whether it got there
+pub fn step_towards(canvas_state: &mut CanvasState, target: &CanvasState, t: f32) -> bool {
+    canvas_state.zoom += (target.zoom - canvas_state.zoom) * t;
+    canvas_state.offset = canvas_state.offset.lerp(target.offset, t);
+    let close = (target.zoom - canvas_state.zoom).abs() < 1e-3
+        && (target.offset - canvas_state.offset).length() * canvas_state.zoom < 0.5;
+    if close {
+        *canvas_state = target.clone();
+    }
+    close
+}
+
+/// The grid point nearest to `position`
+pub fn snap_to_grid(position: Vec2, spacing: f32) -> Vec2 {
+    (position / spacing).round() * spacing
+}
+
+/// Change the zoom so the canvas point under `screen_pos` stays under it
+pub fn zoom_around(canvas_state: &mut CanvasState, screen_pos: Vec2, zoom: f32) {
+    let anchor = screen_to_canvas(screen_pos, canvas_state);
+    canvas_state.zoom = zoom;
+    canvas_state.offset = anchor - screen_pos / zoom;
+}
+
+/// Canvas-space rectangle around the nodes, or `None` when there are none
+pub fn nodes_bounds<'a>(nodes: impl IntoIterator<Item = &'a NodeInstance>) -> Option<Rect> {
+    nodes
+        .into_iter()
+        .map(node_rect)
+        .reduce(|bounds, rect| bounds.union(rect))
+}
+
+/// The view that shows `bounds` as large as the limits allow, centred in the screen-space
+/// `viewport` with `frame_margin` left around it
+pub fn frame_bounds(bounds: Rect, viewport: Rect, settings: &CanvasSettings) -> CanvasState {
+    let room = (viewport.size() - Vec2::splat(settings.frame_margin * 2.0)).max(Vec2::ONE);
+    let size = bounds.size().max(Vec2::ONE);
+    let zoom = settings.clamp_zoom((room / size).min_element());
+    CanvasState {
+        zoom,
+        offset: bounds.center() - viewport.center() / zoom,
+    }
 }
 
+/// The one canvas/screen transform: the canvas point `offset` sits at the screen's top-left
+/// corner and one canvas unit is `zoom` pixels
 pub fn screen_to_canvas(screen_pos: Vec2, canvas_state: &CanvasState) -> Vec2 {
     (screen_pos / canvas_state.zoom) + canvas_state.offset
 }
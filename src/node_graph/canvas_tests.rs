@@ -1,7 +1,13 @@
 #[cfg(test)]
 mod tests {
-    use crate::node_graph::canvas::{canvas_to_screen, screen_to_canvas, update_canvas_system};
-    use crate::node_graph::model::{CanvasState, NodeGraph};
+    use crate::node_graph::canvas::{
+        CanvasAnimation, CanvasSettings, GRID_SPACING, canvas_to_screen, frame_bounds,
+        nodes_bounds, screen_to_canvas, snap_to_grid, step_towards, update_canvas_system,
+        zoom_around,
+    };
+    use crate::node_graph::model::{CanvasState, NodeGraph, NodeId};
+    use crate::node_graph::node_factory::NodeFactory;
+    use crate::shader_graph::node_kind::NodeKind;
     use bevy::input::mouse::{MouseMotion, MouseWheel};
     use bevy::prelude::*;
 
@@ -314,6 +320,32 @@ mod tests {
         assert_eq!(node_graph.canvas_state.offset, Vec2::ZERO);
     }
 
+    #[test]
+    fn test_update_canvas_system_leaves_the_graph_unchanged() {
+        let mut app = create_test_app();
+        app.update();
+        let last_changed = |app: &App| app.world().resource_ref::<NodeGraph>().last_changed();
+        let before = last_changed(&app);
+
+        // An idle frame must not make the preview recompile
+        app.update();
+        assert_eq!(last_changed(&app), before);
+
+        // Neither does moving the view
+        let mut button_input = ButtonInput::<MouseButton>::default();
+        button_input.press(MouseButton::Right);
+        app.world_mut().insert_resource(button_input);
+        app.world_mut().write_message(MouseMotion {
+            delta: Vec2::new(10.0, -5.0),
+        });
+        app.update();
+        assert_ne!(
+            app.world().resource::<NodeGraph>().canvas_state.offset,
+            Vec2::ZERO
+        );
+        assert_eq!(last_changed(&app), before);
+    }
+
     #[test]
     fn test_update_canvas_system_pan_with_zoom() {
         let mut app = create_test_app();
@@ -386,4 +418,122 @@ mod tests {
         assert!((original_canvas.x - back_to_canvas.x).abs() < 0.0001);
         assert!((original_canvas.y - back_to_canvas.y).abs() < 0.0001);
     }
+
+    #[test]
+    fn test_zoom_around_keeps_the_point_under_the_cursor() {
+        let mut canvas_state = CanvasState {
+            zoom: 1.5,
+            offset: Vec2::new(-120.0, 40.0),
+        };
+        let cursor = Vec2::new(640.0, 360.0);
+        let anchor = screen_to_canvas(cursor, &canvas_state);
+
+        for zoom in [3.0, 0.25, 1.0] {
+            zoom_around(&mut canvas_state, cursor, zoom);
+            assert_eq!(canvas_state.zoom, zoom);
+            assert!((canvas_to_screen(anchor, &canvas_state) - cursor).length() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_zoom_limits_come_from_the_settings() {
+        let mut app = create_test_app();
+        app.insert_resource(CanvasSettings {
+            min_zoom: 0.5,
+            max_zoom: 2.0,
+            ..default()
+        });
+        app.insert_resource(CanvasAnimation {
+            target: Some(CanvasState::default()),
+        });
+
+        app.world_mut().write_message(MouseWheel {
+            y: 100.0,
+            x: 0.0,
+            unit: bevy::input::mouse::MouseScrollUnit::Line,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+        assert_eq!(app.world().resource::<NodeGraph>().canvas_state.zoom, 2.0);
+        // Scrolling takes over from a framing animation
+        assert!(app.world().resource::<CanvasAnimation>().target.is_none());
+
+        app.world_mut().write_message(MouseWheel {
+            y: -100.0,
+            x: 0.0,
+            unit: bevy::input::mouse::MouseScrollUnit::Line,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+        assert_eq!(app.world().resource::<NodeGraph>().canvas_state.zoom, 0.5);
+    }
+
+    #[test]
+    fn test_frame_bounds_fits_and_centres() {
+        let settings = CanvasSettings::default();
+        let viewport = Rect::new(0.0, 0.0, 1920.0, 1080.0);
+
+        // Wide bounds are limited by the width
+        let bounds = Rect::new(-500.0, 100.0, 1500.0, 400.0);
+        let view = frame_bounds(bounds, viewport, &settings);
+        assert!((view.zoom - (1920.0 - 96.0) / 2000.0).abs() < 1e-5);
+        let centre = canvas_to_screen(bounds.center(), &view);
+        assert!((centre - viewport.center()).length() < 1e-3);
+        let left = canvas_to_screen(bounds.min, &view);
+        assert!((left.x - settings.frame_margin).abs() < 1e-3);
+
+        // A single small node is not blown up past the zoom limit
+        let bounds = Rect::new(0.0, 0.0, 220.0, 100.0);
+        let view = frame_bounds(bounds, viewport, &settings);
+        assert_eq!(view.zoom, settings.max_zoom);
+        assert!((canvas_to_screen(bounds.center(), &view) - viewport.center()).length() < 1e-3);
+    }
+
+    #[test]
+    fn test_nodes_bounds() {
+        let mut graph = NodeGraph::new();
+        assert_eq!(nodes_bounds(graph.nodes.values()), None);
+        for (id, position) in [(1, Vec2::new(-100.0, 50.0)), (2, Vec2::new(300.0, -20.0))] {
+            let (node, _) = NodeFactory::create_node(NodeId(id), position, NodeKind::Test, id * 10);
+            graph.add_node(node);
+        }
+        assert_eq!(
+            nodes_bounds(graph.nodes.values()),
+            Some(Rect::new(-100.0, -20.0, 520.0, 150.0))
+        );
+    }
+
+    #[test]
+    fn test_animation_converges_on_the_target() {
+        let mut canvas_state = CanvasState::default();
+        let target = CanvasState {
+            zoom: 0.5,
+            offset: Vec2::new(400.0, -300.0),
+        };
+        assert!(!step_towards(&mut canvas_state, &target, 0.2));
+        assert!(canvas_state.zoom < 1.0 && canvas_state.zoom > 0.5);
+        let mut steps = 1;
+        while !step_towards(&mut canvas_state, &target, 0.2) {
+            steps += 1;
+            assert!(steps < 100, "never arrived");
+        }
+        assert_eq!(canvas_state, target);
+    }
+
+    #[test]
+    fn test_snap_to_grid() {
+        assert!(!CanvasSettings::default().snap_to_grid);
+        assert_eq!(
+            snap_to_grid(Vec2::new(70.0, 23.0), GRID_SPACING),
+            Vec2::new(48.0, 0.0)
+        );
+        assert_eq!(
+            snap_to_grid(Vec2::new(73.0, -25.0), GRID_SPACING),
+            Vec2::new(96.0, -48.0)
+        );
+        assert_eq!(
+            snap_to_grid(Vec2::splat(144.0), GRID_SPACING),
+            Vec2::splat(144.0)
+        );
+    }
 }
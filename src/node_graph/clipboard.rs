@@ -0,0 +1,77 @@
+use crate::node_graph::model::{Connection, NodeGraph, NodeId, NodeInstance, PinId};
+use crate::node_graph::selection::insert_copies;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Nodes and the wires between them, as copied to the clipboard. The text is RON, so a
+/// fragment copied in one editor pastes into another
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphFragment {
+    pub nodes: Vec<NodeInstance>,
+    pub connections: Vec<Connection>,
+}
+
+impl GraphFragment {
+    /// Copy the nodes, ordered by id, and the connections that run between them
+    pub fn copy(graph: &NodeGraph, node_ids: &[NodeId]) -> Self {
+        let mut nodes: Vec<NodeInstance> = node_ids
+            .iter()
+            .filter_map(|id| graph.nodes.get(id).cloned())
+            .collect();
+        nodes.sort_by_key(|node| node.node_id.0);
+        let pins: Vec<PinId> = nodes
+            .iter()
+            .flat_map(|node| {
+                node.inputs
+                    .iter()
+                    .map(|p| p.pin_id)
+                    .chain(node.outputs.iter().map(|p| p.pin_id))
+            })
+            .collect();
+        let connections = graph
+            .connections
+            .iter()
+            .filter(|c| pins.contains(&c.from_pin) && pins.contains(&c.to_pin))
+            .cloned()
+            .collect();
+        Self { nodes, connections }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn to_text(&self) -> Result<String, String> {
+        let config = ron::ser::PrettyConfig::default().struct_names(true);
+        ron::ser::to_string_pretty(self, config).map_err(|e| e.to_string())
+    }
+
+    /// Read clipboard text; anything other than a copied fragment is an error
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let fragment: Self = ron::from_str(text.trim()).map_err(|e| e.to_string())?;
+        if fragment.is_empty() {
+            return Err("The clipboard holds no nodes".to_string());
+        }
+        Ok(fragment)
+    }
+
+    /// Top-left corner of the copied nodes
+    pub fn origin(&self) -> Vec2 {
+        self.nodes
+            .iter()
+            .map(|node| node.position)
+            .reduce(Vec2::min)
+            .unwrap_or(Vec2::ZERO)
+    }
+
+    /// Add the fragment under fresh ids with its top-left corner at `position`, wired up as
+    /// it was copied. Returns the new nodes
+    pub fn paste(&self, graph: &mut NodeGraph, position: Vec2) -> Vec<NodeId> {
+        insert_copies(
+            graph,
+            &self.nodes,
+            &self.connections,
+            position - self.origin(),
+        )
+    }
+}
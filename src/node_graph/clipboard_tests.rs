@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::clipboard::GraphFragment;
+    use crate::node_graph::model::{NodeGraph, NodeId};
+    use crate::node_graph::node_factory::MathOperation;
+    use crate::node_graph::test_utils::{add_node_at, connect};
+    use crate::shader_graph::node_kind::NodeKind;
+    use crate::shader_graph::nodes::subgraph::collapse_into_subgraph;
+    use bevy::prelude::*;
+
+    /// `a * b`, multiplied again by `b`
+    fn graph() -> (NodeGraph, NodeId, NodeId, NodeId) {
+        let mut graph = NodeGraph::new();
+        let a = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::new(100.0, 50.0));
+        let b = add_node_at(&mut graph, NodeKind::Constant(2.0), Vec2::new(100.0, 250.0));
+        let mul = add_node_at(
+            &mut graph,
+            NodeKind::Math(MathOperation::Multiply),
+            Vec2::new(400.0, 150.0),
+        );
+        let other = add_node_at(
+            &mut graph,
+            NodeKind::Math(MathOperation::Multiply),
+            Vec2::new(400.0, 400.0),
+        );
+        connect(&mut graph, a, 0, mul, 0);
+        connect(&mut graph, b, 0, mul, 1);
+        connect(&mut graph, b, 0, other, 0);
+        connect(&mut graph, mul, 0, other, 1);
+        (graph, a, b, mul)
+    }
+
+    #[test]
+    fn test_copy_takes_only_internal_connections() {
+        let (graph, a, _, mul) = graph();
+        let fragment = GraphFragment::copy(&graph, &[mul, a, NodeId(99)]);
+        let ids: Vec<NodeId> = fragment.nodes.iter().map(|n| n.node_id).collect();
+        assert_eq!(ids, vec![a, mul]);
+        assert_eq!(fragment.connections.len(), 1);
+        assert_eq!(fragment.origin(), Vec2::new(100.0, 50.0));
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let (graph, a, b, mul) = graph();
+        let fragment = GraphFragment::copy(&graph, &[a, b, mul]);
+        let text = fragment.to_text().unwrap();
+        assert!(text.starts_with("GraphFragment("), "{}", text);
+        assert_eq!(GraphFragment::from_text(&text).unwrap(), fragment);
+
+        assert!(GraphFragment::from_text("some copied prose").is_err());
+        assert!(GraphFragment::from_text("GraphFragment(nodes: [], connections: [])").is_err());
+    }
+
+    #[test]
+    fn test_paste_reallocates_ids_at_the_position() {
+        let (mut graph, a, b, mul) = graph();
+        let text = GraphFragment::copy(&graph, &[a, b, mul]).to_text().unwrap();
+        let fragment = GraphFragment::from_text(&text).unwrap();
+
+        let pasted = fragment.paste(&mut graph, Vec2::new(1000.0, 1000.0));
+        assert_eq!(pasted.len(), 3);
+        assert_eq!(graph.nodes.len(), 7);
+        assert!(pasted.iter().all(|id| id.0 > 4));
+        // Same layout, top-left corner at the cursor
+        assert_eq!(graph.nodes[&pasted[0]].position, Vec2::new(1000.0, 1000.0));
+        assert_eq!(graph.nodes[&pasted[1]].position, Vec2::new(1000.0, 1200.0));
+        assert_eq!(graph.nodes[&pasted[2]].position, Vec2::new(1300.0, 1100.0));
+
+        // Both wires into the pasted multiply come from the pasted constants
+        assert_eq!(graph.connections.len(), 6);
+        let pasted_mul = &graph.nodes[&pasted[2]];
+        for (input, from) in [(0, pasted[0]), (1, pasted[1])] {
+            assert_eq!(
+                graph
+                    .connection_into(pasted_mul.inputs[input].pin_id)
+                    .map(|c| c.from_pin),
+                Some(graph.nodes[&from].outputs[0].pin_id)
+            );
+        }
+    }
+
+    #[test]
+    fn test_paste_into_another_graph() {
+        let (mut source, a, b, mul) = graph();
+        let subgraph = collapse_into_subgraph(&mut source, &[mul], "Product").unwrap();
+        let text = GraphFragment::copy(&source, &[a, b, subgraph])
+            .to_text()
+            .unwrap();
+
+        // A different editor with its own nodes
+        let mut target = NodeGraph::new();
+        add_node_at(&mut target, NodeKind::Constant(5.0), Vec2::ZERO);
+        let pasted = GraphFragment::from_text(&text)
+            .unwrap()
+            .paste(&mut target, Vec2::ZERO);
+        assert_eq!(pasted.len(), 3);
+        assert_eq!(target.connections.len(), 2);
+        assert_eq!(target.nodes[&pasted[2]].kind, source.nodes[&subgraph].kind);
+    }
+}
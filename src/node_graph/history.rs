@@ -0,0 +1,121 @@
+use crate::node_graph::model::{NodeGraph, NodeId};
+use bevy::prelude::*;
+
+/// Most steps kept for undo; the oldest go first
+const HISTORY_LIMIT: usize = 100;
+
+/// Nodes moved together by one command, with where each was and where it went
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeMoves {
+    /// Shown on the Undo and Redo buttons, e.g. "Auto Layout"
+    pub label: String,
+    pub moves: Vec<(NodeId, Vec2, Vec2)>,
+}
+
+impl NodeMoves {
+    /// Moves from where the nodes are now to `targets`, leaving out nodes already there
+    pub fn new(label: &str, graph: &NodeGraph, targets: &[(NodeId, Vec2)]) -> Self {
+        let moves = targets
+            .iter()
+            .filter_map(|&(node_id, to)| {
+                let from = graph.nodes.get(&node_id)?.position;
+                (from != to).then_some((node_id, from, to))
+            })
+            .collect();
+        Self {
+            label: label.to_string(),
+            moves,
+        }
+    }
+
+    /// Moves from the positions in `before` to where the nodes are now, for a command that
+    /// has already moved them
+    pub fn since(label: &str, before: &[(NodeId, Vec2)], graph: &NodeGraph) -> Self {
+        let moves = before
+            .iter()
+            .filter_map(|&(node_id, from)| {
+                let to = graph.nodes.get(&node_id)?.position;
+                (from != to).then_some((node_id, from, to))
+            })
+            .collect();
+        Self {
+            label: label.to_string(),
+            moves,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    pub fn before(&self) -> Vec<(NodeId, Vec2)> {
+        self.moves.iter().map(|&(id, from, _)| (id, from)).collect()
+    }
+
+    pub fn after(&self) -> Vec<(NodeId, Vec2)> {
+        self.moves.iter().map(|&(id, _, to)| (id, to)).collect()
+    }
+}
+
+/// Undo and redo stacks of node moves. Node ids are reused, so deleted nodes have to be
+/// `forget`-ten and the whole history cleared when the graph is swapped for another
+#[derive(Debug, Clone, Default, Resource)]
+pub struct UndoHistory {
+    undo: Vec<NodeMoves>,
+    redo: Vec<NodeMoves>,
+}
+
+impl UndoHistory {
+    /// Add a step, dropping anything that could have been redone
+    pub fn record(&mut self, step: NodeMoves) {
+        if step.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        self.undo.push(step);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Drop deleted nodes from every step, and steps left with nothing to move, before a new
+    /// node can take one of their ids
+    pub fn forget(&mut self, node_ids: &[NodeId]) {
+        for stack in [&mut self.undo, &mut self.redo] {
+            for step in stack.iter_mut() {
+                step.moves
+                    .retain(|(node_id, ..)| !node_ids.contains(node_id));
+            }
+            stack.retain(|step| !step.is_empty());
+        }
+    }
+
+    /// Drop every step, for when the graph they moved is replaced
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Take the latest step back; the caller moves its nodes to `before()`
+    pub fn undo(&mut self) -> Option<&NodeMoves> {
+        let step = self.undo.pop()?;
+        self.redo.push(step);
+        self.redo.last()
+    }
+
+    /// Take the latest undone step again; the caller moves its nodes to `after()`
+    pub fn redo(&mut self) -> Option<&NodeMoves> {
+        let step = self.redo.pop()?;
+        self.undo.push(step);
+        self.undo.last()
+    }
+
+    /// Label of the step `undo` would take back
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.last().map(|step| step.label.as_str())
+    }
+
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|step| step.label.as_str())
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::history::{NodeMoves, UndoHistory};
+    use crate::node_graph::model::{NodeGraph, NodeId};
+    use crate::node_graph::selection::delete_nodes;
+    use crate::node_graph::test_utils::add_node_at;
+    use crate::shader_graph::node_kind::NodeKind;
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_moves_skip_nodes_that_stay_or_are_gone() {
+        let mut graph = NodeGraph::new();
+        let a = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::ZERO);
+        let b = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::new(100.0, 0.0));
+        let step = NodeMoves::new(
+            "Auto Layout",
+            &graph,
+            &[
+                (a, Vec2::new(0.0, 50.0)),
+                (b, Vec2::new(100.0, 0.0)),
+                (NodeId(99), Vec2::ZERO),
+            ],
+        );
+        assert_eq!(step.moves, vec![(a, Vec2::ZERO, Vec2::new(0.0, 50.0))]);
+        assert_eq!(step.before(), vec![(a, Vec2::ZERO)]);
+        assert_eq!(step.after(), vec![(a, Vec2::new(0.0, 50.0))]);
+        assert!(NodeMoves::new("Nothing", &graph, &[(b, Vec2::new(100.0, 0.0))]).is_empty());
+    }
+
+    #[test]
+    fn test_undo_redo_order() {
+        let mut graph = NodeGraph::new();
+        let a = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::ZERO);
+        let mut history = UndoHistory::default();
+        assert!(history.undo().is_none());
+
+        history.record(NodeMoves::new("First", &graph, &[(a, Vec2::ONE)]));
+        history.record(NodeMoves::new("Second", &graph, &[(a, Vec2::X)]));
+        history.record(NodeMoves::new("Empty", &graph, &[]));
+        assert_eq!(history.undo_label(), Some("Second"));
+
+        assert_eq!(history.undo().unwrap().label, "Second");
+        assert_eq!(history.redo_label(), Some("Second"));
+        assert_eq!(history.undo().unwrap().before(), vec![(a, Vec2::ZERO)]);
+        assert!(history.undo().is_none());
+        assert_eq!(history.redo().unwrap().after(), vec![(a, Vec2::ONE)]);
+
+        // A new step drops what could have been redone
+        history.record(NodeMoves::new("Third", &graph, &[(a, Vec2::Y)]));
+        assert_eq!(history.redo_label(), None);
+        assert_eq!(history.undo_label(), Some("Third"));
+    }
+
+    #[test]
+    fn test_moves_since_a_command_ran() {
+        let mut graph = NodeGraph::new();
+        let a = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::ZERO);
+        let b = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::X);
+        let before = [(a, Vec2::ZERO), (b, Vec2::X), (NodeId(99), Vec2::ZERO)];
+        graph.nodes.get_mut(&a).unwrap().position = Vec2::new(48.0, 0.0);
+
+        let step = NodeMoves::since("Move", &before, &graph);
+        assert_eq!(step.moves, vec![(a, Vec2::ZERO, Vec2::new(48.0, 0.0))]);
+    }
+
+    #[test]
+    fn test_drag_after_layout_is_undone_first() {
+        let mut graph = NodeGraph::new();
+        let a = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::ZERO);
+        let mut history = UndoHistory::default();
+        history.record(NodeMoves::new("Auto Layout", &graph, &[(a, Vec2::X)]));
+        graph.nodes.get_mut(&a).unwrap().position = Vec2::X;
+
+        // Dragging the node afterwards is a step of its own
+        let before = [(a, Vec2::X)];
+        graph.nodes.get_mut(&a).unwrap().position = Vec2::new(1.0, 40.0);
+        history.record(NodeMoves::since("Move", &before, &graph));
+        assert_eq!(history.undo().unwrap().before(), vec![(a, Vec2::X)]);
+        assert_eq!(history.undo().unwrap().before(), vec![(a, Vec2::ZERO)]);
+    }
+
+    #[test]
+    fn test_undo_skips_a_deleted_node_whose_id_is_reused() {
+        let mut graph = NodeGraph::new();
+        let a = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::ZERO);
+        let b = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::new(100.0, 0.0));
+        let mut history = UndoHistory::default();
+        history.record(NodeMoves::new(
+            "Auto Layout",
+            &graph,
+            &[(a, Vec2::new(0.0, 50.0)), (b, Vec2::new(100.0, 50.0))],
+        ));
+        history.record(NodeMoves::new(
+            "Move",
+            &graph,
+            &[(b, Vec2::new(300.0, 0.0))],
+        ));
+
+        // b has the highest id, so the next node spawned takes it
+        delete_nodes(&mut graph, &[b]);
+        history.forget(&[b]);
+        let c = add_node_at(&mut graph, NodeKind::Constant(2.0), Vec2::new(400.0, 0.0));
+        assert_eq!(c, b);
+
+        // The step that only moved b is gone, the other leaves the new node alone
+        assert_eq!(history.undo_label(), Some("Auto Layout"));
+        assert_eq!(history.undo().unwrap().before(), vec![(a, Vec2::ZERO)]);
+        assert_eq!(
+            history.redo().unwrap().after(),
+            vec![(a, Vec2::new(0.0, 50.0))]
+        );
+        assert!(history.undo().is_some());
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn test_clear_drops_every_step() {
+        let mut graph = NodeGraph::new();
+        let a = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::ZERO);
+        let mut history = UndoHistory::default();
+        history.record(NodeMoves::new("First", &graph, &[(a, Vec2::ONE)]));
+        history.record(NodeMoves::new("Second", &graph, &[(a, Vec2::X)]));
+        history.undo();
+
+        history.clear();
+        assert_eq!(history.undo_label(), None);
+        assert_eq!(history.redo_label(), None);
+    }
+}
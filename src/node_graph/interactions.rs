@@ -1,9 +1,17 @@
+use crate::node_graph::annotations::{annotation_at, drag_annotation, start_annotation_drag};
+use crate::node_graph::canvas::{self, CanvasSettings, GRID_SPACING};
+use crate::node_graph::history::{NodeMoves, UndoHistory};
 use crate::node_graph::model::{CanvasState, Connection, NodeGraph, NodeLayout, PinId};
 use crate::node_graph::pin_manager::PinPositionManager;
-use crate::node_graph::ui_state::{GraphUiState, PendingConnection};
+use crate::node_graph::selection::{node_rect, nodes_in_rect};
+use crate::node_graph::ui_state::{BoxSelect, GraphUiState, PaletteRequest, PendingConnection};
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, egui};
 
+/// Screen pixels a wire has to be dragged before letting go of it opens the node palette;
+/// shorter is a click on the pin, which keeps the wire for clicking an input next
+const WIRE_DROP_DISTANCE: f32 = 12.0;
+
 // Convert from bevy Vec2 to egui Pos2
 fn vec2_to_pos2(vec: Vec2) -> egui::Pos2 {
     egui::pos2(vec.x, vec.y)
@@ -14,9 +22,16 @@ fn pos2_to_vec2(pos: egui::Pos2) -> Vec2 {
     Vec2::new(pos.x, pos.y)
 }
 
+/// Drag node headers, moving the whole selection when the dragged node is part of it, and
+/// rubber-band select nodes by dragging over empty canvas. Comment frames are dragged by their
+/// title bar and sticky notes anywhere, both resized from the corner. With snapping on, the dragged
+/// node moves from grid point to grid point and the rest of the selection keeps its place
+/// relative to it. Each node drag is one step Ctrl+Z takes back
 pub fn handle_node_drag_system(
     mut node_graph: ResMut<NodeGraph>,
     mut ui_state: ResMut<GraphUiState>,
+    mut history: ResMut<UndoHistory>,
+    settings: Res<CanvasSettings>,
     mut egui_contexts: EguiContexts,
 ) {
     let ctx = egui_contexts.ctx_mut().expect("Failed to get egui context");
@@ -24,8 +39,9 @@ pub fn handle_node_drag_system(
 
     // Handle dragging logic - simplified approach using pointer state directly
     let pointer_pos = ctx.input(|i| i.pointer.latest_pos()).unwrap_or_default();
-    let is_primary_pressed = ctx.input(|i| i.pointer.primary_down());
+    let is_primary_pressed = ctx.input(|i| i.pointer.primary_pressed());
     let is_primary_released = ctx.input(|i| i.pointer.primary_released());
+    let modifiers = ctx.input(|i| i.modifiers);
 
     // Stop dragging if primary button was released
     if is_primary_released && ui_state.active_drag_node.is_some() {
@@ -34,6 +50,54 @@ pub fn handle_node_drag_system(
             ui_state.active_drag_node
         );
         ui_state.active_drag_node = None;
+        // A click without moving records nothing
+        let before = std::mem::take(&mut ui_state.drag_start);
+        history.record(NodeMoves::since("Move", &before, &node_graph));
+        return;
+    }
+    if is_primary_released && ui_state.annotation_drag.is_some() {
+        ui_state.annotation_drag = None;
+        return;
+    }
+    if let Some(drag) = ui_state.annotation_drag.as_ref() {
+        let drag_delta = ctx.input(|i| i.pointer.delta());
+        let canvas_delta = Vec2::new(drag_delta.x, drag_delta.y) / node_graph.canvas_state.zoom;
+        if canvas_delta != Vec2::ZERO {
+            drag_annotation(&mut node_graph, drag, canvas_delta);
+        }
+        return;
+    }
+
+    // A click on a pin starts a wire, not a rubber band
+    if ui_state.pending_connection.is_some() {
+        ui_state.box_select = None;
+    }
+
+    if let Some(band) = ui_state.box_select.as_mut() {
+        band.to = pointer_pos;
+        if is_primary_released {
+            let band = *band;
+            let rect = band.rect();
+            let canvas_state = node_graph.canvas_state.clone();
+            // Compare against the rectangles the nodes are drawn at
+            let picked = nodes_in_rect(
+                &node_graph,
+                Rect::new(rect.min.x, rect.min.y, rect.max.x, rect.max.y),
+                |node| {
+                    let bounds = node_rect(node);
+                    Rect::from_corners(
+                        canvas::canvas_to_screen(bounds.min, &canvas_state),
+                        canvas::canvas_to_screen(bounds.max, &canvas_state),
+                    )
+                },
+            );
+            info!("SELECT: Box selected {} nodes", picked.len());
+            if !band.additive {
+                ui_state.selection.clear();
+            }
+            ui_state.extend_selection(picked);
+            ui_state.box_select = None;
+        }
         return;
     }
 
@@ -43,15 +107,32 @@ pub fn handle_node_drag_system(
         if drag_delta != egui::Vec2::ZERO {
             // Convert screen delta to canvas delta
             let canvas_delta = Vec2::new(drag_delta.x, drag_delta.y) / node_graph.canvas_state.zoom;
-
-            // Update node position
-            if let Some(node) = node_graph.nodes.get_mut(&active_drag_node_id) {
-                let new_position = node.position + canvas_delta;
-                info!(
-                    "Moving node {:?} from {:?} to {:?}",
-                    active_drag_node_id, node.position, new_position
-                );
-                node.position = new_position;
+            // Where the pointer has taken the dragged node since the press, snapped or not
+            ui_state.drag_offset += canvas_delta;
+            let mut target = ui_state.drag_origin + ui_state.drag_offset;
+            if settings.snap_to_grid {
+                target = canvas::snap_to_grid(target, GRID_SPACING);
+            }
+            let canvas_delta = node_graph
+                .nodes
+                .get(&active_drag_node_id)
+                .map_or(Vec2::ZERO, |node| target - node.position);
+
+            // Dragging a selected node carries the rest of the selection along
+            let moved = if ui_state.is_selected(active_drag_node_id) {
+                ui_state.selection.clone()
+            } else {
+                vec![active_drag_node_id]
+            };
+            for node_id in moved {
+                if let Some(node) = node_graph.nodes.get_mut(&node_id) {
+                    let new_position = node.position + canvas_delta;
+                    info!(
+                        "Moving node {:?} from {:?} to {:?}",
+                        node_id, node.position, new_position
+                    );
+                    node.position = new_position;
+                }
             }
         }
         return;
@@ -64,10 +145,13 @@ pub fn handle_node_drag_system(
         // Check if pointer is over any node header
         for (_, node_instance) in node_graph.nodes.iter() {
             let canvas_state = &node_graph.canvas_state;
-            let node_screen_pos =
-                vec2_to_pos2((node_instance.position + canvas_state.offset) * canvas_state.zoom);
+            let node_screen_pos = vec2_to_pos2(canvas::canvas_to_screen(
+                node_instance.position,
+                canvas_state,
+            ));
 
-            // Check header area using centralized layout constants
+            // Check header area using centralized layout constants, drawn at the zoom
+            let layout = layout.scaled(canvas_state.zoom);
             let header_rect = egui::Rect::from_min_size(
                 node_screen_pos,
                 egui::vec2(layout.width, layout.header_height),
@@ -81,8 +165,44 @@ pub fn handle_node_drag_system(
                 ui_state.active_drag_node = Some(node_instance.node_id);
                 // Store the current position as the drag origin
                 ui_state.drag_origin = node_instance.position;
-                break;
+                ui_state.drag_offset = Vec2::ZERO;
+                // Grabbing an unselected node picks it alone; Ctrl/Shift+click toggles it
+                // in the header's click handler instead
+                if !(ui_state.is_selected(node_instance.node_id)
+                    || modifiers.command
+                    || modifiers.shift)
+                {
+                    ui_state.selection = vec![node_instance.node_id];
+                }
+                let moving = if ui_state.is_selected(node_instance.node_id) {
+                    ui_state.selection.clone()
+                } else {
+                    vec![node_instance.node_id]
+                };
+                ui_state.drag_start = moving
+                    .into_iter()
+                    .filter_map(|node_id| Some((node_id, node_graph.nodes.get(&node_id)?.position)))
+                    .collect();
+                return;
+            }
+        }
+
+        // Pressing on empty canvas grabs a comment frame or note there, or starts a rubber band
+        if !ctx.is_pointer_over_area() && ui_state.pending_connection.is_none() {
+            let canvas_state = &node_graph.canvas_state;
+            let canvas_pos = canvas::screen_to_canvas(pos2_to_vec2(pointer_pos), canvas_state);
+            if let Some((id, part)) = annotation_at(&node_graph, canvas_pos, canvas_state.zoom) {
+                info!("ANNOTATION: Grabbed {:?} ({:?})", id, part);
+                ui_state.active_annotation = Some(id);
+                ui_state.annotation_drag = Some(start_annotation_drag(&node_graph, id, part));
+                return;
             }
+            ui_state.active_annotation = None;
+            ui_state.box_select = Some(BoxSelect {
+                from: pointer_pos,
+                to: pointer_pos,
+                additive: modifiers.command || modifiers.shift,
+            });
         }
     }
 }
@@ -94,7 +214,8 @@ pub fn handle_pin_interactions_system(
     mut egui_contexts: EguiContexts,
 ) {
     let ctx = egui_contexts.ctx_mut().expect("Failed to get egui context");
-    let layout = NodeLayout::default();
+    // Pins are hit-tested where they are drawn, scaled with the zoom
+    let layout = NodeLayout::default().scaled(node_graph.canvas_state.zoom);
 
     // First check for mouse release on connection completion
     if ctx.input(|i| i.pointer.any_released()) {
@@ -109,24 +230,25 @@ pub fn handle_pin_interactions_system(
             // Check for input pins at release position
             for (_, node_instance) in node_graph.nodes.iter() {
                 let canvas_state = &node_graph.canvas_state;
-                let node_screen_pos = vec2_to_pos2(
-                    (node_instance.position + canvas_state.offset) * canvas_state.zoom,
-                );
+                let node_screen_pos = vec2_to_pos2(canvas::canvas_to_screen(
+                    node_instance.position,
+                    canvas_state,
+                ));
 
                 // Calculate the Area rectangle using centralized layout constants
                 let header_rect = egui::Rect::from_min_size(
                     node_screen_pos,
-                    egui::vec2(layout.width, node_instance.header_height + 70.0),
+                    egui::vec2(
+                        layout.width,
+                        layout.header_height + 70.0 * canvas_state.zoom,
+                    ),
                 );
 
                 // Check input pins - use centralized layout constants matching render system
                 for (i, input_pin) in node_instance.inputs.iter().enumerate() {
                     let pin_radius = layout.pin_radius; // 6px radius from NodeLayout
-                    let pin_x = -layout.pin_margin; // Slightly outside the node border
-                    let pin_y =
-                        layout.header_height + layout.pin_spacing + (i as f32 * layout.pin_spacing); // Below header
-
-                    let pin_center = header_rect.min + egui::vec2(pin_x, pin_y);
+                    let pin_center =
+                        header_rect.min + vec2_to_pos2(layout.input_pin_offset(i)).to_vec2(); // Outside the left border, below header
                     let pin_rect = egui::Rect::from_center_size(
                         pin_center,
                         egui::vec2(pin_radius * 2.0, pin_radius * 2.0),
@@ -166,6 +288,21 @@ pub fn handle_pin_interactions_system(
                     }
                 }
             }
+
+            // A wire dragged out into empty canvas picks the node to connect it to
+            let dragged = pointer_pos.distance(pending.from_screen_pos) > WIRE_DROP_DISTANCE;
+            if dragged && !ctx.is_pointer_over_area() {
+                info!(
+                    "INTERACTION: Wire from pin {:?} dropped on empty canvas, opening palette",
+                    pending.from_pin
+                );
+                ui_state.palette = Some(PaletteRequest {
+                    screen_pos: pointer_pos,
+                    from_pin: Some(pending.from_pin),
+                });
+                ui_state.pending_connection = None;
+                return;
+            }
         }
     }
 
@@ -178,23 +315,25 @@ pub fn handle_pin_interactions_system(
 
         for (_, node_instance) in node_graph.nodes.iter() {
             let canvas_state = &node_graph.canvas_state;
-            let node_screen_pos =
-                vec2_to_pos2((node_instance.position + canvas_state.offset) * canvas_state.zoom);
+            let node_screen_pos = vec2_to_pos2(canvas::canvas_to_screen(
+                node_instance.position,
+                canvas_state,
+            ));
 
             // Calculate the Area rectangle using centralized layout constants
             let header_rect = egui::Rect::from_min_size(
                 node_screen_pos,
-                egui::vec2(layout.width, node_instance.header_height + 70.0),
+                egui::vec2(
+                    layout.width,
+                    layout.header_height + 70.0 * canvas_state.zoom,
+                ),
             );
 
             // Check input pins - use centralized layout constants
             for (i, input_pin) in node_instance.inputs.iter().enumerate() {
                 let pin_radius = layout.pin_radius; // 6px radius from NodeLayout
-                let pin_x = -layout.pin_margin; // Slightly outside the node border
-                let pin_y =
-                    layout.header_height + layout.pin_spacing + (i as f32 * layout.pin_spacing); // Below header
-
-                let pin_center = header_rect.min + egui::vec2(pin_x, pin_y);
+                let pin_center =
+                    header_rect.min + vec2_to_pos2(layout.input_pin_offset(i)).to_vec2(); // Outside the left border, below header
                 let pin_rect = egui::Rect::from_center_size(
                     pin_center,
                     egui::vec2(pin_radius * 2.0, pin_radius * 2.0),
@@ -213,11 +352,8 @@ pub fn handle_pin_interactions_system(
             // Check output pins - use centralized layout constants
             for (i, output_pin) in node_instance.outputs.iter().enumerate() {
                 let pin_radius = layout.pin_radius; // 6px radius from NodeLayout
-                let pin_x = layout.width + layout.pin_margin; // Slightly outside the node border
-                let pin_y =
-                    layout.header_height + layout.pin_spacing + (i as f32 * layout.pin_spacing); // Below header
-
-                let pin_center = header_rect.min + egui::vec2(pin_x, pin_y);
+                let pin_center =
+                    header_rect.min + vec2_to_pos2(layout.output_pin_offset(i)).to_vec2(); // Outside the right border, below header
                 let pin_rect = egui::Rect::from_center_size(
                     pin_center,
                     egui::vec2(pin_radius * 2.0, pin_radius * 2.0),
@@ -242,23 +378,8 @@ pub fn handle_pin_interactions_system(
         if let Some((pin_id, is_input)) = clicked_pin {
             if is_input {
                 // Clicked on input pin - check if we have a pending connection
-                if let Some(pending) = &ui_state.pending_connection {
-                    // Connect to the input pin
-                    let new_connection = Connection {
-                        from_pin: pending.from_pin,
-                        to_pin: pin_id,
-                    };
-
-                    info!(
-                        "INTERACTION: Creating connection from pin {:?} to pin {:?}",
-                        pending.from_pin, pin_id
-                    );
-                    node_graph.add_connection(new_connection);
-                    info!(
-                        "INTERACTION: Connection created successfully. Total connections: {}",
-                        node_graph.connections.len()
-                    );
-                    ui_state.pending_connection = None;
+                if ui_state.pending_connection.is_some() {
+                    click_pending_connection(&mut ui_state, &pin_manager, &mut node_graph, pin_id);
                 } else {
                     info!(
                         "INTERACTION: Clicked input pin {:?} but no pending connection",
@@ -286,27 +407,41 @@ pub fn handle_pin_interactions_system(
     }
 }
 
-// Helper functions that avoid Vec2 type conflicts by using explicit conversions
-pub fn screen_to_canvas(screen_pos: egui::Pos2, canvas_state: &CanvasState) -> Vec2 {
-    // Use explicit scalar operations to prevent type confusion
-    let screen_x = screen_pos.x;
-    let screen_y = screen_pos.y;
+/// Connect the pending wire to the clicked input `to_pin`, checking the same rules as a wire
+/// released over the pin. An invalid input leaves the wire pending. Returns whether the
+/// connection was made
+pub fn click_pending_connection(
+    ui_state: &mut GraphUiState,
+    pin_manager: &PinPositionManager,
+    node_graph: &mut NodeGraph,
+    to_pin: PinId,
+) -> bool {
+    let Some(from_pin) = ui_state.pending_connection.as_ref().map(|p| p.from_pin) else {
+        return false;
+    };
+    if !pin_manager.can_connect_pins(from_pin, to_pin, node_graph) {
+        info!("INTERACTION: Invalid connection - rules not satisfied");
+        return false;
+    }
 
-    // Perform calculations with scalar values directly
-    let result_x = (screen_x / canvas_state.zoom) + canvas_state.offset.x;
-    let result_y = (screen_y / canvas_state.zoom) + canvas_state.offset.y;
+    info!(
+        "INTERACTION: Creating connection from pin {:?} to pin {:?}",
+        from_pin, to_pin
+    );
+    node_graph.add_connection(Connection { from_pin, to_pin });
+    info!(
+        "INTERACTION: Connection created successfully. Total connections: {}",
+        node_graph.connections.len()
+    );
+    ui_state.pending_connection = None;
+    true
+}
 
-    Vec2::new(result_x, result_y)
+// Helper functions that avoid Vec2 type conflicts by using explicit conversions
+pub fn screen_to_canvas(screen_pos: egui::Pos2, canvas_state: &CanvasState) -> Vec2 {
+    canvas::screen_to_canvas(pos2_to_vec2(screen_pos), canvas_state)
 }
 
 pub fn canvas_to_screen(canvas_pos: Vec2, canvas_state: &CanvasState) -> egui::Pos2 {
-    // Use explicit scalar operations to prevent type confusion
-    let canvas_x = canvas_pos.x;
-    let canvas_y = canvas_pos.y;
-
-    // Perform calculations with scalar values directly
-    let screen_x = (canvas_x - canvas_state.offset.x) * canvas_state.zoom;
-    let screen_y = (canvas_y - canvas_state.offset.y) * canvas_state.zoom;
-
-    egui::pos2(screen_x, screen_y)
+    vec2_to_pos2(canvas::canvas_to_screen(canvas_pos, canvas_state))
 }
@@ -0,0 +1,421 @@
+use crate::node_graph::canvas::CanvasSettings;
+use crate::node_graph::model::{NodeGraph, NodeId, PinId};
+use crate::node_graph::ui_state::GraphUiState;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Spacing used by the layered layout, in canvas units
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub struct LayoutSettings {
+    /// Horizontal gap between the widest node of a layer and the next layer
+    pub layer_gap: f32,
+    /// Vertical gap between nodes of the same layer
+    pub node_gap: f32,
+    /// Barycenter passes spent reducing crossings
+    pub sweeps: usize,
+}
+
+impl Default for LayoutSettings {
+    fn default() -> Self {
+        Self {
+            layer_gap: 96.0,
+            node_gap: 32.0,
+            sweeps: 12,
+        }
+    }
+}
+
+/// A node of the layered graph: a real node, or a dummy where a wire crosses a layer
+struct Vertex {
+    layer: usize,
+    size: Vec2,
+    /// Index into the laid out nodes; `None` for a dummy
+    node: Option<usize>,
+}
+
+/// Where a layered (Sugiyama) layout puts `node_ids`: layers run left to right from the
+/// nodes nothing feeds to the ones nothing reads, ordered within a layer to cross as few
+/// wires as possible. Node sizes set the spacing, and the result keeps the top-left corner
+/// the nodes had. Nodes outside `node_ids` stay where they are and their wires are ignored
+pub fn layered_layout(
+    graph: &NodeGraph,
+    node_ids: &[NodeId],
+    settings: &LayoutSettings,
+) -> Vec<(NodeId, Vec2)> {
+    let mut ids: Vec<NodeId> = node_ids
+        .iter()
+        .copied()
+        .filter(|id| graph.nodes.contains_key(id))
+        .collect();
+    ids.sort_by_key(|id| id.0);
+    ids.dedup();
+    if ids.is_empty() {
+        return Vec::new();
+    }
+    let edges = node_edges(graph, &ids);
+    let layers = assign_layers(ids.len(), &edges);
+
+    // Split wires spanning several layers with a dummy per crossed layer
+    let mut vertices: Vec<Vertex> = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| Vertex {
+            layer: layers[i],
+            size: graph.nodes[id].size,
+            node: Some(i),
+        })
+        .collect();
+    let mut links: Vec<(usize, usize)> = Vec::new();
+    for &(from, to) in &edges {
+        // Only a wire closing a cycle can join two nodes of one layer
+        if layers[from] == layers[to] {
+            continue;
+        }
+        let (from, to) = if layers[from] < layers[to] {
+            (from, to)
+        } else {
+            (to, from)
+        };
+        let mut previous = from;
+        for layer in layers[from] + 1..layers[to] {
+            vertices.push(Vertex {
+                layer,
+                size: Vec2::ZERO,
+                node: None,
+            });
+            links.push((previous, vertices.len() - 1));
+            previous = vertices.len() - 1;
+        }
+        links.push((previous, to));
+    }
+
+    // Start from the current top-to-bottom order, dummies following the node they leave
+    let layer_count = layers.iter().max().map_or(0, |max| max + 1);
+    let mut start_y = vec![0.0; vertices.len()];
+    for (v, vertex) in vertices.iter().enumerate() {
+        start_y[v] = match vertex.node {
+            Some(node) => graph.nodes[&ids[node]].position.y,
+            // Dummies are created after the vertex their link leaves
+            None => links
+                .iter()
+                .find(|(_, to)| *to == v)
+                .map_or(0.0, |(from, _)| start_y[*from]),
+        };
+    }
+    let mut order: Vec<Vec<usize>> = vec![Vec::new(); layer_count];
+    for (v, vertex) in vertices.iter().enumerate() {
+        order[vertex.layer].push(v);
+    }
+    for layer in &mut order {
+        layer.sort_by(|a, b| start_y[*a].total_cmp(&start_y[*b]));
+    }
+    let order = reduce_crossings(order, &links, vertices.len(), settings.sweeps);
+
+    let ys = place_in_layers(&order, &vertices, &links, settings.node_gap);
+    let mut xs = Vec::with_capacity(layer_count);
+    let mut x = 0.0;
+    for layer in &order {
+        xs.push(x);
+        let width = layer
+            .iter()
+            .map(|&v| vertices[v].size.x)
+            .fold(0.0, f32::max);
+        x += width + settings.layer_gap;
+    }
+
+    let mut positions: Vec<(NodeId, Vec2)> = vertices
+        .iter()
+        .enumerate()
+        .filter_map(|(v, vertex)| {
+            let node = vertex.node?;
+            Some((ids[node], Vec2::new(xs[vertex.layer], ys[v])))
+        })
+        .collect();
+    positions.sort_by_key(|(id, _)| id.0);
+
+    let before = ids
+        .iter()
+        .map(|id| graph.nodes[id].position)
+        .reduce(Vec2::min);
+    let after = positions.iter().map(|(_, p)| *p).reduce(Vec2::min);
+    if let (Some(before), Some(after)) = (before, after) {
+        for (_, position) in &mut positions {
+            *position += before - after;
+        }
+    }
+    positions
+}
+
+/// Wires between the nodes as index pairs, one per pair of nodes
+fn node_edges(graph: &NodeGraph, ids: &[NodeId]) -> Vec<(usize, usize)> {
+    let mut owners: HashMap<PinId, usize> = HashMap::new();
+    for (i, id) in ids.iter().enumerate() {
+        let node = &graph.nodes[id];
+        for pin in &node.inputs {
+            owners.insert(pin.pin_id, i);
+        }
+        for pin in &node.outputs {
+            owners.insert(pin.pin_id, i);
+        }
+    }
+    let mut edges: Vec<(usize, usize)> = graph
+        .connections
+        .iter()
+        .filter_map(|c| Some((*owners.get(&c.from_pin)?, *owners.get(&c.to_pin)?)))
+        .filter(|(from, to)| from != to)
+        .collect();
+    edges.sort();
+    edges.dedup();
+    edges
+}
+
+/// Longest-path layering: every node sits one layer right of its furthest source. Wires
+/// closing a cycle are left out, and a node fed by nothing moves up next to its first reader
+fn assign_layers(count: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut incoming = vec![0; count];
+    let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); count];
+    for &(from, to) in edges {
+        outgoing[from].push(to);
+        incoming[to] += 1;
+    }
+    let mut layers = vec![0; count];
+    let mut placed = vec![false; count];
+    let mut topological = Vec::with_capacity(count);
+    while topological.len() < count {
+        // A cycle leaves no node without inputs; break it at the lowest unplaced node
+        let mut ready: Vec<usize> = (0..count)
+            .filter(|&v| !placed[v] && incoming[v] == 0)
+            .collect();
+        if ready.is_empty() {
+            ready.extend((0..count).find(|&v| !placed[v]));
+        }
+        for v in ready {
+            placed[v] = true;
+            topological.push(v);
+            for &to in &outgoing[v] {
+                if !placed[to] {
+                    incoming[to] -= 1;
+                    layers[to] = layers[to].max(layers[v] + 1);
+                }
+            }
+        }
+    }
+    for &v in topological.iter().rev() {
+        let has_inputs = edges.iter().any(|&(_, to)| to == v);
+        let readers = outgoing[v].iter().map(|&to| layers[to]).min();
+        if !has_inputs && let Some(reader) = readers.filter(|&reader| reader > layers[v]) {
+            layers[v] = reader - 1;
+        }
+    }
+    layers
+}
+
+/// Reorder each layer by the mean position of its neighbours, sweeping right then left, and
+/// keep the order with the fewest crossings
+fn reduce_crossings(
+    mut order: Vec<Vec<usize>>,
+    links: &[(usize, usize)],
+    vertex_count: usize,
+    sweeps: usize,
+) -> Vec<Vec<usize>> {
+    let mut best = order.clone();
+    let mut best_crossings = count_crossings(&order, links, vertex_count);
+    for sweep in 0..sweeps {
+        let rightwards = sweep % 2 == 0;
+        let layer_indices: Vec<usize> = if rightwards {
+            (1..order.len()).collect()
+        } else {
+            (0..order.len().saturating_sub(1)).rev().collect()
+        };
+        for layer in layer_indices {
+            let index = positions_in_layers(&order, vertex_count);
+            let barycenter = |v: usize| -> f32 {
+                let neighbours: Vec<f32> = neighbours(links, v, rightwards)
+                    .map(|n| index[n] as f32)
+                    .collect();
+                if neighbours.is_empty() {
+                    index[v] as f32
+                } else {
+                    neighbours.iter().sum::<f32>() / neighbours.len() as f32
+                }
+            };
+            let mut keyed: Vec<(f32, usize)> =
+                order[layer].iter().map(|&v| (barycenter(v), v)).collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            order[layer] = keyed.into_iter().map(|(_, v)| v).collect();
+        }
+        let crossings = count_crossings(&order, links, vertex_count);
+        if crossings < best_crossings {
+            best = order.clone();
+            best_crossings = crossings;
+        }
+    }
+    best
+}
+
+/// Vertices linked to `v` from the layer on its left, or from the one on its right
+fn neighbours(links: &[(usize, usize)], v: usize, left: bool) -> impl Iterator<Item = usize> + '_ {
+    links.iter().filter_map(move |&(from, to)| {
+        if left {
+            (to == v).then_some(from)
+        } else {
+            (from == v).then_some(to)
+        }
+    })
+}
+
+fn positions_in_layers(order: &[Vec<usize>], vertex_count: usize) -> Vec<usize> {
+    let mut index = vec![0; vertex_count];
+    for layer in order {
+        for (i, &v) in layer.iter().enumerate() {
+            index[v] = i;
+        }
+    }
+    index
+}
+
+/// Pairs of links between neighbouring layers that cross
+pub(crate) fn count_crossings(
+    order: &[Vec<usize>],
+    links: &[(usize, usize)],
+    vertex_count: usize,
+) -> usize {
+    let index = positions_in_layers(order, vertex_count);
+    let mut crossings = 0;
+    for (i, &(a_from, a_to)) in links.iter().enumerate() {
+        for &(b_from, b_to) in &links[i + 1..] {
+            let from = index[a_from] as i64 - index[b_from] as i64;
+            let to = index[a_to] as i64 - index[b_to] as i64;
+            if from * to < 0 {
+                crossings += 1;
+            }
+        }
+    }
+    crossings
+}
+
+/// Vertical positions: each layer stacked in order with `gap` between nodes, then nudged
+/// towards the nodes it is wired to without letting nodes overlap
+fn place_in_layers(
+    order: &[Vec<usize>],
+    vertices: &[Vertex],
+    links: &[(usize, usize)],
+    gap: f32,
+) -> Vec<f32> {
+    let mut ys = vec![0.0; vertices.len()];
+    for layer in order {
+        let mut y = 0.0;
+        for &v in layer {
+            ys[v] = y;
+            y += vertices[v].size.y + gap;
+        }
+        let height = y - gap;
+        for &v in layer {
+            ys[v] -= height / 2.0;
+        }
+    }
+
+    for pass in 0..4 {
+        let rightwards = pass % 2 == 0;
+        let layer_indices: Vec<usize> = if rightwards {
+            (1..order.len()).collect()
+        } else {
+            (0..order.len().saturating_sub(1)).rev().collect()
+        };
+        for layer in layer_indices {
+            let centre = |v: usize, ys: &[f32]| ys[v] + vertices[v].size.y / 2.0;
+            let wanted: Vec<f32> = order[layer]
+                .iter()
+                .map(|&v| {
+                    let neighbours: Vec<f32> = neighbours(links, v, rightwards)
+                        .map(|n| centre(n, &ys))
+                        .collect();
+                    if neighbours.is_empty() {
+                        centre(v, &ys)
+                    } else {
+                        neighbours.iter().sum::<f32>() / neighbours.len() as f32
+                    }
+                })
+                .collect();
+            // Top to bottom, each node as close to where it wants to be as the one above
+            // allows, then the layer shifted so the nodes are on average where they want
+            let mut bottom = f32::NEG_INFINITY;
+            let mut drift = 0.0;
+            for (&v, wanted) in order[layer].iter().zip(&wanted) {
+                let top = (wanted - vertices[v].size.y / 2.0).max(bottom);
+                ys[v] = top;
+                bottom = top + vertices[v].size.y + gap;
+                drift += wanted - centre(v, &ys);
+            }
+            let shift = drift / order[layer].len().max(1) as f32;
+            for &v in &order[layer] {
+                ys[v] += shift;
+            }
+        }
+    }
+    ys
+}
+
+/// Nodes gliding to new positions after an auto layout, undo or redo; grabbing a node stops
+/// them where they are
+#[derive(Debug, Clone, Default, Resource)]
+pub struct NodeAnimation {
+    pub targets: HashMap<NodeId, Vec2>,
+}
+
+impl NodeAnimation {
+    pub fn start(&mut self, targets: impl IntoIterator<Item = (NodeId, Vec2)>) {
+        self.targets.extend(targets);
+    }
+
+    /// Stop moving deleted nodes, whose ids a new node may take
+    pub fn forget(&mut self, node_ids: &[NodeId]) {
+        self.targets
+            .retain(|node_id, _| !node_ids.contains(node_id));
+    }
+
+    /// Put every node where it is heading
+    pub fn finish(&mut self, graph: &mut NodeGraph) {
+        for (node_id, target) in self.targets.drain() {
+            if let Some(node) = graph.nodes.get_mut(&node_id) {
+                node.position = target;
+            }
+        }
+    }
+
+    /// Move every node the fraction `t` of the way to its target, landing once close.
+    /// Returns whether all of them got there
+    pub fn step(&mut self, graph: &mut NodeGraph, t: f32) -> bool {
+        self.targets.retain(|node_id, target| {
+            let Some(node) = graph.nodes.get_mut(node_id) else {
+                return false;
+            };
+            node.position = node.position.lerp(*target, t);
+            if node.position.distance(*target) < 0.5 {
+                node.position = *target;
+                return false;
+            }
+            true
+        });
+        self.targets.is_empty()
+    }
+}
+
+/// Glide animated nodes towards their targets at the canvas animation rate
+pub fn animate_nodes_system(
+    time: Res<Time>,
+    settings: Res<CanvasSettings>,
+    ui_state: Res<GraphUiState>,
+    mut animation: ResMut<NodeAnimation>,
+    mut node_graph: ResMut<NodeGraph>,
+) {
+    if animation.targets.is_empty() {
+        return;
+    }
+    if ui_state.active_drag_node.is_some() {
+        animation.targets.clear();
+        return;
+    }
+    let t = 1.0 - (-settings.animation_rate * time.delta_secs()).exp();
+    animation.step(&mut node_graph, t);
+}
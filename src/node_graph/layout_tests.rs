@@ -0,0 +1,157 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::layout::{
+        LayoutSettings, NodeAnimation, count_crossings, layered_layout,
+    };
+    use crate::node_graph::model::{NodeGraph, NodeId};
+    use crate::node_graph::node_factory::{MathOperation, NodeFactory};
+    use crate::node_graph::test_utils::{add_node_at, connect};
+    use crate::shader_graph::node_kind::NodeKind;
+    use bevy::prelude::*;
+    use std::collections::HashMap;
+
+    /// Stacked the way the spawn shortcuts place nodes
+    fn add_node(graph: &mut NodeGraph, kind: NodeKind) -> NodeId {
+        let next = NodeFactory::get_next_node_id(graph).0 as f32;
+        add_node_at(graph, kind, Vec2::new(next * 40.0, next * 20.0))
+    }
+
+    fn add(graph: &mut NodeGraph) -> NodeId {
+        add_node(graph, NodeKind::Math(MathOperation::Add))
+    }
+
+    fn positions(graph: &NodeGraph, ids: &[NodeId]) -> HashMap<NodeId, Vec2> {
+        layered_layout(graph, ids, &LayoutSettings::default())
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_layers_run_from_inputs_to_outputs() {
+        let mut graph = NodeGraph::new();
+        let a = add_node(&mut graph, NodeKind::Constant(1.0));
+        let first = add(&mut graph);
+        let second = add(&mut graph);
+        let late = add_node(&mut graph, NodeKind::Constant(2.0));
+        connect(&mut graph, a, 0, first, 0);
+        connect(&mut graph, first, 0, second, 0);
+        connect(&mut graph, late, 0, second, 1);
+
+        let all = [a, first, second, late];
+        let laid_out = positions(&graph, &all);
+        assert_eq!(laid_out.len(), 4);
+        assert!(laid_out[&a].x < laid_out[&first].x);
+        assert!(laid_out[&first].x < laid_out[&second].x);
+        // A constant read only by the last node sits right before it, not with `a`
+        assert_eq!(laid_out[&late].x, laid_out[&first].x);
+
+        // The arranged nodes keep the top-left corner of the stack they came from
+        let corner = laid_out.values().copied().reduce(Vec2::min).unwrap();
+        assert_eq!(corner, Vec2::new(40.0, 20.0));
+    }
+
+    #[test]
+    fn test_spacing_follows_node_sizes() {
+        let mut graph = NodeGraph::new();
+        let wide = add_node(&mut graph, NodeKind::Constant(1.0));
+        let tall = add_node(&mut graph, NodeKind::Constant(2.0));
+        let sum = add(&mut graph);
+        connect(&mut graph, wide, 0, sum, 0);
+        connect(&mut graph, tall, 0, sum, 1);
+        graph.nodes.get_mut(&wide).unwrap().size = Vec2::new(500.0, 80.0);
+        graph.nodes.get_mut(&tall).unwrap().size = Vec2::new(150.0, 400.0);
+
+        let settings = LayoutSettings::default();
+        let laid_out = positions(&graph, &[wide, tall, sum]);
+        assert_eq!(laid_out[&wide].x, laid_out[&tall].x);
+        assert_eq!(
+            laid_out[&sum].x - laid_out[&wide].x,
+            500.0 + settings.layer_gap
+        );
+
+        // Stacked in their layer without overlapping
+        let (upper, lower) = if laid_out[&wide].y < laid_out[&tall].y {
+            (wide, tall)
+        } else {
+            (tall, wide)
+        };
+        let upper_bottom = laid_out[&upper].y + graph.nodes[&upper].size.y;
+        assert!(laid_out[&lower].y - upper_bottom >= settings.node_gap - 1e-3);
+    }
+
+    #[test]
+    fn test_crossed_wires_are_untangled() {
+        let mut graph = NodeGraph::new();
+        let top = add_node(&mut graph, NodeKind::Constant(1.0));
+        let bottom = add_node(&mut graph, NodeKind::Constant(2.0));
+        let reads_bottom = add(&mut graph);
+        let reads_top = add(&mut graph);
+        connect(&mut graph, top, 0, reads_top, 0);
+        connect(&mut graph, bottom, 0, reads_bottom, 0);
+
+        // `reads_bottom` starts above `reads_top`, so the wires cross
+        let laid_out = positions(&graph, &[top, bottom, reads_bottom, reads_top]);
+        let sources_down = laid_out[&top].y < laid_out[&bottom].y;
+        let readers_down = laid_out[&reads_top].y < laid_out[&reads_bottom].y;
+        assert_eq!(sources_down, readers_down);
+    }
+
+    #[test]
+    fn test_count_crossings() {
+        // Layer 0 holds 0 and 1, layer 1 holds 2 and 3
+        let links = [(0, 3), (1, 2)];
+        assert_eq!(count_crossings(&[vec![0, 1], vec![2, 3]], &links, 4), 1);
+        assert_eq!(count_crossings(&[vec![0, 1], vec![3, 2]], &links, 4), 0);
+    }
+
+    #[test]
+    fn test_selection_only_and_cycles() {
+        let mut graph = NodeGraph::new();
+        let outside = add_node(&mut graph, NodeKind::Constant(1.0));
+        let a = add(&mut graph);
+        let b = add(&mut graph);
+        connect(&mut graph, outside, 0, a, 0);
+        connect(&mut graph, a, 0, b, 0);
+        connect(&mut graph, b, 0, a, 1);
+
+        let laid_out = positions(&graph, &[a, b, NodeId(99)]);
+        assert_eq!(laid_out.len(), 2);
+        assert!(!laid_out.contains_key(&outside));
+        assert_ne!(laid_out[&a].x, laid_out[&b].x);
+        assert!(positions(&graph, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_animation_lands_on_targets() {
+        let mut graph = NodeGraph::new();
+        let a = add_node(&mut graph, NodeKind::Constant(1.0));
+        let b = add_node(&mut graph, NodeKind::Constant(2.0));
+        let mut animation = NodeAnimation::default();
+        animation.start([(a, Vec2::new(400.0, 0.0)), (NodeId(99), Vec2::ZERO)]);
+
+        assert!(!animation.step(&mut graph, 0.5));
+        assert_eq!(graph.nodes[&a].position, Vec2::new(220.0, 10.0));
+        // Nodes that no longer exist are dropped
+        assert_eq!(animation.targets.len(), 1);
+        while !animation.step(&mut graph, 0.5) {}
+        assert_eq!(graph.nodes[&a].position, Vec2::new(400.0, 0.0));
+
+        animation.start([(b, Vec2::new(-50.0, 60.0))]);
+        animation.finish(&mut graph);
+        assert!(animation.targets.is_empty());
+        assert_eq!(graph.nodes[&b].position, Vec2::new(-50.0, 60.0));
+    }
+
+    #[test]
+    fn test_animation_forgets_deleted_nodes() {
+        let mut graph = NodeGraph::new();
+        let a = add_node(&mut graph, NodeKind::Constant(1.0));
+        let b = add_node(&mut graph, NodeKind::Constant(2.0));
+        let mut animation = NodeAnimation::default();
+        animation.start([(a, Vec2::new(400.0, 0.0)), (b, Vec2::new(0.0, 400.0))]);
+
+        animation.forget(&[b]);
+        assert_eq!(animation.targets.len(), 1);
+        assert!(animation.targets.contains_key(&a));
+    }
+}
@@ -0,0 +1,208 @@
+use crate::node_graph::canvas::{CanvasAnimation, nodes_bounds, screen_to_canvas};
+use crate::node_graph::model::{CanvasState, NodeGraph, NodeId, NodeLayout, PinId};
+use crate::node_graph::selection::node_rect;
+use crate::node_graph::ui_state::GraphUiState;
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use std::collections::HashMap;
+
+/// Screen size of the minimap
+const MINIMAP_SIZE: egui::Vec2 = egui::vec2(240.0, 160.0);
+/// Canvas units of empty space kept around the graph in the minimap
+const MINIMAP_PADDING: f32 = 100.0;
+
+/// The corner minimap, toggled with B or from the View window
+#[derive(Resource)]
+pub struct Minimap {
+    pub visible: bool,
+    /// Node rectangles and wires, rebuilt only when the graph is edited or a node is drawn at
+    /// a new size
+    pub cache: MinimapCache,
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            cache: MinimapCache::default(),
+        }
+    }
+}
+
+/// What the minimap draws, in canvas space
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MinimapCache {
+    /// Everything drawn, padded; `None` for an empty graph
+    pub bounds: Option<Rect>,
+    pub nodes: Vec<(NodeId, Rect)>,
+    /// From output pin to input pin
+    pub wires: Vec<(Vec2, Vec2)>,
+}
+
+impl MinimapCache {
+    pub fn build(graph: &NodeGraph) -> Self {
+        let layout = NodeLayout::default();
+        let mut pins: HashMap<PinId, Vec2> = HashMap::new();
+        let mut nodes = Vec::with_capacity(graph.nodes.len());
+        for node in graph.nodes.values() {
+            nodes.push((node.node_id, node_rect(node)));
+            for (i, pin) in node.inputs.iter().enumerate() {
+                pins.insert(pin.pin_id, node.position + layout.input_pin_offset(i));
+            }
+            for (i, pin) in node.outputs.iter().enumerate() {
+                pins.insert(pin.pin_id, node.position + layout.output_pin_offset(i));
+            }
+        }
+        nodes.sort_by_key(|(id, _)| id.0);
+        let wires = graph
+            .connections
+            .iter()
+            .filter_map(|c| Some((*pins.get(&c.from_pin)?, *pins.get(&c.to_pin)?)))
+            .collect();
+        Self {
+            bounds: nodes_bounds(graph.nodes.values()).map(|b| b.inflate(MINIMAP_PADDING)),
+            nodes,
+            wires,
+        }
+    }
+}
+
+/// Maps canvas space onto the minimap: `bounds` fitted into `rect`, centred, aspect kept
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinimapTransform {
+    pub bounds: Rect,
+    pub rect: Rect,
+    pub scale: f32,
+}
+
+impl MinimapTransform {
+    pub fn new(bounds: Rect, rect: Rect) -> Self {
+        let scale = (rect.size() / bounds.size().max(Vec2::ONE)).min_element();
+        Self {
+            bounds,
+            rect,
+            scale,
+        }
+    }
+
+    pub fn to_minimap(self, canvas_pos: Vec2) -> Vec2 {
+        self.rect.center() + (canvas_pos - self.bounds.center()) * self.scale
+    }
+
+    pub fn to_canvas(self, minimap_pos: Vec2) -> Vec2 {
+        self.bounds.center() + (minimap_pos - self.rect.center()) / self.scale
+    }
+}
+
+/// Canvas-space rectangle the screen-space `viewport` shows
+pub fn visible_canvas_rect(canvas_state: &CanvasState, viewport: Rect) -> Rect {
+    Rect::from_corners(
+        screen_to_canvas(viewport.min, canvas_state),
+        screen_to_canvas(viewport.max, canvas_state),
+    )
+}
+
+/// Pan so the canvas point `target` is in the middle of `viewport`, keeping the zoom
+pub fn centre_on(canvas_state: &mut CanvasState, target: Vec2, viewport: Rect) {
+    let centre = screen_to_canvas(viewport.center(), canvas_state);
+    canvas_state.offset += target - centre;
+}
+
+fn to_pos2(v: Vec2) -> egui::Pos2 {
+    egui::pos2(v.x, v.y)
+}
+
+fn to_rect(rect: egui::Rect) -> Rect {
+    Rect::new(rect.min.x, rect.min.y, rect.max.x, rect.max.y)
+}
+
+/// Corner minimap of the whole graph with the visible part outlined; clicking or dragging in
+/// it moves the view there
+pub fn minimap_system(
+    mut node_graph: ResMut<NodeGraph>,
+    mut minimap: ResMut<Minimap>,
+    mut animation: ResMut<CanvasAnimation>,
+    ui_state: Res<GraphUiState>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    mut egui_contexts: EguiContexts,
+) {
+    let ctx = egui_contexts.ctx_mut().expect("Failed to get egui context");
+    if !ctx.wants_keyboard_input() && key_input.just_pressed(KeyCode::KeyB) {
+        minimap.visible = !minimap.visible;
+    }
+    if !minimap.visible {
+        return;
+    }
+    // Panning and zooming leave the graph unchanged, while drawing a node at a new size and
+    // toggling the minimap on mark the minimap changed. Storing the cache doesn't, so an
+    // untouched graph is not walked again
+    if node_graph.is_changed() || minimap.is_changed() {
+        minimap.bypass_change_detection().cache = MinimapCache::build(&node_graph);
+    }
+    let Some(bounds) = minimap.cache.bounds else {
+        return;
+    };
+
+    let viewport = to_rect(ctx.viewport_rect());
+    let mut jump_to: Option<Vec2> = None;
+    egui::Area::new(egui::Id::new("minimap"))
+        .order(egui::Order::Foreground)
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-16.0, -16.0))
+        .show(ctx, |ui| {
+            let (response, painter) =
+                ui.allocate_painter(MINIMAP_SIZE, egui::Sense::click_and_drag());
+            let transform = MinimapTransform::new(bounds, to_rect(response.rect));
+            let map = |v: Vec2| to_pos2(transform.to_minimap(v));
+
+            painter.rect(
+                response.rect,
+                4.0,
+                egui::Color32::from_black_alpha(200),
+                egui::Stroke::new(1.0, egui::Color32::from_gray(90)),
+                egui::StrokeKind::Inside,
+            );
+            let painter = painter.with_clip_rect(response.rect);
+
+            let wire = egui::Stroke::new(1.0, egui::Color32::from_gray(110));
+            for &(from, to) in &minimap.cache.wires {
+                painter.line_segment([map(from), map(to)], wire);
+            }
+            for (node_id, rect) in &minimap.cache.nodes {
+                let color = if ui_state.is_selected(*node_id) {
+                    egui::Color32::from_rgb(255, 200, 80)
+                } else {
+                    egui::Color32::from_gray(160)
+                };
+                painter.rect_filled(
+                    egui::Rect::from_min_max(map(rect.min), map(rect.max)),
+                    1.0,
+                    color,
+                );
+            }
+
+            let visible = visible_canvas_rect(&node_graph.canvas_state, viewport);
+            painter.rect_stroke(
+                egui::Rect::from_min_max(map(visible.min), map(visible.max)),
+                0.0,
+                egui::Stroke::new(1.5, egui::Color32::WHITE),
+                egui::StrokeKind::Middle,
+            );
+
+            if (response.clicked() || response.dragged())
+                && let Some(pointer) = response.interact_pointer_pos()
+            {
+                jump_to = Some(transform.to_canvas(Vec2::new(pointer.x, pointer.y)));
+            }
+        });
+
+    if let Some(target) = jump_to {
+        animation.target = None;
+        // Moving the view is not an edit, see `update_canvas_system`
+        centre_on(
+            &mut node_graph.bypass_change_detection().canvas_state,
+            target,
+            viewport,
+        );
+        debug!("MINIMAP: Centred the view on {:?}", target);
+    }
+}
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::canvas::screen_to_canvas;
+    use crate::node_graph::minimap::{
+        MinimapCache, MinimapTransform, centre_on, visible_canvas_rect,
+    };
+    use crate::node_graph::model::{CanvasState, Connection, NodeGraph, NodeId, NodeLayout};
+    use crate::node_graph::node_factory::MathOperation;
+    use crate::node_graph::test_utils::{add_node_at, assert_near};
+    use crate::shader_graph::node_kind::NodeKind;
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_cache_holds_nodes_wires_and_padded_bounds() {
+        let mut graph = NodeGraph::new();
+        assert_eq!(MinimapCache::build(&graph), MinimapCache::default());
+
+        let a = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::new(0.0, 0.0));
+        let add = add_node_at(
+            &mut graph,
+            NodeKind::Math(MathOperation::Add),
+            Vec2::new(400.0, 200.0),
+        );
+        graph.add_connection(Connection {
+            from_pin: graph.nodes[&a].outputs[0].pin_id,
+            to_pin: graph.nodes[&add].inputs[1].pin_id,
+        });
+
+        let cache = MinimapCache::build(&graph);
+        let ids: Vec<NodeId> = cache.nodes.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![a, add]);
+        assert_eq!(cache.nodes[1].1.min, Vec2::new(400.0, 200.0));
+
+        let layout = NodeLayout::default();
+        assert_eq!(
+            cache.wires,
+            vec![(
+                layout.output_pin_offset(0),
+                Vec2::new(400.0, 200.0) + layout.input_pin_offset(1)
+            )]
+        );
+
+        let bounds = cache.bounds.unwrap();
+        let size = graph.nodes[&add].size;
+        assert_eq!(bounds.min, Vec2::splat(-100.0));
+        assert_eq!(bounds.max, Vec2::new(400.0, 200.0) + size + 100.0);
+    }
+
+    #[test]
+    fn test_transform_fits_bounds_and_round_trips() {
+        // Twice as wide as the minimap is tall, so the width decides the scale
+        let bounds = Rect::new(-500.0, -100.0, 500.0, 100.0);
+        let rect = Rect::new(10.0, 10.0, 250.0, 170.0);
+        let transform = MinimapTransform::new(bounds, rect);
+        assert_eq!(transform.scale, 0.24);
+
+        assert_near(transform.to_minimap(Vec2::ZERO), rect.center());
+        assert_near(
+            transform.to_minimap(bounds.min),
+            Vec2::new(10.0, 90.0 - 24.0),
+        );
+        let point = Vec2::new(123.0, -45.0);
+        assert_near(transform.to_canvas(transform.to_minimap(point)), point);
+    }
+
+    #[test]
+    fn test_visible_rect_follows_pan_and_zoom() {
+        let viewport = Rect::new(0.0, 0.0, 800.0, 600.0);
+        let canvas_state = CanvasState {
+            zoom: 2.0,
+            offset: Vec2::new(100.0, 50.0),
+        };
+
+        let visible = visible_canvas_rect(&canvas_state, viewport);
+        assert_eq!(visible.min, Vec2::new(100.0, 50.0));
+        assert_eq!(visible.max, Vec2::new(500.0, 350.0));
+    }
+
+    #[test]
+    fn test_centre_on_keeps_zoom() {
+        let viewport = Rect::new(0.0, 0.0, 800.0, 600.0);
+        let mut canvas_state = CanvasState {
+            zoom: 0.5,
+            offset: Vec2::ZERO,
+        };
+
+        let target = Vec2::new(1000.0, -300.0);
+        centre_on(&mut canvas_state, target, viewport);
+        assert_eq!(canvas_state.zoom, 0.5);
+        assert_near(screen_to_canvas(viewport.center(), &canvas_state), target);
+    }
+}
@@ -1,18 +1,41 @@
+pub mod annotations;
 pub mod canvas;
+pub mod clipboard;
+pub mod history;
 pub mod interactions;
+pub mod layout;
+pub mod minimap;
 pub mod model;
+pub mod node_body;
 pub mod node_factory;
 pub mod pin_manager;
 pub mod render;
+pub mod selection;
 pub mod ui_state;
 
+/// Graph building and shader checks shared by the tests
+#[cfg(test)]
+pub mod test_utils;
+
+#[cfg(test)]
+mod annotations_tests;
 #[cfg(test)]
 mod canvas_tests;
 #[cfg(test)]
+mod clipboard_tests;
+#[cfg(test)]
+mod history_tests;
+#[cfg(test)]
+mod layout_tests;
+#[cfg(test)]
+mod minimap_tests;
+#[cfg(test)]
 mod model_tests;
 #[cfg(test)]
 mod node_factory_tests;
 #[cfg(test)]
 mod pin_manager_tests;
 #[cfg(test)]
+mod selection_tests;
+#[cfg(test)]
 mod ui_state_tests;
@@ -1,4 +1,7 @@
+use crate::shader_graph::node_kind::{NodeKind, ShaderNode};
+use crate::shader_graph::types::PinType;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Centralized layout constants for node rendering - single source of truth
@@ -29,28 +32,59 @@ impl Default for NodeLayout {
     }
 }
 
+impl NodeLayout {
+    /// The layout as drawn at `zoom`: every length scaled
+    pub fn scaled(&self, zoom: f32) -> Self {
+        Self {
+            width: self.width * zoom,
+            min_height: self.min_height * zoom,
+            header_height: self.header_height * zoom,
+            pin_radius: self.pin_radius * zoom,
+            input_node_offset: self.input_node_offset * zoom,
+            output_node_offset: self.output_node_offset * zoom,
+            pin_spacing: self.pin_spacing * zoom,
+            pin_margin: self.pin_margin * zoom,
+        }
+    }
+
+    /// Centre of the input pin at `index`, from the node's top-left corner: just outside the
+    /// left border, one row per pin below the header
+    pub fn input_pin_offset(&self, index: usize) -> Vec2 {
+        Vec2::new(-self.pin_margin, self.pin_row(index))
+    }
+
+    /// Centre of the output pin at `index`, just outside the right border
+    pub fn output_pin_offset(&self, index: usize) -> Vec2 {
+        Vec2::new(self.width + self.pin_margin, self.pin_row(index))
+    }
+
+    fn pin_row(&self, index: usize) -> f32 {
+        self.header_height + self.pin_spacing + index as f32 * self.pin_spacing
+    }
+}
+
 // Unique identifiers for nodes and pins
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct NodeId(pub u32);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct PinId(pub u32);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct InputPin {
     pub pin_id: PinId,
     pub label: String,
     pub parent_node: NodeId,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct OutputPin {
     pub pin_id: PinId,
     pub label: String,
     pub parent_node: NodeId,
 }
 
-#[derive(Debug, Clone, Component)]
+#[derive(Debug, Clone, PartialEq, Component, Serialize, Deserialize)]
 pub struct NodeInstance {
     pub node_id: NodeId,
     pub position: Vec2,
@@ -60,22 +94,63 @@ pub struct NodeInstance {
     pub size: Vec2,
     pub header_height: f32,
     pub pin_offsets: (Vec<(PinId, Vec2)>, Vec<(PinId, Vec2)>), // (input_pin_offsets, output_pin_offsets)
+    pub kind: NodeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinDirection {
+    Input,
+    Output,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Connection {
     pub from_pin: PinId,
     pub to_pin: PinId,
 }
 
-#[derive(Debug, Clone, Resource, Default)]
+/// Id shared by comment frames and sticky notes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AnnotationId(pub u32);
+
+/// Titled, coloured box drawn behind the nodes; dragging its title bar carries the nodes
+/// inside it along
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommentFrame {
+    pub id: AnnotationId,
+    pub title: String,
+    pub position: Vec2,
+    pub size: Vec2,
+    /// RGB
+    pub color: [u8; 3],
+}
+
+/// Free-floating note. The text is shown with light markdown: `#` headings, `-` bullets,
+/// `**bold**`, `*italics*` and `` `code` ``
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StickyNote {
+    pub id: AnnotationId,
+    pub text: String,
+    pub position: Vec2,
+    pub size: Vec2,
+    /// RGB
+    pub color: [u8; 3],
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Default, Serialize, Deserialize)]
 pub struct NodeGraph {
     pub nodes: HashMap<NodeId, NodeInstance>,
     pub connections: Vec<Connection>,
     pub canvas_state: CanvasState,
+    /// Drawn in order, so later frames sit on top of earlier ones. Missing from graphs saved
+    /// before there were frames
+    #[serde(default)]
+    pub frames: Vec<CommentFrame>,
+    #[serde(default)]
+    pub notes: Vec<StickyNote>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CanvasState {
     pub zoom: f32,
     pub offset: Vec2,
@@ -96,6 +171,8 @@ impl NodeGraph {
             nodes: HashMap::new(),
             connections: Vec::new(),
             canvas_state: CanvasState::default(),
+            frames: Vec::new(),
+            notes: Vec::new(),
         }
     }
 
@@ -104,12 +181,18 @@ impl NodeGraph {
     }
 
     pub fn remove_node(&mut self, node_id: NodeId) {
-        self.nodes.remove(&node_id);
+        let Some(node) = self.nodes.remove(&node_id) else {
+            return;
+        };
         // Remove connections related to this node
-        self.connections.retain(|_conn| {
-            // Check if the nodes connected to this connection still exist
-            true // Placeholder - actual implementation would check node existence
-        });
+        let pins: Vec<PinId> = node
+            .inputs
+            .iter()
+            .map(|p| p.pin_id)
+            .chain(node.outputs.iter().map(|p| p.pin_id))
+            .collect();
+        self.connections
+            .retain(|conn| !pins.contains(&conn.from_pin) && !pins.contains(&conn.to_pin));
     }
 
     pub fn add_connection(&mut self, connection: Connection) {
@@ -119,4 +202,33 @@ impl NodeGraph {
     pub fn remove_connection(&mut self, connection: &Connection) {
         self.connections.retain(|c| c != connection);
     }
+
+    /// Locate a pin: owning node, which side it is on and its index on that side
+    pub fn find_pin(&self, pin_id: PinId) -> Option<(NodeId, PinDirection, usize)> {
+        self.nodes.values().find_map(|node| {
+            if let Some(index) = node.inputs.iter().position(|p| p.pin_id == pin_id) {
+                return Some((node.node_id, PinDirection::Input, index));
+            }
+            node.outputs
+                .iter()
+                .position(|p| p.pin_id == pin_id)
+                .map(|index| (node.node_id, PinDirection::Output, index))
+        })
+    }
+
+    /// Data type of a pin, read off its node's signature
+    pub fn pin_type(&self, pin_id: PinId) -> Option<PinType> {
+        let (node_id, direction, index) = self.find_pin(pin_id)?;
+        let signature = self.nodes[&node_id].kind.signature();
+        let pins = match direction {
+            PinDirection::Input => signature.inputs,
+            PinDirection::Output => signature.outputs,
+        };
+        pins.get(index).map(|spec| spec.ty)
+    }
+
+    /// The connection feeding an input pin, if any
+    pub fn connection_into(&self, to_pin: PinId) -> Option<&Connection> {
+        self.connections.iter().find(|c| c.to_pin == to_pin)
+    }
 }
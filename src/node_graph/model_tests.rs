@@ -4,6 +4,7 @@ mod tests {
         CanvasState, Connection, InputPin, NodeGraph, NodeId, NodeInstance, NodeLayout, OutputPin,
         PinId,
     };
+    use crate::shader_graph::node_kind::NodeKind;
     use bevy::prelude::*;
 
     #[test]
@@ -19,6 +20,23 @@ mod tests {
         assert_eq!(layout.pin_margin, 6.0);
     }
 
+    #[test]
+    fn test_node_layout_scaled_pin_offsets() {
+        let layout = NodeLayout::default();
+        assert_eq!(layout.input_pin_offset(0), Vec2::new(-6.0, 44.0));
+        assert_eq!(layout.output_pin_offset(2), Vec2::new(226.0, 84.0));
+
+        let zoomed = layout.scaled(2.0);
+        assert_eq!(zoomed.width, 440.0);
+        assert_eq!(zoomed.pin_radius, 12.0);
+        // Every part of a pin's offset scales together
+        assert_eq!(zoomed.input_pin_offset(1), layout.input_pin_offset(1) * 2.0);
+        assert_eq!(
+            zoomed.output_pin_offset(1),
+            layout.output_pin_offset(1) * 2.0
+        );
+    }
+
     #[test]
     fn test_node_layout_clone() {
         let layout = NodeLayout::default();
@@ -164,6 +182,7 @@ mod tests {
             size: Vec2::new(220.0, 100.0),
             header_height: 24.0,
             pin_offsets: (vec![], vec![]),
+            kind: NodeKind::Test,
         };
 
         assert_eq!(node.node_id, node_id);
@@ -199,6 +218,7 @@ mod tests {
             size: Vec2::new(220.0, 100.0),
             header_height: 24.0,
             pin_offsets: (vec![], vec![]),
+            kind: NodeKind::Test,
         };
 
         let cloned = node.clone();
@@ -324,6 +344,7 @@ mod tests {
             size: Vec2::new(220.0, 100.0),
             header_height: 24.0,
             pin_offsets: (vec![], vec![]),
+            kind: NodeKind::Test,
         };
 
         graph.add_node(node);
@@ -355,6 +376,7 @@ mod tests {
             size: Vec2::new(220.0, 100.0),
             header_height: 24.0,
             pin_offsets: (vec![], vec![]),
+            kind: NodeKind::Test,
         };
 
         graph.add_node(node);
@@ -365,6 +387,53 @@ mod tests {
         assert!(!graph.nodes.contains_key(&node_id));
     }
 
+    #[test]
+    fn test_remove_node_drops_its_connections() {
+        let mut graph = NodeGraph::new();
+        for (id, pins) in [(1, (1, 2)), (2, (3, 4)), (3, (5, 6))] {
+            let node_id = NodeId(id);
+            graph.add_node(NodeInstance {
+                node_id,
+                position: Vec2::ZERO,
+                inputs: vec![InputPin {
+                    pin_id: PinId(pins.0),
+                    label: "Input".to_string(),
+                    parent_node: node_id,
+                }],
+                outputs: vec![OutputPin {
+                    pin_id: PinId(pins.1),
+                    label: "Output".to_string(),
+                    parent_node: node_id,
+                }],
+                title: format!("Node {}", id),
+                size: Vec2::new(220.0, 100.0),
+                header_height: 24.0,
+                pin_offsets: (vec![], vec![]),
+                kind: NodeKind::Test,
+            });
+        }
+        // 1 -> 2 -> 3, and 1 -> 3
+        let keep = Connection {
+            from_pin: PinId(2),
+            to_pin: PinId(5),
+        };
+        graph.add_connection(Connection {
+            from_pin: PinId(2),
+            to_pin: PinId(3),
+        });
+        graph.add_connection(Connection {
+            from_pin: PinId(4),
+            to_pin: PinId(5),
+        });
+        graph.add_connection(keep.clone());
+
+        graph.remove_node(NodeId(2));
+        assert_eq!(graph.connections, vec![keep]);
+        // Removing a node that isn't there changes nothing
+        graph.remove_node(NodeId(2));
+        assert_eq!(graph.connections.len(), 1);
+    }
+
     #[test]
     fn test_add_connection() {
         let mut graph = NodeGraph::new();
@@ -438,6 +507,7 @@ mod tests {
             size: Vec2::new(220.0, 100.0),
             header_height: 24.0,
             pin_offsets: (vec![], vec![]),
+            kind: NodeKind::Test,
         };
 
         // Add second node
@@ -462,6 +532,7 @@ mod tests {
             size: Vec2::new(220.0, 100.0),
             header_height: 24.0,
             pin_offsets: (vec![], vec![]),
+            kind: NodeKind::Test,
         };
 
         graph.add_node(node1);
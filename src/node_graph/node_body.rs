@@ -0,0 +1,420 @@
+use crate::node_graph::model::NodeInstance;
+use crate::shader_graph::node_kind::{NodeKind, OutputTarget};
+use crate::shader_graph::nodes::compute::{ComputeNode, STORAGE_SLOTS, WorkgroupSize};
+use crate::shader_graph::nodes::custom::CustomFunction;
+use crate::shader_graph::nodes::gradient::{ColorRamp, RampInterpolation};
+use crate::shader_graph::nodes::library::LibraryFunction;
+use crate::shader_graph::nodes::logic::{BooleanOp, CompareOp, LogicNode, SWITCH_CASES};
+use crate::shader_graph::nodes::normal::NormalNode;
+use crate::shader_graph::nodes::texture::{
+    ImageAssets, SamplerState, TextureFilter, TextureNode, TextureWrap,
+};
+use bevy_egui::egui;
+
+// Convert a straight RGBA float colour to an egui colour
+fn vec4_to_color32(color: bevy::prelude::Vec4) -> egui::Color32 {
+    egui::Rgba::from_rgba_unmultiplied(color.x, color.y, color.z, color.w).into()
+}
+
+/// Inline editors drawn in a node's content area, below its pins.
+/// Returns true when the node's parameters were edited this frame.
+pub fn node_body_ui(
+    ui: &mut egui::Ui,
+    node: &mut NodeInstance,
+    images: &mut ImageAssets,
+    width: f32,
+) -> bool {
+    let id = egui::Id::new(("node_body", node.node_id.0));
+    match &mut node.kind {
+        NodeKind::ColorRamp(ramp) => color_ramp_editor(ui, id, ramp, width),
+        NodeKind::Texture(TextureNode::Texture2D { path }) => {
+            image_picker(ui, id, path, images, width)
+        }
+        NodeKind::Texture(TextureNode::Sample(sampler) | TextureNode::SampleLevel(sampler)) => {
+            sampler_editor(ui, id, sampler, width)
+        }
+        NodeKind::Normal(NormalNode::FromTexture(sampler) | NormalNode::Triplanar(sampler)) => {
+            sampler_editor(ui, id, sampler, width)
+        }
+        NodeKind::FragmentOutput(target) => output_target_editor(ui, id, target, width),
+        NodeKind::ComputeOutput(size) => workgroup_size_editor(ui, size),
+        NodeKind::Compute(node) => storage_slot_editor(ui, node),
+        NodeKind::Logic(node) => logic_editor(ui, id, node, width),
+        NodeKind::Custom(function) => custom_function_editor(ui, id, function, width),
+        NodeKind::Library(function) => library_function_info(ui, function),
+        _ => false,
+    }
+}
+
+/// Gradient bar with draggable stop markers, a colour picker for the selected stop
+/// and an interpolation selector. Double-click the bar to add a stop.
+pub fn color_ramp_editor(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    ramp: &mut ColorRamp,
+    width: f32,
+) -> bool {
+    let mut changed = false;
+    let selected_id = id.with("selected_stop");
+    let mut selected: usize = ui.data(|d| d.get_temp(selected_id)).unwrap_or(0);
+    selected = selected.min(ramp.stops.len().saturating_sub(1));
+
+    // Gradient bar
+    let (bar_rect, bar_response) =
+        ui.allocate_exact_size(egui::vec2(width, 18.0), egui::Sense::click());
+    let painter = ui.painter();
+    let strips = 48;
+    let strip_width = bar_rect.width() / strips as f32;
+    for i in 0..strips {
+        let t = (i as f32 + 0.5) / strips as f32;
+        let strip = egui::Rect::from_min_size(
+            bar_rect.min + egui::vec2(i as f32 * strip_width, 0.0),
+            egui::vec2(strip_width + 0.5, bar_rect.height()),
+        );
+        painter.rect_filled(strip, 0.0, vec4_to_color32(ramp.sample(t)));
+    }
+    painter.rect_stroke(
+        bar_rect,
+        2.0,
+        egui::Stroke::new(1.0, egui::Color32::from_gray(100)),
+        egui::StrokeKind::Outside,
+    );
+
+    if bar_response.double_clicked()
+        && let Some(pointer) = bar_response.interact_pointer_pos()
+    {
+        let t = (pointer.x - bar_rect.left()) / bar_rect.width();
+        selected = ramp.add_stop(t);
+        changed = true;
+    }
+
+    // Stop markers under the bar
+    let (marker_row, _) = ui.allocate_exact_size(egui::vec2(width, 12.0), egui::Sense::hover());
+    for (i, stop) in ramp.stops.iter_mut().enumerate() {
+        let x = bar_rect.left() + stop.position * bar_rect.width();
+        let marker_rect = egui::Rect::from_center_size(
+            egui::pos2(x, marker_row.center().y),
+            egui::vec2(10.0, 12.0),
+        );
+        let response = ui.interact(
+            marker_rect,
+            id.with(("stop", i)),
+            egui::Sense::click_and_drag(),
+        );
+
+        if response.clicked() || response.drag_started() {
+            selected = i;
+        }
+        if response.dragged()
+            && let Some(pointer) = response.interact_pointer_pos()
+        {
+            stop.position = ((pointer.x - bar_rect.left()) / bar_rect.width()).clamp(0.0, 1.0);
+            changed = true;
+        }
+
+        let outline = if i == selected {
+            egui::Color32::WHITE
+        } else {
+            egui::Color32::from_gray(120)
+        };
+        ui.painter().add(egui::Shape::convex_polygon(
+            vec![
+                egui::pos2(x, marker_rect.top()),
+                egui::pos2(marker_rect.right(), marker_rect.bottom()),
+                egui::pos2(marker_rect.left(), marker_rect.bottom()),
+            ],
+            vec4_to_color32(stop.color),
+            egui::Stroke::new(1.5, outline),
+        ));
+    }
+
+    // Selected stop controls
+    ui.horizontal(|ui| {
+        if let Some(stop) = ramp.stops.get_mut(selected) {
+            let mut rgba = stop.color.to_array();
+            if ui.color_edit_button_rgba_unmultiplied(&mut rgba).changed() {
+                stop.color = bevy::prelude::Vec4::from_array(rgba);
+                changed = true;
+            }
+            if ui
+                .add(
+                    egui::DragValue::new(&mut stop.position)
+                        .range(0.0..=1.0)
+                        .speed(0.01)
+                        .max_decimals(3),
+                )
+                .changed()
+            {
+                changed = true;
+            }
+        }
+        if ui.small_button("+").clicked() {
+            selected = ramp.add_stop(0.5);
+            changed = true;
+        }
+        if ui.small_button("-").clicked() && ramp.remove_stop(selected) {
+            selected = selected.saturating_sub(1);
+            changed = true;
+        }
+    });
+
+    egui::ComboBox::from_id_salt(id.with("interpolation"))
+        .selected_text(ramp.interpolation.label())
+        .width(width * 0.5)
+        .show_ui(ui, |ui| {
+            for mode in RampInterpolation::ALL {
+                if ui
+                    .selectable_value(&mut ramp.interpolation, mode, mode.label())
+                    .changed()
+                {
+                    changed = true;
+                }
+            }
+        });
+
+    ui.data_mut(|d| d.insert_temp(selected_id, selected));
+    changed
+}
+
+/// Image asset selector listing the images under `assets/`, listed again when it is opened
+pub fn image_picker(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    path: &mut Option<String>,
+    images: &mut ImageAssets,
+    width: f32,
+) -> bool {
+    let mut changed = false;
+    let combo = egui::ComboBox::from_id_salt(id.with("image"))
+        .selected_text(path.as_deref().unwrap_or("None (white)"))
+        .width(width)
+        .show_ui(ui, |ui| {
+            if ui.selectable_value(path, None, "None (white)").changed() {
+                changed = true;
+            }
+            for image in &images.paths {
+                if ui
+                    .selectable_value(path, Some(image.clone()), image)
+                    .changed()
+                {
+                    changed = true;
+                }
+            }
+        });
+    if combo.response.clicked() {
+        images.refresh();
+    }
+    changed
+}
+
+/// Filter and wrap selectors for the nodes sampling a texture
+pub fn sampler_editor(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    sampler: &mut SamplerState,
+    width: f32,
+) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt(id.with("filter"))
+            .selected_text(sampler.filter.label())
+            .width(width * 0.45)
+            .show_ui(ui, |ui| {
+                for filter in TextureFilter::ALL {
+                    if ui
+                        .selectable_value(&mut sampler.filter, filter, filter.label())
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                }
+            });
+        egui::ComboBox::from_id_salt(id.with("wrap"))
+            .selected_text(sampler.wrap.label())
+            .width(width * 0.45)
+            .show_ui(ui, |ui| {
+                for wrap in TextureWrap::ALL {
+                    if ui
+                        .selectable_value(&mut sampler.wrap, wrap, wrap.label())
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                }
+            });
+    });
+    changed
+}
+
+/// Which kind of shader a `Fragment Output` graph compiles into
+pub fn output_target_editor(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    target: &mut OutputTarget,
+    width: f32,
+) -> bool {
+    let mut changed = false;
+    egui::ComboBox::from_id_salt(id.with("target"))
+        .selected_text(target.label())
+        .width(width)
+        .show_ui(ui, |ui| {
+            for option in OutputTarget::ALL {
+                if ui
+                    .selectable_value(target, option, option.label())
+                    .changed()
+                {
+                    changed = true;
+                }
+            }
+        });
+    changed
+}
+
+/// Workgroup size of a `Compute Output` node, each axis within WebGPU's default limits
+pub fn workgroup_size_editor(ui: &mut egui::Ui, size: &mut WorkgroupSize) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Workgroup");
+        for (axis, max) in [&mut size.x, &mut size.y, &mut size.z]
+            .into_iter()
+            .zip(WorkgroupSize::MAX)
+        {
+            if ui
+                .add(egui::DragValue::new(axis).range(1..=max).speed(0.25))
+                .changed()
+            {
+                changed = true;
+            }
+        }
+    });
+    if !size.is_valid() {
+        ui.colored_label(
+            egui::Color32::from_rgb(230, 120, 90),
+            format!("over {} invocations", WorkgroupSize::MAX_INVOCATIONS),
+        );
+    }
+    changed
+}
+
+/// Which storage buffer or texture slot a compute node binds
+pub fn storage_slot_editor(ui: &mut egui::Ui, node: &mut ComputeNode) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Slot");
+        if ui
+            .add(egui::DragValue::new(node.slot_mut()).range(0..=STORAGE_SLOTS - 1))
+            .changed()
+        {
+            changed = true;
+        }
+    });
+    changed
+}
+
+/// Operator and operand type of a logic node, plus a Switch node's case count
+pub fn logic_editor(ui: &mut egui::Ui, id: egui::Id, node: &mut LogicNode, width: f32) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        match node {
+            LogicNode::Compare(op, _) => {
+                egui::ComboBox::from_id_salt(id.with("op"))
+                    .selected_text(op.symbol())
+                    .width(width * 0.45)
+                    .show_ui(ui, |ui| {
+                        for option in CompareOp::ALL {
+                            if ui.selectable_value(op, option, option.label()).changed() {
+                                changed = true;
+                            }
+                        }
+                    });
+            }
+            LogicNode::Boolean(op, _) => {
+                egui::ComboBox::from_id_salt(id.with("op"))
+                    .selected_text(op.label())
+                    .width(width * 0.45)
+                    .show_ui(ui, |ui| {
+                        for option in BooleanOp::ALL {
+                            if ui.selectable_value(op, option, option.label()).changed() {
+                                changed = true;
+                            }
+                        }
+                    });
+            }
+            LogicNode::Switch { cases, .. } => {
+                ui.label("Cases");
+                if ui
+                    .add(egui::DragValue::new(cases).range(SWITCH_CASES).speed(0.1))
+                    .changed()
+                {
+                    changed = true;
+                }
+            }
+            LogicNode::Select(_) => {}
+        }
+
+        let options = node.type_options();
+        let ty = node.ty_mut();
+        egui::ComboBox::from_id_salt(id.with("type"))
+            .selected_text(ty.wgsl_type())
+            .width(width * 0.45)
+            .show_ui(ui, |ui| {
+                for option in options {
+                    if ui
+                        .selectable_value(ty, option, option.wgsl_type())
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                }
+            });
+    });
+    changed
+}
+
+/// WGSL code box of a Custom Function node, with the parse or validation error below it.
+/// Pins follow the source on every edit that parses.
+pub fn custom_function_editor(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    function: &mut CustomFunction,
+    width: f32,
+) -> bool {
+    let mut source = function.source().to_string();
+    let changed = ui
+        .add(
+            egui::TextEdit::multiline(&mut source)
+                .id_salt(id.with("source"))
+                .code_editor()
+                .desired_width(width)
+                .desired_rows(4),
+        )
+        .changed();
+    if changed {
+        function.set_source(&source);
+    }
+    if let Some(error) = function.error() {
+        ui.add(
+            egui::Label::new(
+                egui::RichText::new(error)
+                    .small()
+                    .color(egui::Color32::from_rgb(230, 120, 90)),
+            )
+            .wrap(),
+        );
+    }
+    changed
+}
+
+/// Where a library node's WGSL comes from, and its doc comment. Nothing to edit: the file is
+/// the source of truth.
+pub fn library_function_info(ui: &mut egui::Ui, function: &LibraryFunction) -> bool {
+    ui.label(
+        egui::RichText::new(format!("{}::{}", function.file, function.signature.name))
+            .small()
+            .weak(),
+    );
+    if !function.description.is_empty() {
+        ui.add(egui::Label::new(egui::RichText::new(&function.description).small()).wrap());
+    }
+    false
+}
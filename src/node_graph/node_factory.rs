@@ -1,48 +1,69 @@
 use crate::node_graph::model::{InputPin, NodeGraph, NodeId, NodeInstance, OutputPin, PinId};
+use crate::shader_graph::node_kind::{NodeKind, ShaderNode};
+use serde::{Deserialize, Serialize};
 
 /// Centralized node factory - single source of truth for all node creation
 pub struct NodeFactory;
 
 impl NodeFactory {
-    /// Create a new test node with standard configuration
-    pub fn create_test_node(
+    /// Create a node of any kind - pins are allocated sequentially from the kind's signature
+    pub fn create_node(
         node_id: NodeId,
         position: bevy::prelude::Vec2,
+        kind: NodeKind,
         next_pin_id: u32,
     ) -> (NodeInstance, u32) {
-        let pin_a = PinId(next_pin_id);
-        let pin_b = PinId(next_pin_id + 1);
-        let pin_out = PinId(next_pin_id + 2);
-
-        let input_pin1 = InputPin {
-            pin_id: pin_a,
-            label: "A".to_string(),
-            parent_node: node_id,
-        };
-        let input_pin2 = InputPin {
-            pin_id: pin_b,
-            label: "B".to_string(),
-            parent_node: node_id,
-        };
-        let output_pin = OutputPin {
-            pin_id: pin_out,
-            label: "Out".to_string(),
-            parent_node: node_id,
-        };
+        let signature = kind.signature();
+        let mut pin_id = next_pin_id;
+
+        let inputs = signature
+            .inputs
+            .iter()
+            .map(|spec| {
+                pin_id += 1;
+                InputPin {
+                    pin_id: PinId(pin_id - 1),
+                    label: spec.label.clone(),
+                    parent_node: node_id,
+                }
+            })
+            .collect();
+        let outputs = signature
+            .outputs
+            .iter()
+            .map(|spec| {
+                pin_id += 1;
+                OutputPin {
+                    pin_id: PinId(pin_id - 1),
+                    label: spec.label.clone(),
+                    parent_node: node_id,
+                }
+            })
+            .collect();
 
         let layout = crate::node_graph::model::NodeLayout::default();
         let node_instance = NodeInstance {
             node_id,
             position,
-            inputs: vec![input_pin1, input_pin2],
-            outputs: vec![output_pin],
-            title: format!("TestNode {}", node_id.0),
+            inputs,
+            outputs,
+            title: format!("{} {}", kind.title(), node_id.0),
             size: bevy::prelude::Vec2::new(layout.width, layout.min_height),
             header_height: layout.header_height,
             pin_offsets: (vec![], vec![]), // Will be populated by rendering system
+            kind,
         };
 
-        (node_instance, next_pin_id + 3)
+        (node_instance, pin_id)
+    }
+
+    /// Create a new test node with standard configuration
+    pub fn create_test_node(
+        node_id: NodeId,
+        position: bevy::prelude::Vec2,
+        next_pin_id: u32,
+    ) -> (NodeInstance, u32) {
+        Self::create_node(node_id, position, NodeKind::Test, next_pin_id)
     }
 
     /// Create a basic math operation node (add, subtract, multiply, divide)
@@ -52,46 +73,7 @@ impl NodeFactory {
         operation: MathOperation,
         next_pin_id: u32,
     ) -> (NodeInstance, u32) {
-        let (title, operation_label) = match operation {
-            MathOperation::Add => ("Add", "Add"),
-            MathOperation::Subtract => ("Subtract", "Sub"),
-            MathOperation::Multiply => ("Multiply", "Mul"),
-            MathOperation::Divide => ("Divide", "Div"),
-        };
-
-        let pin_a = PinId(next_pin_id);
-        let pin_b = PinId(next_pin_id + 1);
-        let pin_result = PinId(next_pin_id + 2);
-
-        let input_pin1 = InputPin {
-            pin_id: pin_a,
-            label: "A".to_string(),
-            parent_node: node_id,
-        };
-        let input_pin2 = InputPin {
-            pin_id: pin_b,
-            label: "B".to_string(),
-            parent_node: node_id,
-        };
-        let output_pin = OutputPin {
-            pin_id: pin_result,
-            label: operation_label.to_string(),
-            parent_node: node_id,
-        };
-
-        let layout = crate::node_graph::model::NodeLayout::default();
-        let node_instance = NodeInstance {
-            node_id,
-            position,
-            inputs: vec![input_pin1, input_pin2],
-            outputs: vec![output_pin],
-            title: format!("{} {}", title, node_id.0),
-            size: bevy::prelude::Vec2::new(layout.width, layout.min_height),
-            header_height: layout.header_height,
-            pin_offsets: (vec![], vec![]), // Will be populated by rendering system
-        };
-
-        (node_instance, next_pin_id + 3)
+        Self::create_node(node_id, position, NodeKind::Math(operation), next_pin_id)
     }
 
     /// Create a constant value node
@@ -101,27 +83,62 @@ impl NodeFactory {
         value: f32,
         next_pin_id: u32,
     ) -> (NodeInstance, u32) {
-        let pin_out = PinId(next_pin_id);
-
-        let output_pin = OutputPin {
-            pin_id: pin_out,
-            label: format!("{:.2}", value),
-            parent_node: node_id,
-        };
+        Self::create_node(node_id, position, NodeKind::Constant(value), next_pin_id)
+    }
 
-        let layout = crate::node_graph::model::NodeLayout::default();
-        let node_instance = NodeInstance {
-            node_id,
-            position,
-            inputs: vec![], // No inputs for constant nodes
-            outputs: vec![output_pin],
-            title: format!("Const {}", node_id.0),
-            size: bevy::prelude::Vec2::new(layout.width, layout.min_height),
-            header_height: layout.header_height,
-            pin_offsets: (vec![], vec![]), // Will be populated by rendering system
+    /// Bring a node's pins and title back in line with its kind after an edit changed the
+    /// signature. Pins keep their IDs and connections by index; missing pins are allocated
+    /// after the graph's highest pin ID and dropped pins lose their connections.
+    pub fn sync_pins(node_graph: &mut NodeGraph, node_id: NodeId) {
+        let mut next_pin_id = Self::get_next_pin_id(node_graph);
+        let Some(node) = node_graph.nodes.get_mut(&node_id) else {
+            return;
         };
-
-        (node_instance, next_pin_id + 1)
+        let signature = node.kind.signature();
+        let mut dropped = Vec::new();
+
+        dropped.extend(
+            node.inputs
+                .drain(signature.inputs.len().min(node.inputs.len())..)
+                .map(|pin| pin.pin_id),
+        );
+        for (i, spec) in signature.inputs.iter().enumerate() {
+            match node.inputs.get_mut(i) {
+                Some(pin) => pin.label = spec.label.clone(),
+                None => {
+                    node.inputs.push(InputPin {
+                        pin_id: PinId(next_pin_id),
+                        label: spec.label.clone(),
+                        parent_node: node_id,
+                    });
+                    next_pin_id += 1;
+                }
+            }
+        }
+
+        dropped.extend(
+            node.outputs
+                .drain(signature.outputs.len().min(node.outputs.len())..)
+                .map(|pin| pin.pin_id),
+        );
+        for (i, spec) in signature.outputs.iter().enumerate() {
+            match node.outputs.get_mut(i) {
+                Some(pin) => pin.label = spec.label.clone(),
+                None => {
+                    node.outputs.push(OutputPin {
+                        pin_id: PinId(next_pin_id),
+                        label: spec.label.clone(),
+                        parent_node: node_id,
+                    });
+                    next_pin_id += 1;
+                }
+            }
+        }
+
+        node.title = format!("{} {}", node.kind.title(), node_id.0);
+        node_graph
+            .connections
+            .retain(|c| !dropped.contains(&c.from_pin) && !dropped.contains(&c.to_pin));
     }
 
     /// Get the next available node ID from the current graph state
@@ -155,7 +172,7 @@ impl NodeFactory {
 }
 
 /// Supported math operations for math nodes
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MathOperation {
     Add,
     Subtract,
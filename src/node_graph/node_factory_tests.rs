@@ -2,6 +2,7 @@
 mod tests {
     use crate::node_graph::model::{InputPin, NodeGraph, NodeId, NodeInstance, OutputPin, PinId};
     use crate::node_graph::node_factory::{MathOperation, NodeFactory};
+    use crate::shader_graph::node_kind::NodeKind;
     use bevy::prelude::*;
 
     #[test]
@@ -354,6 +355,7 @@ mod tests {
             size: Vec2::new(220.0, 100.0),
             header_height: 24.0,
             pin_offsets: (vec![], vec![]),
+            kind: NodeKind::Test,
         }
     }
 }
@@ -1,3 +1,4 @@
+use crate::node_graph::canvas::{canvas_to_screen, screen_to_canvas};
 use crate::node_graph::model::{CanvasState, NodeGraph, NodeId, NodeLayout, PinId};
 use bevy::prelude::*;
 
@@ -38,42 +39,23 @@ impl PinPositionManager {
         position
     }
 
-    /// Calculate pin position in screen space (internal calculation)
+    /// Calculate pin position in screen space (internal calculation). Pin offsets are laid
+    /// out in canvas units, so they scale with the zoom like the node they belong to
     pub fn calculate_pin_position_raw(
         &self,
         pin_id: PinId,
         node_graph: &NodeGraph,
         canvas_state: &CanvasState,
     ) -> Option<Vec2> {
-        let zoom = canvas_state.zoom;
-
         for (_, node) in &node_graph.nodes {
             // Check traditional pins - use centralized layout matching render system
-            for (i, input_pin) in node.inputs.iter().enumerate() {
-                if input_pin.pin_id == pin_id {
-                    // Pin position matches render system: outside left margin, below header
-                    let pin_offset = Vec2::new(
-                        -self.layout.pin_margin,
-                        self.layout.header_height
-                            + self.layout.pin_spacing
-                            + (i as f32 * self.layout.pin_spacing),
-                    );
-                    let screen_pos = ((node.position + canvas_state.offset) * zoom) + pin_offset;
-                    return Some(screen_pos);
-                }
+            if let Some(i) = node.inputs.iter().position(|p| p.pin_id == pin_id) {
+                let pin_pos = node.position + self.layout.input_pin_offset(i);
+                return Some(canvas_to_screen(pin_pos, canvas_state));
             }
-            for (i, output_pin) in node.outputs.iter().enumerate() {
-                if output_pin.pin_id == pin_id {
-                    // Pin position matches render system: outside right margin, below header
-                    let pin_offset = Vec2::new(
-                        self.layout.width + self.layout.pin_margin,
-                        self.layout.header_height
-                            + self.layout.pin_spacing
-                            + (i as f32 * self.layout.pin_spacing),
-                    );
-                    let screen_pos = ((node.position + canvas_state.offset) * zoom) + pin_offset;
-                    return Some(screen_pos);
-                }
+            if let Some(i) = node.outputs.iter().position(|p| p.pin_id == pin_id) {
+                let pin_pos = node.position + self.layout.output_pin_offset(i);
+                return Some(canvas_to_screen(pin_pos, canvas_state));
             }
         }
         None
@@ -94,7 +76,7 @@ impl PinPositionManager {
         canvas_state: &CanvasState,
     ) -> Option<Vec2> {
         let screen_pos = self.get_pin_screen_position(pin_id, node_graph, canvas_state)?;
-        Some(screen_pos / canvas_state.zoom - canvas_state.offset)
+        Some(screen_to_canvas(screen_pos, canvas_state))
     }
 
     /// Get connection endpoints for drawing (returns screen space positions)
@@ -132,7 +114,7 @@ impl PinPositionManager {
         None
     }
 
-    /// Check if two pins can connect (Output->Input only, cross-window only)
+    /// Check if two pins can connect (Output->Input only, cross-window only, compatible types)
     pub fn can_connect_pins(&self, from_pin: PinId, to_pin: PinId, node_graph: &NodeGraph) -> bool {
         // Must be Output->Input
         if !Self::is_output_node(from_pin) || !Self::is_input_node(to_pin) {
@@ -149,6 +131,15 @@ impl PinPositionManager {
             None => return false,
         };
 
-        from_node != to_node
+        from_node != to_node && Self::pin_types_compatible(from_pin, to_pin, node_graph)
+    }
+
+    /// Check if an output's type converts to an input's type - textures only connect to
+    /// textures. Pins without a type in the node's signature are treated as compatible.
+    pub fn pin_types_compatible(from_pin: PinId, to_pin: PinId, node_graph: &NodeGraph) -> bool {
+        match (node_graph.pin_type(from_pin), node_graph.pin_type(to_pin)) {
+            (Some(from), Some(to)) => from.converts_to(to),
+            _ => true,
+        }
     }
 }
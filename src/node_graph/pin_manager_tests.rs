@@ -1,10 +1,16 @@
 #[cfg(test)]
 mod tests {
+    use crate::node_graph::interactions::click_pending_connection;
     use crate::node_graph::model::{
-        CanvasState, InputPin, NodeGraph, NodeId, NodeInstance, NodeLayout, OutputPin, PinId,
+        CanvasState, InputPin, NodeGraph, NodeId, NodeInstance, OutputPin, PinId,
     };
     use crate::node_graph::pin_manager::PinPositionManager;
+    use crate::node_graph::test_utils::add_node;
+    use crate::node_graph::ui_state::{GraphUiState, PendingConnection};
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget};
+    use crate::shader_graph::nodes::texture::{SamplerState, TextureNode};
     use bevy::prelude::*;
+    use bevy_egui::egui;
 
     fn create_test_node_graph() -> NodeGraph {
         let mut graph = NodeGraph::new();
@@ -43,6 +49,7 @@ mod tests {
             size: Vec2::new(220.0, 100.0),
             header_height: 24.0,
             pin_offsets: (vec![], vec![]),
+            kind: NodeKind::Test,
         };
 
         // Create second test node
@@ -69,6 +76,7 @@ mod tests {
             size: Vec2::new(220.0, 100.0),
             header_height: 24.0,
             pin_offsets: (vec![], vec![]),
+            kind: NodeKind::Test,
         };
 
         graph.add_node(node1);
@@ -88,7 +96,6 @@ mod tests {
         let manager = PinPositionManager::default();
         assert!(manager.cached_positions.is_empty());
         assert_eq!(manager.frame_version, 0);
-        assert_eq!(manager.current_frame, 0);
         assert_eq!(manager.layout.width, 220.0);
     }
 
@@ -181,6 +188,53 @@ mod tests {
         assert!(!manager.can_connect_pins(PinId(99), PinId(100), &graph));
     }
 
+    #[test]
+    fn test_click_to_connect_checks_pin_types() {
+        let manager = PinPositionManager::default();
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let texture = add_node(
+            &mut graph,
+            NodeKind::Texture(TextureNode::Texture2D { path: None }),
+        );
+        let sample = add_node(
+            &mut graph,
+            NodeKind::Texture(TextureNode::Sample(SamplerState::default())),
+        );
+        let texture_out = graph.nodes[&texture].outputs[0].pin_id;
+        let color_in = graph.nodes[&output].inputs[0].pin_id;
+        let sample_texture_in = graph.nodes[&sample].inputs[0].pin_id;
+
+        let mut ui_state = GraphUiState {
+            pending_connection: Some(PendingConnection {
+                from_pin: texture_out,
+                from_screen_pos: egui::Pos2::ZERO,
+            }),
+            ..default()
+        };
+
+        // A texture clicked into a colour input is refused and the wire stays pending
+        assert!(!click_pending_connection(
+            &mut ui_state,
+            &manager,
+            &mut graph,
+            color_in
+        ));
+        assert!(graph.connections.is_empty());
+        assert!(ui_state.pending_connection.is_some());
+
+        assert!(click_pending_connection(
+            &mut ui_state,
+            &manager,
+            &mut graph,
+            sample_texture_in
+        ));
+        assert_eq!(graph.connections.len(), 1);
+        assert_eq!(graph.connections[0].from_pin, texture_out);
+        assert_eq!(graph.connections[0].to_pin, sample_texture_in);
+        assert!(ui_state.pending_connection.is_none());
+    }
+
     #[test]
     fn test_invalidate_cache() {
         let mut manager = PinPositionManager::default();
@@ -256,8 +310,8 @@ mod tests {
         assert!(screen_pos.is_some());
         assert!(canvas_pos.is_some());
 
-        // Canvas position should be screen position divided by zoom, minus offset
-        let expected_canvas = screen_pos.unwrap() / canvas_state.zoom - canvas_state.offset;
+        // Canvas position should be screen position divided by zoom, plus offset
+        let expected_canvas = screen_pos.unwrap() / canvas_state.zoom + canvas_state.offset;
         assert!((canvas_pos.unwrap().x - expected_canvas.x).abs() < 0.001);
         assert!((canvas_pos.unwrap().y - expected_canvas.y).abs() < 0.001);
     }
@@ -303,11 +357,11 @@ mod tests {
         let pos = position.unwrap();
 
         // Should be on the left side of the node
-        let expected_x = (100.0 + 50.0) * 1.0 - 6.0; // node.x + offset.x - margin
+        let expected_x = (100.0 - 50.0) * 1.0 - 6.0; // node.x - offset.x - margin
         assert!((pos.x - expected_x).abs() < 0.1);
 
         // Should be below header with spacing
-        let expected_y = (200.0 + 25.0) * 1.0 + 24.0 + 20.0 + 0.0 * 20.0; // node.y + offset.y + header + spacing + index*spacing
+        let expected_y = (200.0 - 25.0) * 1.0 + 24.0 + 20.0 + 0.0 * 20.0; // node.y - offset.y + header + spacing + index*spacing
         assert!((pos.y - expected_y).abs() < 0.1);
     }
 
@@ -324,11 +378,11 @@ mod tests {
         let pos = position.unwrap();
 
         // Should be on the right side of the node
-        let expected_x = (100.0 + 50.0) * 1.0 + 220.0 + 6.0; // node.x + offset.x + width + margin
+        let expected_x = (100.0 - 50.0) * 1.0 + 220.0 + 6.0; // node.x - offset.x + width + margin
         assert!((pos.x - expected_x).abs() < 0.1);
 
         // Should be below header with spacing
-        let expected_y = (200.0 + 25.0) * 1.0 + 24.0 + 20.0 + 0.0 * 20.0; // node.y + offset.y + header + spacing + index*spacing
+        let expected_y = (200.0 - 25.0) * 1.0 + 24.0 + 20.0 + 0.0 * 20.0; // node.y - offset.y + header + spacing + index*spacing
         assert!((pos.y - expected_y).abs() < 0.1);
     }
 
@@ -344,9 +398,11 @@ mod tests {
         assert!(position.is_some());
         let pos = position.unwrap();
 
-        // Position should be scaled by zoom
-        let expected_x = ((100.0 + 50.0) * 2.0) - 6.0; // (node.x + offset.x) * zoom - margin
+        // Position and the pin's offset from the node should both be scaled by zoom
+        let expected_x = (100.0 - 50.0 - 6.0) * 2.0; // (node.x - offset.x - margin) * zoom
         assert!((pos.x - expected_x).abs() < 0.1);
+        let expected_y = (200.0 - 25.0 + 24.0 + 20.0) * 2.0; // (node.y - offset.y + header + spacing) * zoom
+        assert!((pos.y - expected_y).abs() < 0.1);
     }
 
     #[test]
@@ -1,6 +1,14 @@
+use crate::node_graph::annotations::paint_annotations;
+use crate::node_graph::canvas::{GRID_SPACING, canvas_to_screen, screen_to_canvas};
+use crate::node_graph::minimap::Minimap;
 use crate::node_graph::model::{NodeGraph, NodeLayout};
+use crate::node_graph::node_body::node_body_ui;
+use crate::node_graph::node_factory::NodeFactory;
 use crate::node_graph::pin_manager::PinPositionManager;
 use crate::node_graph::ui_state::GraphUiState;
+use crate::shader_graph::node_kind::{NodeKind, ShaderNode};
+use crate::shader_graph::nodes::texture::ImageAssets;
+use crate::shader_graph::types::PinType;
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, egui};
 
@@ -9,13 +17,30 @@ fn vec2_to_pos2(vec: Vec2) -> egui::Pos2 {
     egui::pos2(vec.x, vec.y)
 }
 
+/// Outline of selected nodes and the rubber band
+pub const SELECTION_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 200, 80);
+
+/// Pin fill by data type - booleans and masks stand apart from float data
+pub fn pin_color(ty: Option<PinType>) -> egui::Color32 {
+    match ty {
+        Some(PinType::Bool) => egui::Color32::from_rgb(230, 90, 90),
+        Some(PinType::BVec2 | PinType::BVec3 | PinType::BVec4) => {
+            egui::Color32::from_rgb(240, 150, 60)
+        }
+        Some(PinType::Texture2D) => egui::Color32::from_rgb(200, 120, 220),
+        _ => egui::Color32::from_gray(200), // Neutral tone
+    }
+}
+
 // Convert from egui Pos2 to bevy Vec2
 fn pos2_to_vec2(pos: egui::Pos2) -> Vec2 {
     Vec2::new(pos.x, pos.y)
 }
 
+/// Grid, then comment frames and sticky notes, all behind the wires and nodes
 pub fn render_canvas_background_system(
     node_graph: Res<NodeGraph>,
+    ui_state: Res<GraphUiState>,
     mut egui_contexts: EguiContexts,
 ) {
     let ctx = egui_contexts.ctx_mut().expect("Failed to get egui context");
@@ -23,19 +48,18 @@ pub fn render_canvas_background_system(
 
     // Draw grid background
     let canvas_state = &node_graph.canvas_state;
-    let zoom = canvas_state.zoom;
 
     // Calculate visible grid range in screen space
     let screen_rect = ctx.viewport_rect();
 
     // Draw grid lines using canvas->screen transforms
-    let grid_spacing = 48.0; // Base grid spacing in canvas space
+    let grid_spacing = GRID_SPACING; // Base grid spacing in canvas space
     let visible_min = pos2_to_vec2(screen_rect.min);
     let visible_max = pos2_to_vec2(screen_rect.max);
 
     // Convert screen bounds to canvas bounds
-    let canvas_min = (visible_min / zoom) - canvas_state.offset;
-    let canvas_max = (visible_max / zoom) - canvas_state.offset;
+    let canvas_min = screen_to_canvas(visible_min, canvas_state);
+    let canvas_max = screen_to_canvas(visible_max, canvas_state);
 
     // Find grid lines in visible range
     let start_x = (canvas_min.x / grid_spacing).floor() * grid_spacing;
@@ -46,26 +70,28 @@ pub fn render_canvas_background_system(
     // Draw vertical grid lines
     for x in (start_x as i32..=end_x as i32).step_by(grid_spacing as usize) {
         let canvas_pos = Vec2::new(x as f32, start_y);
-        let screen_pos = vec2_to_pos2((canvas_pos + canvas_state.offset) * zoom);
-        let screen_pos2 = vec2_to_pos2((Vec2::new(x as f32, end_y) + canvas_state.offset) * zoom);
+        let screen_pos = vec2_to_pos2(canvas_to_screen(canvas_pos, canvas_state));
+        let screen_pos2 = vec2_to_pos2(canvas_to_screen(Vec2::new(x as f32, end_y), canvas_state));
 
         painter.line_segment(
             [screen_pos, screen_pos2],
-            egui::Stroke::new(1.0 / zoom, egui::Color32::from_gray(40)),
+            egui::Stroke::new(1.0, egui::Color32::from_gray(40)),
         );
     }
 
     // Draw horizontal grid lines
     for y in (start_y as i32..=end_y as i32).step_by(grid_spacing as usize) {
         let canvas_pos = Vec2::new(start_x, y as f32);
-        let screen_pos = vec2_to_pos2((canvas_pos + canvas_state.offset) * zoom);
-        let screen_pos2 = vec2_to_pos2((Vec2::new(end_x, y as f32) + canvas_state.offset) * zoom);
+        let screen_pos = vec2_to_pos2(canvas_to_screen(canvas_pos, canvas_state));
+        let screen_pos2 = vec2_to_pos2(canvas_to_screen(Vec2::new(end_x, y as f32), canvas_state));
 
         painter.line_segment(
             [screen_pos, screen_pos2],
-            egui::Stroke::new(1.0 / zoom, egui::Color32::from_gray(40)),
+            egui::Stroke::new(1.0, egui::Color32::from_gray(40)),
         );
     }
+
+    paint_annotations(&painter, &node_graph, ui_state.active_annotation);
 }
 
 pub fn render_connections_system(
@@ -98,8 +124,10 @@ pub fn render_connections_system(
             let direction = (to_screen - from_screen).normalized();
             let perpendicular = egui::vec2(-direction.y, direction.x);
 
-            let ctrl1 = from_screen + perpendicular * 20.0 + direction * ctrl_offset;
-            let ctrl2 = to_screen - perpendicular * 20.0 - direction * ctrl_offset;
+            let ctrl1 =
+                from_screen + perpendicular * 20.0 * canvas_state.zoom + direction * ctrl_offset;
+            let ctrl2 =
+                to_screen - perpendicular * 20.0 * canvas_state.zoom - direction * ctrl_offset;
 
             // Draw smooth bezier curve using multiple line segments
             let segments = 12;
@@ -128,7 +156,7 @@ pub fn render_connections_system(
 
                 painter.line_segment(
                     [prev_point, current_point],
-                    egui::Stroke::new(3.0, egui::Color32::LIGHT_GRAY),
+                    egui::Stroke::new(3.0 * canvas_state.zoom, egui::Color32::LIGHT_GRAY),
                 );
 
                 prev_point = current_point;
@@ -162,49 +190,108 @@ pub fn render_pending_connection_system(
             let end_pos = ctx.input(|i| i.pointer.latest_pos()).unwrap_or(from_screen);
 
             // Draw temporary bezier curve using line segments
-            let ctrl_offset = Vec2::new(80.0, 0.0);
+            let ctrl_offset = Vec2::new(80.0, 0.0) * canvas_state.zoom;
             let ctrl1 = from_screen + egui::vec2(ctrl_offset.x, ctrl_offset.y);
             let ctrl2 = end_pos - egui::vec2(ctrl_offset.x, ctrl_offset.y);
 
             // Simple line approximation for now
             painter.line_segment(
                 [from_screen, ctrl1],
-                egui::Stroke::new(2.0 / canvas_state.zoom, egui::Color32::WHITE),
+                egui::Stroke::new(2.0 * canvas_state.zoom, egui::Color32::WHITE),
             );
             painter.line_segment(
                 [ctrl1, ctrl2],
-                egui::Stroke::new(2.0 / canvas_state.zoom, egui::Color32::WHITE),
+                egui::Stroke::new(2.0 * canvas_state.zoom, egui::Color32::WHITE),
             );
             painter.line_segment(
                 [ctrl2, end_pos],
-                egui::Stroke::new(2.0 / canvas_state.zoom, egui::Color32::WHITE),
+                egui::Stroke::new(2.0 * canvas_state.zoom, egui::Color32::WHITE),
             );
         }
     }
 }
 
-pub fn render_nodes_system(node_graph: Res<NodeGraph>, mut egui_contexts: EguiContexts) {
+/// `style` with every text size and spacing scaled by `zoom`, so a node's widgets grow and
+/// shrink with the canvas
+fn zoomed_style(style: &egui::Style, zoom: f32) -> egui::Style {
+    let mut style = style.clone();
+    for font in style.text_styles.values_mut() {
+        font.size *= zoom;
+    }
+    let spacing = &mut style.spacing;
+    spacing.item_spacing *= zoom;
+    spacing.button_padding *= zoom;
+    spacing.interact_size *= zoom;
+    spacing.indent *= zoom;
+    spacing.slider_width *= zoom;
+    spacing.combo_width *= zoom;
+    spacing.text_edit_width *= zoom;
+    spacing.icon_width *= zoom;
+    spacing.icon_width_inner *= zoom;
+    spacing.icon_spacing *= zoom;
+    style
+}
+
+/// Rubber band over the nodes while dragging out a box selection
+pub fn render_box_select_system(ui_state: Res<GraphUiState>, mut egui_contexts: EguiContexts) {
+    let Some(band) = &ui_state.box_select else {
+        return;
+    };
     let ctx = egui_contexts.ctx_mut().expect("Failed to get egui context");
-    let canvas_state = &node_graph.canvas_state;
-    let layout = NodeLayout::default();
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("box_select"),
+    ));
+    painter.rect(
+        band.rect(),
+        0.0,
+        SELECTION_COLOR.gamma_multiply(0.1),
+        egui::Stroke::new(1.0, SELECTION_COLOR),
+        egui::StrokeKind::Inside,
+    );
+}
+
+pub fn render_nodes_system(
+    mut node_graph: ResMut<NodeGraph>,
+    mut ui_state: ResMut<GraphUiState>,
+    mut minimap: ResMut<Minimap>,
+    mut images: ResMut<ImageAssets>,
+    mut egui_contexts: EguiContexts,
+) {
+    let ctx = egui_contexts.ctx_mut().expect("Failed to get egui context");
+    let mut edited_nodes = Vec::new();
+    let mut resized = false;
+
+    // Only flag the graph as changed when an inline editor actually edited a node
+    let graph = node_graph.bypass_change_detection();
+    let canvas_state = graph.canvas_state.clone();
+    let zoom = canvas_state.zoom;
+    // Everything inside a node is laid out in canvas units and drawn scaled
+    let layout = NodeLayout::default().scaled(zoom);
 
     // Create a window for each node using proper canvas->screen transforms
-    for (_, node_instance) in node_graph.nodes.iter() {
+    for (_, node_instance) in graph.nodes.iter_mut() {
         let window_id = egui::Id::new(node_instance.node_id.0);
 
         // Convert node position from canvas to screen space
-        let screen_pos =
-            vec2_to_pos2((node_instance.position + canvas_state.offset) * canvas_state.zoom);
+        let screen_pos = vec2_to_pos2(canvas_to_screen(node_instance.position, &canvas_state));
 
-        egui::Area::new(window_id)
+        let area = egui::Area::new(window_id)
             .fixed_pos(screen_pos)
             .movable(false) // We'll handle dragging manually
             .show(ctx, |ui| {
+                ui.set_style(zoomed_style(ui.style(), zoom));
+                let font = egui::TextStyle::Body.resolve(ui.style());
                 // Create node frame with header and content area
+                let border = if ui_state.is_selected(node_instance.node_id) {
+                    egui::Stroke::new(2.0 * zoom, SELECTION_COLOR)
+                } else {
+                    egui::Stroke::new(1.0 * zoom, egui::Color32::from_gray(100))
+                };
                 let frame = egui::Frame::NONE
                     .fill(egui::Color32::from_rgb(50, 50, 50)) // Dark gray background
-                    .stroke(egui::Stroke::new(1.0, egui::Color32::from_gray(100))) // Border
-                    .corner_radius(4.0);
+                    .stroke(border)
+                    .corner_radius(4.0 * zoom);
 
                 frame.show(ui, |ui| {
                     // Set up layout for node content using centralized constants
@@ -213,13 +300,13 @@ pub fn render_nodes_system(node_graph: Res<NodeGraph>, mut egui_contexts: EguiCo
                     // Node header with drag area using centralized constants
                     let (header_response, painter) = ui.allocate_painter(
                         egui::vec2(layout.width, layout.header_height),
-                        egui::Sense::drag(),
+                        egui::Sense::click_and_drag(),
                     );
 
                     // Draw header background
                     painter.rect_filled(
                         header_response.rect,
-                        4.0,
+                        4.0 * zoom,
                         egui::Color32::from_rgb(60, 60, 60),
                     );
 
@@ -229,10 +316,26 @@ pub fn render_nodes_system(node_graph: Res<NodeGraph>, mut egui_contexts: EguiCo
                         title_pos,
                         egui::Align2::CENTER_CENTER,
                         &node_instance.title,
-                        egui::FontId::default(),
+                        font.clone(),
                         egui::Color32::WHITE,
                     );
 
+                    // A click selects the node alone, Ctrl/Shift+click adds or removes it,
+                    // double-clicking a subgraph opens it
+                    if header_response.clicked() {
+                        let modifiers = ui.input(|i| i.modifiers);
+                        if modifiers.command || modifiers.shift {
+                            ui_state.toggle_selected(node_instance.node_id);
+                        } else {
+                            ui_state.selection = vec![node_instance.node_id];
+                        }
+                    }
+                    if header_response.double_clicked()
+                        && matches!(node_instance.kind, NodeKind::Subgraph(_))
+                    {
+                        ui_state.open_subgraph = Some(node_instance.node_id);
+                    }
+
                     // Handle dragging
                     if header_response.dragged() {
                         // We'll handle this in the interaction system
@@ -243,32 +346,29 @@ pub fn render_nodes_system(node_graph: Res<NodeGraph>, mut egui_contexts: EguiCo
 
                     // Create a painter for the current content area
                     let content_painter = ui.painter();
+                    let signature = node_instance.kind.signature();
 
                     // Draw input pins on the left with labels
                     for (i, input_pin) in node_instance.inputs.iter().enumerate() {
                         // Calculate pin position using centralized layout constants
-                        let pin_x = -layout.pin_margin; // Slightly outside of node border
-                        let pin_y = layout.header_height
-                            + layout.pin_spacing
-                            + (i as f32 * layout.pin_spacing);
+                        let pin_pos = header_response.rect.min
+                            + vec2_to_pos2(layout.input_pin_offset(i)).to_vec2();
 
-                        let pin_pos = header_response.rect.min + egui::vec2(pin_x, pin_y);
-
-                        // Draw pin circle (6px radius as per SPEC.md)
+                        // Draw pin circle (6px radius as per SPEC.md), coloured by type
                         content_painter.circle_filled(
                             pin_pos,
                             layout.pin_radius, // 6px radius from NodeLayout
-                            egui::Color32::from_gray(200), // Neutral tone
+                            pin_color(signature.inputs.get(i).map(|spec| spec.ty)),
                         );
 
                         // Draw pin label next to pin
-                        let label_pos =
-                            pin_pos + egui::vec2(layout.pin_radius + 4.0, -layout.pin_radius / 2.0);
+                        let label_pos = pin_pos
+                            + egui::vec2(layout.pin_radius + 4.0 * zoom, -layout.pin_radius / 2.0);
                         content_painter.text(
                             label_pos,
                             egui::Align2::LEFT_CENTER,
                             &input_pin.label,
-                            egui::FontId::default(),
+                            font.clone(),
                             egui::Color32::WHITE,
                         );
                     }
@@ -276,39 +376,61 @@ pub fn render_nodes_system(node_graph: Res<NodeGraph>, mut egui_contexts: EguiCo
                     // Draw output pins on the right with labels
                     for (i, output_pin) in node_instance.outputs.iter().enumerate() {
                         // Calculate pin position using centralized layout constants
-                        let pin_x = layout.width + layout.pin_margin; // Slightly outside of node border
-                        let pin_y = layout.header_height
-                            + layout.pin_spacing
-                            + (i as f32 * layout.pin_spacing);
+                        let pin_pos = header_response.rect.min
+                            + vec2_to_pos2(layout.output_pin_offset(i)).to_vec2();
 
-                        let pin_pos = header_response.rect.min + egui::vec2(pin_x, pin_y);
-
-                        // Draw pin circle (6px radius as per SPEC.md)
+                        // Draw pin circle (6px radius as per SPEC.md), coloured by type
                         content_painter.circle_filled(
                             pin_pos,
                             layout.pin_radius, // 6px radius from NodeLayout
-                            egui::Color32::from_gray(200), // Neutral tone
+                            pin_color(signature.outputs.get(i).map(|spec| spec.ty)),
                         );
 
                         // Draw pin label next to pin (right-aligned)
                         let label_pos = pin_pos
-                            + egui::vec2(-layout.pin_radius - 4.0, -layout.pin_radius / 2.0);
+                            + egui::vec2(-layout.pin_radius - 4.0 * zoom, -layout.pin_radius / 2.0);
                         content_painter.text(
                             label_pos,
                             egui::Align2::RIGHT_CENTER,
                             &output_pin.label,
-                            egui::FontId::default(),
+                            font.clone(),
                             egui::Color32::WHITE,
                         );
                     }
 
-                    // Reserve space for internal content area (empty for now as per SPEC.md)
+                    // Reserve space for the pin rows
                     let content_height =
                         (node_instance.inputs.len().max(node_instance.outputs.len()) as f32
                             * layout.pin_spacing)
-                            + 20.0;
+                            + 20.0 * zoom;
                     ui.add_space(content_height);
+
+                    // Inline parameter editors below the pins
+                    if node_body_ui(ui, node_instance, &mut images, layout.width - 16.0 * zoom) {
+                        edited_nodes.push(node_instance.node_id);
+                    }
                 });
             });
+
+        // Remember the drawn size, in canvas units, for rubber-band selection and aligning
+        let drawn = area.response.rect.size() / zoom;
+        let size = Vec2::new(drawn.x, drawn.y);
+        if node_instance.size != size {
+            node_instance.size = size;
+            resized = true;
+        }
+    }
+
+    // The minimap draws the sizes too, without the preview recompiling for them
+    if resized {
+        minimap.set_changed();
+    }
+
+    // Edits may change the pin layout, e.g. a Switch node's case count
+    for node_id in &edited_nodes {
+        NodeFactory::sync_pins(graph, *node_id);
+    }
+    if !edited_nodes.is_empty() {
+        node_graph.set_changed();
     }
 }
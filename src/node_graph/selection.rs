@@ -0,0 +1,255 @@
+use crate::node_graph::model::{Connection, NodeGraph, NodeId, NodeInstance, PinId};
+use crate::node_graph::node_factory::NodeFactory;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Which edge or centre line `align_nodes` lines the nodes up on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    /// Centres on one vertical line
+    CenterX,
+    /// Centres on one horizontal line
+    CenterY,
+}
+
+impl Align {
+    pub const ALL: [Align; 6] = [
+        Align::Left,
+        Align::CenterX,
+        Align::Right,
+        Align::Top,
+        Align::CenterY,
+        Align::Bottom,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Align::Left => "Left",
+            Align::Right => "Right",
+            Align::Top => "Top",
+            Align::Bottom => "Bottom",
+            Align::CenterX => "Center X",
+            Align::CenterY => "Center Y",
+        }
+    }
+}
+
+/// Which way `distribute_nodes` spreads the nodes out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Distribute {
+    Horizontally,
+    Vertically,
+}
+
+impl Distribute {
+    pub fn label(self) -> &'static str {
+        match self {
+            Distribute::Horizontally => "Horizontally",
+            Distribute::Vertically => "Vertically",
+        }
+    }
+}
+
+/// Canvas-space rectangle a node covers, using the size it was last drawn at
+pub fn node_rect(node: &NodeInstance) -> Rect {
+    Rect::from_corners(node.position, node.position + node.size)
+}
+
+/// Every node in the graph, ordered by id
+pub fn all_nodes(graph: &NodeGraph) -> Vec<NodeId> {
+    let mut nodes: Vec<NodeId> = graph.nodes.keys().copied().collect();
+    nodes.sort_by_key(|id| id.0);
+    nodes
+}
+
+/// Nodes touching `rect`, ordered by id. `rect` and the node rectangles are compared in
+/// whatever space `to_rect` maps nodes into, so a screen-space rubber band can be tested
+/// against the rectangles the nodes are drawn at
+pub fn nodes_in_rect(
+    graph: &NodeGraph,
+    rect: Rect,
+    to_rect: impl Fn(&NodeInstance) -> Rect,
+) -> Vec<NodeId> {
+    let mut nodes: Vec<NodeId> = graph
+        .nodes
+        .values()
+        .filter(|node| !to_rect(node).intersect(rect).is_empty())
+        .map(|node| node.node_id)
+        .collect();
+    nodes.sort_by_key(|id| id.0);
+    nodes
+}
+
+/// Every node not in `selection`, ordered by id
+pub fn invert_selection(graph: &NodeGraph, selection: &[NodeId]) -> Vec<NodeId> {
+    all_nodes(graph)
+        .into_iter()
+        .filter(|id| !selection.contains(id))
+        .collect()
+}
+
+/// Remove the nodes and every connection to them
+pub fn delete_nodes(graph: &mut NodeGraph, node_ids: &[NodeId]) {
+    for &node_id in node_ids {
+        graph.remove_node(node_id);
+    }
+}
+
+/// Copy the nodes and the connections between them, moved by `offset`. Connections to nodes
+/// outside the selection are left behind. Returns the copies in the order of `node_ids`
+pub fn duplicate_nodes(graph: &mut NodeGraph, node_ids: &[NodeId], offset: Vec2) -> Vec<NodeId> {
+    let nodes: Vec<NodeInstance> = node_ids
+        .iter()
+        .filter_map(|id| graph.nodes.get(id).cloned())
+        .collect();
+    let connections = graph.connections.clone();
+    insert_copies(graph, &nodes, &connections, offset)
+}
+
+/// Add copies of `nodes` under fresh node and pin ids, moved by `offset`, along with those of
+/// `connections` that run between them. The nodes don't need to come from `graph`
+pub fn insert_copies(
+    graph: &mut NodeGraph,
+    nodes: &[NodeInstance],
+    connections: &[Connection],
+    offset: Vec2,
+) -> Vec<NodeId> {
+    let mut pins: HashMap<PinId, PinId> = HashMap::new();
+    let mut copies = Vec::with_capacity(nodes.len());
+
+    for node in nodes {
+        let node_id = NodeFactory::get_next_node_id(graph);
+        let next_pin_id = NodeFactory::get_next_pin_id(graph);
+        let (mut copy, _) = NodeFactory::create_node(
+            node_id,
+            node.position + offset,
+            node.kind.clone(),
+            next_pin_id,
+        );
+        copy.size = node.size;
+        for (old, new) in node.inputs.iter().zip(&copy.inputs) {
+            pins.insert(old.pin_id, new.pin_id);
+        }
+        for (old, new) in node.outputs.iter().zip(&copy.outputs) {
+            pins.insert(old.pin_id, new.pin_id);
+        }
+        graph.add_node(copy);
+        copies.push(node_id);
+    }
+
+    for connection in connections {
+        if let (Some(&from_pin), Some(&to_pin)) =
+            (pins.get(&connection.from_pin), pins.get(&connection.to_pin))
+        {
+            graph.add_connection(Connection { from_pin, to_pin });
+        }
+    }
+    copies
+}
+
+/// Line the nodes up on the outermost edge, or the average centre, of the group
+pub fn align_nodes(graph: &mut NodeGraph, node_ids: &[NodeId], align: Align) {
+    let rects: Vec<Rect> = node_ids
+        .iter()
+        .filter_map(|id| graph.nodes.get(id).map(node_rect))
+        .collect();
+    if rects.len() < 2 {
+        return;
+    }
+    let count = rects.len() as f32;
+    let target = match align {
+        Align::Left => rects.iter().map(|r| r.min.x).fold(f32::INFINITY, f32::min),
+        Align::Right => rects
+            .iter()
+            .map(|r| r.max.x)
+            .fold(f32::NEG_INFINITY, f32::max),
+        Align::Top => rects.iter().map(|r| r.min.y).fold(f32::INFINITY, f32::min),
+        Align::Bottom => rects
+            .iter()
+            .map(|r| r.max.y)
+            .fold(f32::NEG_INFINITY, f32::max),
+        Align::CenterX => rects.iter().map(|r| r.center().x).sum::<f32>() / count,
+        Align::CenterY => rects.iter().map(|r| r.center().y).sum::<f32>() / count,
+    };
+
+    for node_id in node_ids {
+        let Some(node) = graph.nodes.get_mut(node_id) else {
+            continue;
+        };
+        let size = node.size;
+        match align {
+            Align::Left => node.position.x = target,
+            Align::Right => node.position.x = target - size.x,
+            Align::Top => node.position.y = target,
+            Align::Bottom => node.position.y = target - size.y,
+            Align::CenterX => node.position.x = target - size.x / 2.0,
+            Align::CenterY => node.position.y = target - size.y / 2.0,
+        }
+    }
+}
+
+/// Space the nodes out between the first and last along the axis so the gaps between
+/// neighbours are equal. Those two stay put; fewer than three nodes have nothing to even out
+pub fn distribute_nodes(graph: &mut NodeGraph, node_ids: &[NodeId], distribute: Distribute) {
+    let axis = |v: Vec2| match distribute {
+        Distribute::Horizontally => v.x,
+        Distribute::Vertically => v.y,
+    };
+    let mut rects: Vec<(NodeId, Rect)> = node_ids
+        .iter()
+        .filter_map(|id| graph.nodes.get(id).map(|node| (*id, node_rect(node))))
+        .collect();
+    if rects.len() < 3 {
+        return;
+    }
+    rects.sort_by(|(_, a), (_, b)| axis(a.min).total_cmp(&axis(b.min)));
+    let start = axis(rects[0].1.min);
+    let end = axis(rects[rects.len() - 1].1.max);
+    let occupied: f32 = rects.iter().map(|(_, r)| axis(r.size())).sum();
+    let gap = (end - start - occupied) / (rects.len() - 1) as f32;
+
+    let last = rects.len() - 1;
+    let mut edge = axis(rects[0].1.max) + gap;
+    for (node_id, rect) in &rects[1..last] {
+        if let Some(node) = graph.nodes.get_mut(node_id) {
+            match distribute {
+                Distribute::Horizontally => node.position.x = edge,
+                Distribute::Vertically => node.position.y = edge,
+            }
+        }
+        edge += axis(rect.size()) + gap;
+    }
+}
+
+/// Stack the nodes into one column on the leftmost edge, in their top-to-bottom order, each
+/// `spacing` below the last. The topmost node stays where it is
+pub fn stack_nodes(graph: &mut NodeGraph, node_ids: &[NodeId], spacing: f32) {
+    let mut rects: Vec<(NodeId, Rect)> = node_ids
+        .iter()
+        .filter_map(|id| graph.nodes.get(id).map(|node| (*id, node_rect(node))))
+        .collect();
+    if rects.len() < 2 {
+        return;
+    }
+    rects.sort_by(|(_, a), (_, b)| {
+        a.min
+            .y
+            .total_cmp(&b.min.y)
+            .then(a.min.x.total_cmp(&b.min.x))
+    });
+    let left = rects
+        .iter()
+        .map(|(_, r)| r.min.x)
+        .fold(f32::INFINITY, f32::min);
+    let mut top = rects[0].1.min.y;
+    for (node_id, rect) in rects {
+        if let Some(node) = graph.nodes.get_mut(&node_id) {
+            node.position = Vec2::new(left, top);
+        }
+        top += rect.height() + spacing;
+    }
+}
@@ -0,0 +1,182 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::{NodeGraph, NodeId};
+    use crate::node_graph::node_factory::MathOperation;
+    use crate::node_graph::selection::{
+        Align, Distribute, align_nodes, all_nodes, delete_nodes, distribute_nodes, duplicate_nodes,
+        invert_selection, node_rect, nodes_in_rect, stack_nodes,
+    };
+    use crate::node_graph::test_utils::{add_node_at, connect};
+    use crate::shader_graph::node_kind::NodeKind;
+    use bevy::prelude::*;
+
+    /// Two constants into an Add, laid out left to right
+    fn chain() -> (NodeGraph, NodeId, NodeId, NodeId) {
+        let mut graph = NodeGraph::new();
+        let a = add_node_at(&mut graph, NodeKind::Constant(1.0), Vec2::new(0.0, 0.0));
+        let b = add_node_at(&mut graph, NodeKind::Constant(2.0), Vec2::new(0.0, 200.0));
+        let add = add_node_at(
+            &mut graph,
+            NodeKind::Math(MathOperation::Add),
+            Vec2::new(400.0, 100.0),
+        );
+        connect(&mut graph, a, 0, add, 0);
+        connect(&mut graph, b, 0, add, 1);
+        (graph, a, b, add)
+    }
+
+    #[test]
+    fn test_nodes_in_rect_uses_node_bounds() {
+        let (graph, a, b, add) = chain();
+        // Crossing the corner of `a` is enough
+        let picked = nodes_in_rect(&graph, Rect::new(200.0, 80.0, 260.0, 120.0), node_rect);
+        assert_eq!(picked, vec![a]);
+        let picked = nodes_in_rect(&graph, Rect::new(100.0, 50.0, 450.0, 250.0), node_rect);
+        assert_eq!(picked, vec![a, b, add]);
+        // Empty canvas between the nodes
+        let picked = nodes_in_rect(&graph, Rect::new(250.0, 0.0, 350.0, 40.0), node_rect);
+        assert!(picked.is_empty());
+
+        // Screen space at twice the zoom puts `add` at 800
+        let picked = nodes_in_rect(&graph, Rect::new(700.0, 0.0, 790.0, 400.0), |node| {
+            Rect::from_corners(node.position * 2.0, node.position * 2.0 + node.size)
+        });
+        assert!(picked.is_empty());
+    }
+
+    #[test]
+    fn test_select_all_and_invert() {
+        let (graph, a, b, add) = chain();
+        assert_eq!(all_nodes(&graph), vec![a, b, add]);
+        assert_eq!(invert_selection(&graph, &[b]), vec![a, add]);
+        assert!(invert_selection(&graph, &[a, b, add]).is_empty());
+    }
+
+    #[test]
+    fn test_delete_removes_connections() {
+        let (mut graph, a, _, add) = chain();
+        delete_nodes(&mut graph, &[a, add]);
+        assert_eq!(graph.nodes.len(), 1);
+        assert!(graph.connections.is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_keeps_internal_connections() {
+        let (mut graph, a, b, add) = chain();
+        let copies = duplicate_nodes(&mut graph, &[a, add], Vec2::new(40.0, 40.0));
+        assert_eq!(copies.len(), 2);
+        assert_eq!(graph.nodes.len(), 5);
+        assert_eq!(graph.nodes[&copies[0]].position, Vec2::new(40.0, 40.0));
+        assert_eq!(graph.nodes[&copies[0]].kind, NodeKind::Constant(1.0));
+        assert_eq!(
+            graph.nodes[&copies[1]].kind,
+            NodeKind::Math(MathOperation::Add)
+        );
+
+        // a -> add is copied, b -> add runs out of the selection and is not
+        assert_eq!(graph.connections.len(), 3);
+        let copy_input = graph.nodes[&copies[1]].inputs[0].pin_id;
+        assert_eq!(
+            graph.connection_into(copy_input).map(|c| c.from_pin),
+            Some(graph.nodes[&copies[0]].outputs[0].pin_id)
+        );
+        let unconnected = graph.nodes[&copies[1]].inputs[1].pin_id;
+        assert!(graph.connection_into(unconnected).is_none());
+
+        // Every pin id is still unique
+        let mut pins: Vec<u32> = graph
+            .nodes
+            .values()
+            .flat_map(|n| {
+                n.inputs
+                    .iter()
+                    .map(|p| p.pin_id.0)
+                    .chain(n.outputs.iter().map(|p| p.pin_id.0))
+            })
+            .collect();
+        let count = pins.len();
+        pins.sort();
+        pins.dedup();
+        assert_eq!(pins.len(), count);
+        assert!(!copies.contains(&b));
+    }
+
+    #[test]
+    fn test_align_edges_and_centres() {
+        let (mut graph, a, b, add) = chain();
+        graph.nodes.get_mut(&add).unwrap().size = Vec2::new(200.0, 60.0);
+        let nodes = [a, b, add];
+
+        align_nodes(&mut graph, &nodes, Align::Left);
+        assert!(nodes.iter().all(|id| graph.nodes[id].position.x == 0.0));
+
+        align_nodes(&mut graph, &nodes, Align::Right);
+        assert_eq!(graph.nodes[&a].position.x, 0.0);
+        assert_eq!(graph.nodes[&add].position.x, 20.0);
+
+        align_nodes(&mut graph, &nodes, Align::Bottom);
+        assert_eq!(graph.nodes[&b].position.y, 200.0);
+        assert_eq!(graph.nodes[&add].position.y, 240.0);
+
+        align_nodes(&mut graph, &nodes, Align::CenterY);
+        let centres: Vec<f32> = nodes
+            .iter()
+            .map(|id| node_rect(&graph.nodes[id]).center().y)
+            .collect();
+        assert!(centres.iter().all(|&y| (y - centres[0]).abs() < 1e-4));
+
+        // One node has nothing to line up with
+        let before = graph.nodes[&a].position;
+        align_nodes(&mut graph, &[a], Align::Top);
+        assert_eq!(graph.nodes[&a].position, before);
+    }
+
+    #[test]
+    fn test_distribute_evens_out_gaps() {
+        let (mut graph, a, b, add) = chain();
+        let extra = add_node_at(&mut graph, NodeKind::Constant(3.0), Vec2::new(100.0, 900.0));
+        graph.nodes.get_mut(&add).unwrap().size = Vec2::new(200.0, 60.0);
+        let nodes = [a, b, add, extra];
+
+        distribute_nodes(&mut graph, &nodes, Distribute::Vertically);
+        let mut rects: Vec<Rect> = nodes.iter().map(|id| node_rect(&graph.nodes[id])).collect();
+        rects.sort_by(|a, b| a.min.y.total_cmp(&b.min.y));
+        // The outermost nodes stay, the gaps between the rest match
+        assert_eq!(rects[0].min.y, 0.0);
+        assert_eq!(rects[3].min.y, 900.0);
+        let gaps: Vec<f32> = rects.windows(2).map(|w| w[1].min.y - w[0].max.y).collect();
+        assert!(
+            gaps.iter().all(|gap| (gap - gaps[0]).abs() < 1e-3),
+            "{:?}",
+            gaps
+        );
+        // Only the y changed
+        assert_eq!(graph.nodes[&add].position.x, 400.0);
+
+        distribute_nodes(&mut graph, &nodes, Distribute::Horizontally);
+        assert_eq!(graph.nodes[&a].position.x, 0.0);
+        assert_eq!(node_rect(&graph.nodes[&add]).max.x, 600.0);
+
+        // Two nodes have no gap between them to even out
+        let before = graph.nodes[&b].position;
+        distribute_nodes(&mut graph, &[a, b], Distribute::Vertically);
+        assert_eq!(graph.nodes[&b].position, before);
+    }
+
+    #[test]
+    fn test_stack_into_a_column() {
+        let (mut graph, a, b, add) = chain();
+        let size = graph.nodes[&a].size;
+        graph.nodes.get_mut(&a).unwrap().position = Vec2::new(30.0, 50.0);
+
+        // Stacked in the order they ran top to bottom, on the leftmost edge
+        stack_nodes(&mut graph, &[b, add, a], 24.0);
+        assert_eq!(graph.nodes[&a].position, Vec2::new(0.0, 50.0));
+        assert_eq!(
+            graph.nodes[&add].position,
+            Vec2::new(0.0, 50.0 + size.y + 24.0)
+        );
+        let add_bottom = node_rect(&graph.nodes[&add]).max.y;
+        assert_eq!(graph.nodes[&b].position, Vec2::new(0.0, add_bottom + 24.0));
+    }
+}
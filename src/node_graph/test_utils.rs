@@ -0,0 +1,132 @@
+use crate::node_graph::model::{Connection, NodeGraph, NodeId};
+use crate::node_graph::node_factory::NodeFactory;
+use crate::shader_graph::compiler::{CompileError, validate_wgsl};
+use crate::shader_graph::node_kind::NodeKind;
+use bevy::prelude::*;
+
+/// Add a node at the canvas origin, returning its id
+pub fn add_node(graph: &mut NodeGraph, kind: NodeKind) -> NodeId {
+    add_node_at(graph, kind, Vec2::ZERO)
+}
+
+pub fn add_node_at(graph: &mut NodeGraph, kind: NodeKind, position: Vec2) -> NodeId {
+    let node_id = NodeFactory::get_next_node_id(graph);
+    let next_pin_id = NodeFactory::get_next_pin_id(graph);
+    let (node, _) = NodeFactory::create_node(node_id, position, kind, next_pin_id);
+    graph.add_node(node);
+    node_id
+}
+
+/// Wire output `output` of `from` into input `input` of `to`
+pub fn connect(graph: &mut NodeGraph, from: NodeId, output: usize, to: NodeId, input: usize) {
+    let from_pin = graph.nodes[&from].outputs[output].pin_id;
+    let to_pin = graph.nodes[&to].inputs[input].pin_id;
+    graph.add_connection(Connection { from_pin, to_pin });
+}
+
+/// Scalars and vectors compared component by component
+pub trait Near: Copy + std::fmt::Debug {
+    /// Largest difference between matching components
+    fn max_difference(self, other: Self) -> f32;
+}
+
+impl Near for f32 {
+    fn max_difference(self, other: Self) -> f32 {
+        (self - other).abs()
+    }
+}
+
+impl Near for Vec2 {
+    fn max_difference(self, other: Self) -> f32 {
+        (self - other).abs().max_element()
+    }
+}
+
+impl Near for Vec3 {
+    fn max_difference(self, other: Self) -> f32 {
+        (self - other).abs().max_element()
+    }
+}
+
+impl Near for Vec4 {
+    fn max_difference(self, other: Self) -> f32 {
+        (self - other).abs().max_element()
+    }
+}
+
+/// Fail unless every component of `a` is within 1e-4 of `b`'s
+#[track_caller]
+pub fn assert_near<T: Near>(a: T, b: T) {
+    assert!(a.max_difference(b) < 1e-4, "{:?} != {:?}", a, b);
+}
+
+/// Stand-ins for what Bevy's preprocessor provides a mesh material, so naga can check the rest
+pub const BEVY_MESH_STUBS: &str = "struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec4<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(4) world_tangent: vec4<f32>,
+    @location(5) color: vec4<f32>,
+};
+
+struct View {
+    clip_from_world: mat4x4<f32>,
+    view_from_world: mat4x4<f32>,
+    world_position: vec3<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> view: View;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(4) tangent: vec4<f32>,
+    @location(5) color: vec4<f32>,
+};
+
+fn get_world_from_local(instance_index: u32) -> mat4x4<f32> {
+    return view.view_from_world;
+}
+
+fn mesh_normal_local_to_world(normal: vec3<f32>, instance_index: u32) -> vec3<f32> {
+    return normal;
+}
+
+fn mesh_position_local_to_world(world_from_local: mat4x4<f32>, position: vec4<f32>) -> vec4<f32> {
+    return world_from_local * position;
+}
+
+fn mesh_tangent_local_to_world(
+    world_from_local: mat4x4<f32>,
+    tangent: vec4<f32>,
+    instance_index: u32,
+) -> vec4<f32> {
+    return tangent;
+}
+
+fn position_world_to_clip(position: vec3<f32>) -> vec4<f32> {
+    return view.clip_from_world * vec4<f32>(position, 1.0);
+}
+";
+
+/// Validate a material shader the way Bevy would see it, after `stubs`: imports dropped,
+/// `#ifdef` blocks for pipeline flags the stubs leave out skipped and the material in group 3
+pub fn validate_with_bevy_stubs(stubs: &str, source: &str) -> Result<(), CompileError> {
+    let mut flagged = false;
+    let body: String = source
+        .lines()
+        .filter(|line| {
+            flagged |= line.starts_with("#ifdef");
+            let keep = !flagged && !line.starts_with("#import");
+            flagged &= !line.starts_with("#endif");
+            keep
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .replace("#{MATERIAL_BIND_GROUP}", "3");
+    validate_wgsl(&format!("{}\n{}", stubs, body))
+}
@@ -1,4 +1,4 @@
-use crate::node_graph::model::{NodeId, PinId};
+use crate::node_graph::model::{AnnotationId, NodeId, PinId};
 use bevy::prelude::*;
 use bevy_egui::egui;
 
@@ -8,12 +8,67 @@ pub struct PendingConnection {
     pub from_screen_pos: egui::Pos2,
 }
 
+/// Rubber band dragged out over empty canvas, in screen space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoxSelect {
+    pub from: egui::Pos2,
+    pub to: egui::Pos2,
+    /// Shift or Ctrl was held: add to the selection instead of replacing it
+    pub additive: bool,
+}
+
+impl BoxSelect {
+    pub fn rect(&self) -> egui::Rect {
+        egui::Rect::from_two_pos(self.from, self.to)
+    }
+}
+
+/// Which part of a comment frame or sticky note the pointer grabbed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationPart {
+    /// A frame's title bar or anywhere on a note
+    Move,
+    /// The bottom-right corner
+    Resize,
+}
+
+/// A comment frame or sticky note being dragged
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotationDrag {
+    pub id: AnnotationId,
+    pub part: AnnotationPart,
+    /// Nodes and notes inside a moved frame when it was grabbed; they move with it
+    pub carried_nodes: Vec<NodeId>,
+    pub carried_notes: Vec<AnnotationId>,
+}
+
+/// Where the node search palette opens, and the wire the picked node connects to
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteRequest {
+    pub screen_pos: egui::Pos2,
+    /// Output pin a wire was dragged from into empty canvas; only nodes taking its type are
+    /// listed
+    pub from_pin: Option<PinId>,
+}
+
 #[derive(Debug, Clone, Resource, Default)]
 pub struct GraphUiState {
     pub pending_connection: Option<PendingConnection>,
     pub active_drag_node: Option<NodeId>,
     pub drag_origin: Vec2,
     pub drag_offset: Vec2,
+    /// Where the dragged nodes were when the drag started, for the undo step it records
+    pub drag_start: Vec<(NodeId, Vec2)>,
+    /// Selected nodes; dragging any of their headers moves them all
+    pub selection: Vec<NodeId>,
+    pub box_select: Option<BoxSelect>,
+    /// Subgraph node double-clicked this frame, opened by `subgraph_ui_system`
+    pub open_subgraph: Option<NodeId>,
+    /// Set to open the node search palette, taken by `node_palette_system`
+    pub palette: Option<PaletteRequest>,
+    /// Comment frame or sticky note last clicked, edited in the Comments window
+    pub active_annotation: Option<AnnotationId>,
+    pub annotation_drag: Option<AnnotationDrag>,
 }
 
 impl GraphUiState {
@@ -21,9 +76,35 @@ impl GraphUiState {
         self.active_drag_node = None;
         self.drag_origin = Vec2::ZERO;
         self.drag_offset = Vec2::ZERO;
+        self.drag_start.clear();
+        self.box_select = None;
+        self.annotation_drag = None;
     }
 
     pub fn clear_pending_connection(&mut self) {
         self.pending_connection = None;
     }
+
+    pub fn is_selected(&self, node_id: NodeId) -> bool {
+        self.selection.contains(&node_id)
+    }
+
+    /// Add nodes to the selection, skipping those already in
+    pub fn extend_selection(&mut self, node_ids: impl IntoIterator<Item = NodeId>) {
+        for node_id in node_ids {
+            if !self.is_selected(node_id) {
+                self.selection.push(node_id);
+            }
+        }
+    }
+
+    /// Add a node to the selection, or take it out if it is already in
+    pub fn toggle_selected(&mut self, node_id: NodeId) {
+        match self.selection.iter().position(|&id| id == node_id) {
+            Some(index) => {
+                self.selection.remove(index);
+            }
+            None => self.selection.push(node_id),
+        }
+    }
 }
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
     use crate::node_graph::model::{NodeId, PinId};
-    use crate::node_graph::ui_state::{GraphUiState, PendingConnection};
+    use crate::node_graph::ui_state::{BoxSelect, GraphUiState, PendingConnection};
     use bevy::prelude::*;
     use bevy_egui::egui;
 
@@ -118,4 +118,36 @@ mod tests {
         assert_eq!(ui_state.drag_origin, Vec2::ZERO);
         assert_eq!(ui_state.drag_offset, Vec2::ZERO);
     }
+
+    #[test]
+    fn test_selection_toggle_and_extend() {
+        let mut ui_state = GraphUiState::default();
+        ui_state.toggle_selected(NodeId(1));
+        ui_state.toggle_selected(NodeId(2));
+        ui_state.toggle_selected(NodeId(1));
+        assert_eq!(ui_state.selection, vec![NodeId(2)]);
+
+        ui_state.extend_selection([NodeId(3), NodeId(2), NodeId(4)]);
+        assert_eq!(ui_state.selection, vec![NodeId(2), NodeId(3), NodeId(4)]);
+        assert!(ui_state.is_selected(NodeId(3)));
+        assert!(!ui_state.is_selected(NodeId(1)));
+    }
+
+    #[test]
+    fn test_box_select_rect_from_any_corner() {
+        let mut ui_state = GraphUiState::default();
+        let band = BoxSelect {
+            from: egui::pos2(300.0, 50.0),
+            to: egui::pos2(100.0, 250.0),
+            additive: false,
+        };
+        assert_eq!(
+            band.rect(),
+            egui::Rect::from_min_max(egui::pos2(100.0, 50.0), egui::pos2(300.0, 250.0))
+        );
+
+        ui_state.box_select = Some(band);
+        ui_state.clear_drag_state();
+        assert_eq!(ui_state.box_select, None);
+    }
 }
@@ -1,6 +1,13 @@
 pub static NODE_WGSL_MAP: &[(&str, &str)] = &[
     ("Add", "fn add_node(a: vec3<f32>, b: vec3<f32>) -> vec3<f32> { return a + b; }"),
     ("Multiply", "fn mul_node(a: vec3<f32>, b: vec3<f32>) -> vec3<f32> { return a * b; }"),
-    ("ColorRamp", "fn color_ramp(t: f32, colors: array<vec4<f32>, 4>) -> vec4<f32> { /* impl */ }"),
+    // Generated per node from its stops - see shader_graph::nodes::gradient::ColorRamp::wgsl_function
+    ("ColorRamp", "fn color_ramp_<hash>(t: f32) -> vec4<f32>"),
+    // User-authored in the node body and saved in its NodeKind - see shader_graph::nodes::custom
+    ("CustomFunction", "fn <name>(<args>) -> <type or struct>"),
+    // Read from assets/shaders/library with its dependencies - see shader_graph::nodes::library
+    ("LibraryFunction", "fn <name>(<args>) -> <type or struct>"),
+    // Compiled from the nodes inside, once per subgraph - see shader_graph::compiler::compile_subgraph
+    ("Subgraph", "fn subgraph_<name>_<hash>(in0.., input, global_id) -> <type or struct>"),
     // ... all your node types
 ];
\ No newline at end of file
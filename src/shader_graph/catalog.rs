@@ -0,0 +1,451 @@
+use crate::node_graph::model::{Connection, NodeGraph, NodeId, PinId};
+use crate::node_graph::node_factory::{MathOperation, NodeFactory};
+use crate::shader_graph::node_kind::{NodeKind, OutputTarget, ShaderNode};
+use crate::shader_graph::nodes::color::{BlendMode, ColorNode};
+use crate::shader_graph::nodes::compute::{ComputeNode, WorkgroupSize};
+use crate::shader_graph::nodes::custom::CustomFunction;
+use crate::shader_graph::nodes::geometry::{GeometryNode, Space};
+use crate::shader_graph::nodes::gradient::ColorRamp;
+use crate::shader_graph::nodes::library::WgslLibrary;
+use crate::shader_graph::nodes::lighting::LightingNode;
+use crate::shader_graph::nodes::logic::{BooleanOp, CompareOp, LogicNode};
+use crate::shader_graph::nodes::normal::{NormalBlendMode, NormalNode};
+use crate::shader_graph::nodes::texture::{SamplerState, TextureNode};
+use crate::shader_graph::nodes::time::{TimeNode, Waveform};
+use crate::shader_graph::nodes::uv::UvNode;
+use crate::shader_graph::types::PinType;
+use bevy::prelude::*;
+
+/// One entry of the node search palette: a node kind as it is created, with the category
+/// it is listed under and a line describing it
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeTemplate {
+    pub title: String,
+    pub category: String,
+    pub description: String,
+    pub kind: NodeKind,
+    /// Pin types in pin order, read off the signature once
+    inputs: Vec<PinType>,
+}
+
+impl NodeTemplate {
+    pub fn new(category: &str, description: &str, kind: NodeKind) -> Self {
+        let signature = kind.signature();
+        Self {
+            title: kind.title(),
+            category: category.to_string(),
+            description: description.to_string(),
+            inputs: signature.inputs.iter().map(|spec| spec.ty).collect(),
+            kind,
+        }
+    }
+
+    /// Input a wire of type `ty` connects to: the first of exactly that type, else the first
+    /// it converts to
+    pub fn input_for(&self, ty: PinType) -> Option<usize> {
+        self.inputs
+            .iter()
+            .position(|&input| input == ty)
+            .or_else(|| self.inputs.iter().position(|&input| ty.converts_to(input)))
+    }
+
+    /// Add the node at `position`. With `wire`, an output pin dragged out to where the node
+    /// goes, the wire is connected to the node's matching input.
+    pub fn spawn(&self, graph: &mut NodeGraph, position: Vec2, wire: Option<PinId>) -> NodeId {
+        let node_id = NodeFactory::get_next_node_id(graph);
+        let next_pin_id = NodeFactory::get_next_pin_id(graph);
+        let (node, _) = NodeFactory::create_node(node_id, position, self.kind.clone(), next_pin_id);
+        let target = wire
+            .and_then(|from_pin| Some((from_pin, graph.pin_type(from_pin)?)))
+            .and_then(|(from_pin, ty)| {
+                let index = self.input_for(ty)?;
+                Some(Connection {
+                    from_pin,
+                    to_pin: node.inputs[index].pin_id,
+                })
+            });
+        graph.add_node(node);
+        if let Some(connection) = target {
+            graph.add_connection(connection);
+        }
+        node_id
+    }
+
+    /// How well the template matches a search: the title counts most, then the category,
+    /// then words found in the description
+    pub fn score(&self, query: &str) -> Option<i32> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Some(0);
+        }
+        let title = fuzzy_score(query, &self.title).map(|score| score + 20);
+        let category = fuzzy_score(query, &self.category);
+        let description = self.description.to_lowercase();
+        let described = query
+            .split_whitespace()
+            .all(|word| description.contains(&word.to_lowercase()))
+            .then_some(1);
+        title.max(category).max(described)
+    }
+}
+
+/// How well `query` matches `text`: every non-space query character has to appear in order,
+/// ignoring case. Runs of consecutive characters and matches at word starts score higher, gaps
+/// cost a little, so "rgbhsv" prefers "RGB to HSV" over "Rotate UV".
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let wanted = wanted.to_ascii_lowercase();
+        let found = (next..text.len()).find(|&i| text[i].to_ascii_lowercase() == wanted)?;
+        let word_start = found == 0
+            || !text[found - 1].is_alphanumeric()
+            || (text[found - 1].is_lowercase() && text[found].is_uppercase());
+        score += 1;
+        if word_start {
+            score += 8;
+        }
+        match previous {
+            Some(previous) if previous + 1 == found => score += 5,
+            Some(previous) => score -= (found - previous - 1).min(3) as i32,
+            None => score -= found.min(3) as i32,
+        }
+        previous = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+/// Templates matching `query`, best first and grouped by category, the category of the best
+/// match leading. With `accepts`, only templates with an input a wire of that type connects to.
+pub fn search(templates: &[NodeTemplate], query: &str, accepts: Option<PinType>) -> Vec<usize> {
+    let mut matches: Vec<(usize, i32)> = templates
+        .iter()
+        .enumerate()
+        .filter(|(_, template)| accepts.is_none_or(|ty| template.input_for(ty).is_some()))
+        .filter_map(|(index, template)| template.score(query).map(|score| (index, score)))
+        .collect();
+    // Stable, so equal scores keep the catalog order
+    matches.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+
+    let mut categories: Vec<&str> = Vec::new();
+    for &(index, _) in &matches {
+        let category = templates[index].category.as_str();
+        if !categories.contains(&category) {
+            categories.push(category);
+        }
+    }
+    let rank = |index: usize| {
+        categories
+            .iter()
+            .position(|&category| category == templates[index].category)
+    };
+    matches.sort_by_key(|&(index, _)| rank(index));
+    matches.into_iter().map(|(index, _)| index).collect()
+}
+
+/// Every node type the editor can create, by category, followed by the WGSL library's
+/// functions under their own categories
+pub fn node_catalog(library: &WgslLibrary) -> Vec<NodeTemplate> {
+    let t = NodeTemplate::new;
+    let mut templates = builtin_templates();
+    for function in &library.functions {
+        let description = if function.description.is_empty() {
+            format!("{}::{}", function.file, function.signature.name)
+        } else {
+            function.description.clone()
+        };
+        templates.push(t(
+            &function.category,
+            &description,
+            NodeKind::Library(function.clone()),
+        ));
+    }
+    templates
+}
+
+/// The node types built into the editor, by category
+pub fn builtin_templates() -> Vec<NodeTemplate> {
+    let t = NodeTemplate::new;
+    let mut templates = vec![
+        t("Input", "A constant float value", NodeKind::Constant(1.0)),
+        t("Input", "Mesh UV0", NodeKind::Uv(UvNode::Uv)),
+        t(
+            "Input",
+            "Seconds, sine and cosine of seconds, and the frame delta",
+            NodeKind::Time(TimeNode::Time),
+        ),
+        t("Math", "A + B", NodeKind::Math(MathOperation::Add)),
+        t("Math", "A - B", NodeKind::Math(MathOperation::Subtract)),
+        t("Math", "A * B", NodeKind::Math(MathOperation::Multiply)),
+        t("Math", "A / B", NodeKind::Math(MathOperation::Divide)),
+        t(
+            "Math",
+            "Linearly map a value from one range to another",
+            NodeKind::Time(TimeNode::Remap),
+        ),
+    ];
+
+    let colors = [
+        (
+            ColorNode::RgbToHsv,
+            "Hue, saturation and value of an RGB colour",
+        ),
+        (
+            ColorNode::HsvToRgb,
+            "RGB colour from hue, saturation and value",
+        ),
+        (
+            ColorNode::RgbToHsl,
+            "Hue, saturation and lightness of an RGB colour",
+        ),
+        (
+            ColorNode::HslToRgb,
+            "RGB colour from hue, saturation and lightness",
+        ),
+        (
+            ColorNode::SrgbToLinear,
+            "Decode sRGB-encoded colour to linear",
+        ),
+        (ColorNode::LinearToSrgb, "Encode linear colour as sRGB"),
+        (ColorNode::Luminance, "Perceived brightness of a colour"),
+        (
+            ColorNode::Contrast,
+            "Push colours away from or towards mid grey",
+        ),
+        (ColorNode::Saturation, "Scale a colour's saturation"),
+        (ColorNode::HueShift, "Rotate a colour's hue"),
+    ];
+    for (node, description) in colors {
+        templates.push(t("Color", description, NodeKind::Color(node)));
+    }
+    for mode in [
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Overlay,
+        BlendMode::SoftLight,
+        BlendMode::Add,
+        BlendMode::Difference,
+    ] {
+        templates.push(t(
+            "Color",
+            "Blend a colour over a base by an opacity",
+            NodeKind::Blend(mode),
+        ));
+    }
+    templates.push(t(
+        "Color",
+        "Colour gradient sampled at a position, stops edited in the node",
+        NodeKind::ColorRamp(ColorRamp::default()),
+    ));
+
+    let uvs = [
+        (UvNode::TilingOffset, "Scale and shift UVs"),
+        (
+            UvNode::Rotate,
+            "Rotate UVs around a pivot, angle in radians",
+        ),
+        (UvNode::Flipbook, "Pick one cell of a sprite sheet"),
+        (UvNode::CartesianToPolar, "UV to radius and angle"),
+        (UvNode::PolarToCartesian, "Radius and angle back to UV"),
+        (UvNode::Twirl, "Swirl UVs around a centre"),
+        (UvNode::Spherize, "Bulge UVs as if wrapped on a sphere"),
+        (UvNode::RadialShear, "Shear UVs around a centre"),
+        (
+            UvNode::ScreenPosition,
+            "Fragment position in framebuffer pixels",
+        ),
+    ];
+    for (node, description) in uvs {
+        templates.push(t("UV", description, NodeKind::Uv(node)));
+    }
+
+    for space in Space::ALL {
+        templates.push(t(
+            "Geometry",
+            "Surface position",
+            NodeKind::Geometry(GeometryNode::Position(space)),
+        ));
+        templates.push(t(
+            "Geometry",
+            "Surface normal",
+            NodeKind::Geometry(GeometryNode::Normal(space)),
+        ));
+    }
+    let geometry = [
+        (GeometryNode::Tangent, "World-space tangent from the mesh"),
+        (
+            GeometryNode::ViewDirection,
+            "World-space unit vector from the surface towards the camera",
+        ),
+        (GeometryNode::CameraPosition, "World-space camera position"),
+        (GeometryNode::VertexColor, "Colour attribute of the mesh"),
+        (
+            GeometryNode::ScreenPosition,
+            "Screen position, 0 to 1 from the top-left corner",
+        ),
+        (
+            GeometryNode::Custom(0),
+            "Custom interpolant written by the Vertex Output",
+        ),
+        (
+            GeometryNode::Custom(1),
+            "Custom interpolant written by the Vertex Output",
+        ),
+    ];
+    for (node, description) in geometry {
+        templates.push(t("Geometry", description, NodeKind::Geometry(node)));
+    }
+
+    for waveform in Waveform::ALL {
+        templates.push(t(
+            "Time",
+            "amplitude * wave(time * frequency + phase)",
+            NodeKind::Time(TimeNode::Oscillator(waveform)),
+        ));
+    }
+
+    let textures = [
+        (
+            TextureNode::default_texture(),
+            "Image asset, picked in the node",
+        ),
+        (
+            TextureNode::Sample(SamplerState::default()),
+            "Sample a texture at a UV, the mesh UVs when unconnected",
+        ),
+        (
+            TextureNode::SampleLevel(SamplerState::default()),
+            "Sample a texture at an explicit mip level, also in the vertex stage",
+        ),
+    ];
+    for (node, description) in textures {
+        templates.push(t("Texture", description, NodeKind::Texture(node)));
+    }
+
+    let normals = [
+        (
+            NormalNode::FromTexture(SamplerState::default()),
+            "Decode a tangent-space normal map",
+        ),
+        (NormalNode::FromHeight, "Normal of a height field"),
+        (
+            NormalNode::Triplanar(SamplerState::default()),
+            "Sample a texture projected along the three world axes",
+        ),
+        (
+            NormalNode::ParallaxOffset,
+            "Shift UVs along the view direction by a height",
+        ),
+    ];
+    for (node, description) in normals {
+        templates.push(t("Normal", description, NodeKind::Normal(node)));
+    }
+    for mode in NormalBlendMode::ALL {
+        templates.push(t(
+            "Normal",
+            "Combine two tangent-space normals",
+            NodeKind::Normal(NormalNode::Blend(mode)),
+        ));
+    }
+
+    let lighting = [
+        (
+            LightingNode::Light,
+            "Directional light, point light and ambient colour of the scene",
+        ),
+        (
+            LightingNode::Lambert,
+            "Diffuse lighting, color * max(N.L, 0)",
+        ),
+        (
+            LightingNode::BlinnPhong,
+            "Specular highlight from the half vector",
+        ),
+        (
+            LightingNode::Fresnel,
+            "Schlick's approximation of reflectance",
+        ),
+        (LightingNode::Rim, "Glow towards the silhouette"),
+        (LightingNode::ToonRamp, "Lambert quantised into flat bands"),
+    ];
+    for (node, description) in lighting {
+        templates.push(t("Lighting", description, NodeKind::Lighting(node)));
+    }
+
+    let logic = [
+        (
+            LogicNode::Compare(CompareOp::Less, PinType::Float),
+            "Compare two values, operator and type picked in the node",
+        ),
+        (
+            LogicNode::Boolean(BooleanOp::And, PinType::Bool),
+            "Combine conditions, operator and type picked in the node",
+        ),
+        (
+            LogicNode::Select(PinType::Float),
+            "True where the condition holds, False elsewhere",
+        ),
+        (
+            LogicNode::Switch {
+                cases: 3,
+                ty: PinType::Vec3,
+            },
+            "The case input picked by an index",
+        ),
+    ];
+    for (node, description) in logic {
+        templates.push(t("Logic", description, NodeKind::Logic(node)));
+    }
+
+    let compute = [
+        (ComputeNode::ReadBuffer(0), "Read a storage buffer element"),
+        (
+            ComputeNode::WriteBuffer(0),
+            "Write a storage buffer element",
+        ),
+        (
+            ComputeNode::ReadTexture(0),
+            "Load a storage texture texel at integer pixel coordinates",
+        ),
+        (
+            ComputeNode::WriteTexture(0),
+            "Store a storage texture texel at integer pixel coordinates",
+        ),
+    ];
+    for (node, description) in compute {
+        templates.push(t("Compute", description, NodeKind::Compute(node)));
+    }
+
+    templates.push(t(
+        "Custom",
+        "A WGSL function written in the node, pins read off its signature",
+        NodeKind::Custom(CustomFunction::default()),
+    ));
+
+    templates.extend([
+        t(
+            "Output",
+            "Final colour and alpha of the chosen target",
+            NodeKind::FragmentOutput(OutputTarget::default()),
+        ),
+        t(
+            "Output",
+            "Surface inputs for Bevy's PBR lighting",
+            NodeKind::PbrOutput,
+        ),
+        t(
+            "Output",
+            "Displacement, normal and custom interpolants of the vertex stage",
+            NodeKind::VertexOutput,
+        ),
+        t(
+            "Output",
+            "Marks the graph as a compute kernel with its workgroup size",
+            NodeKind::ComputeOutput(WorkgroupSize::default()),
+        ),
+    ]);
+    templates
+}
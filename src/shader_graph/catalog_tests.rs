@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::NodeGraph;
+    use crate::node_graph::node_factory::MathOperation;
+    use crate::node_graph::test_utils::add_node;
+    use crate::shader_graph::catalog::{NodeTemplate, fuzzy_score, node_catalog, search};
+    use crate::shader_graph::node_kind::NodeKind;
+    use crate::shader_graph::nodes::library::{LIBRARY_DIR, WgslLibrary};
+    use crate::shader_graph::nodes::texture::{SamplerState, TextureNode};
+    use crate::shader_graph::types::PinType;
+    use bevy::prelude::*;
+
+    fn catalog() -> Vec<NodeTemplate> {
+        node_catalog(&WgslLibrary::load(LIBRARY_DIR))
+    }
+
+    #[test]
+    fn test_fuzzy_score_prefers_word_starts_and_runs() {
+        assert_eq!(fuzzy_score("xyz", "Add"), None);
+        assert_eq!(fuzzy_score("", "Add"), Some(0));
+        assert!(fuzzy_score("rgbhsv", "RGB to HSV").is_some());
+        assert!(fuzzy_score("hsv", "HSV to RGB") > fuzzy_score("hsv", "RGB to HSV"));
+        assert!(fuzzy_score("mul", "Multiply") > fuzzy_score("mul", "Simulate"));
+    }
+
+    #[test]
+    fn test_catalog_lists_every_kind_with_a_description() {
+        let templates = catalog();
+        assert!(templates.iter().all(|t| !t.title.is_empty()));
+        assert!(templates.iter().all(|t| !t.description.is_empty()));
+        for kind in [
+            NodeKind::Math(MathOperation::Divide),
+            NodeKind::PbrOutput,
+            NodeKind::Texture(TextureNode::Sample(SamplerState::default())),
+        ] {
+            assert!(templates.iter().any(|t| t.kind == kind), "{:?}", kind);
+        }
+        // Library functions come under their own categories
+        let fbm = templates
+            .iter()
+            .find(|t| matches!(&t.kind, NodeKind::Library(f) if f.signature.name == "fbm"))
+            .expect("fbm should be listed");
+        assert_eq!(fbm.category, "Noise");
+    }
+
+    #[test]
+    fn test_search_ranks_and_groups() {
+        let templates = catalog();
+        let results = search(&templates, "multiply", None);
+        assert_eq!(
+            templates[results[0]].kind,
+            NodeKind::Math(MathOperation::Multiply)
+        );
+
+        // Every category appears in one run
+        let all = search(&templates, "", None);
+        assert_eq!(all.len(), templates.len());
+        let mut seen: Vec<&str> = Vec::new();
+        for &index in &all {
+            let category = templates[index].category.as_str();
+            if seen.last() != Some(&category) {
+                assert!(!seen.contains(&category), "{} is split", category);
+                seen.push(category);
+            }
+        }
+
+        // Descriptions are searched too
+        let results = search(&templates, "sprite sheet", None);
+        assert_eq!(templates[results[0]].title, "Flipbook");
+    }
+
+    #[test]
+    fn test_wire_type_filters_the_results() {
+        let templates = catalog();
+        let results = search(&templates, "", Some(PinType::Texture2D));
+        assert!(!results.is_empty());
+        assert!(
+            results
+                .iter()
+                .all(|&i| templates[i].input_for(PinType::Texture2D).is_some())
+        );
+        assert!(
+            !results
+                .iter()
+                .any(|&i| matches!(templates[i].kind, NodeKind::Math(_)))
+        );
+        // Nodes without inputs can't take a wire
+        let results = search(&templates, "", Some(PinType::Float));
+        assert!(
+            !results
+                .iter()
+                .any(|&i| templates[i].kind == NodeKind::Constant(1.0))
+        );
+    }
+
+    #[test]
+    fn test_spawn_connects_the_dropped_wire() {
+        let templates = catalog();
+        let template = |kind: NodeKind| templates.iter().find(|t| t.kind == kind).unwrap();
+
+        let mut graph = NodeGraph::new();
+        let texture = add_node(
+            &mut graph,
+            NodeKind::Texture(TextureNode::default_texture()),
+        );
+        let wire = graph.nodes[&texture].outputs[0].pin_id;
+        let sample = template(NodeKind::Texture(TextureNode::Sample(
+            SamplerState::default(),
+        )))
+        .spawn(&mut graph, Vec2::new(300.0, 40.0), Some(wire));
+        assert_eq!(graph.nodes[&sample].position, Vec2::new(300.0, 40.0));
+        // The texture input, not the UV input before or after it
+        let texture_input = graph.nodes[&sample]
+            .inputs
+            .iter()
+            .position(|pin| graph.connection_into(pin.pin_id).is_some());
+        assert_eq!(
+            graph.pin_type(graph.nodes[&sample].inputs[texture_input.unwrap()].pin_id),
+            Some(PinType::Texture2D)
+        );
+
+        // A float wire goes into the first input of an Add
+        let constant = add_node(&mut graph, NodeKind::Constant(2.0));
+        let wire = graph.nodes[&constant].outputs[0].pin_id;
+        let add =
+            template(NodeKind::Math(MathOperation::Add)).spawn(&mut graph, Vec2::ZERO, Some(wire));
+        let first_input = graph.nodes[&add].inputs[0].pin_id;
+        assert_eq!(
+            graph.connection_into(first_input).map(|c| c.from_pin),
+            Some(wire)
+        );
+        assert_eq!(graph.connections.len(), 2);
+
+        // Without a wire nothing is connected
+        template(NodeKind::PbrOutput).spawn(&mut graph, Vec2::ZERO, None);
+        assert_eq!(graph.connections.len(), 2);
+    }
+}
@@ -0,0 +1,190 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::test_utils::assert_near;
+    use crate::shader_graph::inputs::EvalContext;
+    use crate::shader_graph::node_kind::ShaderNode;
+    use crate::shader_graph::nodes::color::{
+        BlendMode, ColorNode, hsl_to_rgb, hsv_to_rgb, linear_to_srgb, luminance, rgb_to_hsl,
+        rgb_to_hsv, srgb_to_linear,
+    };
+    use crate::shader_graph::types::{PinType, Value};
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_rgb_to_hsv_primaries() {
+        assert_near(
+            rgb_to_hsv(Vec3::new(1.0, 0.0, 0.0)),
+            Vec3::new(0.0, 1.0, 1.0),
+        );
+        assert_near(
+            rgb_to_hsv(Vec3::new(0.0, 1.0, 0.0)),
+            Vec3::new(1.0 / 3.0, 1.0, 1.0),
+        );
+        assert_near(
+            rgb_to_hsv(Vec3::new(0.0, 0.0, 1.0)),
+            Vec3::new(2.0 / 3.0, 1.0, 1.0),
+        );
+    }
+
+    #[test]
+    fn test_rgb_to_hsv_grey_has_no_saturation() {
+        let hsv = rgb_to_hsv(Vec3::splat(0.5));
+        assert_eq!(hsv.y, 0.0);
+        assert_eq!(hsv.z, 0.5);
+    }
+
+    #[test]
+    fn test_hsv_roundtrip() {
+        for color in [
+            Vec3::new(0.2, 0.4, 0.8),
+            Vec3::new(0.9, 0.1, 0.3),
+            Vec3::new(0.5, 0.5, 0.1),
+        ] {
+            assert_near(hsv_to_rgb(rgb_to_hsv(color)), color);
+        }
+    }
+
+    #[test]
+    fn test_hsl_roundtrip() {
+        for color in [
+            Vec3::new(0.2, 0.4, 0.8),
+            Vec3::new(0.9, 0.1, 0.3),
+            Vec3::new(1.0, 1.0, 1.0),
+        ] {
+            assert_near(hsl_to_rgb(rgb_to_hsl(color)), color);
+        }
+    }
+
+    #[test]
+    fn test_rgb_to_hsl_pure_red() {
+        assert_near(
+            rgb_to_hsl(Vec3::new(1.0, 0.0, 0.0)),
+            Vec3::new(0.0, 1.0, 0.5),
+        );
+    }
+
+    #[test]
+    fn test_srgb_linear_roundtrip() {
+        for c in [0.0, 0.02, 0.2, 0.5, 1.0] {
+            assert!((linear_to_srgb(srgb_to_linear(c)) - c).abs() < 1e-5);
+        }
+        assert!((srgb_to_linear(0.5) - 0.214).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_luminance_rec709() {
+        assert!((luminance(Vec3::ONE) - 1.0).abs() < 1e-5);
+        assert!((luminance(Vec3::new(0.0, 1.0, 0.0)) - 0.7152).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_saturation_zero_is_grey() {
+        let result = ColorNode::Saturation.evaluate(
+            &[Value::Vec3(Vec3::new(1.0, 0.0, 0.0)), Value::Float(0.0)],
+            &EvalContext::default(),
+        );
+        assert_near(result[0].as_vec3(), Vec3::splat(0.2126));
+    }
+
+    #[test]
+    fn test_contrast_pivots_on_mid_grey() {
+        let result = ColorNode::Contrast.evaluate(
+            &[Value::Vec3(Vec3::splat(0.5)), Value::Float(3.0)],
+            &EvalContext::default(),
+        );
+        assert_near(result[0].as_vec3(), Vec3::splat(0.5));
+
+        let result = ColorNode::Contrast.evaluate(
+            &[Value::Vec3(Vec3::splat(0.75)), Value::Float(2.0)],
+            &EvalContext::default(),
+        );
+        assert_near(result[0].as_vec3(), Vec3::splat(1.0));
+    }
+
+    #[test]
+    fn test_hue_shift_full_turn_is_identity() {
+        let color = Vec3::new(0.3, 0.6, 0.9);
+        let result = ColorNode::HueShift.evaluate(
+            &[Value::Vec3(color), Value::Float(1.0)],
+            &EvalContext::default(),
+        );
+        assert_near(result[0].as_vec3(), color);
+    }
+
+    #[test]
+    fn test_hue_shift_third_turn_rotates_primaries() {
+        let result = ColorNode::HueShift.evaluate(
+            &[
+                Value::Vec3(Vec3::new(1.0, 0.0, 0.0)),
+                Value::Float(1.0 / 3.0),
+            ],
+            &EvalContext::default(),
+        );
+        assert_near(result[0].as_vec3(), Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_color_node_signatures() {
+        let sig = ColorNode::Luminance.signature();
+        assert_eq!(sig.inputs.len(), 1);
+        assert_eq!(sig.outputs[0].ty, PinType::Float);
+
+        let sig = ColorNode::HueShift.signature();
+        assert_eq!(sig.inputs.len(), 2);
+        assert_eq!(sig.inputs[1].ty, PinType::Float);
+        assert_eq!(sig.outputs[0].ty, PinType::Vec3);
+    }
+
+    #[test]
+    fn test_color_helpers_list_dependencies_first() {
+        let names: Vec<String> = ColorNode::HueShift
+            .wgsl_helpers()
+            .into_iter()
+            .map(|h| h.name)
+            .collect();
+        assert_eq!(names, vec!["rgb_to_hsv", "hsv_to_rgb", "hue_shift"]);
+    }
+
+    fn blend(mode: BlendMode, base: f32, layer: f32, opacity: f32) -> Vec3 {
+        mode.evaluate(
+            &[
+                Value::Vec3(Vec3::splat(base)),
+                Value::Vec3(Vec3::splat(layer)),
+                Value::Float(opacity),
+            ],
+            &EvalContext::default(),
+        )[0]
+        .as_vec3()
+    }
+
+    #[test]
+    fn test_blend_modes() {
+        assert_near(blend(BlendMode::Multiply, 0.5, 0.5, 1.0), Vec3::splat(0.25));
+        assert_near(blend(BlendMode::Screen, 0.5, 0.5, 1.0), Vec3::splat(0.75));
+        assert_near(blend(BlendMode::Overlay, 0.25, 0.5, 1.0), Vec3::splat(0.25));
+        assert_near(blend(BlendMode::Overlay, 0.75, 0.5, 1.0), Vec3::splat(0.75));
+        assert_near(blend(BlendMode::SoftLight, 0.5, 0.5, 1.0), Vec3::splat(0.5));
+        assert_near(blend(BlendMode::Add, 0.75, 0.5, 1.0), Vec3::ONE);
+        assert_near(
+            blend(BlendMode::Difference, 0.25, 0.75, 1.0),
+            Vec3::splat(0.5),
+        );
+    }
+
+    #[test]
+    fn test_blend_opacity_mixes_with_base() {
+        assert_near(blend(BlendMode::Multiply, 0.5, 0.0, 0.0), Vec3::splat(0.5));
+        assert_near(blend(BlendMode::Multiply, 0.5, 0.0, 0.5), Vec3::splat(0.25));
+    }
+
+    #[test]
+    fn test_blend_emission_wraps_in_mix() {
+        let exprs = BlendMode::Difference.emit_wgsl(&["a".into(), "b".into(), "o".into()]);
+        assert_eq!(exprs, vec!["mix(a, abs(a - b), o)".to_string()]);
+    }
+
+    #[test]
+    fn test_blend_title() {
+        assert_eq!(BlendMode::SoftLight.title(), "Blend (Soft Light)");
+    }
+}
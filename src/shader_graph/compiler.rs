@@ -0,0 +1,1082 @@
+use crate::node_graph::model::{NodeGraph, NodeId, PinDirection, PinId};
+use crate::shader_graph::inputs::{CUSTOM_INTERPOLANTS, Interpolant, ShaderInput};
+use crate::shader_graph::node_kind::{NodeKind, ShaderNode, ShaderStage, WgslHelper};
+use crate::shader_graph::nodes::compute::{StorageAccess, StorageResource, WorkgroupSize};
+use crate::shader_graph::nodes::subgraph::{Subgraph, argument_name};
+use crate::shader_graph::nodes::texture::SamplerState;
+use crate::shader_graph::scaffold::{
+    PREVIEW_TEXTURE_SLOTS, PreviewTarget, compute_prelude, preview_entry_point, preview_prelude,
+    preview_resource_bindings, preview_vertex_entry_point, resource_bindings, vertex_stage,
+};
+use crate::shader_graph::types::{DEFAULT_TEXTURE, PinType};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompileError {
+    /// The graph has no output node (`Fragment Output` or `PBR Output`)
+    MissingOutput,
+    /// More than one output node - the graph's result is ambiguous
+    MultipleOutputs(Vec<NodeId>),
+    /// A PBR material was requested but the graph ends in a `Fragment Output` node
+    NotPbr,
+    /// The node depends on its own output
+    Cycle(NodeId),
+    /// The node feeds an output of a stage it can't run in (e.g. derivatives in `vs_main`)
+    WrongStage { node: NodeId, stage: ShaderStage },
+    /// A connection references a pin that no longer exists
+    DanglingConnection(PinId),
+    /// An output feeds an input it cannot be converted to (textures only connect to textures)
+    IncompatibleTypes {
+        pin: PinId,
+        from: PinType,
+        to: PinType,
+    },
+    /// The graph binds more textures than the preview material has slots for
+    TooManyTextures(usize),
+    /// The `Compute Output` node asks for a workgroup beyond WebGPU's default limits
+    InvalidWorkgroupSize(WorkgroupSize),
+    /// The kernel reads and writes one storage texture slot. WebGPU only allows `read_write`
+    /// storage textures in r32 formats, not `rgba32float`
+    ReadWriteStorageTexture(u32),
+    /// A Custom Interpolant node reads an index the `Vertex Output` node has no pin for
+    NoSuchInterpolant { node: NodeId, index: usize },
+    /// A sprite, UI or fullscreen graph reads a custom interpolant - those previews don't run
+    /// the vertex graph that writes it
+    CustomInterpolantInPreview(usize),
+    /// A Custom Function node's source does not parse or validate
+    InvalidCustomFunction { node: NodeId, message: String },
+    /// The contents of a subgraph node don't compile; `error` names the node inside it
+    InSubgraph {
+        node: NodeId,
+        error: Box<CompileError>,
+    },
+    /// A subgraph binds a texture - its function can only take and return values
+    TextureInSubgraph,
+    /// The node emitted no WGSL where the kernel needs its write statement
+    NoStatement(NodeId),
+    /// The generated source was rejected by naga
+    Validation(String),
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::MissingOutput => write!(f, "graph has no output node"),
+            CompileError::MultipleOutputs(ids) => {
+                write!(f, "graph has {} output nodes", ids.len())
+            }
+            CompileError::NotPbr => write!(f, "graph has no PBR Output node"),
+            CompileError::Cycle(id) => write!(f, "node {} is part of a cycle", id.0),
+            CompileError::WrongStage { node, stage } => {
+                write!(
+                    f,
+                    "node {} can't run in the {} stage",
+                    node.0,
+                    stage.label()
+                )
+            }
+            CompileError::DanglingConnection(pin) => {
+                write!(f, "connection references missing pin {}", pin.0)
+            }
+            CompileError::IncompatibleTypes { pin, from, to } => write!(
+                f,
+                "pin {} takes {} but is connected to {}",
+                pin.0,
+                to.wgsl_type(),
+                from.wgsl_type()
+            ),
+            CompileError::TooManyTextures(count) => write!(
+                f,
+                "graph binds {} textures, the preview supports {}",
+                count, PREVIEW_TEXTURE_SLOTS
+            ),
+            CompileError::InvalidWorkgroupSize(size) => write!(
+                f,
+                "workgroup size {}x{}x{} is outside the supported limits",
+                size.x, size.y, size.z
+            ),
+            CompileError::ReadWriteStorageTexture(slot) => write!(
+                f,
+                "storage texture {} is both read and written, use two slots",
+                slot
+            ),
+            CompileError::NoSuchInterpolant { node, index } => write!(
+                f,
+                "node {} reads custom interpolant {}, there are {}",
+                node.0, index, CUSTOM_INTERPOLANTS
+            ),
+            CompileError::CustomInterpolantInPreview(index) => write!(
+                f,
+                "custom interpolant {} needs the vertex graph, which only mesh and PBR previews run",
+                index
+            ),
+            CompileError::InvalidCustomFunction { node, message } => {
+                write!(f, "node {} has invalid WGSL: {}", node.0, message)
+            }
+            CompileError::InSubgraph { node, error } => {
+                write!(f, "in subgraph node {}: {}", node.0, error)
+            }
+            CompileError::TextureInSubgraph => write!(f, "subgraphs can't bind textures"),
+            CompileError::NoStatement(id) => write!(f, "node {} emits no statement", id.0),
+            CompileError::Validation(message) => write!(f, "invalid WGSL: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+/// Find the single output node (`Fragment Output` or `PBR Output`) of a graph
+pub fn find_output_node(graph: &NodeGraph) -> Result<NodeId, CompileError> {
+    let mut outputs: Vec<NodeId> = graph
+        .nodes
+        .values()
+        .filter(|node| node.kind.is_output())
+        .map(|node| node.node_id)
+        .collect();
+    outputs.sort_by_key(|id| id.0);
+
+    match outputs.len() {
+        0 => Err(CompileError::MissingOutput),
+        1 => Ok(outputs[0]),
+        _ => Err(CompileError::MultipleOutputs(outputs)),
+    }
+}
+
+/// Find the `Vertex Output` node, if the graph has a vertex stage
+pub fn find_vertex_output(graph: &NodeGraph) -> Result<Option<NodeId>, CompileError> {
+    let mut outputs: Vec<NodeId> = graph
+        .nodes
+        .values()
+        .filter(|node| node.kind == NodeKind::VertexOutput)
+        .map(|node| node.node_id)
+        .collect();
+    outputs.sort_by_key(|id| id.0);
+
+    match outputs.len() {
+        0 => Ok(None),
+        1 => Ok(Some(outputs[0])),
+        _ => Err(CompileError::MultipleOutputs(outputs)),
+    }
+}
+
+/// Find the `Compute Output` node and the workgroup size it sets
+pub fn find_compute_output(graph: &NodeGraph) -> Result<(NodeId, WorkgroupSize), CompileError> {
+    let mut outputs: Vec<(NodeId, WorkgroupSize)> = graph
+        .nodes
+        .values()
+        .filter_map(|node| match node.kind {
+            NodeKind::ComputeOutput(size) => Some((node.node_id, size)),
+            _ => None,
+        })
+        .collect();
+    outputs.sort_by_key(|(id, _)| id.0);
+
+    match outputs.len() {
+        0 => Err(CompileError::MissingOutput),
+        1 => Ok(outputs[0]),
+        _ => Err(CompileError::MultipleOutputs(
+            outputs.into_iter().map(|(id, _)| id).collect(),
+        )),
+    }
+}
+
+/// Storage write nodes in the order a kernel runs them: the order they were created in
+pub fn storage_writes(graph: &NodeGraph) -> Vec<NodeId> {
+    let mut writes: Vec<NodeId> = graph
+        .nodes
+        .values()
+        .filter(|node| matches!(node.kind, NodeKind::Compute(compute) if compute.is_write()))
+        .map(|node| node.node_id)
+        .collect();
+    writes.sort_by_key(|id| id.0);
+    writes
+}
+
+/// Resolve the output pin feeding `input_pin` into (source node, output index)
+pub fn resolve_input_source(
+    graph: &NodeGraph,
+    input_pin: PinId,
+) -> Result<Option<(NodeId, usize)>, CompileError> {
+    let Some(connection) = graph.connection_into(input_pin) else {
+        return Ok(None);
+    };
+    match graph.find_pin(connection.from_pin) {
+        Some((node_id, PinDirection::Output, index)) => Ok(Some((node_id, index))),
+        _ => Err(CompileError::DanglingConnection(connection.from_pin)),
+    }
+}
+
+/// Fail unless an output of type `from` may feed `pin`, an input of type `to`
+pub fn check_connection_types(pin: PinId, from: PinType, to: PinType) -> Result<(), CompileError> {
+    if from.converts_to(to) {
+        Ok(())
+    } else {
+        Err(CompileError::IncompatibleTypes { pin, from, to })
+    }
+}
+
+/// Name of the WGSL `let` holding a node output (or, for textures, of its binding)
+pub fn output_var_name(node_id: NodeId, output_index: usize) -> String {
+    format!("node{}_out{}", node_id.0, output_index)
+}
+
+/// A `texture_2d<f32>` the compiled shader declares
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureBinding {
+    pub name: String,
+    /// Asset path of the bound image, `None` for plain white
+    pub image: Option<String>,
+}
+
+/// Walks the graph backwards from a stage's output node and emits one `let` per node output
+struct StageEmitter<'a> {
+    graph: &'a NodeGraph,
+    stage: ShaderStage,
+    statements: Vec<String>,
+    helpers: Vec<WgslHelper>,
+    /// Fragment data read by the emitted expressions
+    shader_inputs: BTreeSet<ShaderInput>,
+    /// Texture bindings in first-use order
+    textures: Vec<TextureBinding>,
+    samplers: BTreeSet<SamplerState>,
+    /// Storage bindings the emitted expressions read or write
+    storage: BTreeMap<StorageResource, StorageAccess>,
+    emitted: HashSet<NodeId>,
+    visiting: HashSet<NodeId>,
+    /// Subgraphs compiled so far, so nodes using the same one compile it once
+    functions: Vec<(&'a Subgraph, SubgraphFunction)>,
+}
+
+impl<'a> StageEmitter<'a> {
+    fn new(graph: &'a NodeGraph, stage: ShaderStage) -> Self {
+        Self {
+            graph,
+            stage,
+            statements: Vec::new(),
+            helpers: Vec::new(),
+            shader_inputs: BTreeSet::new(),
+            textures: Vec::new(),
+            samplers: BTreeSet::new(),
+            storage: BTreeMap::new(),
+            emitted: HashSet::new(),
+            visiting: HashSet::new(),
+            functions: Vec::new(),
+        }
+    }
+
+    /// The function a subgraph compiles into for this stage, compiled on first use
+    fn subgraph_function(
+        &mut self,
+        subgraph: &'a Subgraph,
+    ) -> Result<SubgraphFunction, CompileError> {
+        if let Some((_, function)) = self.functions.iter().find(|(other, _)| *other == subgraph) {
+            return Ok(function.clone());
+        }
+        let function = compile_subgraph(subgraph, self.stage)?;
+        self.functions.push((subgraph, function.clone()));
+        Ok(function)
+    }
+
+    /// Emit a node's dependencies and return the WGSL expressions for its outputs
+    fn node_expressions(&mut self, node_id: NodeId) -> Result<Vec<String>, CompileError> {
+        if !self.visiting.insert(node_id) {
+            return Err(CompileError::Cycle(node_id));
+        }
+
+        let graph = self.graph;
+        let node = &graph.nodes[&node_id];
+        let signature = node.kind.signature();
+        let stage = self.stage;
+        let wrong_stage = CompileError::WrongStage {
+            node: node_id,
+            stage,
+        };
+        let runs_here = |source: &ShaderInput| source.stages().contains(&stage);
+        if !node.kind.stages().contains(&self.stage)
+            || !node.kind.shader_inputs().iter().all(runs_here)
+        {
+            return Err(wrong_stage);
+        }
+        if let Some(index) = node
+            .kind
+            .shader_inputs()
+            .iter()
+            .find_map(|source| match source {
+                ShaderInput::Custom(index) if *index >= CUSTOM_INTERPOLANTS => Some(*index),
+                _ => None,
+            })
+        {
+            return Err(CompileError::NoSuchInterpolant {
+                node: node_id,
+                index,
+            });
+        }
+        if let NodeKind::Custom(custom) = &node.kind
+            && let Some(message) = custom.error()
+        {
+            return Err(CompileError::InvalidCustomFunction {
+                node: node_id,
+                message: message.to_string(),
+            });
+        }
+
+        let function = match &node.kind {
+            NodeKind::Subgraph(subgraph) => {
+                Some(self.subgraph_function(subgraph).map_err(|error| {
+                    CompileError::InSubgraph {
+                        node: node_id,
+                        error: Box::new(error),
+                    }
+                })?)
+            }
+            _ => None,
+        };
+
+        let mut inputs = Vec::with_capacity(node.inputs.len());
+        for (pin, spec) in node.inputs.iter().zip(signature.inputs.iter()) {
+            let expr = match resolve_input_source(self.graph, pin.pin_id)? {
+                Some((source_id, output_index)) => {
+                    self.emit_node(source_id)?;
+                    let source_type =
+                        self.graph.nodes[&source_id].kind.signature().outputs[output_index].ty;
+                    check_connection_types(pin.pin_id, source_type, spec.ty)?;
+                    source_type.convert_expr(&output_var_name(source_id, output_index), spec.ty)
+                }
+                None => {
+                    if spec.implicit.is_some_and(|source| !runs_here(&source)) {
+                        return Err(wrong_stage);
+                    }
+                    self.shader_inputs.extend(spec.implicit);
+                    if spec.ty.is_texture() {
+                        self.bind_texture(DEFAULT_TEXTURE.to_string(), None);
+                    }
+                    spec.default_wgsl()
+                }
+            };
+            inputs.push(expr);
+        }
+
+        // A subgraph's function already knows what its body reads
+        let (shader_inputs, samplers, storage, helpers) = match &function {
+            Some(function) => (
+                function.shader_inputs.clone(),
+                function.samplers.clone(),
+                function.storage.clone(),
+                function.helpers.clone(),
+            ),
+            None => (
+                node.kind.shader_inputs(),
+                node.kind.samplers(),
+                node.kind.storage(),
+                node.kind.wgsl_helpers(),
+            ),
+        };
+        self.shader_inputs.extend(shader_inputs);
+        self.samplers.extend(samplers);
+        for (resource, access) in storage {
+            self.storage
+                .entry(resource)
+                .and_modify(|existing| *existing = existing.merge(access))
+                .or_insert(access);
+        }
+        for helper in helpers {
+            if !self.helpers.iter().any(|h| h.name == helper.name) {
+                self.helpers.push(helper);
+            }
+        }
+
+        self.visiting.remove(&node_id);
+        match function {
+            // One call, kept in a `let`, however many outputs read it
+            Some(function) => {
+                let mut call = function.call(&inputs);
+                if function.returns_struct() {
+                    let result = format!("node{}_result", node_id.0);
+                    self.statements.push(format!("let {} = {};", result, call));
+                    call = result;
+                }
+                Ok(function.read_outputs(&call))
+            }
+            None => Ok(node.kind.emit_wgsl(&inputs)),
+        }
+    }
+
+    /// Emit `let` statements for a node's outputs (once per node)
+    fn emit_node(&mut self, node_id: NodeId) -> Result<(), CompileError> {
+        if self.emitted.contains(&node_id) {
+            return Ok(());
+        }
+
+        let expressions = self.node_expressions(node_id)?;
+        let kind = &self.graph.nodes[&node_id].kind;
+        let outputs = kind.signature().outputs;
+        for (index, (expr, spec)) in expressions.iter().zip(outputs.iter()).enumerate() {
+            // Textures can't live in a `let` - the output is a binding under the same name
+            if spec.ty.is_texture() {
+                self.bind_texture(output_var_name(node_id, index), kind.texture_image());
+                continue;
+            }
+            self.statements.push(format!(
+                "let {}: {} = {};",
+                output_var_name(node_id, index),
+                spec.ty.wgsl_type(),
+                expr
+            ));
+        }
+
+        self.emitted.insert(node_id);
+        Ok(())
+    }
+
+    fn bind_texture(&mut self, name: String, image: Option<String>) {
+        if !self.textures.iter().any(|t| t.name == name) {
+            self.textures.push(TextureBinding { name, image });
+        }
+    }
+}
+
+/// Expressions a `PBR Output` node feeds into Bevy's `PbrInput`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PbrSurface {
+    pub metallic: String,
+    pub roughness: String,
+    /// World-space shading normal
+    pub normal: String,
+    pub emissive: String,
+    pub occlusion: String,
+}
+
+/// The graph's contribution to a fragment shader, independent of the module around it
+pub struct FragmentBody {
+    /// `let` statements in dependency order
+    pub statements: Vec<String>,
+    /// Final RGBA expression - the base colour and alpha of a PBR surface
+    pub color: String,
+    /// The rest of the surface when the graph ends in a `PBR Output` node
+    pub surface: Option<PbrSurface>,
+    pub helpers: Vec<WgslHelper>,
+    /// Interpolants the statements read from the fragment input
+    pub interpolants: Vec<Interpolant>,
+    pub textures: Vec<TextureBinding>,
+    /// Sampler states the statements read, each bound once
+    pub samplers: Vec<SamplerState>,
+}
+
+impl FragmentBody {
+    /// Take on the vertex graph's helpers and bindings, so the module declares each once
+    fn absorb(&mut self, vertex: VertexBody) {
+        for helper in vertex.helpers {
+            if !self.helpers.iter().any(|h| h.name == helper.name) {
+                self.helpers.push(helper);
+            }
+        }
+        for texture in vertex.textures {
+            if !self.textures.iter().any(|t| t.name == texture.name) {
+                self.textures.push(texture);
+            }
+        }
+        self.samplers.extend(vertex.samplers);
+        self.samplers.sort();
+        self.samplers.dedup();
+    }
+
+    fn texture_names(&self) -> Vec<String> {
+        self.textures.iter().map(|t| t.name.clone()).collect()
+    }
+
+    /// Helper functions, one after another
+    fn helpers_wgsl(&self) -> String {
+        let mut source = String::new();
+        for helper in &self.helpers {
+            source.push_str(&helper.source);
+            source.push_str("\n\n");
+        }
+        source
+    }
+
+    fn statements_wgsl(&self) -> String {
+        let mut source = String::new();
+        for statement in &self.statements {
+            source.push_str("    ");
+            source.push_str(statement);
+            source.push('\n');
+        }
+        source
+    }
+}
+
+/// Walk the graph back from its output node
+pub fn emit_fragment_body(graph: &NodeGraph) -> Result<FragmentBody, CompileError> {
+    let output_id = find_output_node(graph)?;
+
+    let mut emitter = StageEmitter::new(graph, ShaderStage::Fragment);
+    let mut expressions = emitter.node_expressions(output_id)?.into_iter();
+    let color = expressions.next().unwrap_or_default();
+    let surface = match graph.nodes[&output_id].kind {
+        NodeKind::PbrOutput => {
+            let mut next = || expressions.next().unwrap_or_default();
+            Some(PbrSurface {
+                metallic: next(),
+                roughness: next(),
+                normal: next(),
+                emissive: next(),
+                occlusion: next(),
+            })
+        }
+        _ => None,
+    };
+
+    let interpolants = emitter
+        .shader_inputs
+        .iter()
+        .filter_map(|input| input.interpolant())
+        .collect();
+
+    Ok(FragmentBody {
+        statements: emitter.statements,
+        color,
+        surface,
+        helpers: emitter.helpers,
+        interpolants,
+        textures: emitter.textures,
+        samplers: emitter.samplers.into_iter().collect(),
+    })
+}
+
+/// The graph's contribution to `vs_main`: what feeds the `Vertex Output` node
+pub struct VertexBody {
+    /// `let` statements in dependency order, reading the undisplaced vertex as `input`
+    pub statements: Vec<String>,
+    /// Object-space position offset
+    pub offset: String,
+    /// Object-space normal replacing the mesh normal
+    pub normal: String,
+    /// One expression per custom interpolant
+    pub custom: Vec<String>,
+    pub helpers: Vec<WgslHelper>,
+    /// Interpolants the statements read from the undisplaced vertex
+    pub interpolants: Vec<Interpolant>,
+    pub textures: Vec<TextureBinding>,
+    pub samplers: Vec<SamplerState>,
+}
+
+impl VertexBody {
+    /// The graph run on `input`, the undisplaced vertex, leaving the displaced copy of `vertex`
+    /// in `displaced`
+    fn displace_wgsl(&self) -> String {
+        let mut source = String::new();
+        for statement in &self.statements {
+            source.push_str(&format!("    {}\n", statement));
+        }
+        source.push_str(&format!(
+            "    var displaced = vertex;
+    displaced.position = vertex.position + {};
+    displaced.normal = {};
+",
+            self.offset, self.normal
+        ));
+        source
+    }
+
+    /// Body of `vs_main`: run the graph on the mesh vertex, then run the mesh vertex again
+    /// displaced, and write the custom interpolants the fragment stage reads
+    fn vs_main_wgsl(&self, fragment_interpolants: &[Interpolant]) -> String {
+        let mut source = String::from("    let input = mesh_vertex(vertex);\n");
+        source.push_str(&self.displace_wgsl());
+        source.push_str("    var out = mesh_vertex(displaced);\n");
+        for interpolant in fragment_interpolants {
+            if let Interpolant::Custom(index) = *interpolant {
+                source.push_str(&format!(
+                    "    out.{} = {};\n",
+                    interpolant.field(),
+                    self.custom[index]
+                ));
+            }
+        }
+        source.push_str("    return out;\n");
+        source
+    }
+}
+
+/// Walk the graph back from its `Vertex Output` node, if it has one
+pub fn emit_vertex_body(graph: &NodeGraph) -> Result<Option<VertexBody>, CompileError> {
+    let Some(output_id) = find_vertex_output(graph)? else {
+        return Ok(None);
+    };
+
+    let mut emitter = StageEmitter::new(graph, ShaderStage::Vertex);
+    let mut expressions = emitter.node_expressions(output_id)?.into_iter();
+    let offset = expressions.next().unwrap_or_default();
+    let normal = expressions.next().unwrap_or_default();
+    let custom = expressions.collect();
+
+    let interpolants = emitter
+        .shader_inputs
+        .iter()
+        .filter_map(|input| input.interpolant())
+        .collect();
+
+    Ok(Some(VertexBody {
+        statements: emitter.statements,
+        offset,
+        normal,
+        custom,
+        helpers: emitter.helpers,
+        interpolants,
+        textures: emitter.textures,
+        samplers: emitter.samplers.into_iter().collect(),
+    }))
+}
+
+/// A subgraph compiled into one WGSL function
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubgraphFunction {
+    pub name: String,
+    /// What the body calls, then the function itself with its result struct
+    pub helpers: Vec<WgslHelper>,
+    /// Everything the body reads, passed on to the calling graph
+    pub shader_inputs: Vec<ShaderInput>,
+    /// Fields of the entry point's `input` the body reads, passed in as a struct of its own
+    pub input_fields: Vec<(&'static str, PinType)>,
+    /// Whether the body reads the compute invocation, passed in as `global_id`
+    pub reads_invocation: bool,
+    pub samplers: Vec<SamplerState>,
+    pub storage: Vec<(StorageResource, StorageAccess)>,
+    /// Number of values returned
+    pub outputs: usize,
+}
+
+impl SubgraphFunction {
+    /// Call with one expression per subgraph input, from where the caller's `input` and
+    /// `global_id` are in scope
+    pub fn call(&self, inputs: &[String]) -> String {
+        let mut arguments = inputs.to_vec();
+        if !self.input_fields.is_empty() {
+            let fields: Vec<String> = self
+                .input_fields
+                .iter()
+                .map(|(field, _)| format!("input.{}", field))
+                .collect();
+            arguments.push(format!("{}_input({})", self.name, fields.join(", ")));
+        }
+        if self.reads_invocation {
+            arguments.push("global_id".to_string());
+        }
+        format!("{}({})", self.name, arguments.join(", "))
+    }
+
+    /// Several outputs come back in a struct, one field each
+    pub fn returns_struct(&self) -> bool {
+        self.outputs > 1
+    }
+
+    /// One expression per output, given the result of a call
+    pub fn read_outputs(&self, result: &str) -> Vec<String> {
+        if !self.returns_struct() {
+            return vec![result.to_string(); self.outputs];
+        }
+        (0..self.outputs)
+            .map(|index| format!("{}.out{}", result, index))
+            .collect()
+    }
+}
+
+/// WGSL identifier made from a display name
+fn identifier(name: &str) -> String {
+    let mut ident: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    ident.truncate(32);
+    ident
+}
+
+/// Compile a subgraph's contents for `stage` into a function taking its inputs and returning
+/// its outputs (in a struct when there are several).
+///
+/// Module-scope data (the clock, camera, lights, samplers, storage) reads the same inside a
+/// function, but the entry point's `input` and `global_id` do not exist there: the function
+/// takes parameters of the same names, `input` a struct of just the fields the body reads, so
+/// the body's expressions stay as they are. The function is named after a hash of its text,
+/// so identical subgraphs share one copy.
+pub fn compile_subgraph(
+    subgraph: &Subgraph,
+    stage: ShaderStage,
+) -> Result<SubgraphFunction, CompileError> {
+    let graph = &subgraph.graph;
+    let mut outputs: Vec<NodeId> = graph
+        .nodes
+        .values()
+        .filter(|node| matches!(node.kind, NodeKind::SubgraphOutputs(_)))
+        .map(|node| node.node_id)
+        .collect();
+    outputs.sort_by_key(|id| id.0);
+    let output_id = match outputs.len() {
+        0 => return Err(CompileError::MissingOutput),
+        1 => outputs[0],
+        _ => return Err(CompileError::MultipleOutputs(outputs)),
+    };
+
+    let mut emitter = StageEmitter::new(graph, stage);
+    let results = emitter.node_expressions(output_id)?;
+    if !emitter.textures.is_empty() {
+        return Err(CompileError::TextureInSubgraph);
+    }
+
+    let input_fields: BTreeMap<&'static str, PinType> = emitter
+        .shader_inputs
+        .iter()
+        .filter_map(|input| input.input_field())
+        .collect();
+    let reads_invocation = emitter.shader_inputs.contains(&ShaderInput::InvocationId);
+
+    let ports = subgraph.inputs();
+    let mut parameters: Vec<String> = ports
+        .iter()
+        .enumerate()
+        .map(|(index, port)| format!("{}: {}", argument_name(index), port.ty.wgsl_type()))
+        .collect();
+    // `@@NAME@@` stands in for the function's name until the text is hashed
+    let mut source = String::new();
+    if !input_fields.is_empty() {
+        let fields: Vec<String> = input_fields
+            .iter()
+            .map(|(field, ty)| format!("    {}: {},\n", field, ty.wgsl_type()))
+            .collect();
+        source.push_str(&format!(
+            "struct @@NAME@@_input {{\n{}}}\n\n",
+            fields.concat()
+        ));
+        parameters.push("input: @@NAME@@_input".to_string());
+    }
+    if reads_invocation {
+        parameters.push("global_id: vec3<u32>".to_string());
+    }
+
+    let output_ports = subgraph.outputs();
+    let mut body = String::new();
+    for statement in &emitter.statements {
+        body.push_str(&format!("    {}\n", statement));
+    }
+    source.push_str(&match output_ports {
+        [] => format!(
+            "fn @@NAME@@({}) -> f32 {{\n{}    return 0.0;\n}}",
+            parameters.join(", "),
+            body
+        ),
+        [port] => format!(
+            "fn @@NAME@@({}) -> {} {{\n{}    return {};\n}}",
+            parameters.join(", "),
+            port.ty.wgsl_type(),
+            body,
+            results[0]
+        ),
+        ports => {
+            let fields: Vec<String> = ports
+                .iter()
+                .enumerate()
+                .map(|(index, port)| format!("    out{}: {},\n", index, port.ty.wgsl_type()))
+                .collect();
+            format!(
+                "struct @@NAME@@_result {{\n{}}}\n\nfn @@NAME@@({}) -> @@NAME@@_result {{\n{}    return @@NAME@@_result({});\n}}",
+                fields.concat(),
+                parameters.join(", "),
+                body,
+                results.join(", ")
+            )
+        }
+    });
+
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    for helper in &emitter.helpers {
+        helper.name.hash(&mut hasher);
+        helper.source.hash(&mut hasher);
+    }
+    let name = format!(
+        "subgraph_{}_{:016x}",
+        identifier(&subgraph.name),
+        hasher.finish()
+    );
+    let mut helpers = emitter.helpers;
+    helpers.push(WgslHelper::new(&name, &source.replace("@@NAME@@", &name)));
+
+    Ok(SubgraphFunction {
+        name,
+        helpers,
+        shader_inputs: emitter.shader_inputs.into_iter().collect(),
+        input_fields: input_fields.into_iter().collect(),
+        reads_invocation,
+        samplers: emitter.samplers.into_iter().collect(),
+        storage: emitter.storage.into_iter().collect(),
+        outputs: output_ports.len().max(1),
+    })
+}
+
+/// Compile the graph into a complete WGSL module.
+///
+/// The vertex stage follows `default.wgsl`, extended with the interpolants the graph reads
+/// and, with a `Vertex Output` node, displaced by the vertex graph; the graph replaces
+/// `fs_main`. A module has no lighting model, so a PBR surface writes its base colour.
+pub fn compile_fragment(graph: &NodeGraph) -> Result<String, CompileError> {
+    let mut body = emit_fragment_body(graph)?;
+    let vertex = emit_vertex_body(graph)?;
+
+    let vs_main = vertex
+        .as_ref()
+        .map(|vertex| vertex.vs_main_wgsl(&body.interpolants));
+    let mut interpolants = body.interpolants.clone();
+    if let Some(vertex) = vertex {
+        interpolants.extend(vertex.interpolants.iter().copied());
+        body.absorb(vertex);
+    }
+
+    let mut source = vertex_stage(&interpolants, vs_main.as_deref());
+    source.push_str("\n\n");
+    source.push_str(&resource_bindings(&body.texture_names(), &body.samplers));
+    source.push_str(&body.helpers_wgsl());
+
+    source.push_str("@fragment\nfn fs_main(input: VertexOutput) -> FragmentOutput {\n");
+    source.push_str("    var out: FragmentOutput;\n");
+    source.push_str(&body.statements_wgsl());
+    source.push_str(&format!("    out.color = {};\n", body.color));
+    source.push_str("    return out;\n}\n");
+
+    Ok(source)
+}
+
+/// A compute kernel and what the host has to bind for it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputeShader {
+    /// Module with a single `@compute fn cs_main`
+    pub source: String,
+    pub workgroup_size: WorkgroupSize,
+    /// Storage bindings in group 0, by `StorageResource::binding`
+    pub storage: Vec<(StorageResource, StorageAccess)>,
+    /// Sampled textures in group 1, in binding order, `None` for plain white
+    pub images: Vec<Option<String>>,
+}
+
+/// Compile a graph with a `Compute Output` node into a compute kernel.
+///
+/// `cs_main` runs every storage write node in `storage_writes` order, each after the nodes it
+/// depends on. The clock is `globals` at binding 0 of group 0, storage follows it in the same
+/// group and sampled textures and samplers go in group 1, as for `compile_fragment`.
+pub fn compile_compute(graph: &NodeGraph) -> Result<ComputeShader, CompileError> {
+    let (_, workgroup_size) = find_compute_output(graph)?;
+    if !workgroup_size.is_valid() {
+        return Err(CompileError::InvalidWorkgroupSize(workgroup_size));
+    }
+
+    let mut emitter = StageEmitter::new(graph, ShaderStage::Compute);
+    for write in storage_writes(graph) {
+        let statement = emitter
+            .node_expressions(write)?
+            .into_iter()
+            .next()
+            .ok_or(CompileError::NoStatement(write))?;
+        emitter.statements.push(statement);
+    }
+
+    let storage: Vec<(StorageResource, StorageAccess)> = emitter.storage.into_iter().collect();
+    if let Some(slot) = storage.iter().find_map(|entry| match entry {
+        (StorageResource::Texture(slot), StorageAccess::ReadWrite) => Some(*slot),
+        _ => None,
+    }) {
+        return Err(CompileError::ReadWriteStorageTexture(slot));
+    }
+    let texture_names: Vec<String> = emitter.textures.iter().map(|t| t.name.clone()).collect();
+    let samplers: Vec<SamplerState> = emitter.samplers.into_iter().collect();
+
+    let mut source = compute_prelude(&storage);
+    source.push_str(&resource_bindings(&texture_names, &samplers));
+    for helper in &emitter.helpers {
+        source.push_str(&helper.source);
+        source.push_str("\n\n");
+    }
+    source.push_str(&format!(
+        "@compute {}\nfn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {{\n",
+        workgroup_size.wgsl_attribute()
+    ));
+    for statement in &emitter.statements {
+        source.push_str(&format!("    {}\n", statement));
+    }
+    source.push_str("}\n");
+
+    Ok(ComputeShader {
+        source,
+        workgroup_size,
+        storage,
+        images: emitter.textures.into_iter().map(|t| t.image).collect(),
+    })
+}
+
+/// Fragment shader for one of Bevy's pipelines, with the images its texture slots need
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewShader {
+    pub source: String,
+    /// Image for each texture slot in order, `None` for plain white
+    pub images: Vec<Option<String>>,
+}
+
+/// Compile the graph into the vertex and fragment shader of `GraphPreviewMaterial`.
+///
+/// The result uses Bevy's `#import`s and material bind group, so it is only complete once
+/// Bevy's shader preprocessor has run - naga cannot validate it directly.
+pub fn compile_preview(graph: &NodeGraph) -> Result<PreviewShader, CompileError> {
+    compile_unlit(graph, PreviewTarget::Unlit)
+}
+
+/// Compile the graph for the target its output node picks: a `PBR Output` graph becomes a
+/// `StandardMaterial` extension, a `Fragment Output` graph the mesh, sprite, fullscreen or
+/// UI shader it is set to.
+pub fn compile_target(graph: &NodeGraph) -> Result<PreviewShader, CompileError> {
+    let output = find_output_node(graph)?;
+    match graph.nodes[&output].kind {
+        NodeKind::FragmentOutput(target) => compile_unlit(graph, target.into()),
+        _ => compile_pbr(graph),
+    }
+}
+
+/// Graph colour straight into the target's `fragment` entry point
+fn compile_unlit(graph: &NodeGraph, target: PreviewTarget) -> Result<PreviewShader, CompileError> {
+    let mut body = emit_fragment_body(graph)?;
+    let mut source = preview_module_start(graph, &mut body, target)?;
+
+    source.push_str("fn graph_fragment(input: GraphInput) -> vec4<f32> {\n");
+    source.push_str(&body.statements_wgsl());
+    source.push_str(&format!("    return {};\n}}\n\n", body.color));
+    source.push_str(&preview_entry_point(&body.interpolants, target));
+
+    Ok(PreviewShader {
+        source,
+        images: body.textures.into_iter().map(|t| t.image).collect(),
+    })
+}
+
+/// Compile a graph ending in `PBR Output` into a `StandardMaterial` extension shader.
+///
+/// The graph fills a `GraphSurface`, which overrides the `PbrInput` Bevy builds from the base
+/// material before running its own lighting, shadows and fog. Like `compile_preview`, the
+/// result needs Bevy's preprocessor.
+pub fn compile_pbr(graph: &NodeGraph) -> Result<PreviewShader, CompileError> {
+    let mut body = emit_fragment_body(graph)?;
+    let Some(surface) = body.surface.take() else {
+        return Err(CompileError::NotPbr);
+    };
+    let mut source = preview_module_start(graph, &mut body, PreviewTarget::Pbr)?;
+
+    source.push_str("fn graph_surface(input: GraphInput) -> GraphSurface {\n");
+    source.push_str(&body.statements_wgsl());
+    source.push_str(&format!(
+        "    return GraphSurface({}, {}, {}, {}, {}, {});\n}}\n\n",
+        body.color,
+        surface.metallic,
+        surface.roughness,
+        surface.normal,
+        surface.emissive,
+        surface.occlusion
+    ));
+    source.push_str(&preview_entry_point(&body.interpolants, PreviewTarget::Pbr));
+
+    Ok(PreviewShader {
+        source,
+        images: body.textures.into_iter().map(|t| t.image).collect(),
+    })
+}
+
+/// Prelude, material bindings, helpers and, for the mesh targets, the `vertex` entry point of a
+/// preview shader, up to the graph function. The vertex graph's helpers and bindings join
+/// `body`'s; the targets without a vertex stage only check that it compiles, and reject
+/// custom interpolants, which nothing there writes.
+fn preview_module_start(
+    graph: &NodeGraph,
+    body: &mut FragmentBody,
+    target: PreviewTarget,
+) -> Result<String, CompileError> {
+    let mut custom_reads: Vec<usize> = body
+        .interpolants
+        .iter()
+        .filter_map(|interpolant| match interpolant {
+            Interpolant::Custom(index) => Some(*index),
+            _ => None,
+        })
+        .collect();
+    custom_reads.sort();
+    custom_reads.dedup();
+    if let Some(&index) = custom_reads.first()
+        && !target.has_vertex_stage()
+    {
+        return Err(CompileError::CustomInterpolantInPreview(index));
+    }
+    // Without a vertex graph the custom interpolants start, and stay, at zero
+    let mut custom: Vec<(usize, String)> = custom_reads
+        .iter()
+        .map(|&index| (index, Interpolant::Custom(index).vertex_expr().to_string()))
+        .collect();
+
+    let vertex = emit_vertex_body(graph)?;
+    let mut interpolants = body.interpolants.clone();
+    let mut vertex_interpolants = Vec::new();
+    let mut vertex_main = None;
+    if let Some(vertex) = vertex
+        && target.has_vertex_stage()
+    {
+        vertex_interpolants = vertex.interpolants.clone();
+        vertex_main = Some(vertex.displace_wgsl());
+        for (index, expr) in custom.iter_mut() {
+            *expr = vertex.custom[*index].clone();
+        }
+        interpolants.extend(vertex.interpolants.iter().copied());
+        body.absorb(vertex);
+    }
+    if body.textures.len() > PREVIEW_TEXTURE_SLOTS {
+        return Err(CompileError::TooManyTextures(body.textures.len()));
+    }
+
+    let mut source = preview_prelude(&interpolants, target);
+    source.push_str("\n\n");
+    source.push_str(&preview_resource_bindings(
+        &body.texture_names(),
+        &body.samplers,
+        target,
+    ));
+    source.push_str(&body.helpers_wgsl());
+    if target.has_vertex_stage() {
+        source.push_str(&preview_vertex_entry_point(
+            &vertex_interpolants,
+            vertex_main.as_deref(),
+            &custom,
+        ));
+    }
+    Ok(source)
+}
+
+/// Parse and validate WGSL with naga
+pub fn validate_wgsl(source: &str) -> Result<(), CompileError> {
+    let module = naga::front::wgsl::parse_str(source)
+        .map_err(|e| CompileError::Validation(e.emit_to_string(source)))?;
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .map_err(|e| CompileError::Validation(e.emit_to_string(source)))?;
+    Ok(())
+}
+
+/// Compile and validate in one step
+pub fn compile_and_validate(graph: &NodeGraph) -> Result<String, CompileError> {
+    let source = compile_fragment(graph)?;
+    validate_wgsl(&source)?;
+    Ok(source)
+}
@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::NodeGraph;
+    use crate::node_graph::node_factory::MathOperation;
+    use crate::node_graph::test_utils::{add_node, connect};
+    use crate::shader_graph::compiler::{
+        CompileError, compile_and_validate, compile_fragment, find_output_node, validate_wgsl,
+    };
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget};
+    use crate::shader_graph::nodes::color::{BlendMode, ColorNode};
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_missing_output_is_an_error() {
+        let mut graph = NodeGraph::new();
+        add_node(&mut graph, NodeKind::Constant(1.0));
+        assert_eq!(compile_fragment(&graph), Err(CompileError::MissingOutput));
+    }
+
+    #[test]
+    fn test_multiple_outputs_is_an_error() {
+        let mut graph = NodeGraph::new();
+        let a = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let b = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        assert_eq!(
+            find_output_node(&graph),
+            Err(CompileError::MultipleOutputs(vec![a, b]))
+        );
+    }
+
+    #[test]
+    fn test_unconnected_output_uses_defaults() {
+        let mut graph = NodeGraph::new();
+        add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+
+        let source = compile_and_validate(&graph).expect("default graph should compile");
+        assert!(source.contains("out.color = vec4<f32>(vec3<f32>(0.2, 0.6, 1.0), 1.0);"));
+        // The vertex stage is generated alongside the fragment stage
+        assert!(source.contains("fn vs_main(vertex: VertexInput) -> VertexOutput"));
+    }
+
+    #[test]
+    fn test_connected_nodes_emit_lets_in_dependency_order() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let constant = add_node(&mut graph, NodeKind::Constant(0.5));
+        let add = add_node(&mut graph, NodeKind::Math(MathOperation::Add));
+        connect(&mut graph, constant, 0, add, 0);
+        connect(&mut graph, constant, 0, add, 1);
+        connect(&mut graph, add, 0, output, 0);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        let constant_let = source.find("let node2_out0: f32 = 0.5;").unwrap();
+        let add_let = source
+            .find("let node3_out0: f32 = (node2_out0 + node2_out0);")
+            .unwrap();
+        assert!(constant_let < add_let);
+        // Float result is splatted into the vec3 colour input
+        assert!(source.contains("vec4<f32>(vec3<f32>(node3_out0), 1.0)"));
+    }
+
+    #[test]
+    fn test_infinite_constant_still_validates() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let constant = add_node(&mut graph, NodeKind::Constant(f32::INFINITY));
+        connect(&mut graph, constant, 0, output, 0);
+
+        let source = compile_and_validate(&graph).expect("infinity should clamp");
+        assert!(!source.contains("inf"));
+    }
+
+    #[test]
+    fn test_shared_node_is_emitted_once() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let constant = add_node(&mut graph, NodeKind::Constant(0.5));
+        connect(&mut graph, constant, 0, output, 0);
+        connect(&mut graph, constant, 0, output, 1);
+
+        let source = compile_fragment(&graph).unwrap();
+        assert_eq!(source.matches("let node2_out0").count(), 1);
+    }
+
+    #[test]
+    fn test_cycle_is_reported() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let a = add_node(&mut graph, NodeKind::Math(MathOperation::Add));
+        let b = add_node(&mut graph, NodeKind::Math(MathOperation::Add));
+        connect(&mut graph, a, 0, b, 0);
+        connect(&mut graph, b, 0, a, 0);
+        connect(&mut graph, b, 0, output, 0);
+
+        assert!(matches!(
+            compile_fragment(&graph),
+            Err(CompileError::Cycle(_))
+        ));
+    }
+
+    #[test]
+    fn test_every_color_node_compiles_and_validates() {
+        for node in [
+            ColorNode::RgbToHsv,
+            ColorNode::HsvToRgb,
+            ColorNode::RgbToHsl,
+            ColorNode::HslToRgb,
+            ColorNode::SrgbToLinear,
+            ColorNode::LinearToSrgb,
+            ColorNode::Luminance,
+            ColorNode::Contrast,
+            ColorNode::Saturation,
+            ColorNode::HueShift,
+        ] {
+            let mut graph = NodeGraph::new();
+            let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+            let color = add_node(&mut graph, NodeKind::Color(node));
+            connect(&mut graph, color, 0, output, 0);
+
+            if let Err(e) = compile_and_validate(&graph) {
+                panic!("{:?} failed: {}", node, e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_blend_mode_compiles_and_validates() {
+        for mode in [
+            BlendMode::Multiply,
+            BlendMode::Screen,
+            BlendMode::Overlay,
+            BlendMode::SoftLight,
+            BlendMode::Add,
+            BlendMode::Difference,
+        ] {
+            let mut graph = NodeGraph::new();
+            let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+            let blend = add_node(&mut graph, NodeKind::Blend(mode));
+            connect(&mut graph, blend, 0, output, 0);
+
+            if let Err(e) = compile_and_validate(&graph) {
+                panic!("{:?} failed: {}", mode, e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shared_helpers_are_emitted_once() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let to_hsv = add_node(&mut graph, NodeKind::Color(ColorNode::RgbToHsv));
+        let shift = add_node(&mut graph, NodeKind::Color(ColorNode::HueShift));
+        connect(&mut graph, to_hsv, 0, shift, 0);
+        connect(&mut graph, shift, 0, output, 0);
+
+        let source = compile_and_validate(&graph).unwrap();
+        assert_eq!(source.matches("fn rgb_to_hsv(").count(), 1);
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_wgsl() {
+        assert!(matches!(
+            validate_wgsl("fn broken( -> f32 {}"),
+            Err(CompileError::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,301 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::NodeGraph;
+    use crate::node_graph::node_factory::MathOperation;
+    use crate::node_graph::test_utils::{add_node, connect};
+    use crate::shader_graph::compiler::{
+        CompileError, compile_and_validate, compile_compute, validate_wgsl,
+    };
+    use crate::shader_graph::evaluator::GraphEvaluator;
+    use crate::shader_graph::inputs::EvalContext;
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget, ShaderNode, ShaderStage};
+    use crate::shader_graph::nodes::compute::{
+        ComputeNode, ComputeStorage, StorageAccess, StorageImage, StorageResource, WorkgroupSize,
+    };
+    use crate::shader_graph::nodes::geometry::{GeometryNode, Space};
+    use crate::shader_graph::nodes::texture::TextureNode;
+    use crate::shader_graph::nodes::time::TimeNode;
+    use crate::shader_graph::nodes::uv::UvNode;
+    use crate::shader_graph::types::Value;
+    use bevy::prelude::*;
+
+    /// Kernel copying buffer 0 into buffer 1, element by element
+    fn copy_kernel(size: WorkgroupSize) -> NodeGraph {
+        let mut graph = NodeGraph::new();
+        add_node(&mut graph, NodeKind::ComputeOutput(size));
+        let read = add_node(&mut graph, NodeKind::Compute(ComputeNode::ReadBuffer(0)));
+        let write = add_node(&mut graph, NodeKind::Compute(ComputeNode::WriteBuffer(1)));
+        connect(&mut graph, read, 0, write, 1);
+        graph
+    }
+
+    fn compile_and_validate_compute(graph: &NodeGraph) -> String {
+        let shader = compile_compute(graph).expect("kernel should compile");
+        validate_wgsl(&shader.source)
+            .unwrap_or_else(|e| panic!("kernel failed to validate: {}\n{}", e, shader.source));
+        shader.source
+    }
+
+    #[test]
+    fn test_copy_kernel_compiles_to_a_compute_entry_point() {
+        let graph = copy_kernel(WorkgroupSize::default());
+        let source = compile_and_validate_compute(&graph);
+        assert!(source.contains(
+            "@compute @workgroup_size(64, 1, 1)\nfn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {"
+        ));
+        assert!(source.contains("@group(0) @binding(0)\nvar<uniform> globals: Globals;"));
+        assert!(
+            source.contains("@group(0) @binding(1)\nvar<storage, read> buffer0: array<vec4<f32>>;")
+        );
+        assert!(source.contains(
+            "@group(0) @binding(2)\nvar<storage, read_write> buffer1: array<vec4<f32>>;"
+        ));
+        // Unconnected indices follow the invocation
+        assert!(source.contains("buffer0[u32((vec3<f32>(global_id)).x)]"));
+
+        let shader = compile_compute(&graph).expect("kernel should compile");
+        assert_eq!(
+            shader.storage,
+            [
+                (StorageResource::Buffer(0), StorageAccess::Read),
+                (StorageResource::Buffer(1), StorageAccess::Write),
+            ]
+        );
+        assert_eq!(shader.workgroup_size, WorkgroupSize::new(64, 1, 1));
+    }
+
+    #[test]
+    fn test_storage_texture_access_follows_use() {
+        let mut graph = NodeGraph::new();
+        add_node(
+            &mut graph,
+            NodeKind::ComputeOutput(WorkgroupSize::new(8, 8, 1)),
+        );
+        let read = add_node(&mut graph, NodeKind::Compute(ComputeNode::ReadTexture(0)));
+        let write = add_node(&mut graph, NodeKind::Compute(ComputeNode::WriteTexture(1)));
+        connect(&mut graph, read, 0, write, 1);
+
+        let source = compile_and_validate_compute(&graph);
+        assert!(source.contains("@workgroup_size(8, 8, 1)"));
+        assert!(source.contains(
+            "@group(0) @binding(5)\nvar storage_texture0: texture_storage_2d<rgba32float, read>;"
+        ));
+        assert!(source.contains(
+            "@group(0) @binding(6)\nvar storage_texture1: texture_storage_2d<rgba32float, write>;"
+        ));
+        assert!(
+            source
+                .contains("textureStore(storage_texture1, vec2<u32>((vec3<f32>(global_id)).xy), ")
+        );
+
+        // Reading and writing one slot would need a read-write rgba32float texture
+        let feedback = add_node(&mut graph, NodeKind::Compute(ComputeNode::WriteTexture(0)));
+        connect(&mut graph, read, 0, feedback, 1);
+        assert_eq!(
+            compile_compute(&graph),
+            Err(CompileError::ReadWriteStorageTexture(0))
+        );
+    }
+
+    #[test]
+    fn test_kernel_can_read_the_clock_and_sample_textures() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(
+            &mut graph,
+            NodeKind::ComputeOutput(WorkgroupSize::default()),
+        );
+        let time = add_node(&mut graph, NodeKind::Time(TimeNode::Time));
+        let texture = add_node(
+            &mut graph,
+            NodeKind::Texture(TextureNode::default_texture()),
+        );
+        let sample = add_node(
+            &mut graph,
+            NodeKind::Texture(TextureNode::SampleLevel(Default::default())),
+        );
+        let write = add_node(&mut graph, NodeKind::Compute(ComputeNode::WriteBuffer(0)));
+        connect(&mut graph, texture, 0, sample, 0);
+        connect(&mut graph, output, 0, sample, 1);
+        connect(&mut graph, sample, 0, write, 1);
+        connect(&mut graph, time, 0, write, 0);
+
+        let source = compile_and_validate_compute(&graph);
+        assert!(source.contains("globals.time"));
+        assert!(source.contains("@group(1) @binding(0)\nvar node"));
+        let shader = compile_compute(&graph).expect("kernel should compile");
+        assert_eq!(shader.images, [Some("ducky.png".to_string())]);
+    }
+
+    #[test]
+    fn test_mesh_inputs_are_rejected_in_the_compute_stage() {
+        let cases = [
+            NodeKind::Geometry(GeometryNode::Position(Space::World)),
+            NodeKind::Uv(UvNode::Uv),
+            NodeKind::Texture(TextureNode::Sample(Default::default())),
+        ];
+        for kind in cases {
+            let mut graph = copy_kernel(WorkgroupSize::default());
+            let node = add_node(&mut graph, kind.clone());
+            let write = add_node(&mut graph, NodeKind::Compute(ComputeNode::WriteBuffer(2)));
+            connect(&mut graph, node, 0, write, 1);
+            assert_eq!(
+                compile_compute(&graph),
+                Err(CompileError::WrongStage {
+                    node,
+                    stage: ShaderStage::Compute
+                }),
+                "{:?} should not run in a kernel",
+                kind
+            );
+        }
+    }
+
+    #[test]
+    fn test_compute_nodes_are_rejected_in_the_fragment_stage() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let read = add_node(&mut graph, NodeKind::Compute(ComputeNode::ReadBuffer(0)));
+        connect(&mut graph, read, 0, output, 0);
+        assert_eq!(
+            compile_and_validate(&graph),
+            Err(CompileError::WrongStage {
+                node: read,
+                stage: ShaderStage::Fragment
+            })
+        );
+    }
+
+    #[test]
+    fn test_kernel_needs_one_valid_compute_output() {
+        let mut graph = NodeGraph::new();
+        add_node(&mut graph, NodeKind::Compute(ComputeNode::WriteBuffer(0)));
+        assert_eq!(compile_compute(&graph), Err(CompileError::MissingOutput));
+
+        let first = add_node(
+            &mut graph,
+            NodeKind::ComputeOutput(WorkgroupSize::default()),
+        );
+        let second = add_node(
+            &mut graph,
+            NodeKind::ComputeOutput(WorkgroupSize::default()),
+        );
+        assert_eq!(
+            compile_compute(&graph),
+            Err(CompileError::MultipleOutputs(vec![first, second]))
+        );
+
+        let too_big = WorkgroupSize::new(32, 32, 1);
+        assert!(!too_big.is_valid());
+        assert!(!WorkgroupSize::new(0, 1, 1).is_valid());
+        assert!(WorkgroupSize::new(16, 16, 1).is_valid());
+        assert_eq!(
+            compile_compute(&copy_kernel(too_big)),
+            Err(CompileError::InvalidWorkgroupSize(too_big))
+        );
+    }
+
+    #[test]
+    fn test_dispatch_copies_a_buffer_on_the_cpu() {
+        let graph = copy_kernel(WorkgroupSize::new(2, 1, 1));
+        let mut storage = ComputeStorage::default();
+        let source: Vec<Vec4> = (0..5).map(|i| Vec4::splat(i as f32)).collect();
+        storage.buffers.insert(0, source.clone());
+        storage.buffers.insert(1, vec![Vec4::NEG_ONE; 5]);
+
+        let mut evaluator = GraphEvaluator::with_storage(&graph, storage);
+        evaluator
+            .dispatch(UVec3::new(2, 1, 1))
+            .expect("kernel should run");
+        // Four invocations: the fifth element is left alone
+        let copied = &evaluator.storage().buffers[&1];
+        assert_eq!(copied[..4], source[..4]);
+        assert_eq!(copied[4], Vec4::NEG_ONE);
+    }
+
+    #[test]
+    fn test_dispatch_runs_an_image_pass() {
+        let mut graph = NodeGraph::new();
+        add_node(
+            &mut graph,
+            NodeKind::ComputeOutput(WorkgroupSize::new(8, 8, 1)),
+        );
+        let read = add_node(&mut graph, NodeKind::Compute(ComputeNode::ReadTexture(0)));
+        let invert = add_node(&mut graph, NodeKind::Math(MathOperation::Add));
+        let write = add_node(&mut graph, NodeKind::Compute(ComputeNode::WriteTexture(1)));
+        connect(&mut graph, read, 0, invert, 0);
+        connect(&mut graph, invert, 0, write, 1);
+        compile_and_validate_compute(&graph);
+
+        let mut source = StorageImage::new(UVec2::new(3, 2));
+        source.set(UVec2::new(2, 1), Vec4::new(0.5, 0.0, 0.0, 0.0));
+        let mut storage = ComputeStorage::default();
+        storage.textures.insert(0, source);
+        storage
+            .textures
+            .insert(1, StorageImage::new(UVec2::new(3, 2)));
+
+        let mut evaluator = GraphEvaluator::with_storage(&graph, storage);
+        evaluator.dispatch(UVec3::ONE).expect("kernel should run");
+        let result = &evaluator.storage().textures[&1];
+        // The red channel goes through `A + 0`, splatting to every component
+        assert_eq!(result.get(UVec2::new(2, 1)), Some(Vec4::splat(0.5)));
+        assert_eq!(result.get(UVec2::new(0, 0)), Some(Vec4::ZERO));
+        assert_eq!(result.get(UVec2::new(3, 0)), None);
+    }
+
+    #[test]
+    fn test_writes_run_in_creation_order() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(
+            &mut graph,
+            NodeKind::ComputeOutput(WorkgroupSize::new(1, 1, 1)),
+        );
+        let first = add_node(&mut graph, NodeKind::Compute(ComputeNode::WriteBuffer(0)));
+        let read = add_node(&mut graph, NodeKind::Compute(ComputeNode::ReadBuffer(0)));
+        let second = add_node(&mut graph, NodeKind::Compute(ComputeNode::WriteBuffer(1)));
+        // Buffer 0 gets the invocation ID, then buffer 1 copies it back out
+        connect(&mut graph, output, 0, first, 1);
+        connect(&mut graph, read, 0, second, 1);
+
+        let source = compile_and_validate_compute(&graph);
+        let write_at = source.find("buffer0[u32(").expect("first write is emitted");
+        let read_at = source
+            .find(&format!("let node{}_out0", read.0))
+            .expect("read is emitted");
+        assert!(write_at < read_at);
+
+        let mut storage = ComputeStorage::default();
+        storage.buffers.insert(0, vec![Vec4::ZERO; 3]);
+        storage.buffers.insert(1, vec![Vec4::ZERO; 3]);
+        let mut evaluator = GraphEvaluator::with_storage(&graph, storage);
+        evaluator
+            .dispatch(UVec3::new(3, 1, 1))
+            .expect("kernel should run");
+        assert_eq!(
+            evaluator.storage().buffers[&1][2],
+            Vec4::new(2.0, 0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            evaluator.storage().buffers[&0],
+            evaluator.storage().buffers[&1]
+        );
+    }
+
+    #[test]
+    fn test_compute_output_exposes_the_invocation() {
+        let ctx = EvalContext {
+            invocation_id: UVec3::new(3, 4, 5),
+            ..Default::default()
+        };
+        let kind = NodeKind::ComputeOutput(WorkgroupSize::default());
+        assert_eq!(
+            kind.evaluate(&[], &ctx),
+            [Value::Vec3(Vec3::new(3.0, 4.0, 5.0)), Value::Float(3.0)]
+        );
+        // Without bound storage, reads give zero
+        assert_eq!(
+            ComputeNode::ReadBuffer(0).evaluate(&[Value::Float(0.0)], &ctx),
+            [Value::Vec4(Vec4::ZERO)]
+        );
+    }
+}
@@ -0,0 +1,210 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::{NodeGraph, NodeId};
+    use crate::node_graph::node_factory::NodeFactory;
+    use crate::node_graph::test_utils::{add_node, connect};
+    use crate::shader_graph::compiler::{CompileError, compile_and_validate};
+    use crate::shader_graph::evaluator::GraphEvaluator;
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget, ShaderNode, ShaderStage};
+    use crate::shader_graph::nodes::custom::{CustomFunction, parse_signature};
+    use crate::shader_graph::nodes::time::TimeNode;
+    use crate::shader_graph::types::PinType;
+    use bevy::prelude::*;
+
+    const STRUCT_SOURCE: &str = "struct Split {
+    low: f32,
+    high: vec3<f32>,
+}
+
+fn half(x: f32) -> f32 {
+    return x * 0.5;
+}
+
+fn split(value: f32, color: vec3<f32>) -> Split {
+    return Split(half(value), color * value);
+}";
+
+    fn labels(graph: &NodeGraph, node: NodeId) -> (Vec<String>, Vec<String>) {
+        let node = &graph.nodes[&node];
+        (
+            node.inputs.iter().map(|pin| pin.label.clone()).collect(),
+            node.outputs.iter().map(|pin| pin.label.clone()).collect(),
+        )
+    }
+
+    #[test]
+    fn test_default_function_pins() {
+        let function = CustomFunction::default();
+        assert_eq!(function.error(), None);
+        assert_eq!(function.title(), "custom");
+        let signature = function.signature();
+        let inputs: Vec<(&str, PinType)> = signature
+            .inputs
+            .iter()
+            .map(|spec| (spec.label.as_str(), spec.ty))
+            .collect();
+        assert_eq!(inputs, [("uv", PinType::Vec2), ("scale", PinType::Float)]);
+        assert_eq!(signature.outputs[0].label, "Out");
+        assert_eq!(signature.outputs[0].ty, PinType::Vec3);
+    }
+
+    #[test]
+    fn test_struct_fields_become_outputs() {
+        let signature = parse_signature(STRUCT_SOURCE).expect("source should parse");
+        assert_eq!(signature.name, "split");
+        assert_eq!(
+            signature.outputs,
+            [
+                ("low".to_string(), PinType::Float),
+                ("high".to_string(), PinType::Vec3)
+            ]
+        );
+        assert!(signature.returns_struct);
+        assert_eq!(
+            CustomFunction::new(STRUCT_SOURCE).emit_wgsl(&["a".to_string(), "b".to_string()]),
+            ["split(a, b).low", "split(a, b).high"]
+        );
+    }
+
+    #[test]
+    fn test_errors_name_the_line() {
+        let error = parse_signature("fn broken() -> f32 {\n    return 1.0 +;\n}")
+            .expect_err("source should not parse");
+        assert!(error.starts_with("line 2: "), "{}", error);
+
+        let error = parse_signature("fn wrong() -> f32 {\n    return vec2<f32>(1.0);\n}")
+            .expect_err("source should not validate");
+        assert!(error.contains("wrong"), "{}", error);
+
+        assert!(parse_signature("fn nothing() {}").is_err());
+        assert!(parse_signature("fn int(x: i32) -> f32 { return f32(x); }").is_err());
+        assert!(parse_signature("const X: f32 = 1.0;").is_err());
+    }
+
+    #[test]
+    fn test_pins_survive_an_error() {
+        let mut function = CustomFunction::default();
+        let signature = function.signature();
+        function.set_source("fn custom(uv: vec2<f32>");
+        assert!(function.error().is_some());
+        assert_eq!(function.signature(), signature);
+        assert_eq!(function.source(), "fn custom(uv: vec2<f32>");
+    }
+
+    #[test]
+    fn test_custom_function_compiles_into_the_graph() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let custom = add_node(
+            &mut graph,
+            NodeKind::Custom(CustomFunction::new(STRUCT_SOURCE)),
+        );
+        let time = add_node(&mut graph, NodeKind::Time(TimeNode::Time));
+        connect(&mut graph, time, 0, custom, 0);
+        connect(&mut graph, custom, 1, output, 0);
+        connect(&mut graph, custom, 0, output, 1);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert_eq!(source.matches("struct Split {").count(), 1);
+        assert!(source.contains(&format!(
+            "let node{}_out1: vec3<f32> = split(node{}_out0, vec3<f32>(0.0, 0.0, 0.0)).high;",
+            custom.0, time.0
+        )));
+
+        // Two nodes with the same source share its declarations
+        let second = add_node(
+            &mut graph,
+            NodeKind::Custom(CustomFunction::new(STRUCT_SOURCE)),
+        );
+        graph
+            .connections
+            .retain(|c| c.to_pin != graph.nodes[&output].inputs[1].pin_id);
+        connect(&mut graph, second, 0, output, 1);
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert_eq!(source.matches("fn split(").count(), 1);
+    }
+
+    #[test]
+    fn test_invalid_source_is_a_compile_error() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let mut function = CustomFunction::default();
+        function.set_source("fn custom(uv: vec2<f32>) -> vec3<f32> { return uv; }");
+        let message = function
+            .error()
+            .expect("source should not validate")
+            .to_string();
+        let custom = add_node(&mut graph, NodeKind::Custom(function));
+        connect(&mut graph, custom, 0, output, 0);
+        assert_eq!(
+            compile_and_validate(&graph),
+            Err(CompileError::InvalidCustomFunction {
+                node: custom,
+                message
+            })
+        );
+    }
+
+    #[test]
+    fn test_fragment_only_functions_are_rejected_in_the_vertex_stage() {
+        let edge = "fn edge(x: f32) -> f32 {
+    return fwidth(x);
+}
+
+fn outline(x: f32) -> vec3<f32> {
+    return vec3<f32>(edge(x));
+}";
+        let function = CustomFunction::new(edge);
+        assert_eq!(function.error(), None);
+        assert_eq!(function.stages(), &[ShaderStage::Fragment]);
+        assert_eq!(CustomFunction::default().stages(), &ShaderStage::ALL);
+
+        let mut graph = NodeGraph::new();
+        add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let vertex = add_node(&mut graph, NodeKind::VertexOutput);
+        let custom = add_node(&mut graph, NodeKind::Custom(function));
+        connect(&mut graph, custom, 0, vertex, 0);
+        assert_eq!(
+            compile_and_validate(&graph),
+            Err(CompileError::WrongStage {
+                node: custom,
+                stage: ShaderStage::Vertex
+            })
+        );
+    }
+
+    #[test]
+    fn test_sync_pins_follows_the_source() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let custom = add_node(&mut graph, NodeKind::Custom(CustomFunction::default()));
+        connect(&mut graph, custom, 0, output, 0);
+
+        if let NodeKind::Custom(function) = &mut graph.nodes.get_mut(&custom).unwrap().kind {
+            function.set_source(STRUCT_SOURCE);
+        }
+        NodeFactory::sync_pins(&mut graph, custom);
+        assert_eq!(
+            labels(&graph, custom),
+            (
+                vec!["value".to_string(), "color".to_string()],
+                vec!["low".to_string(), "high".to_string()]
+            )
+        );
+        assert_eq!(graph.nodes[&custom].title, format!("split {}", custom.0));
+        // The first output kept its pin, so the connection to the output node stays
+        assert_eq!(graph.connections.len(), 1);
+    }
+
+    #[test]
+    fn test_cpu_evaluates_to_zero() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let custom = add_node(&mut graph, NodeKind::Custom(CustomFunction::default()));
+        connect(&mut graph, custom, 0, output, 0);
+        let color = GraphEvaluator::new(&graph)
+            .evaluate_fragment()
+            .expect("graph should evaluate");
+        assert_eq!(color, Vec4::new(0.0, 0.0, 0.0, 1.0));
+    }
+}
@@ -0,0 +1,169 @@
+use crate::node_graph::model::{NodeGraph, NodeId, PinDirection, PinId};
+use crate::shader_graph::compiler::{
+    CompileError, check_connection_types, find_compute_output, find_output_node,
+    find_vertex_output, resolve_input_source, storage_writes,
+};
+use crate::shader_graph::inputs::EvalContext;
+use crate::shader_graph::node_kind::{NodeKind, ShaderNode};
+use crate::shader_graph::nodes::compute::ComputeStorage;
+use crate::shader_graph::nodes::subgraph::Subgraph;
+use crate::shader_graph::types::Value;
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Evaluates a graph on the CPU - mirrors what the compiled WGSL computes for one fragment
+pub struct GraphEvaluator<'a> {
+    graph: &'a NodeGraph,
+    ctx: EvalContext,
+    /// Buffers and textures compute nodes read and write
+    storage: ComputeStorage,
+    cache: HashMap<NodeId, Vec<Value>>,
+    visiting: HashSet<NodeId>,
+}
+
+impl<'a> GraphEvaluator<'a> {
+    pub fn new(graph: &'a NodeGraph) -> Self {
+        Self::with_context(graph, EvalContext::default())
+    }
+
+    /// Evaluator for one specific fragment (UV, screen position)
+    pub fn with_context(graph: &'a NodeGraph, ctx: EvalContext) -> Self {
+        Self {
+            graph,
+            ctx,
+            storage: ComputeStorage::default(),
+            cache: HashMap::new(),
+            visiting: HashSet::new(),
+        }
+    }
+
+    /// Evaluator for a compute graph bound to `storage`
+    pub fn with_storage(graph: &'a NodeGraph, storage: ComputeStorage) -> Self {
+        Self {
+            storage,
+            ..Self::new(graph)
+        }
+    }
+
+    pub fn storage(&self) -> &ComputeStorage {
+        &self.storage
+    }
+
+    /// Run the compute kernel for a grid of `workgroups`, one invocation at a time in x, y, z
+    /// order. Each invocation runs the storage writes in the order `cs_main` does, so reads see
+    /// what earlier writes left behind.
+    pub fn dispatch(&mut self, workgroups: UVec3) -> Result<(), CompileError> {
+        let (_, workgroup_size) = find_compute_output(self.graph)?;
+        if !workgroup_size.is_valid() {
+            return Err(CompileError::InvalidWorkgroupSize(workgroup_size));
+        }
+        let writes = storage_writes(self.graph);
+
+        let grid = workgroups * workgroup_size.as_uvec3();
+        for z in 0..grid.z {
+            for y in 0..grid.y {
+                for x in 0..grid.x {
+                    self.ctx.invocation_id = UVec3::new(x, y, z);
+                    self.cache.clear();
+                    for &write in &writes {
+                        let inputs = self.gather_inputs(write)?;
+                        if let NodeKind::Compute(node) = &self.graph.nodes[&write].kind {
+                            node.store(&inputs, &mut self.storage);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Value produced by an output pin
+    pub fn evaluate_output_pin(&mut self, pin_id: PinId) -> Result<Value, CompileError> {
+        match self.graph.find_pin(pin_id) {
+            Some((node_id, PinDirection::Output, index)) => Ok(self.evaluate_node(node_id)?[index]),
+            _ => Err(CompileError::DanglingConnection(pin_id)),
+        }
+    }
+
+    /// Final RGBA colour the `Fragment Output` node receives
+    pub fn evaluate_fragment(&mut self) -> Result<Vec4, CompileError> {
+        let output_id = find_output_node(self.graph)?;
+        let inputs = self.gather_inputs(output_id)?;
+        let kind = &self.graph.nodes[&output_id].kind;
+        Ok(kind.evaluate(&inputs, &self.ctx)[0].as_vec4())
+    }
+
+    /// What the `Vertex Output` node receives for the vertex in the context: position offset,
+    /// normal and custom interpolants. `None` when the graph has no vertex stage.
+    pub fn evaluate_vertex(&mut self) -> Result<Option<Vec<Value>>, CompileError> {
+        let Some(output_id) = find_vertex_output(self.graph)? else {
+            return Ok(None);
+        };
+        let inputs = self.gather_inputs(output_id)?;
+        let kind = &self.graph.nodes[&output_id].kind;
+        Ok(Some(kind.evaluate(&inputs, &self.ctx)))
+    }
+
+    /// Values of every output of a node, evaluating its dependencies first
+    pub fn evaluate_node(&mut self, node_id: NodeId) -> Result<Vec<Value>, CompileError> {
+        if let Some(values) = self.cache.get(&node_id) {
+            return Ok(values.clone());
+        }
+
+        let inputs = self.gather_inputs(node_id)?;
+        let values = match &self.graph.nodes[&node_id].kind {
+            NodeKind::Compute(node) => node.evaluate_storage(&inputs, &self.storage),
+            kind => kind.evaluate(&inputs, &self.ctx),
+        };
+        self.cache.insert(node_id, values.clone());
+        Ok(values)
+    }
+
+    fn gather_inputs(&mut self, node_id: NodeId) -> Result<Vec<Value>, CompileError> {
+        if !self.visiting.insert(node_id) {
+            return Err(CompileError::Cycle(node_id));
+        }
+
+        let node = &self.graph.nodes[&node_id];
+        let signature = node.kind.signature();
+
+        let mut inputs = Vec::with_capacity(node.inputs.len());
+        for (pin, spec) in node.inputs.iter().zip(signature.inputs.iter()) {
+            let value = match resolve_input_source(self.graph, pin.pin_id)? {
+                Some((source_id, output_index)) => {
+                    let value = self.evaluate_node(source_id)?[output_index];
+                    check_connection_types(pin.pin_id, value.pin_type(), spec.ty)?;
+                    value.convert(spec.ty)
+                }
+                None => spec.default_value(&self.ctx),
+            };
+            inputs.push(value);
+        }
+
+        self.visiting.remove(&node_id);
+        Ok(inputs)
+    }
+}
+
+/// What a subgraph's outputs receive when its inputs hold `inputs`
+pub fn evaluate_subgraph(
+    subgraph: &Subgraph,
+    inputs: &[Value],
+    ctx: &EvalContext,
+) -> Result<Vec<Value>, CompileError> {
+    let graph = &subgraph.graph;
+    let boundary = |is_inputs: bool| {
+        graph.nodes.values().find_map(|node| match node.kind {
+            NodeKind::SubgraphInputs(_) if is_inputs => Some(node.node_id),
+            NodeKind::SubgraphOutputs(_) if !is_inputs => Some(node.node_id),
+            _ => None,
+        })
+    };
+    let output_id = boundary(false).ok_or(CompileError::MissingOutput)?;
+
+    let mut evaluator = GraphEvaluator::with_context(graph, *ctx);
+    if let Some(input_id) = boundary(true) {
+        evaluator.cache.insert(input_id, inputs.to_vec());
+    }
+    evaluator.gather_inputs(output_id)
+}
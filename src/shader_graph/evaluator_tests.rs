@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::NodeGraph;
+    use crate::node_graph::node_factory::MathOperation;
+    use crate::node_graph::test_utils::{add_node, connect};
+    use crate::shader_graph::compiler::CompileError;
+    use crate::shader_graph::evaluator::GraphEvaluator;
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget};
+    use crate::shader_graph::nodes::color::{BlendMode, ColorNode};
+    use crate::shader_graph::types::Value;
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_default_fragment_color() {
+        let mut graph = NodeGraph::new();
+        add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+
+        let color = GraphEvaluator::new(&graph).evaluate_fragment().unwrap();
+        assert_eq!(color, Vec4::new(0.2, 0.6, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_math_chain() {
+        let mut graph = NodeGraph::new();
+        let a = add_node(&mut graph, NodeKind::Constant(3.0));
+        let b = add_node(&mut graph, NodeKind::Constant(4.0));
+        let mul = add_node(&mut graph, NodeKind::Math(MathOperation::Multiply));
+        connect(&mut graph, a, 0, mul, 0);
+        connect(&mut graph, b, 0, mul, 1);
+
+        let out_pin = graph.nodes[&mul].outputs[0].pin_id;
+        let value = GraphEvaluator::new(&graph)
+            .evaluate_output_pin(out_pin)
+            .unwrap();
+        assert_eq!(value, Value::Float(12.0));
+    }
+
+    #[test]
+    fn test_scalar_is_splatted_into_color_input() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let constant = add_node(&mut graph, NodeKind::Constant(0.25));
+        connect(&mut graph, constant, 0, output, 0);
+
+        let color = GraphEvaluator::new(&graph).evaluate_fragment().unwrap();
+        assert_eq!(color, Vec4::new(0.25, 0.25, 0.25, 1.0));
+    }
+
+    #[test]
+    fn test_color_chain_roundtrips() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let to_linear = add_node(&mut graph, NodeKind::Color(ColorNode::SrgbToLinear));
+        let to_srgb = add_node(&mut graph, NodeKind::Color(ColorNode::LinearToSrgb));
+        connect(&mut graph, to_linear, 0, to_srgb, 0);
+        connect(&mut graph, to_srgb, 0, output, 0);
+
+        // Unconnected colour input defaults to white, which survives the roundtrip
+        let color = GraphEvaluator::new(&graph).evaluate_fragment().unwrap();
+        assert!((color - Vec4::ONE).abs().max_element() < 1e-5);
+    }
+
+    #[test]
+    fn test_blend_with_luminance_opacity() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let blend = add_node(&mut graph, NodeKind::Blend(BlendMode::Multiply));
+        let half = add_node(&mut graph, NodeKind::Constant(0.5));
+        connect(&mut graph, half, 0, blend, 2);
+        connect(&mut graph, blend, 0, output, 0);
+
+        // Base 0.5 * blend 0.5 = 0.25, mixed at 50% with base 0.5 = 0.375
+        let color = GraphEvaluator::new(&graph).evaluate_fragment().unwrap();
+        assert!((color.x - 0.375).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_cycle_is_reported() {
+        let mut graph = NodeGraph::new();
+        let a = add_node(&mut graph, NodeKind::Math(MathOperation::Add));
+        let b = add_node(&mut graph, NodeKind::Math(MathOperation::Add));
+        connect(&mut graph, a, 0, b, 0);
+        connect(&mut graph, b, 0, a, 0);
+
+        let out_pin = graph.nodes[&a].outputs[0].pin_id;
+        assert!(matches!(
+            GraphEvaluator::new(&graph).evaluate_output_pin(out_pin),
+            Err(CompileError::Cycle(_))
+        ));
+    }
+}
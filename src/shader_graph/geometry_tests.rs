@@ -0,0 +1,190 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::NodeGraph;
+    use crate::node_graph::test_utils::{add_node, connect};
+    use crate::shader_graph::compiler::{compile_and_validate, validate_wgsl};
+    use crate::shader_graph::evaluator::GraphEvaluator;
+    use crate::shader_graph::inputs::{EvalContext, Interpolant, ShaderInput};
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget, ShaderNode};
+    use crate::shader_graph::nodes::geometry::{GeometryNode, Space};
+    use crate::shader_graph::nodes::uv::UvNode;
+    use crate::shader_graph::scaffold::vertex_stage;
+    use crate::shader_graph::types::{PinType, Value};
+    use bevy::prelude::*;
+
+    fn all_geometry_nodes() -> Vec<GeometryNode> {
+        let mut nodes = Vec::new();
+        for space in Space::ALL {
+            nodes.push(GeometryNode::Position(space));
+            nodes.push(GeometryNode::Normal(space));
+        }
+        nodes.extend([
+            GeometryNode::Tangent,
+            GeometryNode::ViewDirection,
+            GeometryNode::CameraPosition,
+            GeometryNode::VertexColor,
+            GeometryNode::ScreenPosition,
+        ]);
+        nodes
+    }
+
+    fn compile_geometry(node: GeometryNode) -> String {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let geometry = add_node(&mut graph, NodeKind::Geometry(node));
+        connect(&mut graph, geometry, 0, output, 0);
+        compile_and_validate(&graph)
+            .unwrap_or_else(|e| panic!("{:?} failed to compile: {}", node, e))
+    }
+
+    #[test]
+    fn test_every_geometry_node_validates() {
+        for node in all_geometry_nodes() {
+            compile_geometry(node);
+        }
+    }
+
+    #[test]
+    fn test_geometry_nodes_have_no_inputs() {
+        for node in all_geometry_nodes() {
+            let signature = node.signature();
+            assert!(signature.inputs.is_empty());
+            assert_eq!(signature.outputs.len(), 1);
+            assert_eq!(signature.outputs[0].ty, node.source().pin_type());
+        }
+    }
+
+    #[test]
+    fn test_world_position_reads_existing_field() {
+        let source = compile_geometry(GeometryNode::Position(Space::World));
+        assert!(source.contains("input.world_position"));
+        // No extra interpolants when the built-in fields are enough
+        assert!(!source.contains("@location(3)"));
+    }
+
+    #[test]
+    fn test_object_position_adds_interpolant() {
+        let source = compile_geometry(GeometryNode::Position(Space::Object));
+        assert!(source.contains("@location(3) object_position: vec3<f32>,"));
+        assert!(source.contains("out.object_position = vertex.position;"));
+        assert!(source.contains("input.object_position"));
+    }
+
+    #[test]
+    fn test_vertex_color_adds_attribute_and_interpolant() {
+        let source = compile_geometry(GeometryNode::VertexColor);
+        assert!(source.contains("@location(5) color: vec4<f32>,"));
+        assert!(source.contains("out.color = vertex.color;"));
+    }
+
+    #[test]
+    fn test_tangent_keeps_bitangent_sign() {
+        let source = compile_geometry(GeometryNode::Tangent);
+        assert!(source.contains("@location(4) tangent: vec4<f32>,"));
+        assert!(source.contains("world_tangent: vec4<f32>"));
+        assert!(source.contains("vertex.tangent.w"));
+    }
+
+    #[test]
+    fn test_view_space_inputs_use_camera_uniform() {
+        assert!(compile_geometry(GeometryNode::Position(Space::View)).contains("camera.view * "));
+        assert!(compile_geometry(GeometryNode::ViewDirection).contains("camera.position"));
+    }
+
+    #[test]
+    fn test_interpolant_locations_are_packed_in_order() {
+        let source = vertex_stage(
+            &[
+                Interpolant::ScreenPosition,
+                Interpolant::ObjectPosition,
+                Interpolant::ObjectPosition,
+            ],
+            None,
+        );
+        assert!(source.contains("@location(3) object_position"));
+        assert!(source.contains("@location(4) screen_position"));
+        assert_eq!(source.matches("object_position:").count(), 1);
+    }
+
+    #[test]
+    fn test_vertex_stage_validates_with_every_interpolant() {
+        let source = format!(
+            "{}\n\n@fragment\nfn fs_main(input: VertexOutput) -> FragmentOutput {{\n    var out: FragmentOutput;\n    out.color = input.color;\n    return out;\n}}\n",
+            vertex_stage(
+                &[
+                    Interpolant::ObjectPosition,
+                    Interpolant::ObjectNormal,
+                    Interpolant::WorldTangent,
+                    Interpolant::VertexColor,
+                    Interpolant::ScreenPosition,
+                ],
+                None
+            )
+        );
+        validate_wgsl(&source).expect("scaffold should validate");
+    }
+
+    #[test]
+    fn test_several_inputs_share_one_vertex_stage() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let normal = add_node(
+            &mut graph,
+            NodeKind::Geometry(GeometryNode::Normal(Space::Object)),
+        );
+        let color = add_node(&mut graph, NodeKind::Geometry(GeometryNode::VertexColor));
+        connect(&mut graph, normal, 0, output, 0);
+        connect(&mut graph, color, 0, output, 1);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert!(source.contains("@location(3) object_normal: vec3<f32>,"));
+        assert!(source.contains("@location(4) color: vec4<f32>,"));
+    }
+
+    #[test]
+    fn test_uv_node_needs_no_interpolant() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let uv = add_node(&mut graph, NodeKind::Uv(UvNode::Uv));
+        connect(&mut graph, uv, 0, output, 0);
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert!(!source.contains("@location(3)"));
+    }
+
+    #[test]
+    fn test_view_direction_points_at_camera() {
+        let ctx = EvalContext::default();
+        assert_eq!(
+            ShaderInput::ViewDirection.evaluate(&ctx),
+            Value::Vec3(Vec3::Z)
+        );
+    }
+
+    #[test]
+    fn test_view_position_is_in_front_of_camera() {
+        let position = ShaderInput::ViewPosition.evaluate(&EvalContext::default());
+        // Right-handed view space looks down -Z
+        assert_eq!(position.pin_type(), PinType::Vec3);
+        assert!((position.as_vec3() - Vec3::new(0.0, 0.0, -4.0)).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_graph_evaluator_reads_context_geometry() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let normal = add_node(
+            &mut graph,
+            NodeKind::Geometry(GeometryNode::Normal(Space::World)),
+        );
+        connect(&mut graph, normal, 0, output, 0);
+
+        let ctx = EvalContext {
+            world_normal: Vec3::new(0.0, 2.0, 0.0),
+            ..Default::default()
+        };
+        let color = GraphEvaluator::with_context(&graph, ctx)
+            .evaluate_fragment()
+            .expect("graph should evaluate");
+        assert_eq!(color, Vec4::new(0.0, 1.0, 0.0, 1.0));
+    }
+}
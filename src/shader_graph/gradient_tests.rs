@@ -0,0 +1,194 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::NodeGraph;
+    use crate::node_graph::test_utils::{add_node, assert_near, connect};
+    use crate::shader_graph::compiler::compile_and_validate;
+    use crate::shader_graph::evaluator::GraphEvaluator;
+    use crate::shader_graph::inputs::EvalContext;
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget, ShaderNode};
+    use crate::shader_graph::nodes::gradient::{ColorRamp, ColorStop, RampInterpolation};
+    use crate::shader_graph::types::Value;
+    use bevy::prelude::*;
+
+    fn red_green_blue(interpolation: RampInterpolation) -> ColorRamp {
+        ColorRamp {
+            stops: vec![
+                ColorStop {
+                    position: 1.0,
+                    color: Vec4::new(0.0, 0.0, 1.0, 1.0),
+                },
+                ColorStop {
+                    position: 0.0,
+                    color: Vec4::new(1.0, 0.0, 0.0, 1.0),
+                },
+                ColorStop {
+                    position: 0.5,
+                    color: Vec4::new(0.0, 1.0, 0.0, 0.5),
+                },
+            ],
+            interpolation,
+        }
+    }
+
+    #[test]
+    fn test_default_ramp_is_black_to_white() {
+        let ramp = ColorRamp::default();
+        assert_near(ramp.sample(0.0), Vec4::new(0.0, 0.0, 0.0, 1.0));
+        assert_near(ramp.sample(0.5), Vec4::new(0.5, 0.5, 0.5, 1.0));
+        assert_near(ramp.sample(1.0), Vec4::ONE);
+    }
+
+    #[test]
+    fn test_sample_linear_uses_sorted_stops() {
+        let ramp = red_green_blue(RampInterpolation::Linear);
+        assert_near(ramp.sample(0.25), Vec4::new(0.5, 0.5, 0.0, 0.75));
+        assert_near(ramp.sample(0.5), Vec4::new(0.0, 1.0, 0.0, 0.5));
+        assert_near(ramp.sample(0.75), Vec4::new(0.0, 0.5, 0.5, 0.75));
+    }
+
+    #[test]
+    fn test_sample_constant_holds_left_stop() {
+        let ramp = red_green_blue(RampInterpolation::Constant);
+        assert_near(ramp.sample(0.49), Vec4::new(1.0, 0.0, 0.0, 1.0));
+        assert_near(ramp.sample(0.51), Vec4::new(0.0, 1.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn test_sample_smooth_eases_between_stops() {
+        let ramp = red_green_blue(RampInterpolation::Smooth);
+        // Midpoint of a smoothstep is still the midpoint
+        assert_near(ramp.sample(0.25), Vec4::new(0.5, 0.5, 0.0, 0.75));
+        // But a quarter of the way is eased towards the left stop
+        let quarter = ramp.sample(0.125);
+        assert!(quarter.x > 0.75 + 0.05, "{:?}", quarter);
+    }
+
+    #[test]
+    fn test_sample_clamps_outside_range() {
+        let ramp = red_green_blue(RampInterpolation::Linear);
+        assert_near(ramp.sample(-1.0), Vec4::new(1.0, 0.0, 0.0, 1.0));
+        assert_near(ramp.sample(2.0), Vec4::new(0.0, 0.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_add_stop_keeps_current_colour() {
+        let mut ramp = ColorRamp::default();
+        let before = ramp.sample(0.25);
+        let index = ramp.add_stop(0.25);
+        assert_eq!(ramp.stops.len(), 3);
+        assert_eq!(ramp.stops[index].position, 0.25);
+        assert_near(ramp.stops[index].color, before);
+    }
+
+    #[test]
+    fn test_add_stop_clamps_position() {
+        let mut ramp = ColorRamp::default();
+        let index = ramp.add_stop(1.5);
+        assert_eq!(ramp.stops[index].position, 1.0);
+    }
+
+    #[test]
+    fn test_remove_stop_keeps_at_least_one() {
+        let mut ramp = ColorRamp::default();
+        assert!(ramp.remove_stop(0));
+        assert!(!ramp.remove_stop(0));
+        assert_eq!(ramp.stops.len(), 1);
+        assert!(!ramp.remove_stop(5));
+    }
+
+    #[test]
+    fn test_function_name_tracks_content() {
+        let a = ColorRamp::default();
+        let b = ColorRamp::default();
+        assert_eq!(a.wgsl_function_name(), b.wgsl_function_name());
+
+        let mut c = ColorRamp::default();
+        c.stops[1].color = Vec4::new(1.0, 0.0, 0.0, 1.0);
+        assert_ne!(a.wgsl_function_name(), c.wgsl_function_name());
+
+        let d = ColorRamp {
+            interpolation: RampInterpolation::Smooth,
+            ..Default::default()
+        };
+        assert_ne!(a.wgsl_function_name(), d.wgsl_function_name());
+    }
+
+    #[test]
+    fn test_emit_calls_generated_function() {
+        let ramp = ColorRamp::default();
+        let name = ramp.wgsl_function_name();
+        assert_eq!(
+            ramp.emit_wgsl(&["x".to_string()]),
+            vec![format!("{}(x)", name), format!("{}(x).a", name)]
+        );
+        assert_eq!(ramp.wgsl_helpers()[0].name, name);
+        assert!(ramp.wgsl_function().contains("array<vec4<f32>, 2>"));
+    }
+
+    #[test]
+    fn test_evaluate_outputs_colour_and_alpha() {
+        let ramp = red_green_blue(RampInterpolation::Linear);
+        let outputs = ramp.evaluate(&[Value::Float(0.5)], &EvalContext::default());
+        assert_eq!(outputs[0], Value::Vec4(Vec4::new(0.0, 1.0, 0.0, 0.5)));
+        assert_eq!(outputs[1], Value::Float(0.5));
+    }
+
+    #[test]
+    fn test_every_interpolation_mode_validates() {
+        for mode in RampInterpolation::ALL {
+            let mut graph = NodeGraph::new();
+            let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+            let ramp = add_node(&mut graph, NodeKind::ColorRamp(red_green_blue(mode)));
+            connect(&mut graph, ramp, 0, output, 0);
+            connect(&mut graph, ramp, 1, output, 1);
+
+            let source = compile_and_validate(&graph)
+                .unwrap_or_else(|e| panic!("{:?} ramp failed to compile: {}", mode, e));
+            assert!(source.contains("fn color_ramp_"));
+        }
+    }
+
+    #[test]
+    fn test_single_stop_ramp_validates() {
+        let mut ramp = ColorRamp::default();
+        ramp.remove_stop(0);
+
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let node = add_node(&mut graph, NodeKind::ColorRamp(ramp));
+        connect(&mut graph, node, 0, output, 0);
+        compile_and_validate(&graph).expect("single stop ramp should compile");
+    }
+
+    #[test]
+    fn test_two_identical_ramps_emit_one_function() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let a = add_node(&mut graph, NodeKind::ColorRamp(ColorRamp::default()));
+        let b = add_node(&mut graph, NodeKind::ColorRamp(ColorRamp::default()));
+        connect(&mut graph, a, 0, output, 0);
+        connect(&mut graph, b, 1, output, 1);
+
+        let source = compile_and_validate(&graph).expect("ramps should compile");
+        let name = ColorRamp::default().wgsl_function_name();
+        assert_eq!(source.matches(&format!("fn {}", name)).count(), 1);
+    }
+
+    #[test]
+    fn test_graph_evaluator_samples_ramp() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let fac = add_node(&mut graph, NodeKind::Constant(0.25));
+        let ramp = add_node(
+            &mut graph,
+            NodeKind::ColorRamp(red_green_blue(RampInterpolation::Linear)),
+        );
+        connect(&mut graph, fac, 0, ramp, 0);
+        connect(&mut graph, ramp, 0, output, 0);
+
+        let color = GraphEvaluator::new(&graph)
+            .evaluate_fragment()
+            .expect("graph should evaluate");
+        assert_near(color, Vec4::new(0.5, 0.5, 0.0, 1.0));
+    }
+}
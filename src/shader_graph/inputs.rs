@@ -0,0 +1,380 @@
+use crate::shader_graph::node_kind::ShaderStage;
+use crate::shader_graph::types::{PinType, Value};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Number of custom `vec4` interpolants a `Vertex Output` node can pass to the fragment stage
+pub const CUSTOM_INTERPOLANTS: usize = 2;
+
+/// `VertexOutput` field of each custom interpolant
+const CUSTOM_FIELDS: [&str; CUSTOM_INTERPOLANTS] = ["custom0", "custom1"];
+
+/// Each custom interpolant in the preview's `fragment`, whose `GraphVertexOutput` is `graph`
+const PREVIEW_CUSTOM_EXPRS: [&str; CUSTOM_INTERPOLANTS] = ["graph.custom0", "graph.custom1"];
+
+/// Per-fragment data a node reads from the vertex stage, the camera or the rasterizer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ShaderInput {
+    /// Mesh UV0, interpolated through `VertexOutput.uv`
+    Uv,
+    /// Framebuffer position of the fragment in pixels
+    FragCoord,
+    WorldPosition,
+    ObjectPosition,
+    ViewPosition,
+    WorldNormal,
+    ObjectNormal,
+    ViewNormal,
+    WorldTangent,
+    /// Bitangent sign of the mesh tangent (`w`), `cross(normal, tangent) * sign` is the bitangent
+    TangentSign,
+    /// Unit vector from the surface towards the camera, in world space
+    ViewDirection,
+    CameraPosition,
+    VertexColor,
+    /// Fragment position on screen, `0..=1` from the top-left corner
+    ScreenPosition,
+    /// Unit vector from the surface towards the main (directional) light
+    LightDirection,
+    LightColor,
+    /// Unit vector from the surface towards the point light
+    PointLightDirection,
+    PointLightColor,
+    AmbientColor,
+    /// Seconds on the preview clock (`globals.time`)
+    Time,
+    /// Seconds the preview clock advanced since the previous frame
+    DeltaTime,
+    /// Custom interpolant written by the `Vertex Output` node, zero when nothing writes it
+    Custom(usize),
+    /// `global_invocation_id` of a compute invocation
+    InvocationId,
+}
+
+impl ShaderInput {
+    pub fn pin_type(self) -> PinType {
+        match self {
+            ShaderInput::Uv | ShaderInput::FragCoord | ShaderInput::ScreenPosition => PinType::Vec2,
+            ShaderInput::VertexColor | ShaderInput::Custom(_) => PinType::Vec4,
+            ShaderInput::InvocationId => PinType::Vec3,
+            ShaderInput::TangentSign | ShaderInput::Time | ShaderInput::DeltaTime => PinType::Float,
+            _ => PinType::Vec3,
+        }
+    }
+
+    /// Stages that can read the input. Rasterizer data only exists in the fragment stage, and a
+    /// compute kernel has no vertex, camera or lights - only the clock and its invocation.
+    pub fn stages(self) -> &'static [ShaderStage] {
+        match self {
+            ShaderInput::FragCoord | ShaderInput::ScreenPosition | ShaderInput::Custom(_) => {
+                &[ShaderStage::Fragment]
+            }
+            ShaderInput::Time | ShaderInput::DeltaTime => &ShaderStage::ALL,
+            ShaderInput::InvocationId => &[ShaderStage::Compute],
+            _ => &[ShaderStage::Vertex, ShaderStage::Fragment],
+        }
+    }
+
+    /// Expression reading the input inside `fs_main(input: VertexOutput)`, in `vs_main`
+    /// from the undisplaced vertex, which is also called `input`, or in `cs_main`
+    pub fn wgsl_expr(self) -> String {
+        if let ShaderInput::Custom(index) = self {
+            return format!("input.{}", CUSTOM_FIELDS[index]);
+        }
+        match self {
+            ShaderInput::Uv => "input.uv",
+            ShaderInput::FragCoord => "input.clip_position.xy",
+            ShaderInput::WorldPosition => "input.world_position",
+            ShaderInput::ObjectPosition => "input.object_position",
+            ShaderInput::ViewPosition => "(camera.view * vec4<f32>(input.world_position, 1.0)).xyz",
+            ShaderInput::WorldNormal => "normalize(input.world_normal)",
+            ShaderInput::ObjectNormal => "normalize(input.object_normal)",
+            ShaderInput::ViewNormal => {
+                "normalize((camera.view * vec4<f32>(input.world_normal, 0.0)).xyz)"
+            }
+            ShaderInput::WorldTangent => "normalize(input.world_tangent.xyz)",
+            ShaderInput::TangentSign => "input.world_tangent.w",
+            ShaderInput::ViewDirection => "normalize(camera.position - input.world_position)",
+            ShaderInput::CameraPosition => "camera.position",
+            ShaderInput::VertexColor => "input.color",
+            ShaderInput::ScreenPosition => {
+                "(input.screen_position.xy / input.screen_position.w * vec2<f32>(0.5, -0.5) + 0.5)"
+            }
+            ShaderInput::LightDirection => "normalize(lights.direction.xyz)",
+            ShaderInput::LightColor => "lights.color.rgb",
+            ShaderInput::PointLightDirection => {
+                "normalize(lights.point_position.xyz - input.world_position)"
+            }
+            ShaderInput::PointLightColor => "lights.point_color.rgb",
+            ShaderInput::AmbientColor => "lights.ambient.rgb",
+            ShaderInput::Time => "globals.time",
+            ShaderInput::DeltaTime => "globals.delta_time",
+            ShaderInput::InvocationId => "vec3<f32>(global_id)",
+            ShaderInput::Custom(_) => unreachable!("custom interpolants are formatted above"),
+        }
+        .to_string()
+    }
+
+    /// Field of the entry point's `input` that `wgsl_expr` reads, with its type. `None` for
+    /// module-scope data (camera, lights, clock) and the compute invocation.
+    pub fn input_field(self) -> Option<(&'static str, PinType)> {
+        if let Some(interpolant) = self.interpolant() {
+            return Some((interpolant.field(), interpolant.pin_type()));
+        }
+        match self {
+            ShaderInput::Uv => Some(("uv", PinType::Vec2)),
+            ShaderInput::FragCoord => Some(("clip_position", PinType::Vec4)),
+            ShaderInput::WorldNormal | ShaderInput::ViewNormal => {
+                Some(("world_normal", PinType::Vec3))
+            }
+            ShaderInput::WorldPosition
+            | ShaderInput::ViewPosition
+            | ShaderInput::ViewDirection
+            | ShaderInput::PointLightDirection => Some(("world_position", PinType::Vec3)),
+            _ => None,
+        }
+    }
+
+    /// `VertexOutput` field this input needs beyond the ones every shader has
+    pub fn interpolant(self) -> Option<Interpolant> {
+        match self {
+            ShaderInput::ObjectPosition => Some(Interpolant::ObjectPosition),
+            ShaderInput::ObjectNormal => Some(Interpolant::ObjectNormal),
+            ShaderInput::WorldTangent | ShaderInput::TangentSign => Some(Interpolant::WorldTangent),
+            ShaderInput::VertexColor => Some(Interpolant::VertexColor),
+            ShaderInput::ScreenPosition => Some(Interpolant::ScreenPosition),
+            ShaderInput::Custom(index) => Some(Interpolant::Custom(index)),
+            _ => None,
+        }
+    }
+
+    /// Value of the input for the fragment described by `ctx`
+    pub fn evaluate(self, ctx: &EvalContext) -> Value {
+        match self {
+            ShaderInput::Uv => Value::Vec2(ctx.uv),
+            ShaderInput::FragCoord => Value::Vec2(ctx.frag_coord),
+            ShaderInput::WorldPosition => Value::Vec3(ctx.world_position),
+            ShaderInput::ObjectPosition => Value::Vec3(ctx.object_position),
+            ShaderInput::ViewPosition => Value::Vec3(ctx.view.transform_point3(ctx.world_position)),
+            ShaderInput::WorldNormal => Value::Vec3(ctx.world_normal.normalize_or_zero()),
+            ShaderInput::ObjectNormal => Value::Vec3(ctx.object_normal.normalize_or_zero()),
+            ShaderInput::ViewNormal => Value::Vec3(
+                ctx.view
+                    .transform_vector3(ctx.world_normal)
+                    .normalize_or_zero(),
+            ),
+            ShaderInput::WorldTangent => {
+                Value::Vec3(ctx.world_tangent.truncate().normalize_or_zero())
+            }
+            ShaderInput::TangentSign => Value::Float(ctx.world_tangent.w),
+            ShaderInput::ViewDirection => {
+                Value::Vec3((ctx.camera_position - ctx.world_position).normalize_or_zero())
+            }
+            ShaderInput::CameraPosition => Value::Vec3(ctx.camera_position),
+            ShaderInput::VertexColor => Value::Vec4(ctx.vertex_color),
+            ShaderInput::ScreenPosition => Value::Vec2(ctx.screen_position),
+            ShaderInput::LightDirection => Value::Vec3(ctx.light_direction.normalize_or_zero()),
+            ShaderInput::LightColor => Value::Vec3(ctx.light_color),
+            ShaderInput::PointLightDirection => {
+                Value::Vec3((ctx.point_light_position - ctx.world_position).normalize_or_zero())
+            }
+            ShaderInput::PointLightColor => Value::Vec3(ctx.point_light_color),
+            ShaderInput::AmbientColor => Value::Vec3(ctx.ambient_color),
+            ShaderInput::Time => Value::Float(ctx.time),
+            ShaderInput::DeltaTime => Value::Float(ctx.delta_time),
+            // The compiler rejects indices past the last interpolant; here they read zero
+            ShaderInput::Custom(index) => {
+                Value::Vec4(ctx.custom.get(index).copied().unwrap_or(Vec4::ZERO))
+            }
+            ShaderInput::InvocationId => Value::Vec3(ctx.invocation_id.as_vec3()),
+        }
+    }
+}
+
+/// Extra `VertexOutput` fields, added to the generated shader only when a node reads them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Interpolant {
+    ObjectPosition,
+    ObjectNormal,
+    /// World-space tangent, `w` carries the bitangent sign
+    WorldTangent,
+    VertexColor,
+    /// Clip-space position, divided by `w` in the fragment stage
+    ScreenPosition,
+    /// Written by the vertex graph, see `CUSTOM_INTERPOLANTS`
+    Custom(usize),
+}
+
+impl Interpolant {
+    pub fn field(self) -> &'static str {
+        match self {
+            Interpolant::ObjectPosition => "object_position",
+            Interpolant::ObjectNormal => "object_normal",
+            Interpolant::WorldTangent => "world_tangent",
+            Interpolant::VertexColor => "color",
+            Interpolant::ScreenPosition => "screen_position",
+            Interpolant::Custom(index) => CUSTOM_FIELDS[index],
+        }
+    }
+
+    pub fn pin_type(self) -> PinType {
+        match self {
+            Interpolant::ObjectPosition | Interpolant::ObjectNormal => PinType::Vec3,
+            _ => PinType::Vec4,
+        }
+    }
+
+    /// Value assigned in `vs_main`, after `out.clip_position` is written. Custom interpolants
+    /// start at zero and are overwritten by the vertex graph.
+    pub fn vertex_expr(self) -> &'static str {
+        match self {
+            Interpolant::ObjectPosition => "vertex.position",
+            Interpolant::ObjectNormal => "vertex.normal",
+            Interpolant::WorldTangent => {
+                "vec4<f32>((model.model * vec4<f32>(vertex.tangent.xyz, 0.0)).xyz, vertex.tangent.w)"
+            }
+            Interpolant::VertexColor => "vertex.color",
+            Interpolant::ScreenPosition => "out.clip_position",
+            Interpolant::Custom(_) => "vec4<f32>(0.0)",
+        }
+    }
+
+    /// Value in the preview material's entry points, read from Bevy's mesh `VertexOutput`.
+    /// The preview mesh sits unscaled at the origin, so object space is world space. Custom
+    /// interpolants ride along in the preview's own `GraphVertexOutput`.
+    pub fn preview_expr(self) -> &'static str {
+        match self {
+            Interpolant::ObjectPosition => "mesh.world_position.xyz",
+            Interpolant::ObjectNormal => "mesh.world_normal",
+            Interpolant::WorldTangent => "mesh.world_tangent",
+            Interpolant::VertexColor => "mesh.color",
+            Interpolant::ScreenPosition => "view.clip_from_world * mesh.world_position",
+            Interpolant::Custom(index) => PREVIEW_CUSTOM_EXPRS[index],
+        }
+    }
+
+    /// Value in the `fragment` of targets without a mesh, given the flat `input` fields
+    pub fn flat_expr(self) -> &'static str {
+        match self {
+            Interpolant::ObjectPosition => "input.world_position",
+            Interpolant::ObjectNormal => "input.world_normal",
+            Interpolant::WorldTangent => "vec4<f32>(1.0, 0.0, 0.0, 1.0)",
+            Interpolant::VertexColor => "vec4<f32>(1.0)",
+            Interpolant::ScreenPosition => "vec4<f32>(input.world_position.xy, 0.0, 1.0)",
+            Interpolant::Custom(_) => {
+                unreachable!("targets without a vertex stage reject custom interpolants")
+            }
+        }
+    }
+
+    /// Mesh attribute `vs_main` has to read to fill this interpolant
+    pub fn attribute(self) -> Option<VertexAttribute> {
+        match self {
+            Interpolant::WorldTangent => Some(VertexAttribute::Tangent),
+            Interpolant::VertexColor => Some(VertexAttribute::Color),
+            _ => None,
+        }
+    }
+}
+
+/// Optional mesh attributes beyond position, normal and UV
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum VertexAttribute {
+    Tangent,
+    Color,
+}
+
+impl VertexAttribute {
+    pub fn field(self) -> &'static str {
+        match self {
+            VertexAttribute::Tangent => "tangent",
+            VertexAttribute::Color => "color",
+        }
+    }
+
+    /// Fixed shader location, matching Bevy's mesh attribute order
+    pub fn location(self) -> u32 {
+        match self {
+            VertexAttribute::Tangent => 4,
+            VertexAttribute::Color => 5,
+        }
+    }
+
+    pub fn pin_type(self) -> PinType {
+        PinType::Vec4
+    }
+}
+
+/// The fragment a CPU evaluation stands in for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvalContext {
+    pub uv: Vec2,
+    pub frag_coord: Vec2,
+    pub screen_position: Vec2,
+    pub world_position: Vec3,
+    pub object_position: Vec3,
+    pub world_normal: Vec3,
+    pub object_normal: Vec3,
+    pub world_tangent: Vec4,
+    pub vertex_color: Vec4,
+    pub camera_position: Vec3,
+    /// World to view transform
+    pub view: Mat4,
+    /// Towards the main light
+    pub light_direction: Vec3,
+    pub light_color: Vec3,
+    pub point_light_position: Vec3,
+    pub point_light_color: Vec3,
+    pub ambient_color: Vec3,
+    pub time: f32,
+    pub delta_time: f32,
+    /// Custom interpolants as the vertex graph left them
+    pub custom: [Vec4; CUSTOM_INTERPOLANTS],
+    /// Compute invocation being evaluated
+    pub invocation_id: UVec3,
+}
+
+impl Default for EvalContext {
+    /// The point of a unit sphere facing a camera five units down +Z, lit head-on
+    fn default() -> Self {
+        let camera_position = Vec3::new(0.0, 0.0, 5.0);
+        Self {
+            uv: Vec2::splat(0.5),
+            frag_coord: Vec2::ZERO,
+            screen_position: Vec2::splat(0.5),
+            world_position: Vec3::Z,
+            object_position: Vec3::Z,
+            world_normal: Vec3::Z,
+            object_normal: Vec3::Z,
+            world_tangent: Vec4::new(1.0, 0.0, 0.0, 1.0),
+            vertex_color: Vec4::ONE,
+            camera_position,
+            view: Mat4::look_at_rh(camera_position, Vec3::ZERO, Vec3::Y),
+            light_direction: Vec3::Z,
+            light_color: Vec3::ONE,
+            point_light_position: Vec3::new(0.0, 0.0, 10.0),
+            point_light_color: Vec3::ONE,
+            ambient_color: Vec3::splat(0.1),
+            time: 0.0,
+            delta_time: 0.0,
+            custom: [Vec4::ZERO; CUSTOM_INTERPOLANTS],
+            invocation_id: UVec3::ZERO,
+        }
+    }
+}
+
+impl EvalContext {
+    pub fn at_uv(uv: Vec2) -> Self {
+        Self {
+            uv,
+            ..Default::default()
+        }
+    }
+
+    pub fn at_time(time: f32) -> Self {
+        Self {
+            time,
+            ..Default::default()
+        }
+    }
+}
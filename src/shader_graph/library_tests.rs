@@ -0,0 +1,320 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::NodeGraph;
+    use crate::node_graph::test_utils::{add_node, connect};
+    use crate::shader_graph::compiler::{CompileError, compile_and_validate};
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget, ShaderNode, ShaderStage};
+    use crate::shader_graph::nodes::library::{
+        LIBRARY_DIR, LibraryFunction, WgslLibrary, parse_library_file,
+    };
+    use crate::shader_graph::types::PinType;
+    use bevy::prelude::*;
+    use std::path::PathBuf;
+
+    const NOISE: &str = include_str!("../../assets/shaders/library/noise.wgsl");
+
+    fn noise_function(name: &str) -> LibraryFunction {
+        parse_library_file("noise.wgsl", NOISE)
+            .expect("noise.wgsl should parse")
+            .into_iter()
+            .find(|function| function.signature.name == name)
+            .unwrap_or_else(|| panic!("no function `{}`", name))
+    }
+
+    /// An empty directory of its own under the system temp dir
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("wgsl_library_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("scratch dir should be writable");
+        dir
+    }
+
+    #[test]
+    fn test_doc_comments_name_the_node() {
+        let value_noise = noise_function("value_noise");
+        assert_eq!(value_noise.title(), "Value Noise");
+        assert_eq!(value_noise.category, "Noise");
+        assert!(value_noise.description.starts_with("Smooth noise"));
+        let signature = value_noise.signature();
+        let inputs: Vec<(&str, PinType)> = signature
+            .inputs
+            .iter()
+            .map(|spec| (spec.label.as_str(), spec.ty))
+            .collect();
+        assert_eq!(inputs, [("p", PinType::Vec2)]);
+
+        // Without doc comments: the function name, and the file as category
+        let plain = parse_library_file("extra/hashes.wgsl", "fn hash(x: f32) -> f32 { return x; }")
+            .expect("source should parse")
+            .remove(0);
+        assert_eq!(plain.title, "hash");
+        assert_eq!(plain.category, "hashes");
+        assert_eq!(plain.description, "");
+    }
+
+    #[test]
+    fn test_helpers_are_the_dependencies_in_order() {
+        let names = |function: &LibraryFunction| -> Vec<String> {
+            function
+                .wgsl_helpers()
+                .iter()
+                .map(|helper| helper.name.clone())
+                .collect()
+        };
+        assert_eq!(
+            names(&noise_function("fbm")),
+            [
+                "noise.wgsl::HASH_SCALE",
+                "noise.wgsl::hash21",
+                "noise.wgsl::value_noise",
+                "noise.wgsl::fbm"
+            ]
+        );
+        let voronoi = noise_function("voronoi");
+        assert_eq!(names(&voronoi)[0], "noise.wgsl::Cells");
+        let cells = &voronoi.wgsl_helpers()[0].source;
+        assert!(cells.starts_with("struct Cells {") && cells.ends_with('}'));
+        assert_eq!(
+            voronoi
+                .signature()
+                .outputs
+                .iter()
+                .map(|spec| spec.ty)
+                .collect::<Vec<_>>(),
+            [PinType::Float, PinType::Vec2]
+        );
+    }
+
+    #[test]
+    fn test_only_used_functions_are_compiled() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let noise = add_node(&mut graph, NodeKind::Library(noise_function("value_noise")));
+        connect(&mut graph, noise, 0, output, 1);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert!(source.contains("fn value_noise("));
+        assert_eq!(source.matches("fn hash21(").count(), 1);
+        assert!(source.contains("const HASH_SCALE"));
+        assert!(!source.contains("fn fbm("));
+        assert!(!source.contains("struct Cells"));
+
+        // A second function sharing the hash emits it once
+        let fbm = add_node(&mut graph, NodeKind::Library(noise_function("fbm")));
+        let voronoi = add_node(&mut graph, NodeKind::Library(noise_function("voronoi")));
+        graph
+            .connections
+            .retain(|c| c.to_pin != graph.nodes[&output].inputs[1].pin_id);
+        connect(&mut graph, fbm, 0, output, 1);
+        connect(&mut graph, voronoi, 1, fbm, 0);
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert_eq!(source.matches("fn hash21(").count(), 1);
+        assert_eq!(source.matches("fn value_noise(").count(), 1);
+        assert_eq!(source.matches("struct Cells {").count(), 1);
+    }
+
+    #[test]
+    fn test_stages_follow_the_function_body() {
+        let source = "@group(0) @binding(0) var tex: texture_2d<f32>;
+@group(0) @binding(1) var samp: sampler;
+
+fn sample_implicit(uv: vec2<f32>) -> vec4<f32> {
+    return textureSample(tex, samp, uv);
+}
+
+fn sample_level(uv: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(tex, samp, uv, 0.0);
+}";
+        let functions = parse_library_file("sampling.wgsl", source).expect("source should parse");
+        let stages: Vec<(&str, &[ShaderStage])> = functions
+            .iter()
+            .map(|function| (function.signature.name.as_str(), function.stages()))
+            .collect();
+        assert_eq!(
+            stages,
+            [
+                ("sample_implicit", &[ShaderStage::Fragment][..]),
+                ("sample_level", &ShaderStage::ALL[..]),
+            ]
+        );
+
+        let mut graph = NodeGraph::new();
+        add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let vertex = add_node(&mut graph, NodeKind::VertexOutput);
+        let sample = add_node(&mut graph, NodeKind::Library(functions[0].clone()));
+        connect(&mut graph, sample, 0, vertex, 0);
+        assert_eq!(
+            compile_and_validate(&graph),
+            Err(CompileError::WrongStage {
+                node: sample,
+                stage: ShaderStage::Vertex
+            })
+        );
+    }
+
+    #[test]
+    fn test_bundled_library_loads() {
+        let library = WgslLibrary::load(LIBRARY_DIR);
+        assert_eq!(library.errors, []);
+        assert!(library.find("noise.wgsl", "fbm").is_some());
+        assert!(library.find("shapes.wgsl", "sd_circle").is_some());
+        let categories: Vec<&str> = library
+            .categories()
+            .iter()
+            .map(|(category, _)| *category)
+            .collect();
+        assert_eq!(categories, ["Noise", "shapes"]);
+    }
+
+    #[test]
+    fn test_unusable_functions_and_broken_files() {
+        let dir = scratch_dir("broken");
+        std::fs::write(
+            dir.join("mixed.wgsl"),
+            "fn index(i: i32) -> f32 { return f32(i); }\nfn twice(x: f32) -> f32 { return index(2) * x; }",
+        )
+        .unwrap();
+        std::fs::create_dir(dir.join("nested")).unwrap();
+        std::fs::write(
+            dir.join("nested/broken.wgsl"),
+            "fn broken() -> f32 {\n    return 1.0 +;\n}",
+        )
+        .unwrap();
+        std::fs::write(dir.join("notes.txt"), "not wgsl").unwrap();
+
+        let library = WgslLibrary::load(&dir);
+        // `index` takes an i32, so it is no node, but `twice` still brings it along
+        assert_eq!(library.functions.len(), 1);
+        let twice = library
+            .find("mixed.wgsl", "twice")
+            .expect("twice should load");
+        assert_eq!(twice.helpers.len(), 2);
+        assert_eq!(library.errors.len(), 1);
+        assert_eq!(library.errors[0].0, "nested/broken.wgsl");
+        assert!(
+            library.errors[0].1.starts_with("line 2: "),
+            "{}",
+            library.errors[0].1
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_a_name_declared_by_two_files_is_reported() {
+        let dir = scratch_dir("clash");
+        std::fs::write(
+            dir.join("a.wgsl"),
+            "fn hash(x: f32) -> f32 { return fract(x * 43758.5); }\nfn grain(x: f32) -> f32 { return hash(x); }",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("b.wgsl"),
+            "fn hash(x: f32) -> f32 { return fract(x * 12.9898); }\nfn speckle(x: f32) -> f32 { return hash(x) * 0.5; }\nfn half(x: f32) -> f32 { return x * 0.5; }",
+        )
+        .unwrap();
+
+        let library = WgslLibrary::load(&dir);
+        // The first file keeps `hash`; the second keeps only what doesn't need it
+        assert!(library.find("a.wgsl", "hash").is_some());
+        assert!(library.find("a.wgsl", "grain").is_some());
+        assert!(library.find("b.wgsl", "hash").is_none());
+        assert!(library.find("b.wgsl", "speckle").is_none());
+        assert_eq!(
+            library.errors,
+            [(
+                "b.wgsl".to_string(),
+                "`hash` is already declared in a.wgsl".to_string()
+            )]
+        );
+
+        // Everything that loaded can share one shader
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let mut previous = None;
+        for function in &library.functions {
+            let node = add_node(&mut graph, NodeKind::Library(function.clone()));
+            if let Some(previous) = previous {
+                connect(&mut graph, previous, 0, node, 0);
+            }
+            previous = Some(node);
+        }
+        connect(&mut graph, previous.unwrap(), 0, output, 0);
+        compile_and_validate(&graph).expect("library functions should compile together");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_a_name_a_built_in_node_declares_is_reported() {
+        let dir = scratch_dir("builtin_clash");
+        std::fs::write(
+            dir.join("color.wgsl"),
+            "fn rgb_to_hsv(c: vec3<f32>) -> vec3<f32> { return c.bgr; }\nfn tint(c: vec3<f32>) -> vec3<f32> { return c * 0.5; }",
+        )
+        .unwrap();
+
+        let library = WgslLibrary::load(&dir);
+        assert!(library.find("color.wgsl", "rgb_to_hsv").is_none());
+        assert!(library.find("color.wgsl", "tint").is_some());
+        assert_eq!(
+            library.errors,
+            [(
+                "color.wgsl".to_string(),
+                "`rgb_to_hsv` is already declared in a built-in node".to_string()
+            )]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_file_changes_resync_the_graph() {
+        let dir = scratch_dir("resync");
+        let file = dir.join("ops.wgsl");
+        std::fs::write(
+            &file,
+            "/// Scale\nfn scale(x: f32) -> f32 { return x * 2.0; }",
+        )
+        .unwrap();
+        let mut library = WgslLibrary::load(&dir);
+        assert!(!library.rescan());
+
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let scale = add_node(
+            &mut graph,
+            NodeKind::Library(library.find("ops.wgsl", "scale").unwrap().clone()),
+        );
+        connect(&mut graph, scale, 0, output, 1);
+        assert_eq!(graph.nodes[&scale].title, format!("Scale {}", scale.0));
+
+        std::fs::write(
+            &file,
+            "/// Scale By\nfn scale(x: f32, factor: f32) -> f32 { return x * factor; }",
+        )
+        .unwrap();
+        // Modification times can be coarse; make sure this write counts as a change
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(library.rescan());
+        assert_eq!(library.sync_graph(&mut graph), [scale]);
+        let node = &graph.nodes[&scale];
+        assert_eq!(node.title, format!("Scale By {}", scale.0));
+        assert_eq!(node.inputs.len(), 2);
+        assert_eq!(node.inputs[1].label, "factor");
+        assert_eq!(graph.connections.len(), 1);
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert!(source.contains("return x * factor;"));
+
+        // A removed file leaves the node with the WGSL it had
+        std::fs::remove_file(&file).unwrap();
+        assert!(library.rescan());
+        assert_eq!(library.sync_graph(&mut graph), []);
+        assert!(compile_and_validate(&graph).is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
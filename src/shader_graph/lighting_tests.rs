@@ -0,0 +1,133 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::NodeGraph;
+    use crate::node_graph::test_utils::{add_node, connect};
+    use crate::shader_graph::compiler::{compile_and_validate, compile_preview};
+    use crate::shader_graph::evaluator::GraphEvaluator;
+    use crate::shader_graph::inputs::EvalContext;
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget, ShaderNode};
+    use crate::shader_graph::nodes::lighting::{
+        LightingNode, blinn_phong, fresnel, lambert, toon_ramp,
+    };
+    use crate::shader_graph::scaffold::PREVIEW_LIGHTS_BINDING;
+    use crate::shader_graph::types::Value;
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_lambert_follows_cosine_and_clamps() {
+        let color = Vec3::new(1.0, 0.5, 0.25);
+        assert_eq!(lambert(Vec3::Z, Vec3::Z, color), color);
+        let tilted = lambert(Vec3::Z, Vec3::new(1.0, 0.0, 1.0), Vec3::ONE);
+        assert!((tilted.x - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-5);
+        assert_eq!(lambert(Vec3::Z, Vec3::NEG_Z, Vec3::ONE), Vec3::ZERO);
+    }
+
+    #[test]
+    fn test_blinn_phong_peaks_at_mirror_direction() {
+        let light = Vec3::new(1.0, 0.0, 1.0);
+        let mirror = Vec3::new(-1.0, 0.0, 1.0);
+        let peak = blinn_phong(Vec3::Z, light, Vec3::ONE, mirror, 32.0);
+        assert!((peak.x - 1.0).abs() < 1e-5);
+        let off = blinn_phong(Vec3::Z, light, Vec3::ONE, Vec3::new(0.0, 1.0, 1.0), 32.0);
+        assert!(off.x < peak.x);
+        // No highlight when the light is behind the surface
+        let behind = blinn_phong(Vec3::Z, Vec3::NEG_Z, Vec3::ONE, Vec3::NEG_Z, 32.0);
+        assert_eq!(behind, Vec3::ZERO);
+    }
+
+    #[test]
+    fn test_fresnel_is_f0_head_on_and_one_at_grazing() {
+        assert!((fresnel(Vec3::Z, Vec3::Z, 0.04, 5.0) - 0.04).abs() < 1e-6);
+        assert!((fresnel(Vec3::Z, Vec3::X, 0.04, 5.0) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_toon_ramp_quantises_into_bands() {
+        let band = |light: Vec3| toon_ramp(Vec3::Z, light, Vec3::ONE, 3.0).x;
+        assert_eq!(band(Vec3::Z), 1.0);
+        assert_eq!(band(Vec3::new(1.0, 0.0, 0.5)), 0.5);
+        assert_eq!(band(Vec3::X), 0.0);
+        // A single step is plain on/off
+        assert_eq!(toon_ramp(Vec3::Z, Vec3::Z, Vec3::ONE, 1.0), Vec3::ONE);
+    }
+
+    #[test]
+    fn test_light_node_reads_eval_context() {
+        let ctx = EvalContext {
+            light_direction: Vec3::new(0.0, 2.0, 0.0),
+            light_color: Vec3::new(1.0, 0.9, 0.8),
+            ambient_color: Vec3::splat(0.3),
+            ..Default::default()
+        };
+        let out = LightingNode::Light.evaluate(&[], &ctx);
+        assert_eq!(out[0], Value::Vec3(Vec3::Y));
+        assert_eq!(out[1], Value::Vec3(Vec3::new(1.0, 0.9, 0.8)));
+        // The point light sits at (0, 0, 10), straight out from the fragment
+        assert_eq!(out[2], Value::Vec3(Vec3::Z));
+        assert_eq!(out[4], Value::Vec3(Vec3::splat(0.3)));
+    }
+
+    #[test]
+    fn test_rim_is_dark_facing_the_camera() {
+        let out = LightingNode::Rim.evaluate(
+            &[
+                Value::Vec3(Vec3::Z),
+                Value::Vec3(Vec3::Z),
+                Value::Vec3(Vec3::ONE),
+                Value::Float(3.0),
+                Value::Float(1.0),
+            ],
+            &EvalContext::default(),
+        );
+        assert_eq!(out[0], Value::Vec3(Vec3::ZERO));
+    }
+
+    #[test]
+    fn test_every_lighting_node_validates() {
+        for node in LightingNode::ALL {
+            let mut graph = NodeGraph::new();
+            let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+            let lighting = add_node(&mut graph, NodeKind::Lighting(node));
+            connect(&mut graph, lighting, 0, output, 0);
+
+            let source = compile_and_validate(&graph)
+                .unwrap_or_else(|e| panic!("{:?} failed to compile: {}", node, e));
+            assert!(source.contains("var<uniform> lights: Lights;"));
+        }
+    }
+
+    #[test]
+    fn test_unconnected_pins_read_scene_lights() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let lambert = add_node(&mut graph, NodeKind::Lighting(LightingNode::Lambert));
+        connect(&mut graph, lambert, 0, output, 0);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert!(source.contains(
+            "light_lambert(normalize(input.world_normal), normalize(lights.direction.xyz), lights.color.rgb)"
+        ));
+
+        // The default context lights the fragment head-on with white
+        let color = GraphEvaluator::new(&graph)
+            .evaluate_fragment()
+            .expect("graph should evaluate");
+        assert_eq!(color.truncate(), Vec3::ONE);
+    }
+
+    #[test]
+    fn test_preview_binds_lights_after_samplers() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let toon = add_node(&mut graph, NodeKind::Lighting(LightingNode::ToonRamp));
+        connect(&mut graph, toon, 0, output, 0);
+
+        let source = compile_preview(&graph)
+            .expect("graph should compile")
+            .source;
+        assert!(source.contains(&format!(
+            "@group(#{{MATERIAL_BIND_GROUP}}) @binding({})\nvar<uniform> lights: Lights;",
+            PREVIEW_LIGHTS_BINDING
+        )));
+    }
+}
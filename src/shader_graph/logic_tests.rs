@@ -0,0 +1,243 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::{NodeGraph, PinId};
+    use crate::node_graph::node_factory::NodeFactory;
+    use crate::node_graph::test_utils::{add_node, connect};
+    use crate::shader_graph::compiler::compile_and_validate;
+    use crate::shader_graph::evaluator::GraphEvaluator;
+    use crate::shader_graph::inputs::EvalContext;
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget, ShaderNode};
+    use crate::shader_graph::nodes::logic::{
+        BOOLEAN_TYPES, BooleanOp, CompareOp, LogicNode, VALUE_TYPES,
+    };
+    use crate::shader_graph::types::{PinType, Value};
+    use bevy::prelude::*;
+
+    /// Every logic node, in every type it supports
+    fn all_logic_nodes() -> Vec<LogicNode> {
+        let mut nodes = Vec::new();
+        for ty in VALUE_TYPES {
+            nodes.extend(CompareOp::ALL.map(|op| LogicNode::Compare(op, ty)));
+            nodes.push(LogicNode::Select(ty));
+            nodes.push(LogicNode::Switch { cases: 3, ty });
+        }
+        for ty in BOOLEAN_TYPES {
+            nodes.extend(BooleanOp::ALL.map(|op| LogicNode::Boolean(op, ty)));
+        }
+        nodes
+    }
+
+    fn evaluate(node: LogicNode, inputs: &[Value]) -> Value {
+        node.evaluate(inputs, &EvalContext::default())[0]
+    }
+
+    #[test]
+    fn test_every_logic_node_validates() {
+        for node in all_logic_nodes() {
+            let mut graph = NodeGraph::new();
+            let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+            let logic = add_node(&mut graph, NodeKind::Logic(node));
+            connect(&mut graph, logic, 0, output, 0);
+            compile_and_validate(&graph)
+                .unwrap_or_else(|e| panic!("{:?} failed to compile: {}", node, e));
+        }
+    }
+
+    #[test]
+    fn test_comparisons_follow_wgsl_types() {
+        let scalar = LogicNode::Compare(CompareOp::LessEqual, PinType::Float).signature();
+        assert_eq!(scalar.outputs[0].ty, PinType::Bool);
+        assert_eq!(scalar.outputs[0].label, "<=");
+
+        let vector = LogicNode::Compare(CompareOp::Greater, PinType::Vec3);
+        assert_eq!(vector.signature().outputs[0].ty, PinType::BVec3);
+        assert_eq!(
+            vector.emit_wgsl(&["a".to_string(), "b".to_string()]),
+            ["(a > b)"]
+        );
+        assert_eq!(
+            evaluate(
+                vector,
+                &[
+                    Value::Vec3(Vec3::new(1.0, 2.0, 3.0)),
+                    Value::Vec3(Vec3::splat(2.0))
+                ]
+            ),
+            Value::BVec3(BVec3::new(false, false, true))
+        );
+    }
+
+    #[test]
+    fn test_boolean_ops_on_scalars_and_masks() {
+        let inputs = ["a".to_string(), "b".to_string()];
+        assert_eq!(
+            LogicNode::Boolean(BooleanOp::And, PinType::Bool).emit_wgsl(&inputs),
+            ["(a && b)"]
+        );
+        assert_eq!(
+            LogicNode::Boolean(BooleanOp::Or, PinType::BVec2).emit_wgsl(&inputs),
+            ["(a | b)"]
+        );
+        assert_eq!(
+            LogicNode::Boolean(BooleanOp::Not, PinType::Bool)
+                .signature()
+                .inputs
+                .len(),
+            1
+        );
+
+        let mask = Value::BVec3(BVec3::new(true, false, true));
+        assert_eq!(
+            evaluate(LogicNode::Boolean(BooleanOp::Not, PinType::BVec3), &[mask]),
+            Value::BVec3(BVec3::new(false, true, false))
+        );
+        assert_eq!(
+            evaluate(LogicNode::Boolean(BooleanOp::Any, PinType::BVec3), &[mask]),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            evaluate(LogicNode::Boolean(BooleanOp::All, PinType::BVec3), &[mask]),
+            Value::Bool(false)
+        );
+        assert_eq!(
+            LogicNode::Boolean(BooleanOp::All, PinType::BVec4)
+                .signature()
+                .outputs[0]
+                .ty,
+            PinType::Bool
+        );
+    }
+
+    #[test]
+    fn test_select_chooses_per_component() {
+        let select = LogicNode::Select(PinType::Vec2);
+        assert_eq!(select.signature().inputs[2].ty, PinType::BVec2);
+        assert_eq!(
+            select.emit_wgsl(&["f".to_string(), "t".to_string(), "c".to_string()]),
+            ["select(f, t, c)"]
+        );
+        assert_eq!(
+            evaluate(
+                select,
+                &[
+                    Value::Vec2(Vec2::ZERO),
+                    Value::Vec2(Vec2::ONE),
+                    Value::BVec2(BVec2::new(false, true))
+                ]
+            ),
+            Value::Vec2(Vec2::new(0.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn test_switch_clamps_the_index() {
+        let switch = LogicNode::Switch {
+            cases: 3,
+            ty: PinType::Float,
+        };
+        let cases = [Value::Float(10.0), Value::Float(20.0), Value::Float(30.0)];
+        for (index, expected) in [(-1.0, 10.0), (1.7, 20.0), (2.0, 30.0), (9.0, 30.0)] {
+            let mut inputs = vec![Value::Float(index)];
+            inputs.extend(cases);
+            assert_eq!(evaluate(switch, &inputs), Value::Float(expected));
+        }
+
+        let helper = &switch.wgsl_helpers()[0];
+        assert_eq!(helper.name, "switch_f32_3");
+        assert!(helper.source.contains("switch min(u32(index), 2u) {"));
+    }
+
+    #[test]
+    fn test_graph_branches_on_a_comparison() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let compare = add_node(
+            &mut graph,
+            NodeKind::Logic(LogicNode::Compare(CompareOp::Less, PinType::Float)),
+        );
+        let select = add_node(
+            &mut graph,
+            NodeKind::Logic(LogicNode::Select(PinType::Vec3)),
+        );
+        let value = add_node(&mut graph, NodeKind::Constant(0.5));
+        connect(&mut graph, value, 0, compare, 1);
+        // A scalar condition splats across the mask
+        connect(&mut graph, compare, 0, select, 2);
+        connect(&mut graph, select, 0, output, 0);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert!(source.contains(&format!(
+            "let node{}_out0: bool = (0.0 < node{}_out0);",
+            compare.0, value.0
+        )));
+        assert!(source.contains(&format!(
+            "(vec3<f32>(select(0.0, 1.0, node{}_out0)) != vec3<f32>(0.0, 0.0, 0.0))",
+            compare.0
+        )));
+
+        let color = GraphEvaluator::new(&graph)
+            .evaluate_fragment()
+            .expect("graph should evaluate");
+        assert_eq!(color, Vec4::ONE);
+    }
+
+    #[test]
+    fn test_sync_pins_follows_the_case_count() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let switch = add_node(
+            &mut graph,
+            NodeKind::Logic(LogicNode::Switch {
+                cases: 3,
+                ty: PinType::Vec3,
+            }),
+        );
+        let value = add_node(&mut graph, NodeKind::Constant(1.0));
+        connect(&mut graph, value, 0, switch, 3);
+        connect(&mut graph, switch, 0, output, 0);
+        let kept: Vec<PinId> = graph.nodes[&switch].inputs[..3]
+            .iter()
+            .map(|pin| pin.pin_id)
+            .collect();
+
+        graph.nodes.get_mut(&switch).unwrap().kind = NodeKind::Logic(LogicNode::Switch {
+            cases: 2,
+            ty: PinType::Vec3,
+        });
+        NodeFactory::sync_pins(&mut graph, switch);
+        let node = &graph.nodes[&switch];
+        assert_eq!(node.inputs.len(), 3);
+        assert_eq!(
+            node.inputs.iter().map(|pin| pin.pin_id).collect::<Vec<_>>(),
+            kept
+        );
+        // The connection into the dropped case is gone, the output's stays
+        assert_eq!(graph.connections.len(), 1);
+
+        graph.nodes.get_mut(&switch).unwrap().kind = NodeKind::Logic(LogicNode::Switch {
+            cases: 4,
+            ty: PinType::Vec3,
+        });
+        let next_pin_id = NodeFactory::get_next_pin_id(&graph);
+        NodeFactory::sync_pins(&mut graph, switch);
+        let node = &graph.nodes[&switch];
+        assert_eq!(node.inputs[4].label, "Case 3");
+        assert_eq!(node.inputs[3].pin_id, PinId(next_pin_id));
+        assert_eq!(node.title, format!("Switch {}", switch.0));
+    }
+
+    #[test]
+    fn test_sync_pins_relabels_on_operator_change() {
+        let mut graph = NodeGraph::new();
+        let compare = add_node(
+            &mut graph,
+            NodeKind::Logic(LogicNode::Compare(CompareOp::Equal, PinType::Float)),
+        );
+        graph.nodes.get_mut(&compare).unwrap().kind =
+            NodeKind::Logic(LogicNode::Compare(CompareOp::NotEqual, PinType::Float));
+        NodeFactory::sync_pins(&mut graph, compare);
+        let node = &graph.nodes[&compare];
+        assert_eq!(node.outputs[0].label, "!=");
+        assert_eq!(node.title, format!("Not Equal {}", compare.0));
+    }
+}
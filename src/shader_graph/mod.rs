@@ -0,0 +1,51 @@
+pub mod catalog;
+pub mod compiler;
+pub mod evaluator;
+pub mod inputs;
+pub mod node_kind;
+pub mod nodes;
+pub mod scaffold;
+pub mod types;
+
+#[cfg(test)]
+mod catalog_tests;
+#[cfg(test)]
+mod color_tests;
+#[cfg(test)]
+mod compiler_tests;
+#[cfg(test)]
+mod compute_tests;
+#[cfg(test)]
+mod custom_tests;
+#[cfg(test)]
+mod evaluator_tests;
+#[cfg(test)]
+mod geometry_tests;
+#[cfg(test)]
+mod gradient_tests;
+#[cfg(test)]
+mod library_tests;
+#[cfg(test)]
+mod lighting_tests;
+#[cfg(test)]
+mod logic_tests;
+#[cfg(test)]
+mod normal_tests;
+#[cfg(test)]
+mod pbr_tests;
+#[cfg(test)]
+mod preview_tests;
+#[cfg(test)]
+mod subgraph_tests;
+#[cfg(test)]
+mod targets_tests;
+#[cfg(test)]
+mod texture_tests;
+#[cfg(test)]
+mod time_tests;
+#[cfg(test)]
+mod types_tests;
+#[cfg(test)]
+mod uv_tests;
+#[cfg(test)]
+mod vertex_tests;
@@ -0,0 +1,579 @@
+use crate::node_graph::node_factory::MathOperation;
+use crate::shader_graph::inputs::{CUSTOM_INTERPOLANTS, EvalContext, ShaderInput};
+use crate::shader_graph::nodes::color::{BlendMode, ColorNode};
+use crate::shader_graph::nodes::compute::{
+    ComputeNode, StorageAccess, StorageResource, WorkgroupSize,
+};
+use crate::shader_graph::nodes::custom::CustomFunction;
+use crate::shader_graph::nodes::geometry::GeometryNode;
+use crate::shader_graph::nodes::gradient::ColorRamp;
+use crate::shader_graph::nodes::library::LibraryFunction;
+use crate::shader_graph::nodes::lighting::LightingNode;
+use crate::shader_graph::nodes::logic::LogicNode;
+use crate::shader_graph::nodes::normal::NormalNode;
+use crate::shader_graph::nodes::subgraph::{Subgraph, SubgraphPort, argument_name};
+use crate::shader_graph::nodes::texture::{SamplerState, TextureNode};
+use crate::shader_graph::nodes::time::TimeNode;
+use crate::shader_graph::nodes::uv::UvNode;
+use crate::shader_graph::types::{PinType, Value};
+use serde::{Deserialize, Serialize};
+
+/// Static description of one pin on a node type
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinSpec {
+    pub label: String,
+    pub ty: PinType,
+    /// Value used when an input pin is left unconnected
+    pub default: Value,
+    /// Fragment data used instead of `default` when unconnected (e.g. UV pins read mesh UVs)
+    pub implicit: Option<ShaderInput>,
+}
+
+impl PinSpec {
+    pub fn input(label: &str, ty: PinType, default: Value) -> Self {
+        Self {
+            label: label.to_string(),
+            ty,
+            default: default.convert(ty),
+            implicit: None,
+        }
+    }
+
+    /// Input that reads `source` unless something is connected to it
+    pub fn input_from(label: &str, source: ShaderInput) -> Self {
+        let ty = source.pin_type();
+        Self {
+            label: label.to_string(),
+            ty,
+            default: Value::zero(ty),
+            implicit: Some(source),
+        }
+    }
+
+    /// Read the implicit source as another type, e.g. one component of a vector
+    pub fn typed(mut self, ty: PinType) -> Self {
+        self.ty = ty;
+        self.default = Value::zero(ty);
+        self
+    }
+
+    pub fn output(label: &str, ty: PinType) -> Self {
+        Self {
+            label: label.to_string(),
+            ty,
+            default: Value::zero(ty),
+            implicit: None,
+        }
+    }
+
+    /// WGSL for an unconnected input
+    pub fn default_wgsl(&self) -> String {
+        match self.implicit {
+            Some(source) => source.pin_type().convert_expr(&source.wgsl_expr(), self.ty),
+            None => self.default.to_wgsl(),
+        }
+    }
+
+    /// Value of an unconnected input for the fragment described by `ctx`
+    pub fn default_value(&self, ctx: &EvalContext) -> Value {
+        match self.implicit {
+            Some(source) => source.evaluate(ctx).convert(self.ty),
+            None => self.default,
+        }
+    }
+}
+
+/// Input and output pins of a node type, in pin order
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NodeSignature {
+    pub inputs: Vec<PinSpec>,
+    pub outputs: Vec<PinSpec>,
+}
+
+/// A named WGSL function a node's emitted expression depends on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WgslHelper {
+    pub name: String,
+    pub source: String,
+}
+
+impl WgslHelper {
+    pub fn new(name: &str, source: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            source: source.to_string(),
+        }
+    }
+}
+
+/// Pipeline stage a node's WGSL runs in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+    Compute,
+}
+
+impl ShaderStage {
+    pub const ALL: [ShaderStage; 3] = [
+        ShaderStage::Vertex,
+        ShaderStage::Fragment,
+        ShaderStage::Compute,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ShaderStage::Vertex => "vertex",
+            ShaderStage::Fragment => "fragment",
+            ShaderStage::Compute => "compute",
+        }
+    }
+}
+
+/// Behaviour shared by every node type: pin layout, WGSL emission and CPU evaluation
+pub trait ShaderNode {
+    fn title(&self) -> String;
+
+    fn signature(&self) -> NodeSignature;
+
+    /// One WGSL expression per output, given one expression per input
+    /// (already converted to the input's declared type)
+    fn emit_wgsl(&self, inputs: &[String]) -> Vec<String>;
+
+    /// Helper functions the emitted expressions call, dependencies first
+    fn wgsl_helpers(&self) -> Vec<WgslHelper> {
+        Vec::new()
+    }
+
+    /// Fragment data the emitted expressions read (besides unconnected implicit inputs)
+    fn shader_inputs(&self) -> Vec<ShaderInput> {
+        Vec::new()
+    }
+
+    /// Sampler bindings the emitted expressions read, by `SamplerState::wgsl_name`
+    fn samplers(&self) -> Vec<SamplerState> {
+        Vec::new()
+    }
+
+    /// Storage buffers and textures the emitted WGSL reads or writes
+    fn storage(&self) -> Vec<(StorageResource, StorageAccess)> {
+        Vec::new()
+    }
+
+    /// Stages the emitted WGSL is valid in. Derivatives and `textureSample`'s implicit LOD only
+    /// exist in the fragment stage.
+    fn stages(&self) -> &'static [ShaderStage] {
+        &ShaderStage::ALL
+    }
+
+    /// Image bound to this node's texture outputs, `None` for plain white.
+    /// Texture outputs become bindings named after the output; their expression is unused.
+    fn texture_image(&self) -> Option<String> {
+        None
+    }
+
+    /// One value per output, given one value per input
+    /// (already converted to the input's declared type) and the fragment being evaluated
+    fn evaluate(&self, inputs: &[Value], ctx: &EvalContext) -> Vec<Value>;
+}
+
+/// Kind of shader a `Fragment Output` graph compiles into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum OutputTarget {
+    /// Unlit `Material` on a 3D mesh
+    #[default]
+    Mesh,
+    /// `Material2d` on a 2D mesh or sprite quad
+    Sprite,
+    /// Post-process pass drawn over the whole screen
+    Fullscreen,
+    /// `UiMaterial` filling a UI node
+    Ui,
+}
+
+impl OutputTarget {
+    pub const ALL: [OutputTarget; 4] = [
+        OutputTarget::Mesh,
+        OutputTarget::Sprite,
+        OutputTarget::Fullscreen,
+        OutputTarget::Ui,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OutputTarget::Mesh => "Unlit Mesh",
+            OutputTarget::Sprite => "Sprite (2D)",
+            OutputTarget::Fullscreen => "Fullscreen",
+            OutputTarget::Ui => "UI",
+        }
+    }
+}
+
+/// What a node does - decides its pins, its WGSL and its CPU behaviour
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NodeKind {
+    /// Placeholder node spawned with `N` - passes `A` through
+    Test,
+    Constant(f32),
+    Math(MathOperation),
+    Color(ColorNode),
+    Blend(BlendMode),
+    ColorRamp(ColorRamp),
+    Uv(UvNode),
+    Geometry(GeometryNode),
+    Time(TimeNode),
+    Texture(TextureNode),
+    Normal(NormalNode),
+    Lighting(LightingNode),
+    Compute(ComputeNode),
+    Logic(LogicNode),
+    /// User-written WGSL function, pins read off its signature
+    Custom(CustomFunction),
+    /// Function imported from a `.wgsl` file in the library directory
+    Library(LibraryFunction),
+    /// A graph collapsed into one node, compiled into one function
+    Subgraph(Subgraph),
+    /// Inside a subgraph: one output per input of the subgraph node
+    SubgraphInputs(Vec<SubgraphPort>),
+    /// Inside a subgraph: one input per output of the subgraph node
+    SubgraphOutputs(Vec<SubgraphPort>),
+    /// Final fragment colour of the chosen target - every compiled graph has exactly one
+    /// output node
+    FragmentOutput(OutputTarget),
+    /// Surface inputs for Bevy's PBR lighting - the alternative output node
+    PbrOutput,
+    /// Displacement, normal and custom interpolants of the vertex stage. Optional, at most one;
+    /// the nodes feeding it are compiled into `vs_main`.
+    VertexOutput,
+    /// Marks the graph as a compute kernel with its workgroup size, and exposes
+    /// `global_invocation_id`. The kernel runs the graph's storage writes.
+    ComputeOutput(WorkgroupSize),
+}
+
+impl NodeKind {
+    /// Whether the node terminates the graph (`Fragment Output` or `PBR Output`)
+    pub fn is_output(&self) -> bool {
+        matches!(self, NodeKind::FragmentOutput(_) | NodeKind::PbrOutput)
+    }
+}
+
+impl ShaderNode for NodeKind {
+    fn title(&self) -> String {
+        match self {
+            NodeKind::Test => "TestNode".to_string(),
+            NodeKind::Constant(_) => "Const".to_string(),
+            NodeKind::Math(op) => match op {
+                MathOperation::Add => "Add",
+                MathOperation::Subtract => "Subtract",
+                MathOperation::Multiply => "Multiply",
+                MathOperation::Divide => "Divide",
+            }
+            .to_string(),
+            NodeKind::Color(node) => node.title(),
+            NodeKind::Blend(mode) => mode.title(),
+            NodeKind::ColorRamp(ramp) => ramp.title(),
+            NodeKind::Uv(node) => node.title(),
+            NodeKind::Geometry(node) => node.title(),
+            NodeKind::Time(node) => node.title(),
+            NodeKind::Texture(node) => node.title(),
+            NodeKind::Normal(node) => node.title(),
+            NodeKind::Lighting(node) => node.title(),
+            NodeKind::Compute(node) => node.title(),
+            NodeKind::Logic(node) => node.title(),
+            NodeKind::Custom(node) => node.title(),
+            NodeKind::Library(node) => node.title(),
+            NodeKind::Subgraph(node) => node.title(),
+            NodeKind::SubgraphInputs(_) => "Subgraph Inputs".to_string(),
+            NodeKind::SubgraphOutputs(_) => "Subgraph Outputs".to_string(),
+            NodeKind::FragmentOutput(_) => "Fragment Output".to_string(),
+            NodeKind::PbrOutput => "PBR Output".to_string(),
+            NodeKind::VertexOutput => "Vertex Output".to_string(),
+            NodeKind::ComputeOutput(_) => "Compute Output".to_string(),
+        }
+    }
+
+    fn signature(&self) -> NodeSignature {
+        match self {
+            NodeKind::Test => NodeSignature {
+                inputs: vec![
+                    PinSpec::input("A", PinType::Float, Value::Float(0.0)),
+                    PinSpec::input("B", PinType::Float, Value::Float(0.0)),
+                ],
+                outputs: vec![PinSpec::output("Out", PinType::Float)],
+            },
+            NodeKind::Constant(value) => NodeSignature {
+                inputs: vec![],
+                outputs: vec![PinSpec::output(&format!("{:.2}", value), PinType::Float)],
+            },
+            NodeKind::Math(op) => {
+                let label = match op {
+                    MathOperation::Add => "Add",
+                    MathOperation::Subtract => "Sub",
+                    MathOperation::Multiply => "Mul",
+                    MathOperation::Divide => "Div",
+                };
+                NodeSignature {
+                    inputs: vec![
+                        PinSpec::input("A", PinType::Float, Value::Float(0.0)),
+                        PinSpec::input("B", PinType::Float, Value::Float(0.0)),
+                    ],
+                    outputs: vec![PinSpec::output(label, PinType::Float)],
+                }
+            }
+            NodeKind::Color(node) => node.signature(),
+            NodeKind::Blend(mode) => mode.signature(),
+            NodeKind::ColorRamp(ramp) => ramp.signature(),
+            NodeKind::Uv(node) => node.signature(),
+            NodeKind::Geometry(node) => node.signature(),
+            NodeKind::Time(node) => node.signature(),
+            NodeKind::Texture(node) => node.signature(),
+            NodeKind::Normal(node) => node.signature(),
+            NodeKind::Lighting(node) => node.signature(),
+            NodeKind::Compute(node) => node.signature(),
+            NodeKind::Logic(node) => node.signature(),
+            NodeKind::Custom(node) => node.signature(),
+            NodeKind::Library(node) => node.signature(),
+            NodeKind::Subgraph(node) => node.signature(),
+            NodeKind::SubgraphInputs(ports) => NodeSignature {
+                inputs: vec![],
+                outputs: ports
+                    .iter()
+                    .map(|port| PinSpec::output(&port.name, port.ty))
+                    .collect(),
+            },
+            NodeKind::SubgraphOutputs(ports) => NodeSignature {
+                inputs: ports
+                    .iter()
+                    .map(|port| PinSpec::input(&port.name, port.ty, Value::Float(0.0)))
+                    .collect(),
+                outputs: vec![],
+            },
+            NodeKind::FragmentOutput(_) => NodeSignature {
+                inputs: vec![
+                    PinSpec::input(
+                        "Color",
+                        PinType::Vec3,
+                        Value::Vec3(bevy::prelude::Vec3::new(0.2, 0.6, 1.0)),
+                    ),
+                    PinSpec::input("Alpha", PinType::Float, Value::Float(1.0)),
+                ],
+                outputs: vec![],
+            },
+            NodeKind::PbrOutput => NodeSignature {
+                inputs: vec![
+                    PinSpec::input(
+                        "Base Color",
+                        PinType::Vec3,
+                        Value::Vec3(bevy::prelude::Vec3::new(0.4, 0.7, 1.0)),
+                    ),
+                    PinSpec::input("Metallic", PinType::Float, Value::Float(0.0)),
+                    PinSpec::input("Roughness", PinType::Float, Value::Float(0.5)),
+                    PinSpec::input_from("Normal", ShaderInput::WorldNormal),
+                    PinSpec::input(
+                        "Emissive",
+                        PinType::Vec3,
+                        Value::Vec3(bevy::prelude::Vec3::ZERO),
+                    ),
+                    PinSpec::input("Occlusion", PinType::Float, Value::Float(1.0)),
+                    PinSpec::input("Alpha", PinType::Float, Value::Float(1.0)),
+                ],
+                outputs: vec![],
+            },
+            NodeKind::VertexOutput => {
+                let mut inputs = vec![
+                    PinSpec::input(
+                        "Position Offset",
+                        PinType::Vec3,
+                        Value::Vec3(bevy::prelude::Vec3::ZERO),
+                    ),
+                    PinSpec::input_from("Normal", ShaderInput::ObjectNormal),
+                ];
+                for index in 0..CUSTOM_INTERPOLANTS {
+                    inputs.push(PinSpec::input(
+                        &format!("Custom {}", index),
+                        PinType::Vec4,
+                        Value::Vec4(bevy::prelude::Vec4::ZERO),
+                    ));
+                }
+                NodeSignature {
+                    inputs,
+                    outputs: vec![],
+                }
+            }
+            NodeKind::ComputeOutput(_) => NodeSignature {
+                inputs: vec![],
+                outputs: vec![
+                    PinSpec::output("ID", PinType::Vec3),
+                    PinSpec::output("Index", PinType::Float),
+                ],
+            },
+        }
+    }
+
+    fn emit_wgsl(&self, inputs: &[String]) -> Vec<String> {
+        match self {
+            NodeKind::Test => vec![inputs[0].clone()],
+            NodeKind::Constant(value) => vec![Value::Float(*value).to_wgsl()],
+            NodeKind::Math(op) => {
+                let symbol = match op {
+                    MathOperation::Add => "+",
+                    MathOperation::Subtract => "-",
+                    MathOperation::Multiply => "*",
+                    MathOperation::Divide => "/",
+                };
+                vec![format!("({} {} {})", inputs[0], symbol, inputs[1])]
+            }
+            NodeKind::Color(node) => node.emit_wgsl(inputs),
+            NodeKind::Blend(mode) => mode.emit_wgsl(inputs),
+            NodeKind::ColorRamp(ramp) => ramp.emit_wgsl(inputs),
+            NodeKind::Uv(node) => node.emit_wgsl(inputs),
+            NodeKind::Geometry(node) => node.emit_wgsl(inputs),
+            NodeKind::Time(node) => node.emit_wgsl(inputs),
+            NodeKind::Texture(node) => node.emit_wgsl(inputs),
+            NodeKind::Normal(node) => node.emit_wgsl(inputs),
+            NodeKind::Lighting(node) => node.emit_wgsl(inputs),
+            NodeKind::Compute(node) => node.emit_wgsl(inputs),
+            NodeKind::Logic(node) => node.emit_wgsl(inputs),
+            NodeKind::Custom(node) => node.emit_wgsl(inputs),
+            NodeKind::Library(node) => node.emit_wgsl(inputs),
+            NodeKind::Subgraph(node) => node.emit_wgsl(inputs),
+            // The subgraph function's arguments
+            NodeKind::SubgraphInputs(ports) => (0..ports.len()).map(argument_name).collect(),
+            NodeKind::SubgraphOutputs(_) => inputs.to_vec(),
+            NodeKind::FragmentOutput(_) => vec![format!("vec4<f32>({}, {})", inputs[0], inputs[1])],
+            // RGBA first, as for Fragment Output, then the remaining surface inputs in pin order
+            NodeKind::PbrOutput => {
+                let mut surface = vec![format!("vec4<f32>({}, {})", inputs[0], inputs[6])];
+                surface.extend_from_slice(&inputs[1..6]);
+                surface
+            }
+            // Object-space offset, object-space normal, then the custom interpolants
+            NodeKind::VertexOutput => inputs.to_vec(),
+            NodeKind::ComputeOutput(_) => vec![
+                ShaderInput::InvocationId.wgsl_expr(),
+                "f32(global_id.x)".to_string(),
+            ],
+        }
+    }
+
+    fn wgsl_helpers(&self) -> Vec<WgslHelper> {
+        match self {
+            NodeKind::Color(node) => node.wgsl_helpers(),
+            NodeKind::Blend(mode) => mode.wgsl_helpers(),
+            NodeKind::ColorRamp(ramp) => ramp.wgsl_helpers(),
+            NodeKind::Uv(node) => node.wgsl_helpers(),
+            NodeKind::Geometry(node) => node.wgsl_helpers(),
+            NodeKind::Time(node) => node.wgsl_helpers(),
+            NodeKind::Texture(node) => node.wgsl_helpers(),
+            NodeKind::Normal(node) => node.wgsl_helpers(),
+            NodeKind::Lighting(node) => node.wgsl_helpers(),
+            NodeKind::Logic(node) => node.wgsl_helpers(),
+            NodeKind::Custom(node) => node.wgsl_helpers(),
+            NodeKind::Library(node) => node.wgsl_helpers(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn shader_inputs(&self) -> Vec<ShaderInput> {
+        match self {
+            NodeKind::Uv(node) => node.shader_inputs(),
+            NodeKind::Geometry(node) => node.shader_inputs(),
+            NodeKind::Time(node) => node.shader_inputs(),
+            NodeKind::Texture(node) => node.shader_inputs(),
+            NodeKind::Normal(node) => node.shader_inputs(),
+            NodeKind::Lighting(node) => node.shader_inputs(),
+            NodeKind::ComputeOutput(_) => vec![ShaderInput::InvocationId],
+            _ => Vec::new(),
+        }
+    }
+
+    fn samplers(&self) -> Vec<SamplerState> {
+        match self {
+            NodeKind::Texture(node) => node.samplers(),
+            NodeKind::Normal(node) => node.samplers(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn storage(&self) -> Vec<(StorageResource, StorageAccess)> {
+        match self {
+            NodeKind::Compute(node) => node.storage(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn stages(&self) -> &'static [ShaderStage] {
+        match self {
+            NodeKind::Uv(node) => node.stages(),
+            NodeKind::Geometry(node) => node.stages(),
+            NodeKind::Texture(node) => node.stages(),
+            NodeKind::Normal(node) => node.stages(),
+            NodeKind::FragmentOutput(_) | NodeKind::PbrOutput => &[ShaderStage::Fragment],
+            NodeKind::VertexOutput => &[ShaderStage::Vertex],
+            NodeKind::Compute(node) => node.stages(),
+            NodeKind::ComputeOutput(_) => &[ShaderStage::Compute],
+            NodeKind::Custom(node) => node.stages(),
+            NodeKind::Library(node) => node.stages(),
+            _ => &ShaderStage::ALL,
+        }
+    }
+
+    fn texture_image(&self) -> Option<String> {
+        match self {
+            NodeKind::Texture(node) => node.texture_image(),
+            _ => None,
+        }
+    }
+
+    fn evaluate(&self, inputs: &[Value], ctx: &EvalContext) -> Vec<Value> {
+        match self {
+            NodeKind::Test => vec![inputs[0]],
+            NodeKind::Constant(value) => vec![Value::Float(*value)],
+            NodeKind::Math(op) => {
+                let (a, b) = (inputs[0].as_f32(), inputs[1].as_f32());
+                let result = match op {
+                    MathOperation::Add => a + b,
+                    MathOperation::Subtract => a - b,
+                    MathOperation::Multiply => a * b,
+                    MathOperation::Divide => a / b,
+                };
+                vec![Value::Float(result)]
+            }
+            NodeKind::Color(node) => node.evaluate(inputs, ctx),
+            NodeKind::Blend(mode) => mode.evaluate(inputs, ctx),
+            NodeKind::ColorRamp(ramp) => ramp.evaluate(inputs, ctx),
+            NodeKind::Uv(node) => node.evaluate(inputs, ctx),
+            NodeKind::Geometry(node) => node.evaluate(inputs, ctx),
+            NodeKind::Time(node) => node.evaluate(inputs, ctx),
+            NodeKind::Texture(node) => node.evaluate(inputs, ctx),
+            NodeKind::Normal(node) => node.evaluate(inputs, ctx),
+            NodeKind::Lighting(node) => node.evaluate(inputs, ctx),
+            NodeKind::Compute(node) => node.evaluate(inputs, ctx),
+            NodeKind::Logic(node) => node.evaluate(inputs, ctx),
+            NodeKind::Custom(node) => node.evaluate(inputs, ctx),
+            NodeKind::Library(node) => node.evaluate(inputs, ctx),
+            NodeKind::Subgraph(node) => node.evaluate(inputs, ctx),
+            // `evaluate_subgraph` supplies the values; on their own the inputs are zero
+            NodeKind::SubgraphInputs(ports) => {
+                ports.iter().map(|port| Value::zero(port.ty)).collect()
+            }
+            NodeKind::SubgraphOutputs(_) => inputs.to_vec(),
+            NodeKind::FragmentOutput(_) => {
+                let color = inputs[0].as_vec3();
+                vec![Value::Vec4(color.extend(inputs[1].as_f32()))]
+            }
+            // The CPU has no lighting model, so a PBR surface evaluates to its base colour
+            NodeKind::PbrOutput => {
+                let color = inputs[0].as_vec3();
+                let mut surface = vec![Value::Vec4(color.extend(inputs[6].as_f32()))];
+                surface.extend_from_slice(&inputs[1..6]);
+                surface
+            }
+            NodeKind::VertexOutput => inputs.to_vec(),
+            NodeKind::ComputeOutput(_) => {
+                let id = ctx.invocation_id;
+                vec![Value::Vec3(id.as_vec3()), Value::Float(id.x as f32)]
+            }
+        }
+    }
+}
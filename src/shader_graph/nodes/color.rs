@@ -0,0 +1,379 @@
+use crate::shader_graph::inputs::EvalContext;
+use crate::shader_graph::node_kind::{NodeSignature, PinSpec, ShaderNode, WgslHelper};
+use crate::shader_graph::types::{PinType, Value};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Rec.709 luma coefficients
+pub const LUMINANCE_WEIGHTS: Vec3 = Vec3::new(0.2126, 0.7152, 0.0722);
+
+/// Colour-space conversions and grading operations WGSL does not provide
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ColorNode {
+    RgbToHsv,
+    HsvToRgb,
+    RgbToHsl,
+    HslToRgb,
+    SrgbToLinear,
+    LinearToSrgb,
+    Luminance,
+    Contrast,
+    Saturation,
+    HueShift,
+}
+
+/// Photoshop-style layer blend modes (`Base` is the lower layer)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BlendMode {
+    Multiply,
+    Screen,
+    Overlay,
+    SoftLight,
+    Add,
+    Difference,
+}
+
+const RGB_TO_HSV_WGSL: &str = "fn rgb_to_hsv(c: vec3<f32>) -> vec3<f32> {
+    let max_c = max(c.r, max(c.g, c.b));
+    let min_c = min(c.r, min(c.g, c.b));
+    let delta = max_c - min_c;
+    var h = 0.0;
+    if (delta > 0.0) {
+        if (max_c == c.r) {
+            h = (c.g - c.b) / delta;
+        } else if (max_c == c.g) {
+            h = 2.0 + (c.b - c.r) / delta;
+        } else {
+            h = 4.0 + (c.r - c.g) / delta;
+        }
+        h = fract(h / 6.0);
+    }
+    let s = select(0.0, delta / max_c, max_c > 0.0);
+    return vec3<f32>(h, s, max_c);
+}";
+
+const HSV_TO_RGB_WGSL: &str = "fn hsv_to_rgb(c: vec3<f32>) -> vec3<f32> {
+    let k = clamp(abs(fract(c.x + vec3<f32>(1.0, 2.0 / 3.0, 1.0 / 3.0)) * 6.0 - 3.0) - 1.0, vec3<f32>(0.0), vec3<f32>(1.0));
+    return c.z * mix(vec3<f32>(1.0), k, c.y);
+}";
+
+const RGB_TO_HSL_WGSL: &str = "fn rgb_to_hsl(c: vec3<f32>) -> vec3<f32> {
+    let hsv = rgb_to_hsv(c);
+    let l = hsv.z * (1.0 - hsv.y * 0.5);
+    let s = select(0.0, (hsv.z - l) / min(l, 1.0 - l), l > 0.0 && l < 1.0);
+    return vec3<f32>(hsv.x, s, l);
+}";
+
+const HSL_TO_RGB_WGSL: &str = "fn hsl_to_rgb(c: vec3<f32>) -> vec3<f32> {
+    let v = c.z + c.y * min(c.z, 1.0 - c.z);
+    let s = select(0.0, 2.0 * (1.0 - c.z / v), v > 0.0);
+    return hsv_to_rgb(vec3<f32>(c.x, s, v));
+}";
+
+const SRGB_TO_LINEAR_WGSL: &str = "fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    return select(pow((c + 0.055) / 1.055, vec3<f32>(2.4)), c / 12.92, c <= vec3<f32>(0.04045));
+}";
+
+const LINEAR_TO_SRGB_WGSL: &str = "fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+    return select(1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055, c * 12.92, c <= vec3<f32>(0.0031308));
+}";
+
+const HUE_SHIFT_WGSL: &str = "fn hue_shift(c: vec3<f32>, shift: f32) -> vec3<f32> {
+    let hsv = rgb_to_hsv(c);
+    return hsv_to_rgb(vec3<f32>(fract(hsv.x + shift), hsv.y, hsv.z));
+}";
+
+const BLEND_OVERLAY_WGSL: &str = "fn blend_overlay(base: vec3<f32>, blend: vec3<f32>) -> vec3<f32> {
+    return select(1.0 - 2.0 * (1.0 - base) * (1.0 - blend), 2.0 * base * blend, base < vec3<f32>(0.5));
+}";
+
+const BLEND_SOFT_LIGHT_WGSL: &str =
+    "fn blend_soft_light(base: vec3<f32>, blend: vec3<f32>) -> vec3<f32> {
+    let d = select(sqrt(base), ((16.0 * base - 12.0) * base + 4.0) * base, base <= vec3<f32>(0.25));
+    let dark = base - (1.0 - 2.0 * blend) * base * (1.0 - base);
+    let light = base + (2.0 * blend - 1.0) * (d - base);
+    return select(light, dark, blend <= vec3<f32>(0.5));
+}";
+
+impl ShaderNode for ColorNode {
+    fn title(&self) -> String {
+        match self {
+            ColorNode::RgbToHsv => "RGB to HSV",
+            ColorNode::HsvToRgb => "HSV to RGB",
+            ColorNode::RgbToHsl => "RGB to HSL",
+            ColorNode::HslToRgb => "HSL to RGB",
+            ColorNode::SrgbToLinear => "sRGB to Linear",
+            ColorNode::LinearToSrgb => "Linear to sRGB",
+            ColorNode::Luminance => "Luminance",
+            ColorNode::Contrast => "Contrast",
+            ColorNode::Saturation => "Saturation",
+            ColorNode::HueShift => "Hue Shift",
+        }
+        .to_string()
+    }
+
+    fn signature(&self) -> NodeSignature {
+        let color_in = PinSpec::input("Color", PinType::Vec3, Value::Vec3(Vec3::ONE));
+        let amount_in = |label: &str, default: f32| {
+            PinSpec::input(label, PinType::Float, Value::Float(default))
+        };
+
+        match self {
+            ColorNode::RgbToHsv => NodeSignature {
+                inputs: vec![color_in],
+                outputs: vec![PinSpec::output("HSV", PinType::Vec3)],
+            },
+            ColorNode::RgbToHsl => NodeSignature {
+                inputs: vec![color_in],
+                outputs: vec![PinSpec::output("HSL", PinType::Vec3)],
+            },
+            ColorNode::HsvToRgb => NodeSignature {
+                inputs: vec![PinSpec::input("HSV", PinType::Vec3, Value::Vec3(Vec3::Z))],
+                outputs: vec![PinSpec::output("Color", PinType::Vec3)],
+            },
+            ColorNode::HslToRgb => NodeSignature {
+                inputs: vec![PinSpec::input(
+                    "HSL",
+                    PinType::Vec3,
+                    Value::Vec3(Vec3::new(0.0, 0.0, 1.0)),
+                )],
+                outputs: vec![PinSpec::output("Color", PinType::Vec3)],
+            },
+            ColorNode::SrgbToLinear | ColorNode::LinearToSrgb => NodeSignature {
+                inputs: vec![color_in],
+                outputs: vec![PinSpec::output("Color", PinType::Vec3)],
+            },
+            ColorNode::Luminance => NodeSignature {
+                inputs: vec![color_in],
+                outputs: vec![PinSpec::output("Luma", PinType::Float)],
+            },
+            ColorNode::Contrast => NodeSignature {
+                inputs: vec![color_in, amount_in("Contrast", 1.0)],
+                outputs: vec![PinSpec::output("Color", PinType::Vec3)],
+            },
+            ColorNode::Saturation => NodeSignature {
+                inputs: vec![color_in, amount_in("Saturation", 1.0)],
+                outputs: vec![PinSpec::output("Color", PinType::Vec3)],
+            },
+            ColorNode::HueShift => NodeSignature {
+                inputs: vec![color_in, amount_in("Shift", 0.0)],
+                outputs: vec![PinSpec::output("Color", PinType::Vec3)],
+            },
+        }
+    }
+
+    fn emit_wgsl(&self, inputs: &[String]) -> Vec<String> {
+        let c = &inputs[0];
+        let expr = match self {
+            ColorNode::RgbToHsv => format!("rgb_to_hsv({})", c),
+            ColorNode::HsvToRgb => format!("hsv_to_rgb({})", c),
+            ColorNode::RgbToHsl => format!("rgb_to_hsl({})", c),
+            ColorNode::HslToRgb => format!("hsl_to_rgb({})", c),
+            ColorNode::SrgbToLinear => format!("srgb_to_linear({})", c),
+            ColorNode::LinearToSrgb => format!("linear_to_srgb({})", c),
+            ColorNode::Luminance => {
+                format!("dot({}, {})", c, Value::Vec3(LUMINANCE_WEIGHTS).to_wgsl())
+            }
+            ColorNode::Contrast => format!("(({} - 0.5) * {} + 0.5)", c, inputs[1]),
+            ColorNode::Saturation => format!(
+                "mix(vec3<f32>(dot({}, {})), {}, {})",
+                c,
+                Value::Vec3(LUMINANCE_WEIGHTS).to_wgsl(),
+                c,
+                inputs[1]
+            ),
+            ColorNode::HueShift => format!("hue_shift({}, {})", c, inputs[1]),
+        };
+        vec![expr]
+    }
+
+    fn wgsl_helpers(&self) -> Vec<WgslHelper> {
+        let rgb_to_hsv = WgslHelper::new("rgb_to_hsv", RGB_TO_HSV_WGSL);
+        let hsv_to_rgb = WgslHelper::new("hsv_to_rgb", HSV_TO_RGB_WGSL);
+        match self {
+            ColorNode::RgbToHsv => vec![rgb_to_hsv],
+            ColorNode::HsvToRgb => vec![hsv_to_rgb],
+            ColorNode::RgbToHsl => vec![rgb_to_hsv, WgslHelper::new("rgb_to_hsl", RGB_TO_HSL_WGSL)],
+            ColorNode::HslToRgb => vec![hsv_to_rgb, WgslHelper::new("hsl_to_rgb", HSL_TO_RGB_WGSL)],
+            ColorNode::SrgbToLinear => vec![WgslHelper::new("srgb_to_linear", SRGB_TO_LINEAR_WGSL)],
+            ColorNode::LinearToSrgb => vec![WgslHelper::new("linear_to_srgb", LINEAR_TO_SRGB_WGSL)],
+            ColorNode::HueShift => {
+                vec![
+                    rgb_to_hsv,
+                    hsv_to_rgb,
+                    WgslHelper::new("hue_shift", HUE_SHIFT_WGSL),
+                ]
+            }
+            ColorNode::Luminance | ColorNode::Contrast | ColorNode::Saturation => Vec::new(),
+        }
+    }
+
+    fn evaluate(&self, inputs: &[Value], _ctx: &EvalContext) -> Vec<Value> {
+        let c = inputs[0].as_vec3();
+        let result = match self {
+            ColorNode::RgbToHsv => Value::Vec3(rgb_to_hsv(c)),
+            ColorNode::HsvToRgb => Value::Vec3(hsv_to_rgb(c)),
+            ColorNode::RgbToHsl => Value::Vec3(rgb_to_hsl(c)),
+            ColorNode::HslToRgb => Value::Vec3(hsl_to_rgb(c)),
+            ColorNode::SrgbToLinear => Value::Vec3(c.map(srgb_to_linear)),
+            ColorNode::LinearToSrgb => Value::Vec3(c.map(linear_to_srgb)),
+            ColorNode::Luminance => Value::Float(luminance(c)),
+            ColorNode::Contrast => Value::Vec3((c - 0.5) * inputs[1].as_f32() + 0.5),
+            ColorNode::Saturation => {
+                Value::Vec3(Vec3::splat(luminance(c)).lerp(c, inputs[1].as_f32()))
+            }
+            ColorNode::HueShift => {
+                let hsv = rgb_to_hsv(c);
+                let hue = (hsv.x + inputs[1].as_f32()).rem_euclid(1.0);
+                Value::Vec3(hsv_to_rgb(Vec3::new(hue, hsv.y, hsv.z)))
+            }
+        };
+        vec![result]
+    }
+}
+
+impl ShaderNode for BlendMode {
+    fn title(&self) -> String {
+        let mode = match self {
+            BlendMode::Multiply => "Multiply",
+            BlendMode::Screen => "Screen",
+            BlendMode::Overlay => "Overlay",
+            BlendMode::SoftLight => "Soft Light",
+            BlendMode::Add => "Add",
+            BlendMode::Difference => "Difference",
+        };
+        format!("Blend ({})", mode)
+    }
+
+    fn signature(&self) -> NodeSignature {
+        NodeSignature {
+            inputs: vec![
+                PinSpec::input("Base", PinType::Vec3, Value::Vec3(Vec3::splat(0.5))),
+                PinSpec::input("Blend", PinType::Vec3, Value::Vec3(Vec3::splat(0.5))),
+                PinSpec::input("Opacity", PinType::Float, Value::Float(1.0)),
+            ],
+            outputs: vec![PinSpec::output("Color", PinType::Vec3)],
+        }
+    }
+
+    fn emit_wgsl(&self, inputs: &[String]) -> Vec<String> {
+        let (base, blend, opacity) = (&inputs[0], &inputs[1], &inputs[2]);
+        let blended = match self {
+            BlendMode::Multiply => format!("({} * {})", base, blend),
+            BlendMode::Screen => format!("(1.0 - (1.0 - {}) * (1.0 - {}))", base, blend),
+            BlendMode::Overlay => format!("blend_overlay({}, {})", base, blend),
+            BlendMode::SoftLight => format!("blend_soft_light({}, {})", base, blend),
+            BlendMode::Add => format!("min({} + {}, vec3<f32>(1.0))", base, blend),
+            BlendMode::Difference => format!("abs({} - {})", base, blend),
+        };
+        vec![format!("mix({}, {}, {})", base, blended, opacity)]
+    }
+
+    fn wgsl_helpers(&self) -> Vec<WgslHelper> {
+        match self {
+            BlendMode::Overlay => vec![WgslHelper::new("blend_overlay", BLEND_OVERLAY_WGSL)],
+            BlendMode::SoftLight => {
+                vec![WgslHelper::new("blend_soft_light", BLEND_SOFT_LIGHT_WGSL)]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn evaluate(&self, inputs: &[Value], _ctx: &EvalContext) -> Vec<Value> {
+        let (base, blend) = (inputs[0].as_vec3(), inputs[1].as_vec3());
+        let blended = match self {
+            BlendMode::Multiply => base * blend,
+            BlendMode::Screen => 1.0 - (1.0 - base) * (1.0 - blend),
+            BlendMode::Overlay => zip_channels(base, blend, |a, b| {
+                if a < 0.5 {
+                    2.0 * a * b
+                } else {
+                    1.0 - 2.0 * (1.0 - a) * (1.0 - b)
+                }
+            }),
+            BlendMode::SoftLight => zip_channels(base, blend, |a, b| {
+                if b <= 0.5 {
+                    a - (1.0 - 2.0 * b) * a * (1.0 - a)
+                } else {
+                    let d = if a <= 0.25 {
+                        ((16.0 * a - 12.0) * a + 4.0) * a
+                    } else {
+                        a.sqrt()
+                    };
+                    a + (2.0 * b - 1.0) * (d - a)
+                }
+            }),
+            BlendMode::Add => (base + blend).min(Vec3::ONE),
+            BlendMode::Difference => (base - blend).abs(),
+        };
+        vec![Value::Vec3(base.lerp(blended, inputs[2].as_f32()))]
+    }
+}
+
+fn zip_channels(a: Vec3, b: Vec3, f: impl Fn(f32, f32) -> f32) -> Vec3 {
+    Vec3::new(f(a.x, b.x), f(a.y, b.y), f(a.z, b.z))
+}
+
+pub fn luminance(c: Vec3) -> f32 {
+    c.dot(LUMINANCE_WEIGHTS)
+}
+
+/// Hue, saturation and value all in `0..=1`
+pub fn rgb_to_hsv(c: Vec3) -> Vec3 {
+    let max_c = c.max_element();
+    let min_c = c.min_element();
+    let delta = max_c - min_c;
+    let mut h = 0.0;
+    if delta > 0.0 {
+        h = if max_c == c.x {
+            (c.y - c.z) / delta
+        } else if max_c == c.y {
+            2.0 + (c.z - c.x) / delta
+        } else {
+            4.0 + (c.x - c.y) / delta
+        };
+        h = (h / 6.0).rem_euclid(1.0);
+    }
+    let s = if max_c > 0.0 { delta / max_c } else { 0.0 };
+    Vec3::new(h, s, max_c)
+}
+
+pub fn hsv_to_rgb(c: Vec3) -> Vec3 {
+    let k = ((Vec3::splat(c.x) + Vec3::new(1.0, 2.0 / 3.0, 1.0 / 3.0)).fract_gl() * 6.0 - 3.0)
+        .abs()
+        - 1.0;
+    c.z * Vec3::ONE.lerp(k.clamp(Vec3::ZERO, Vec3::ONE), c.y)
+}
+
+pub fn rgb_to_hsl(c: Vec3) -> Vec3 {
+    let hsv = rgb_to_hsv(c);
+    let l = hsv.z * (1.0 - hsv.y * 0.5);
+    let s = if l > 0.0 && l < 1.0 {
+        (hsv.z - l) / l.min(1.0 - l)
+    } else {
+        0.0
+    };
+    Vec3::new(hsv.x, s, l)
+}
+
+pub fn hsl_to_rgb(c: Vec3) -> Vec3 {
+    let v = c.z + c.y * c.z.min(1.0 - c.z);
+    let s = if v > 0.0 { 2.0 * (1.0 - c.z / v) } else { 0.0 };
+    hsv_to_rgb(Vec3::new(c.x, s, v))
+}
+
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
@@ -0,0 +1,333 @@
+use crate::shader_graph::inputs::{EvalContext, ShaderInput};
+use crate::shader_graph::node_kind::{NodeSignature, PinSpec, ShaderNode, ShaderStage};
+use crate::shader_graph::types::{PinType, Value};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Storage buffers, and separately storage textures, a compute graph can bind
+pub const STORAGE_SLOTS: u32 = 4;
+
+/// Texel format of storage textures. `rgba32float` stores exactly what the graph computes,
+/// like the CPU stand-in.
+pub const STORAGE_TEXTURE_FORMAT: &str = "rgba32float";
+
+/// Invocations per workgroup of a compute kernel, set on the `Compute Output` node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WorkgroupSize {
+    pub x: u32,
+    pub y: u32,
+    pub z: u32,
+}
+
+impl Default for WorkgroupSize {
+    /// A row of 64, a common size for particle buffers
+    fn default() -> Self {
+        Self::new(64, 1, 1)
+    }
+}
+
+impl WorkgroupSize {
+    /// WebGPU's default `maxComputeWorkgroupSizeX/Y/Z`
+    pub const MAX: [u32; 3] = [256, 256, 64];
+    /// WebGPU's default `maxComputeInvocationsPerWorkgroup`
+    pub const MAX_INVOCATIONS: u32 = 256;
+
+    pub const fn new(x: u32, y: u32, z: u32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn as_uvec3(self) -> UVec3 {
+        UVec3::new(self.x, self.y, self.z)
+    }
+
+    /// Whether every device supporting WebGPU's default limits can run it
+    pub fn is_valid(self) -> bool {
+        let size = self.as_uvec3();
+        size.cmpge(UVec3::ONE).all()
+            && size.cmple(UVec3::from_array(Self::MAX)).all()
+            && size.element_product() <= Self::MAX_INVOCATIONS
+    }
+
+    /// `@workgroup_size` attribute of the entry point
+    pub fn wgsl_attribute(self) -> String {
+        format!("@workgroup_size({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+/// A binding a compute graph reads or writes, by slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum StorageResource {
+    /// `array<vec4<f32>>`
+    Buffer(u32),
+    /// `texture_storage_2d` in `STORAGE_TEXTURE_FORMAT`
+    Texture(u32),
+}
+
+impl StorageResource {
+    pub fn wgsl_name(self) -> String {
+        match self {
+            StorageResource::Buffer(slot) => format!("buffer{}", slot),
+            StorageResource::Texture(slot) => format!("storage_texture{}", slot),
+        }
+    }
+
+    /// Binding in group 0 of a compute module: `globals` is 0, then the buffer slots, then the
+    /// texture slots, so a slot keeps its binding whatever else the graph uses
+    pub fn binding(self) -> u32 {
+        match self {
+            StorageResource::Buffer(slot) => 1 + slot,
+            StorageResource::Texture(slot) => 1 + STORAGE_SLOTS + slot,
+        }
+    }
+}
+
+/// How a compute graph uses a storage binding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum StorageAccess {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl StorageAccess {
+    /// Access covering both uses
+    pub fn merge(self, other: StorageAccess) -> StorageAccess {
+        if self == other {
+            self
+        } else {
+            StorageAccess::ReadWrite
+        }
+    }
+
+    /// WGSL access mode. Buffers have no write-only mode, so written buffers are `read_write`;
+    /// `compile_compute` rejects read-write textures, which `rgba32float` doesn't support.
+    pub fn wgsl_mode(self, resource: StorageResource) -> &'static str {
+        match (self, resource) {
+            (StorageAccess::Read, _) => "read",
+            (StorageAccess::Write, StorageResource::Texture(_)) => "write",
+            _ => "read_write",
+        }
+    }
+}
+
+/// CPU stand-in for a storage texture, texels in rows from the top-left
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageImage {
+    pub size: UVec2,
+    pub texels: Vec<Vec4>,
+}
+
+impl StorageImage {
+    /// Image of `size` cleared to zero
+    pub fn new(size: UVec2) -> Self {
+        Self {
+            size,
+            texels: vec![Vec4::ZERO; (size.x * size.y) as usize],
+        }
+    }
+
+    fn index(&self, coord: UVec2) -> Option<usize> {
+        coord
+            .cmplt(self.size)
+            .all()
+            .then(|| (coord.y * self.size.x + coord.x) as usize)
+    }
+
+    pub fn get(&self, coord: UVec2) -> Option<Vec4> {
+        self.index(coord).map(|index| self.texels[index])
+    }
+
+    pub fn set(&mut self, coord: UVec2, texel: Vec4) {
+        if let Some(index) = self.index(coord) {
+            self.texels[index] = texel;
+        }
+    }
+}
+
+/// CPU stand-in for the bindings of a compute graph, by slot. Missing slots read zero and
+/// ignore writes, like out-of-range accesses.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ComputeStorage {
+    pub buffers: BTreeMap<u32, Vec<Vec4>>,
+    pub textures: BTreeMap<u32, StorageImage>,
+}
+
+/// Storage buffer and storage texture access. Indices and coordinates are floats like every
+/// other value, converted with WGSL's saturating `u32()`; out-of-range reads give zero and
+/// out-of-range writes are skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ComputeNode {
+    ReadBuffer(u32),
+    WriteBuffer(u32),
+    /// `textureLoad` at integer pixel coordinates
+    ReadTexture(u32),
+    /// `textureStore` at integer pixel coordinates
+    WriteTexture(u32),
+}
+
+impl ComputeNode {
+    pub fn slot_mut(&mut self) -> &mut u32 {
+        match self {
+            ComputeNode::ReadBuffer(slot)
+            | ComputeNode::WriteBuffer(slot)
+            | ComputeNode::ReadTexture(slot)
+            | ComputeNode::WriteTexture(slot) => slot,
+        }
+    }
+
+    pub fn resource(self) -> StorageResource {
+        match self {
+            ComputeNode::ReadBuffer(slot) | ComputeNode::WriteBuffer(slot) => {
+                StorageResource::Buffer(slot)
+            }
+            ComputeNode::ReadTexture(slot) | ComputeNode::WriteTexture(slot) => {
+                StorageResource::Texture(slot)
+            }
+        }
+    }
+
+    /// Whether the node is a side effect of the kernel rather than a value
+    pub fn is_write(self) -> bool {
+        matches!(
+            self,
+            ComputeNode::WriteBuffer(_) | ComputeNode::WriteTexture(_)
+        )
+    }
+
+    /// Outputs of a read against `storage`; writes pass their inputs through like
+    /// `ShaderNode::evaluate`
+    pub fn evaluate_storage(&self, inputs: &[Value], storage: &ComputeStorage) -> Vec<Value> {
+        let texel = match *self {
+            ComputeNode::ReadBuffer(slot) => storage
+                .buffers
+                .get(&slot)
+                .and_then(|buffer| buffer.get(storage_index(inputs[0]) as usize))
+                .copied(),
+            ComputeNode::ReadTexture(slot) => storage
+                .textures
+                .get(&slot)
+                .and_then(|image| image.get(storage_coord(inputs[0]))),
+            _ => return inputs.to_vec(),
+        };
+        vec![Value::Vec4(texel.unwrap_or(Vec4::ZERO))]
+    }
+
+    /// Apply a write node to `storage`, given its evaluated inputs
+    pub fn store(&self, inputs: &[Value], storage: &mut ComputeStorage) {
+        let value = inputs[1].as_vec4();
+        match *self {
+            ComputeNode::WriteBuffer(slot) => {
+                let index = storage_index(inputs[0]) as usize;
+                if let Some(element) = storage
+                    .buffers
+                    .get_mut(&slot)
+                    .and_then(|buffer| buffer.get_mut(index))
+                {
+                    *element = value;
+                }
+            }
+            ComputeNode::WriteTexture(slot) => {
+                if let Some(image) = storage.textures.get_mut(&slot) {
+                    image.set(storage_coord(inputs[0]), value);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// WGSL's `u32(f32)`: truncates and saturates, NaN becomes zero
+fn storage_index(value: Value) -> u32 {
+    value.as_f32() as u32
+}
+
+fn storage_coord(value: Value) -> UVec2 {
+    let coord = value.as_vec2();
+    UVec2::new(coord.x as u32, coord.y as u32)
+}
+
+impl ShaderNode for ComputeNode {
+    fn title(&self) -> String {
+        match self {
+            ComputeNode::ReadBuffer(_) => "Read Buffer",
+            ComputeNode::WriteBuffer(_) => "Write Buffer",
+            ComputeNode::ReadTexture(_) => "Read Storage Texture",
+            ComputeNode::WriteTexture(_) => "Write Storage Texture",
+        }
+        .to_string()
+    }
+
+    fn signature(&self) -> NodeSignature {
+        // Unconnected, each invocation reads and writes its own element or pixel
+        let index = PinSpec::input_from("Index", ShaderInput::InvocationId).typed(PinType::Float);
+        let coord = PinSpec::input_from("Coord", ShaderInput::InvocationId).typed(PinType::Vec2);
+        let value = PinSpec::input("Value", PinType::Vec4, Value::Vec4(Vec4::ZERO));
+        match self {
+            ComputeNode::ReadBuffer(_) => NodeSignature {
+                inputs: vec![index],
+                outputs: vec![PinSpec::output("Value", PinType::Vec4)],
+            },
+            ComputeNode::WriteBuffer(_) => NodeSignature {
+                inputs: vec![index, value],
+                outputs: vec![],
+            },
+            ComputeNode::ReadTexture(_) => NodeSignature {
+                inputs: vec![coord],
+                outputs: vec![PinSpec::output("RGBA", PinType::Vec4)],
+            },
+            ComputeNode::WriteTexture(_) => NodeSignature {
+                inputs: vec![coord, value],
+                outputs: vec![],
+            },
+        }
+    }
+
+    /// Reads are guarded so they give zero out of range on every device; a write node's only
+    /// expression is the statement performing it
+    fn emit_wgsl(&self, inputs: &[String]) -> Vec<String> {
+        let name = self.resource().wgsl_name();
+        match self {
+            ComputeNode::ReadBuffer(_) => vec![format!(
+                "select(vec4<f32>(0.0), {name}[u32({index})], u32({index}) < arrayLength(&{name}))",
+                name = name,
+                index = inputs[0]
+            )],
+            ComputeNode::WriteBuffer(_) => vec![format!(
+                "if u32({index}) < arrayLength(&{name}) {{ {name}[u32({index})] = {value}; }}",
+                name = name,
+                index = inputs[0],
+                value = inputs[1]
+            )],
+            ComputeNode::ReadTexture(_) => vec![format!(
+                "select(vec4<f32>(0.0), textureLoad({name}, vec2<u32>({coord})), all(vec2<u32>({coord}) < textureDimensions({name})))",
+                name = name,
+                coord = inputs[0]
+            )],
+            // `textureStore` already ignores coordinates outside the texture
+            ComputeNode::WriteTexture(_) => vec![format!(
+                "textureStore({}, vec2<u32>({}), {});",
+                name, inputs[0], inputs[1]
+            )],
+        }
+    }
+
+    fn storage(&self) -> Vec<(StorageResource, StorageAccess)> {
+        let access = if self.is_write() {
+            StorageAccess::Write
+        } else {
+            StorageAccess::Read
+        };
+        vec![(self.resource(), access)]
+    }
+
+    fn stages(&self) -> &'static [ShaderStage] {
+        &[ShaderStage::Compute]
+    }
+
+    /// Without bound storage every read gives zero - `GraphEvaluator::dispatch` evaluates
+    /// against its `ComputeStorage` instead
+    fn evaluate(&self, inputs: &[Value], _ctx: &EvalContext) -> Vec<Value> {
+        self.evaluate_storage(inputs, &ComputeStorage::default())
+    }
+}
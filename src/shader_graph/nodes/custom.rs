@@ -0,0 +1,308 @@
+use crate::shader_graph::inputs::EvalContext;
+use crate::shader_graph::node_kind::{NodeSignature, PinSpec, ShaderNode, ShaderStage, WgslHelper};
+use crate::shader_graph::types::{PinType, Value};
+use serde::{Deserialize, Serialize};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Source a new Custom Function node starts with
+pub const DEFAULT_CUSTOM_SOURCE: &str = "fn custom(uv: vec2<f32>, scale: f32) -> vec3<f32> {
+    return vec3<f32>(fract(uv * scale), 0.0);
+}";
+
+/// Pins derived from a custom function's signature
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct CustomSignature {
+    /// Name of the function the node calls
+    pub name: String,
+    /// Arguments, in order
+    pub inputs: Vec<(String, PinType)>,
+    /// `Out` for a plain return type, else one entry per field of the returned struct
+    pub outputs: Vec<(String, PinType)>,
+    /// Whether the function returns a struct, read field by field
+    pub returns_struct: bool,
+    /// Whether the function, or one it calls, uses derivatives, an implicit-LOD sample or
+    /// `discard`, which only the fragment stage allows
+    #[serde(default)]
+    pub fragment_only: bool,
+}
+
+/// User-written WGSL called as a node. The last function in the source is the one called,
+/// anything above it (structs, helper functions) comes along. Arguments become inputs and the
+/// return value, or each field of a returned struct, becomes an output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomFunction {
+    source: String,
+    /// Signature of the last source that parsed, so pins and connections survive typos
+    signature: CustomSignature,
+    /// Why the current source does not parse or validate, `None` when it does
+    error: Option<String>,
+}
+
+impl Default for CustomFunction {
+    fn default() -> Self {
+        Self::new(DEFAULT_CUSTOM_SOURCE)
+    }
+}
+
+impl CustomFunction {
+    pub fn new(source: &str) -> Self {
+        let mut function = Self {
+            source: String::new(),
+            signature: CustomSignature::default(),
+            error: None,
+        };
+        function.set_source(source);
+        function
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Replace the source and re-derive the pins. On error the previous pins stay.
+    pub fn set_source(&mut self, source: &str) {
+        self.source = source.to_string();
+        match parse_signature(source) {
+            Ok(signature) => {
+                self.signature = signature;
+                self.error = None;
+            }
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    /// Key the source is emitted under: two nodes with the same text share it, different
+    /// texts both reach naga, which reports a clash of function names
+    fn helper_name(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.source.hash(&mut hasher);
+        format!("{}#{:016x}", self.signature.name, hasher.finish())
+    }
+}
+
+/// `PinType` of a naga type, if a pin can carry it
+pub fn pin_type(module: &naga::Module, ty: naga::Handle<naga::Type>) -> Option<PinType> {
+    use naga::{ScalarKind, TypeInner, VectorSize};
+    let (size, scalar) = match module.types[ty].inner {
+        TypeInner::Scalar(scalar) => (None, scalar),
+        TypeInner::Vector { size, scalar } => (Some(size), scalar),
+        _ => return None,
+    };
+    if scalar.width != 4 && scalar.kind != ScalarKind::Bool {
+        return None;
+    }
+    let ty = match (scalar.kind, size) {
+        (ScalarKind::Float, None) => PinType::Float,
+        (ScalarKind::Float, Some(VectorSize::Bi)) => PinType::Vec2,
+        (ScalarKind::Float, Some(VectorSize::Tri)) => PinType::Vec3,
+        (ScalarKind::Float, Some(VectorSize::Quad)) => PinType::Vec4,
+        (ScalarKind::Bool, None) => PinType::Bool,
+        (ScalarKind::Bool, Some(VectorSize::Bi)) => PinType::BVec2,
+        (ScalarKind::Bool, Some(VectorSize::Tri)) => PinType::BVec3,
+        (ScalarKind::Bool, Some(VectorSize::Quad)) => PinType::BVec4,
+        _ => return None,
+    };
+    Some(ty)
+}
+
+/// Parse and validate WGSL with naga, keeping `///` doc comments in the module.
+/// Errors are one line, prefixed with the line they point at.
+pub fn parse_module(source: &str) -> Result<naga::Module, String> {
+    let at_line = |location: Option<naga::SourceLocation>, message: String| match location {
+        Some(location) => format!("line {}: {}", location.line_number, message),
+        None => message,
+    };
+    let module = naga::front::wgsl::Frontend::new_with_options(naga::front::wgsl::Options {
+        parse_doc_comments: true,
+    })
+    .parse(source)
+    .map_err(|e| at_line(e.location(source), e.message().to_string()))?;
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .map_err(|e| {
+        // The outer error only names the function; the cause says what is wrong with it
+        let mut message = e.as_inner().to_string();
+        let mut cause = std::error::Error::source(e.as_inner());
+        while let Some(error) = cause {
+            message = format!("{}: {}", message, error);
+            cause = error.source();
+        }
+        at_line(e.location(source), message)
+    })?;
+    Ok(module)
+}
+
+/// Parse and validate `source` and read the pins off its last function
+pub fn parse_signature(source: &str) -> Result<CustomSignature, String> {
+    let module = parse_module(source)?;
+    let (_, function) = module
+        .functions
+        .iter()
+        .last()
+        .ok_or_else(|| "no function to call".to_string())?;
+    CustomSignature::read(&module, function)
+}
+
+/// Whether `function` or anything it calls needs the fragment stage
+fn uses_fragment_only(module: &naga::Module, function: &naga::Function) -> bool {
+    function.expressions.iter().any(|(_, expression)| {
+        matches!(
+            expression,
+            naga::Expression::Derivative { .. }
+                | naga::Expression::ImageSample {
+                    level: naga::SampleLevel::Auto | naga::SampleLevel::Bias(_),
+                    ..
+                }
+        )
+    }) || block_uses_fragment_only(module, &function.body)
+}
+
+fn block_uses_fragment_only(module: &naga::Module, block: &naga::Block) -> bool {
+    block.iter().any(|statement| match statement {
+        naga::Statement::Kill => true,
+        naga::Statement::Call { function, .. } => {
+            uses_fragment_only(module, &module.functions[*function])
+        }
+        naga::Statement::Block(inner) => block_uses_fragment_only(module, inner),
+        naga::Statement::If { accept, reject, .. } => {
+            block_uses_fragment_only(module, accept) || block_uses_fragment_only(module, reject)
+        }
+        naga::Statement::Switch { cases, .. } => cases
+            .iter()
+            .any(|case| block_uses_fragment_only(module, &case.body)),
+        naga::Statement::Loop {
+            body, continuing, ..
+        } => block_uses_fragment_only(module, body) || block_uses_fragment_only(module, continuing),
+        _ => false,
+    })
+}
+
+impl CustomSignature {
+    /// Pins of a function in `module`, or why it can't be a node
+    pub fn read(module: &naga::Module, function: &naga::Function) -> Result<Self, String> {
+        let name = function.name.clone().unwrap_or_default();
+
+        let mut inputs = Vec::new();
+        for argument in &function.arguments {
+            let arg_name = argument.name.clone().unwrap_or_default();
+            let ty = pin_type(module, argument.ty)
+                .ok_or_else(|| format!("argument `{}` is not a bool, f32 or vector", arg_name))?;
+            inputs.push((arg_name, ty));
+        }
+
+        let result = function
+            .result
+            .as_ref()
+            .ok_or_else(|| format!("`{}` returns nothing", name))?;
+        let (outputs, returns_struct) = match &module.types[result.ty].inner {
+            naga::TypeInner::Struct { members, .. } => {
+                let mut outputs = Vec::new();
+                for member in members {
+                    let field = member.name.clone().unwrap_or_default();
+                    let ty = pin_type(module, member.ty)
+                        .ok_or_else(|| format!("field `{}` is not a bool, f32 or vector", field))?;
+                    outputs.push((field, ty));
+                }
+                (outputs, true)
+            }
+            _ => {
+                let ty = pin_type(module, result.ty).ok_or_else(|| {
+                    format!("`{}` does not return a bool, f32, vector or struct", name)
+                })?;
+                (vec![("Out".to_string(), ty)], false)
+            }
+        };
+
+        Ok(Self {
+            name,
+            inputs,
+            outputs,
+            returns_struct,
+            fragment_only: uses_fragment_only(module, function),
+        })
+    }
+
+    /// Stages a call to the function is valid in
+    pub fn stages(&self) -> &'static [ShaderStage] {
+        if self.fragment_only {
+            &[ShaderStage::Fragment]
+        } else {
+            &ShaderStage::ALL
+        }
+    }
+
+    /// One pin per argument and per output; unconnected inputs are zero
+    pub fn node_signature(&self) -> NodeSignature {
+        NodeSignature {
+            inputs: self
+                .inputs
+                .iter()
+                .map(|(name, ty)| PinSpec::input(name, *ty, Value::Float(0.0)))
+                .collect(),
+            outputs: self
+                .outputs
+                .iter()
+                .map(|(name, ty)| PinSpec::output(name, *ty))
+                .collect(),
+        }
+    }
+
+    /// One expression per output. A struct result is read once per field; the call has no
+    /// side effects, so repeating it only costs what the driver fails to merge.
+    pub fn emit_call(&self, inputs: &[String]) -> Vec<String> {
+        let call = format!("{}({})", self.name, inputs.join(", "));
+        if !self.returns_struct {
+            return vec![call];
+        }
+        self.outputs
+            .iter()
+            .map(|(field, _)| format!("{}.{}", call, field))
+            .collect()
+    }
+
+    /// What the CPU evaluator gives for a call it can't run
+    pub fn zero_outputs(&self) -> Vec<Value> {
+        self.outputs
+            .iter()
+            .map(|(_, ty)| Value::zero(*ty))
+            .collect()
+    }
+}
+
+impl ShaderNode for CustomFunction {
+    fn title(&self) -> String {
+        if self.signature.name.is_empty() {
+            "Custom Function".to_string()
+        } else {
+            self.signature.name.clone()
+        }
+    }
+
+    fn signature(&self) -> NodeSignature {
+        self.signature.node_signature()
+    }
+
+    fn emit_wgsl(&self, inputs: &[String]) -> Vec<String> {
+        self.signature.emit_call(inputs)
+    }
+
+    fn wgsl_helpers(&self) -> Vec<WgslHelper> {
+        vec![WgslHelper::new(&self.helper_name(), &self.source)]
+    }
+
+    fn stages(&self) -> &'static [ShaderStage] {
+        self.signature.stages()
+    }
+
+    /// The CPU can't run user WGSL - every output is zero
+    fn evaluate(&self, _inputs: &[Value], _ctx: &EvalContext) -> Vec<Value> {
+        self.signature.zero_outputs()
+    }
+}
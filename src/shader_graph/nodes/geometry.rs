@@ -0,0 +1,108 @@
+use crate::shader_graph::inputs::{EvalContext, ShaderInput};
+use crate::shader_graph::node_kind::{NodeSignature, PinSpec, ShaderNode, ShaderStage};
+use crate::shader_graph::types::Value;
+use serde::{Deserialize, Serialize};
+
+/// Coordinate space a geometry vector is expressed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Space {
+    Object,
+    World,
+    View,
+}
+
+impl Space {
+    pub const ALL: [Space; 3] = [Space::Object, Space::World, Space::View];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Space::Object => "Object",
+            Space::World => "World",
+            Space::View => "View",
+        }
+    }
+}
+
+/// Read-only access to mesh, camera and rasterizer data - no inputs, one output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GeometryNode {
+    Position(Space),
+    Normal(Space),
+    /// World-space tangent from the mesh's tangent attribute
+    Tangent,
+    /// World-space unit vector from the surface towards the camera
+    ViewDirection,
+    CameraPosition,
+    VertexColor,
+    /// Normalized screen position, `0..=1` from the top-left corner
+    ScreenPosition,
+    /// Custom interpolant the `Vertex Output` node wrote, by index
+    Custom(usize),
+}
+
+impl GeometryNode {
+    /// The fragment data this node exposes
+    pub fn source(self) -> ShaderInput {
+        match self {
+            GeometryNode::Position(Space::Object) => ShaderInput::ObjectPosition,
+            GeometryNode::Position(Space::World) => ShaderInput::WorldPosition,
+            GeometryNode::Position(Space::View) => ShaderInput::ViewPosition,
+            GeometryNode::Normal(Space::Object) => ShaderInput::ObjectNormal,
+            GeometryNode::Normal(Space::World) => ShaderInput::WorldNormal,
+            GeometryNode::Normal(Space::View) => ShaderInput::ViewNormal,
+            GeometryNode::Tangent => ShaderInput::WorldTangent,
+            GeometryNode::ViewDirection => ShaderInput::ViewDirection,
+            GeometryNode::CameraPosition => ShaderInput::CameraPosition,
+            GeometryNode::VertexColor => ShaderInput::VertexColor,
+            GeometryNode::ScreenPosition => ShaderInput::ScreenPosition,
+            GeometryNode::Custom(index) => ShaderInput::Custom(index),
+        }
+    }
+}
+
+impl ShaderNode for GeometryNode {
+    fn title(&self) -> String {
+        match self {
+            GeometryNode::Position(space) => format!("Position ({})", space.label()),
+            GeometryNode::Normal(space) => format!("Normal ({})", space.label()),
+            GeometryNode::Tangent => "Tangent".to_string(),
+            GeometryNode::ViewDirection => "View Direction".to_string(),
+            GeometryNode::CameraPosition => "Camera Position".to_string(),
+            GeometryNode::VertexColor => "Vertex Color".to_string(),
+            GeometryNode::ScreenPosition => "Screen Position (Normalized)".to_string(),
+            GeometryNode::Custom(index) => format!("Custom Interpolant {}", index),
+        }
+    }
+
+    fn signature(&self) -> NodeSignature {
+        let label = match self {
+            GeometryNode::Position(_) | GeometryNode::CameraPosition => "Position",
+            GeometryNode::Normal(_) => "Normal",
+            GeometryNode::Tangent => "Tangent",
+            GeometryNode::ViewDirection => "Direction",
+            GeometryNode::VertexColor => "Color",
+            GeometryNode::ScreenPosition => "Screen",
+            GeometryNode::Custom(_) => "Custom",
+        };
+        NodeSignature {
+            inputs: vec![],
+            outputs: vec![PinSpec::output(label, self.source().pin_type())],
+        }
+    }
+
+    fn emit_wgsl(&self, _inputs: &[String]) -> Vec<String> {
+        vec![self.source().wgsl_expr()]
+    }
+
+    fn shader_inputs(&self) -> Vec<ShaderInput> {
+        vec![self.source()]
+    }
+
+    fn stages(&self) -> &'static [ShaderStage] {
+        self.source().stages()
+    }
+
+    fn evaluate(&self, _inputs: &[Value], ctx: &EvalContext) -> Vec<Value> {
+        vec![self.source().evaluate(ctx)]
+    }
+}
@@ -0,0 +1,207 @@
+use crate::shader_graph::inputs::EvalContext;
+use crate::shader_graph::node_kind::{NodeSignature, PinSpec, ShaderNode, WgslHelper};
+use crate::shader_graph::types::{PinType, Value, wgsl_float};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// How colours between two stops are blended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RampInterpolation {
+    Linear,
+    /// Hold the colour of the stop on the left until the next stop
+    Constant,
+    /// Smoothstep-eased blend
+    Smooth,
+}
+
+impl RampInterpolation {
+    pub const ALL: [RampInterpolation; 3] = [
+        RampInterpolation::Linear,
+        RampInterpolation::Constant,
+        RampInterpolation::Smooth,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RampInterpolation::Linear => "Linear",
+            RampInterpolation::Constant => "Constant",
+            RampInterpolation::Smooth => "Smooth",
+        }
+    }
+}
+
+/// One colour on a ramp - `position` in `0..=1`, `color` is straight (unpremultiplied) RGBA
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ColorStop {
+    pub position: f32,
+    pub color: Vec4,
+}
+
+/// Maps a scalar factor onto a gradient of colour stops
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorRamp {
+    pub stops: Vec<ColorStop>,
+    pub interpolation: RampInterpolation,
+}
+
+impl Default for ColorRamp {
+    fn default() -> Self {
+        Self {
+            stops: vec![
+                ColorStop {
+                    position: 0.0,
+                    color: Vec4::new(0.0, 0.0, 0.0, 1.0),
+                },
+                ColorStop {
+                    position: 1.0,
+                    color: Vec4::ONE,
+                },
+            ],
+            interpolation: RampInterpolation::Linear,
+        }
+    }
+}
+
+impl ColorRamp {
+    /// Stops ordered by position - the order used for sampling and emission
+    pub fn sorted_stops(&self) -> Vec<ColorStop> {
+        let mut stops = self.stops.clone();
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        stops
+    }
+
+    /// Colour at `t`, clamped to the first and last stop
+    pub fn sample(&self, t: f32) -> Vec4 {
+        let stops = self.sorted_stops();
+        let (Some(first), Some(last)) = (stops.first(), stops.last()) else {
+            return Vec4::ZERO;
+        };
+        if t <= first.position {
+            return first.color;
+        }
+
+        for pair in stops.windows(2) {
+            let (left, right) = (pair[0], pair[1]);
+            if t < right.position {
+                let f = (t - left.position) / (right.position - left.position).max(1e-6);
+                return match self.interpolation {
+                    RampInterpolation::Linear => left.color.lerp(right.color, f),
+                    RampInterpolation::Constant => left.color,
+                    RampInterpolation::Smooth => {
+                        left.color.lerp(right.color, f * f * (3.0 - 2.0 * f))
+                    }
+                };
+            }
+        }
+        last.color
+    }
+
+    /// Insert a stop at `position` using the colour the ramp currently has there.
+    /// Returns the index of the new stop.
+    pub fn add_stop(&mut self, position: f32) -> usize {
+        let position = position.clamp(0.0, 1.0);
+        let color = self.sample(position);
+        self.stops.push(ColorStop { position, color });
+        self.stops.len() - 1
+    }
+
+    /// Remove a stop - a ramp always keeps at least one
+    pub fn remove_stop(&mut self, index: usize) -> bool {
+        if self.stops.len() <= 1 || index >= self.stops.len() {
+            return false;
+        }
+        self.stops.remove(index);
+        true
+    }
+
+    /// Name of the emitted lookup function, unique per ramp content
+    pub fn wgsl_function_name(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.interpolation.hash(&mut hasher);
+        for stop in self.sorted_stops() {
+            stop.position.to_bits().hash(&mut hasher);
+            for channel in stop.color.to_array() {
+                channel.to_bits().hash(&mut hasher);
+            }
+        }
+        format!("color_ramp_{:016x}", hasher.finish())
+    }
+
+    /// WGSL lookup function with the stops baked into local arrays
+    pub fn wgsl_function(&self) -> String {
+        let name = self.wgsl_function_name();
+        let stops = self.sorted_stops();
+        if stops.is_empty() {
+            return format!(
+                "fn {}(t: f32) -> vec4<f32> {{\n    return vec4<f32>(0.0);\n}}",
+                name
+            );
+        }
+
+        let count = stops.len();
+        let positions: Vec<String> = stops.iter().map(|s| wgsl_float(s.position)).collect();
+        let colors: Vec<String> = stops
+            .iter()
+            .map(|s| Value::Vec4(s.color).to_wgsl())
+            .collect();
+        let blend = match self.interpolation {
+            RampInterpolation::Linear => "mix(colors[i - 1u], colors[i], f)",
+            RampInterpolation::Constant => "colors[i - 1u]",
+            RampInterpolation::Smooth => "mix(colors[i - 1u], colors[i], smoothstep(0.0, 1.0, f))",
+        };
+
+        format!(
+            "fn {name}(t: f32) -> vec4<f32> {{
+    var positions = array<f32, {count}>({positions});
+    var colors = array<vec4<f32>, {count}>({colors});
+    if (t <= positions[0]) {{
+        return colors[0];
+    }}
+    for (var i = 1u; i < {count}u; i = i + 1u) {{
+        if (t < positions[i]) {{
+            let f = (t - positions[i - 1u]) / max(positions[i] - positions[i - 1u], 0.000001);
+            return {blend};
+        }}
+    }}
+    return colors[{last}];
+}}",
+            positions = positions.join(", "),
+            colors = colors.join(", "),
+            last = count - 1,
+        )
+    }
+}
+
+impl ShaderNode for ColorRamp {
+    fn title(&self) -> String {
+        "Color Ramp".to_string()
+    }
+
+    fn signature(&self) -> NodeSignature {
+        NodeSignature {
+            inputs: vec![PinSpec::input("Fac", PinType::Float, Value::Float(0.5))],
+            outputs: vec![
+                PinSpec::output("Color", PinType::Vec4),
+                PinSpec::output("Alpha", PinType::Float),
+            ],
+        }
+    }
+
+    fn emit_wgsl(&self, inputs: &[String]) -> Vec<String> {
+        let call = format!("{}({})", self.wgsl_function_name(), inputs[0]);
+        vec![call.clone(), format!("{}.a", call)]
+    }
+
+    fn wgsl_helpers(&self) -> Vec<WgslHelper> {
+        vec![WgslHelper {
+            name: self.wgsl_function_name(),
+            source: self.wgsl_function(),
+        }]
+    }
+
+    fn evaluate(&self, inputs: &[Value], _ctx: &EvalContext) -> Vec<Value> {
+        let color = self.sample(inputs[0].as_f32());
+        vec![Value::Vec4(color), Value::Float(color.w)]
+    }
+}
@@ -0,0 +1,460 @@
+use crate::node_graph::model::{NodeGraph, NodeId};
+use crate::node_graph::node_factory::NodeFactory;
+use crate::shader_graph::catalog::builtin_templates;
+use crate::shader_graph::inputs::EvalContext;
+use crate::shader_graph::node_kind::{
+    NodeKind, NodeSignature, ShaderNode, ShaderStage, WgslHelper,
+};
+use crate::shader_graph::nodes::custom::{CustomSignature, parse_module};
+use crate::shader_graph::types::Value;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Directory scanned for `.wgsl` helper files
+pub const LIBRARY_DIR: &str = "assets/shaders/library";
+
+/// Doc comment line naming a function's category, e.g. `/// Category: Noise`
+const CATEGORY_PREFIX: &str = "Category:";
+
+/// Where a clash with a built-in node's helper is reported as declared
+const BUILTIN_DECLARER: &str = "a built-in node";
+
+/// A function from a library file, usable as a node. Carries the WGSL it needs, so a graph
+/// still compiles when the file goes away; `WgslLibrary::sync_graph` refreshes it on change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryFunction {
+    /// File the function comes from, `/`-separated and relative to the library directory
+    pub file: String,
+    /// First doc comment line, else the function name
+    pub title: String,
+    /// `Category:` doc comment line, else the file name without extension
+    pub category: String,
+    /// Remaining doc comment lines
+    pub description: String,
+    pub signature: CustomSignature,
+    /// Declarations the function uses, dependencies first, ending with the function itself
+    pub helpers: Vec<WgslHelper>,
+}
+
+/// A module-scope declaration a function can depend on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Declaration {
+    Type(naga::Handle<naga::Type>),
+    Constant(naga::Handle<naga::Constant>),
+    Global(naga::Handle<naga::GlobalVariable>),
+    Function(naga::Handle<naga::Function>),
+}
+
+/// Collects the declarations a function reaches, each after its own dependencies
+struct DependencyWalk<'a> {
+    module: &'a naga::Module,
+    seen: HashSet<Declaration>,
+    order: Vec<Declaration>,
+}
+
+impl<'a> DependencyWalk<'a> {
+    fn visit(&mut self, declaration: Declaration) {
+        if !self.seen.insert(declaration) {
+            return;
+        }
+        match declaration {
+            Declaration::Type(ty) => self.visit_type_members(ty),
+            Declaration::Constant(constant) => self.visit_type(self.module.constants[constant].ty),
+            Declaration::Global(global) => self.visit_type(self.module.global_variables[global].ty),
+            Declaration::Function(function) => self.visit_function(function),
+        }
+        self.order.push(declaration);
+    }
+
+    /// Only structs are declarations; other types are spelled out where they are used
+    fn visit_type(&mut self, ty: naga::Handle<naga::Type>) {
+        match self.module.types[ty].inner {
+            naga::TypeInner::Struct { .. } => self.visit(Declaration::Type(ty)),
+            naga::TypeInner::Array { base, .. } | naga::TypeInner::BindingArray { base, .. } => {
+                self.visit_type(base)
+            }
+            naga::TypeInner::Pointer { base, .. } => self.visit_type(base),
+            _ => {}
+        }
+    }
+
+    fn visit_type_members(&mut self, ty: naga::Handle<naga::Type>) {
+        if let naga::TypeInner::Struct { members, .. } = &self.module.types[ty].inner {
+            for member in members {
+                self.visit_type(member.ty);
+            }
+        }
+    }
+
+    fn visit_function(&mut self, handle: naga::Handle<naga::Function>) {
+        let function = &self.module.functions[handle];
+        for argument in &function.arguments {
+            self.visit_type(argument.ty);
+        }
+        if let Some(result) = &function.result {
+            self.visit_type(result.ty);
+        }
+        for (_, local) in function.local_variables.iter() {
+            self.visit_type(local.ty);
+        }
+        for (_, expression) in function.expressions.iter() {
+            match *expression {
+                naga::Expression::Compose { ty, .. } | naga::Expression::ZeroValue(ty) => {
+                    self.visit_type(ty)
+                }
+                naga::Expression::Constant(constant) => self.visit(Declaration::Constant(constant)),
+                naga::Expression::GlobalVariable(global) => self.visit(Declaration::Global(global)),
+                _ => {}
+            }
+        }
+        self.visit_calls(&function.body);
+    }
+
+    fn visit_calls(&mut self, block: &naga::Block) {
+        for statement in block.iter() {
+            match statement {
+                naga::Statement::Call { function, .. } => {
+                    self.visit(Declaration::Function(*function))
+                }
+                naga::Statement::Block(inner) => self.visit_calls(inner),
+                naga::Statement::If { accept, reject, .. } => {
+                    self.visit_calls(accept);
+                    self.visit_calls(reject);
+                }
+                naga::Statement::Switch { cases, .. } => {
+                    for case in cases {
+                        self.visit_calls(&case.body);
+                    }
+                }
+                naga::Statement::Loop {
+                    body, continuing, ..
+                } => {
+                    self.visit_calls(body);
+                    self.visit_calls(continuing);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn name_and_span(&self, declaration: Declaration) -> (Option<&'a str>, naga::Span) {
+        let module = self.module;
+        match declaration {
+            Declaration::Type(ty) => (module.types[ty].name.as_deref(), module.types.get_span(ty)),
+            Declaration::Constant(constant) => (
+                module.constants[constant].name.as_deref(),
+                module.constants.get_span(constant),
+            ),
+            Declaration::Global(global) => (
+                module.global_variables[global].name.as_deref(),
+                module.global_variables.get_span(global),
+            ),
+            Declaration::Function(function) => (
+                module.functions[function].name.as_deref(),
+                module.functions.get_span(function),
+            ),
+        }
+    }
+}
+
+/// Source text of a declaration. naga's spans for structs and functions stop short of the
+/// closing brace, so those run on to it.
+fn declaration_text(source: &str, declaration: Declaration, span: naga::Span) -> Option<&str> {
+    let range = span.to_range()?;
+    let end = match declaration {
+        Declaration::Type(_) | Declaration::Function(_) => {
+            range.end + source.get(range.end..)?.find('}')? + 1
+        }
+        Declaration::Constant(_) | Declaration::Global(_) => range.end,
+    };
+    source.get(range.start..end)
+}
+
+/// Title, category and description from a function's `///` lines
+fn read_doc_comments(lines: &[String], name: &str, file: &str) -> (String, String, String) {
+    let mut title = None;
+    let mut category = None;
+    let mut description = Vec::new();
+    for line in lines {
+        let text = line.trim_start_matches('/').trim();
+        if let Some(rest) = text.strip_prefix(CATEGORY_PREFIX) {
+            category = Some(rest.trim().to_string());
+        } else if title.is_none() && !text.is_empty() {
+            title = Some(text.to_string());
+        } else if !text.is_empty() {
+            description.push(text);
+        }
+    }
+    let stem = Path::new(file)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    (
+        title.unwrap_or_else(|| name.to_string()),
+        category.unwrap_or(stem),
+        description.join(" "),
+    )
+}
+
+/// Every function of a library file that can be a node. Functions taking or returning types
+/// pins can't carry stay usable as dependencies of the others; entry points are skipped.
+pub fn parse_library_file(file: &str, source: &str) -> Result<Vec<LibraryFunction>, String> {
+    let module = parse_module(source)?;
+    let mut functions = Vec::new();
+    for (handle, function) in module.functions.iter() {
+        let Ok(signature) = CustomSignature::read(&module, function) else {
+            continue;
+        };
+
+        let mut walk = DependencyWalk {
+            module: &module,
+            seen: HashSet::new(),
+            order: Vec::new(),
+        };
+        walk.visit(Declaration::Function(handle));
+        let mut helpers = Vec::new();
+        for declaration in walk.order.clone() {
+            let (name, span) = walk.name_and_span(declaration);
+            let text = declaration_text(source, declaration, span)
+                .ok_or_else(|| format!("no source for `{}`", name.unwrap_or("?")))?;
+            helpers.push(WgslHelper::new(
+                &format!("{}::{}", file, name.unwrap_or_default()),
+                text,
+            ));
+        }
+
+        let comments = module
+            .doc_comments
+            .as_ref()
+            .and_then(|docs| docs.functions.get(&handle))
+            .cloned()
+            .unwrap_or_default();
+        let (title, category, description) = read_doc_comments(&comments, &signature.name, file);
+        functions.push(LibraryFunction {
+            file: file.to_string(),
+            title,
+            category,
+            description,
+            signature,
+            helpers,
+        });
+    }
+    Ok(functions)
+}
+
+/// WGSL names of the helper functions built-in nodes emit. Fixed helpers are keyed by the
+/// name they declare; generated ones (ramps, switches) carry a hash or type in theirs.
+fn builtin_helper_names() -> HashSet<String> {
+    builtin_templates()
+        .iter()
+        .flat_map(|template| template.kind.wgsl_helpers())
+        .map(|helper| helper.name)
+        .collect()
+}
+
+/// `.wgsl` files under `root`, as sorted `/`-separated paths relative to it
+pub fn find_library_files(root: &Path) -> Vec<String> {
+    fn visit(dir: &Path, prefix: &str, found: &mut Vec<String>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            let relative = format!("{}{}", prefix, name);
+            if path.is_dir() {
+                visit(&path, &format!("{}/", relative), found);
+            } else if path.extension().is_some_and(|ext| ext == "wgsl") {
+                found.push(relative);
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    visit(root, "", &mut found);
+    found.sort();
+    found
+}
+
+/// The node types imported from the library directory
+#[derive(Resource, Debug, Clone)]
+pub struct WgslLibrary {
+    pub root: PathBuf,
+    /// By file, then in source order
+    pub functions: Vec<LibraryFunction>,
+    /// Files that failed to parse or validate, or declare a name another file already does,
+    /// with the reason
+    pub errors: Vec<(String, String)>,
+    /// Modification time of each scanned file, to notice changes
+    stamps: BTreeMap<String, Option<SystemTime>>,
+}
+
+impl Default for WgslLibrary {
+    fn default() -> Self {
+        Self::load(LIBRARY_DIR)
+    }
+}
+
+impl WgslLibrary {
+    /// Scan `root` now
+    pub fn load(root: impl Into<PathBuf>) -> Self {
+        let mut library = Self {
+            root: root.into(),
+            functions: Vec::new(),
+            errors: Vec::new(),
+            stamps: BTreeMap::new(),
+        };
+        library.rescan();
+        library
+    }
+
+    fn current_stamps(&self) -> BTreeMap<String, Option<SystemTime>> {
+        find_library_files(&self.root)
+            .into_iter()
+            .map(|file| {
+                let modified = std::fs::metadata(self.root.join(&file))
+                    .and_then(|metadata| metadata.modified())
+                    .ok();
+                (file, modified)
+            })
+            .collect()
+    }
+
+    /// Re-read the directory if a file was added, removed or modified.
+    /// Returns whether anything changed.
+    pub fn rescan(&mut self) -> bool {
+        let stamps = self.current_stamps();
+        if stamps == self.stamps && !stamps.is_empty() {
+            return false;
+        }
+
+        self.functions.clear();
+        self.errors.clear();
+        // Declarations are emitted under their WGSL names, so one name declared by two files
+        // can't be in the same shader: the first file keeps it and the second loses the
+        // functions that need it. The built-in nodes' helpers come before any file.
+        let mut declared: HashMap<String, String> = builtin_helper_names()
+            .into_iter()
+            .map(|name| (name, BUILTIN_DECLARER.to_string()))
+            .collect();
+        for file in stamps.keys() {
+            let parsed = std::fs::read_to_string(self.root.join(file))
+                .map_err(|e| e.to_string())
+                .and_then(|source| parse_library_file(file, &source));
+            let functions = match parsed {
+                Ok(functions) => functions,
+                Err(error) => {
+                    self.errors.push((file.clone(), error));
+                    continue;
+                }
+            };
+            let mut clashes: Vec<String> = Vec::new();
+            for function in functions {
+                let taken: Vec<String> = function
+                    .declared_names()
+                    .filter_map(|name| {
+                        let other = declared.get(name).filter(|other| *other != file)?;
+                        Some(format!("`{}` is already declared in {}", name, other))
+                    })
+                    .collect();
+                if taken.is_empty() {
+                    for name in function.declared_names() {
+                        declared.insert(name.to_string(), file.clone());
+                    }
+                    self.functions.push(function);
+                } else {
+                    clashes.extend(taken);
+                }
+            }
+            clashes.sort();
+            clashes.dedup();
+            if !clashes.is_empty() {
+                self.errors.push((file.clone(), clashes.join(", ")));
+            }
+        }
+        let changed = stamps != self.stamps;
+        self.stamps = stamps;
+        changed
+    }
+
+    pub fn find(&self, file: &str, name: &str) -> Option<&LibraryFunction> {
+        self.functions
+            .iter()
+            .find(|function| function.file == file && function.signature.name == name)
+    }
+
+    /// Bring every Library node in `graph` up to date with the scanned files, keeping its
+    /// pins (and connections) by index. Nodes whose function is gone keep their last WGSL.
+    /// Returns the nodes that changed.
+    pub fn sync_graph(&self, graph: &mut NodeGraph) -> Vec<NodeId> {
+        let mut changed = Vec::new();
+        for (node_id, node) in graph.nodes.iter_mut() {
+            if let NodeKind::Library(function) = &mut node.kind
+                && let Some(current) = self.find(&function.file, &function.signature.name)
+                && current != function
+            {
+                *function = current.clone();
+                changed.push(*node_id);
+            }
+        }
+        changed.sort_by_key(|node_id| node_id.0);
+        for node_id in &changed {
+            NodeFactory::sync_pins(graph, *node_id);
+        }
+        changed
+    }
+
+    /// Categories in first-seen order, each with its functions
+    pub fn categories(&self) -> Vec<(&str, Vec<&LibraryFunction>)> {
+        let mut categories: Vec<(&str, Vec<&LibraryFunction>)> = Vec::new();
+        for function in &self.functions {
+            match categories
+                .iter_mut()
+                .find(|(category, _)| *category == function.category)
+            {
+                Some((_, functions)) => functions.push(function),
+                None => categories.push((&function.category, vec![function])),
+            }
+        }
+        categories
+    }
+}
+
+impl LibraryFunction {
+    /// WGSL names of the declarations the function brings into a shader, itself included
+    fn declared_names(&self) -> impl Iterator<Item = &str> {
+        self.helpers
+            .iter()
+            .filter_map(|helper| Some(helper.name.rsplit_once("::")?.1))
+    }
+}
+
+impl ShaderNode for LibraryFunction {
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn signature(&self) -> NodeSignature {
+        self.signature.node_signature()
+    }
+
+    fn emit_wgsl(&self, inputs: &[String]) -> Vec<String> {
+        self.signature.emit_call(inputs)
+    }
+
+    fn wgsl_helpers(&self) -> Vec<WgslHelper> {
+        self.helpers.clone()
+    }
+
+    fn stages(&self) -> &'static [ShaderStage] {
+        self.signature.stages()
+    }
+
+    /// The CPU can't run library WGSL - every output is zero
+    fn evaluate(&self, _inputs: &[Value], _ctx: &EvalContext) -> Vec<Value> {
+        self.signature.zero_outputs()
+    }
+}
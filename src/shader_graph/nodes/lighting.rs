@@ -0,0 +1,264 @@
+use crate::shader_graph::inputs::{EvalContext, ShaderInput};
+use crate::shader_graph::node_kind::{NodeSignature, PinSpec, ShaderNode, WgslHelper};
+use crate::shader_graph::types::{PinType, Value};
+use bevy::prelude::Vec3;
+use serde::{Deserialize, Serialize};
+
+/// Stylised and classic lighting terms. Light, normal and view pins read the preview's
+/// `lights` uniform and the surface when unconnected; directions point away from the surface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LightingNode {
+    /// Directional light, point light and ambient colour of the scene
+    Light,
+    /// Diffuse `color * max(N.L, 0)`
+    Lambert,
+    /// Specular highlight from the half vector, zero where the surface faces away from the light
+    BlinnPhong,
+    /// Schlick's approximation, `F0 + (1 - F0) * (1 - N.V)^power`
+    Fresnel,
+    /// Glow towards the silhouette, `color * intensity * (1 - N.V)^power`
+    Rim,
+    /// Lambert quantised into `Steps` flat bands
+    ToonRamp,
+}
+
+impl LightingNode {
+    pub const ALL: [LightingNode; 6] = [
+        LightingNode::Light,
+        LightingNode::Lambert,
+        LightingNode::BlinnPhong,
+        LightingNode::Fresnel,
+        LightingNode::Rim,
+        LightingNode::ToonRamp,
+    ];
+}
+
+const LIGHT_LAMBERT_WGSL: &str = "fn light_lambert(normal: vec3<f32>, light_dir: vec3<f32>, light_color: vec3<f32>) -> vec3<f32> {
+    return light_color * max(dot(normalize(normal), normalize(light_dir)), 0.0);
+}";
+
+const LIGHT_BLINN_PHONG_WGSL: &str = "fn light_blinn_phong(normal: vec3<f32>, light_dir: vec3<f32>, light_color: vec3<f32>, view_dir: vec3<f32>, shininess: f32) -> vec3<f32> {
+    let n = normalize(normal);
+    let l = normalize(light_dir);
+    let h = normalize(l + normalize(view_dir));
+    let facing = select(0.0, 1.0, dot(n, l) > 0.0);
+    return light_color * pow(max(dot(n, h), 0.0), max(shininess, 1.0)) * facing;
+}";
+
+const LIGHT_FRESNEL_WGSL: &str =
+    "fn light_fresnel(normal: vec3<f32>, view_dir: vec3<f32>, f0: f32, power: f32) -> f32 {
+    let facing = 1.0 - clamp(dot(normalize(normal), normalize(view_dir)), 0.0, 1.0);
+    return f0 + (1.0 - f0) * pow(facing, power);
+}";
+
+const LIGHT_RIM_WGSL: &str = "fn light_rim(normal: vec3<f32>, view_dir: vec3<f32>, color: vec3<f32>, power: f32, intensity: f32) -> vec3<f32> {
+    let facing = 1.0 - clamp(dot(normalize(normal), normalize(view_dir)), 0.0, 1.0);
+    return color * intensity * pow(facing, power);
+}";
+
+const LIGHT_TOON_WGSL: &str = "fn light_toon(normal: vec3<f32>, light_dir: vec3<f32>, light_color: vec3<f32>, steps: f32) -> vec3<f32> {
+    let bands = max(floor(steps), 1.0);
+    let n_dot_l = max(dot(normalize(normal), normalize(light_dir)), 0.0);
+    return light_color * min(floor(n_dot_l * bands) / max(bands - 1.0, 1.0), 1.0);
+}";
+
+fn normal_in() -> PinSpec {
+    PinSpec::input_from("Normal", ShaderInput::WorldNormal)
+}
+
+fn light_dir_in() -> PinSpec {
+    PinSpec::input_from("Light Direction", ShaderInput::LightDirection)
+}
+
+fn light_color_in() -> PinSpec {
+    PinSpec::input_from("Light Color", ShaderInput::LightColor)
+}
+
+fn view_dir_in() -> PinSpec {
+    PinSpec::input_from("View Direction", ShaderInput::ViewDirection)
+}
+
+fn float_in(label: &str, default: f32) -> PinSpec {
+    PinSpec::input(label, PinType::Float, Value::Float(default))
+}
+
+impl ShaderNode for LightingNode {
+    fn title(&self) -> String {
+        match self {
+            LightingNode::Light => "Light",
+            LightingNode::Lambert => "Lambert",
+            LightingNode::BlinnPhong => "Blinn-Phong",
+            LightingNode::Fresnel => "Fresnel (Schlick)",
+            LightingNode::Rim => "Rim Light",
+            LightingNode::ToonRamp => "Toon Ramp",
+        }
+        .to_string()
+    }
+
+    fn signature(&self) -> NodeSignature {
+        match self {
+            LightingNode::Light => NodeSignature {
+                inputs: vec![],
+                outputs: vec![
+                    PinSpec::output("Direction", PinType::Vec3),
+                    PinSpec::output("Color", PinType::Vec3),
+                    PinSpec::output("Point Direction", PinType::Vec3),
+                    PinSpec::output("Point Color", PinType::Vec3),
+                    PinSpec::output("Ambient", PinType::Vec3),
+                ],
+            },
+            LightingNode::Lambert => NodeSignature {
+                inputs: vec![normal_in(), light_dir_in(), light_color_in()],
+                outputs: vec![PinSpec::output("Diffuse", PinType::Vec3)],
+            },
+            LightingNode::BlinnPhong => NodeSignature {
+                inputs: vec![
+                    normal_in(),
+                    light_dir_in(),
+                    light_color_in(),
+                    view_dir_in(),
+                    float_in("Shininess", 32.0),
+                ],
+                outputs: vec![PinSpec::output("Specular", PinType::Vec3)],
+            },
+            LightingNode::Fresnel => NodeSignature {
+                inputs: vec![
+                    normal_in(),
+                    view_dir_in(),
+                    float_in("F0", 0.04),
+                    float_in("Power", 5.0),
+                ],
+                outputs: vec![PinSpec::output("Out", PinType::Float)],
+            },
+            LightingNode::Rim => NodeSignature {
+                inputs: vec![
+                    normal_in(),
+                    view_dir_in(),
+                    PinSpec::input("Color", PinType::Vec3, Value::Vec3(Vec3::ONE)),
+                    float_in("Power", 3.0),
+                    float_in("Intensity", 1.0),
+                ],
+                outputs: vec![PinSpec::output("Out", PinType::Vec3)],
+            },
+            LightingNode::ToonRamp => NodeSignature {
+                inputs: vec![
+                    normal_in(),
+                    light_dir_in(),
+                    light_color_in(),
+                    float_in("Steps", 3.0),
+                ],
+                outputs: vec![PinSpec::output("Out", PinType::Vec3)],
+            },
+        }
+    }
+
+    fn emit_wgsl(&self, inputs: &[String]) -> Vec<String> {
+        let call = |name: &str| format!("{}({})", name, inputs.join(", "));
+        match self {
+            LightingNode::Light => self
+                .shader_inputs()
+                .into_iter()
+                .map(ShaderInput::wgsl_expr)
+                .collect(),
+            LightingNode::Lambert => vec![call("light_lambert")],
+            LightingNode::BlinnPhong => vec![call("light_blinn_phong")],
+            LightingNode::Fresnel => vec![call("light_fresnel")],
+            LightingNode::Rim => vec![call("light_rim")],
+            LightingNode::ToonRamp => vec![call("light_toon")],
+        }
+    }
+
+    fn wgsl_helpers(&self) -> Vec<WgslHelper> {
+        let helper = match self {
+            LightingNode::Light => return Vec::new(),
+            LightingNode::Lambert => WgslHelper::new("light_lambert", LIGHT_LAMBERT_WGSL),
+            LightingNode::BlinnPhong => {
+                WgslHelper::new("light_blinn_phong", LIGHT_BLINN_PHONG_WGSL)
+            }
+            LightingNode::Fresnel => WgslHelper::new("light_fresnel", LIGHT_FRESNEL_WGSL),
+            LightingNode::Rim => WgslHelper::new("light_rim", LIGHT_RIM_WGSL),
+            LightingNode::ToonRamp => WgslHelper::new("light_toon", LIGHT_TOON_WGSL),
+        };
+        vec![helper]
+    }
+
+    fn shader_inputs(&self) -> Vec<ShaderInput> {
+        match self {
+            LightingNode::Light => vec![
+                ShaderInput::LightDirection,
+                ShaderInput::LightColor,
+                ShaderInput::PointLightDirection,
+                ShaderInput::PointLightColor,
+                ShaderInput::AmbientColor,
+            ],
+            _ => Vec::new(),
+        }
+    }
+
+    fn evaluate(&self, inputs: &[Value], ctx: &EvalContext) -> Vec<Value> {
+        let vec3 = |i: usize| inputs[i].as_vec3();
+        let float = |i: usize| inputs[i].as_f32();
+        match self {
+            LightingNode::Light => self
+                .shader_inputs()
+                .into_iter()
+                .map(|input| input.evaluate(ctx))
+                .collect(),
+            LightingNode::Lambert => vec![Value::Vec3(lambert(vec3(0), vec3(1), vec3(2)))],
+            LightingNode::BlinnPhong => vec![Value::Vec3(blinn_phong(
+                vec3(0),
+                vec3(1),
+                vec3(2),
+                vec3(3),
+                float(4),
+            ))],
+            LightingNode::Fresnel => {
+                vec![Value::Float(fresnel(vec3(0), vec3(1), float(2), float(3)))]
+            }
+            LightingNode::Rim => {
+                let facing = 1.0 - vec3(0).normalize_or_zero().dot(vec3(1).normalize_or_zero());
+                let rim = facing.clamp(0.0, 1.0).powf(float(3));
+                vec![Value::Vec3(vec3(2) * float(4) * rim)]
+            }
+            LightingNode::ToonRamp => {
+                vec![Value::Vec3(toon_ramp(vec3(0), vec3(1), vec3(2), float(3)))]
+            }
+        }
+    }
+}
+
+pub fn lambert(normal: Vec3, light_dir: Vec3, light_color: Vec3) -> Vec3 {
+    light_color * n_dot(normal, light_dir)
+}
+
+pub fn blinn_phong(
+    normal: Vec3,
+    light_dir: Vec3,
+    light_color: Vec3,
+    view_dir: Vec3,
+    shininess: f32,
+) -> Vec3 {
+    let n = normal.normalize_or_zero();
+    let l = light_dir.normalize_or_zero();
+    if n.dot(l) <= 0.0 {
+        return Vec3::ZERO;
+    }
+    let h = (l + view_dir.normalize_or_zero()).normalize_or_zero();
+    light_color * n.dot(h).max(0.0).powf(shininess.max(1.0))
+}
+
+pub fn fresnel(normal: Vec3, view_dir: Vec3, f0: f32, power: f32) -> f32 {
+    let facing = 1.0 - n_dot(normal, view_dir).min(1.0);
+    f0 + (1.0 - f0) * facing.powf(power)
+}
+
+pub fn toon_ramp(normal: Vec3, light_dir: Vec3, light_color: Vec3, steps: f32) -> Vec3 {
+    let bands = steps.floor().max(1.0);
+    let band = (n_dot(normal, light_dir) * bands).floor() / (bands - 1.0).max(1.0);
+    light_color * band.min(1.0)
+}
+
+/// `max(dot(normalize(a), normalize(b)), 0)`
+fn n_dot(a: Vec3, b: Vec3) -> f32 {
+    a.normalize_or_zero().dot(b.normalize_or_zero()).max(0.0)
+}
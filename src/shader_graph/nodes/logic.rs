@@ -0,0 +1,329 @@
+use crate::shader_graph::inputs::EvalContext;
+use crate::shader_graph::node_kind::{NodeSignature, PinSpec, ShaderNode, WgslHelper};
+use crate::shader_graph::types::{PinType, Value};
+use serde::{Deserialize, Serialize};
+
+/// Types a comparison, Select or Switch node can operate on
+pub const VALUE_TYPES: [PinType; 4] = [PinType::Float, PinType::Vec2, PinType::Vec3, PinType::Vec4];
+
+/// Types a boolean operation can operate on
+pub const BOOLEAN_TYPES: [PinType; 4] = [
+    PinType::Bool,
+    PinType::BVec2,
+    PinType::BVec3,
+    PinType::BVec4,
+];
+
+/// Case inputs a Switch node can have
+pub const SWITCH_CASES: std::ops::RangeInclusive<usize> = 2..=8;
+
+/// Comparison operators. Vectors compare component-wise into a `vecN<bool>` mask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CompareOp {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl CompareOp {
+    pub const ALL: [CompareOp; 6] = [
+        CompareOp::Equal,
+        CompareOp::NotEqual,
+        CompareOp::Less,
+        CompareOp::LessEqual,
+        CompareOp::Greater,
+        CompareOp::GreaterEqual,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CompareOp::Equal => "Equal",
+            CompareOp::NotEqual => "Not Equal",
+            CompareOp::Less => "Less",
+            CompareOp::LessEqual => "Less Or Equal",
+            CompareOp::Greater => "Greater",
+            CompareOp::GreaterEqual => "Greater Or Equal",
+        }
+    }
+
+    /// WGSL operator, also the output pin's label
+    pub fn symbol(self) -> &'static str {
+        match self {
+            CompareOp::Equal => "==",
+            CompareOp::NotEqual => "!=",
+            CompareOp::Less => "<",
+            CompareOp::LessEqual => "<=",
+            CompareOp::Greater => ">",
+            CompareOp::GreaterEqual => ">=",
+        }
+    }
+
+    pub fn apply(self, a: f32, b: f32) -> bool {
+        match self {
+            CompareOp::Equal => a == b,
+            CompareOp::NotEqual => a != b,
+            CompareOp::Less => a < b,
+            CompareOp::LessEqual => a <= b,
+            CompareOp::Greater => a > b,
+            CompareOp::GreaterEqual => a >= b,
+        }
+    }
+}
+
+/// Operations on `bool` and `vecN<bool>`. `Any` and `All` reduce a mask to one `bool`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BooleanOp {
+    And,
+    Or,
+    Not,
+    Any,
+    All,
+}
+
+impl BooleanOp {
+    pub const ALL: [BooleanOp; 5] = [
+        BooleanOp::And,
+        BooleanOp::Or,
+        BooleanOp::Not,
+        BooleanOp::Any,
+        BooleanOp::All,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BooleanOp::And => "And",
+            BooleanOp::Or => "Or",
+            BooleanOp::Not => "Not",
+            BooleanOp::Any => "Any",
+            BooleanOp::All => "All",
+        }
+    }
+
+    /// Whether the operation takes a second operand
+    pub fn is_binary(self) -> bool {
+        matches!(self, BooleanOp::And | BooleanOp::Or)
+    }
+}
+
+/// Comparisons, boolean logic and branching. Each node carries the type it operates on, picked
+/// in its body, so vector comparisons stay masks instead of collapsing to one `bool`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LogicNode {
+    /// `A op B`, one of `VALUE_TYPES` in, the matching `bool` or mask out
+    Compare(CompareOp, PinType),
+    /// One of `BOOLEAN_TYPES` in
+    Boolean(BooleanOp, PinType),
+    /// WGSL `select()`: `True` where the condition holds, `False` elsewhere. Vectors take a
+    /// mask, choosing per component.
+    Select(PinType),
+    /// The case input picked by `Index`, clamped to the last case
+    Switch { cases: usize, ty: PinType },
+}
+
+impl LogicNode {
+    /// Type the node operates on
+    pub fn ty(self) -> PinType {
+        match self {
+            LogicNode::Compare(_, ty)
+            | LogicNode::Boolean(_, ty)
+            | LogicNode::Select(ty)
+            | LogicNode::Switch { ty, .. } => ty,
+        }
+    }
+
+    pub fn ty_mut(&mut self) -> &mut PinType {
+        match self {
+            LogicNode::Compare(_, ty)
+            | LogicNode::Boolean(_, ty)
+            | LogicNode::Select(ty)
+            | LogicNode::Switch { ty, .. } => ty,
+        }
+    }
+
+    /// Types the body lets the node switch between
+    pub fn type_options(self) -> [PinType; 4] {
+        match self {
+            LogicNode::Boolean(..) => BOOLEAN_TYPES,
+            _ => VALUE_TYPES,
+        }
+    }
+
+    fn switch_helper_name(cases: usize, ty: PinType) -> String {
+        let ty = match ty {
+            PinType::Vec2 => "vec2",
+            PinType::Vec3 => "vec3",
+            PinType::Vec4 => "vec4",
+            _ => "f32",
+        };
+        format!("switch_{}_{}", ty, cases)
+    }
+
+    /// `switch` over the clamped index. The result goes through a `var` so every path returns.
+    fn switch_helper(cases: usize, ty: PinType) -> WgslHelper {
+        let name = Self::switch_helper_name(cases, ty);
+        let wgsl_type = ty.wgsl_type();
+        let params: Vec<String> = (0..cases)
+            .map(|i| format!("case{}: {}", i, wgsl_type))
+            .collect();
+        let arms: String = (0..cases - 1)
+            .map(|i| format!("        case {}u: {{ result = case{}; }}\n", i, i))
+            .collect();
+        let source = format!(
+            "fn {name}(index: f32, {params}) -> {ty} {{\n    var result = case{last};\n    switch min(u32(index), {last}u) {{\n{arms}        default: {{}}\n    }}\n    return result;\n}}",
+            name = name,
+            params = params.join(", "),
+            ty = wgsl_type,
+            last = cases - 1,
+            arms = arms
+        );
+        WgslHelper::new(&name, &source)
+    }
+}
+
+/// Components of a value as booleans
+fn bits(value: Value) -> Vec<bool> {
+    value.components().iter().map(|&c| c != 0.0).collect()
+}
+
+fn from_bits(ty: PinType, bits: &[bool]) -> Value {
+    let components: Vec<f32> = bits.iter().map(|&b| if b { 1.0 } else { 0.0 }).collect();
+    Value::from_components(ty, &components)
+}
+
+impl ShaderNode for LogicNode {
+    fn title(&self) -> String {
+        match self {
+            LogicNode::Compare(op, _) => op.label(),
+            LogicNode::Boolean(op, _) => op.label(),
+            LogicNode::Select(_) => "Select",
+            LogicNode::Switch { .. } => "Switch",
+        }
+        .to_string()
+    }
+
+    fn signature(&self) -> NodeSignature {
+        let ty = self.ty();
+        match *self {
+            LogicNode::Compare(op, _) => NodeSignature {
+                inputs: vec![
+                    PinSpec::input("A", ty, Value::Float(0.0)),
+                    PinSpec::input("B", ty, Value::Float(0.0)),
+                ],
+                outputs: vec![PinSpec::output(op.symbol(), ty.bool_type())],
+            },
+            LogicNode::Boolean(op, _) => {
+                let mut inputs = vec![PinSpec::input("A", ty, Value::Bool(false))];
+                if op.is_binary() {
+                    inputs.push(PinSpec::input("B", ty, Value::Bool(false)));
+                }
+                let out = match op {
+                    BooleanOp::Any | BooleanOp::All => PinType::Bool,
+                    _ => ty,
+                };
+                NodeSignature {
+                    inputs,
+                    outputs: vec![PinSpec::output(op.label(), out)],
+                }
+            }
+            LogicNode::Select(_) => NodeSignature {
+                inputs: vec![
+                    PinSpec::input("False", ty, Value::Float(0.0)),
+                    PinSpec::input("True", ty, Value::Float(1.0)),
+                    PinSpec::input("Condition", ty.bool_type(), Value::Bool(false)),
+                ],
+                outputs: vec![PinSpec::output("Out", ty)],
+            },
+            LogicNode::Switch { cases, .. } => {
+                let mut inputs = vec![PinSpec::input("Index", PinType::Float, Value::Float(0.0))];
+                inputs.extend(
+                    (0..cases)
+                        .map(|i| PinSpec::input(&format!("Case {}", i), ty, Value::Float(0.0))),
+                );
+                NodeSignature {
+                    inputs,
+                    outputs: vec![PinSpec::output("Out", ty)],
+                }
+            }
+        }
+    }
+
+    fn emit_wgsl(&self, inputs: &[String]) -> Vec<String> {
+        let expr = match *self {
+            LogicNode::Compare(op, _) => format!("({} {} {})", inputs[0], op.symbol(), inputs[1]),
+            // `&&` and `||` only exist for scalars; `&` and `|` work component-wise on masks
+            LogicNode::Boolean(op, ty) => match op {
+                BooleanOp::And if ty == PinType::Bool => {
+                    format!("({} && {})", inputs[0], inputs[1])
+                }
+                BooleanOp::Or if ty == PinType::Bool => format!("({} || {})", inputs[0], inputs[1]),
+                BooleanOp::And => format!("({} & {})", inputs[0], inputs[1]),
+                BooleanOp::Or => format!("({} | {})", inputs[0], inputs[1]),
+                BooleanOp::Not => format!("(!{})", inputs[0]),
+                BooleanOp::Any | BooleanOp::All if ty == PinType::Bool => inputs[0].clone(),
+                BooleanOp::Any => format!("any({})", inputs[0]),
+                BooleanOp::All => format!("all({})", inputs[0]),
+            },
+            LogicNode::Select(_) => format!("select({}, {}, {})", inputs[0], inputs[1], inputs[2]),
+            LogicNode::Switch { cases, ty } => format!(
+                "{}({})",
+                Self::switch_helper_name(cases, ty),
+                inputs.join(", ")
+            ),
+        };
+        vec![expr]
+    }
+
+    fn wgsl_helpers(&self) -> Vec<WgslHelper> {
+        match *self {
+            LogicNode::Switch { cases, ty } => vec![Self::switch_helper(cases, ty)],
+            _ => Vec::new(),
+        }
+    }
+
+    fn evaluate(&self, inputs: &[Value], _ctx: &EvalContext) -> Vec<Value> {
+        let value = match *self {
+            LogicNode::Compare(op, ty) => {
+                let (a, b) = (inputs[0].components(), inputs[1].components());
+                let mask: Vec<bool> = a.iter().zip(&b).map(|(&a, &b)| op.apply(a, b)).collect();
+                from_bits(ty.bool_type(), &mask)
+            }
+            LogicNode::Boolean(op, ty) => {
+                let a = bits(inputs[0]);
+                match op {
+                    BooleanOp::And | BooleanOp::Or => {
+                        let b = bits(inputs[1]);
+                        let mask: Vec<bool> = a
+                            .iter()
+                            .zip(&b)
+                            .map(|(&a, &b)| if op == BooleanOp::And { a && b } else { a || b })
+                            .collect();
+                        from_bits(ty, &mask)
+                    }
+                    BooleanOp::Not => {
+                        let mask: Vec<bool> = a.iter().map(|&a| !a).collect();
+                        from_bits(ty, &mask)
+                    }
+                    BooleanOp::Any => Value::Bool(a.iter().any(|&a| a)),
+                    BooleanOp::All => Value::Bool(a.iter().all(|&a| a)),
+                }
+            }
+            LogicNode::Select(ty) => {
+                let (f, t) = (inputs[0].components(), inputs[1].components());
+                let condition = bits(inputs[2]);
+                let picked: Vec<f32> = (0..f.len())
+                    .map(|i| if condition[i] { t[i] } else { f[i] })
+                    .collect();
+                Value::from_components(ty, &picked)
+            }
+            // WGSL's `u32(f32)` truncates and saturates, NaN becomes zero
+            LogicNode::Switch { cases, .. } => {
+                let index = (inputs[0].as_f32() as u32 as usize).min(cases - 1);
+                inputs[1 + index]
+            }
+        };
+        vec![value]
+    }
+}
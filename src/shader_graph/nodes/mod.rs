@@ -0,0 +1,13 @@
+pub mod color;
+pub mod compute;
+pub mod custom;
+pub mod geometry;
+pub mod gradient;
+pub mod library;
+pub mod lighting;
+pub mod logic;
+pub mod normal;
+pub mod subgraph;
+pub mod texture;
+pub mod time;
+pub mod uv;
@@ -0,0 +1,376 @@
+use crate::shader_graph::inputs::{EvalContext, ShaderInput};
+use crate::shader_graph::node_kind::{NodeSignature, PinSpec, ShaderNode, ShaderStage, WgslHelper};
+use crate::shader_graph::nodes::texture::SamplerState;
+use crate::shader_graph::types::{PinType, Value};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How Normal Blend combines a detail normal `B` onto a base normal `A`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NormalBlendMode {
+    /// Reoriented Normal Mapping - rotates `B` onto the surface `A` describes
+    Reoriented,
+    /// Adds the slopes and multiplies the heights; cheaper, slightly flatter
+    Whiteout,
+}
+
+impl NormalBlendMode {
+    pub const ALL: [NormalBlendMode; 2] = [NormalBlendMode::Reoriented, NormalBlendMode::Whiteout];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            NormalBlendMode::Reoriented => "Reoriented",
+            NormalBlendMode::Whiteout => "Whiteout",
+        }
+    }
+}
+
+/// Surface detail: normal maps, height-derived normals, triplanar projection and parallax.
+/// Tangent-space vectors use the mesh tangent frame from the geometry inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NormalNode {
+    /// Decode a tangent-space normal map, `Strength` scales the slopes
+    FromTexture(SamplerState),
+    /// Normal of a height field, from screen-space derivatives of `Height`
+    FromHeight,
+    /// Combine two tangent-space normals
+    Blend(NormalBlendMode),
+    /// Sample a texture projected along the three world axes, blended by the normal
+    Triplanar(SamplerState),
+    /// Shift UVs along the tangent-space view direction by a height
+    ParallaxOffset,
+}
+
+const NORMAL_UNPACK_WGSL: &str = "fn normal_unpack(color: vec4<f32>, strength: f32) -> vec3<f32> {
+    let n = color.xyz * 2.0 - 1.0;
+    return normalize(vec3<f32>(n.xy * strength, mix(1.0, n.z, clamp(strength, 0.0, 1.0))));
+}";
+
+const NORMAL_TANGENT_TO_WORLD_WGSL: &str = "fn normal_tangent_to_world(v: vec3<f32>, normal: vec3<f32>, tangent: vec3<f32>, tangent_sign: f32) -> vec3<f32> {
+    let bitangent = cross(normal, tangent) * tangent_sign;
+    return normalize(v.x * tangent + v.y * bitangent + v.z * normal);
+}";
+
+const NORMAL_WORLD_TO_TANGENT_WGSL: &str = "fn normal_world_to_tangent(v: vec3<f32>, normal: vec3<f32>, tangent: vec3<f32>, tangent_sign: f32) -> vec3<f32> {
+    let bitangent = cross(normal, tangent) * tangent_sign;
+    return vec3<f32>(dot(v, tangent), dot(v, bitangent), dot(v, normal));
+}";
+
+/// Surface gradient method (Mikkelsen, "Bump Mapping Unparametrized Surfaces on the GPU")
+const NORMAL_FROM_HEIGHT_WGSL: &str = "fn normal_from_height(height: f32, strength: f32, position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    let dpx = dpdx(position);
+    let dpy = dpdy(position);
+    let cross_x = cross(normal, dpx);
+    let cross_y = cross(dpy, normal);
+    let det = dot(dpx, cross_y);
+    let surface = sign(det) / max(abs(det), 1.0e-7);
+    let gradient = surface * (dpdx(height) * cross_y + dpdy(height) * cross_x);
+    return normalize(normal - strength * gradient);
+}";
+
+const NORMAL_BLEND_RNM_WGSL: &str = "fn normal_blend_rnm(a: vec3<f32>, b: vec3<f32>) -> vec3<f32> {
+    let t = a + vec3<f32>(0.0, 0.0, 1.0);
+    let u = b * vec3<f32>(-1.0, -1.0, 1.0);
+    return normalize(t * dot(t, u) / t.z - u);
+}";
+
+const NORMAL_BLEND_WHITEOUT_WGSL: &str =
+    "fn normal_blend_whiteout(a: vec3<f32>, b: vec3<f32>) -> vec3<f32> {
+    return normalize(vec3<f32>(a.xy + b.xy, a.z * b.z));
+}";
+
+const TRIPLANAR_SAMPLE_WGSL: &str = "fn triplanar_sample(t: texture_2d<f32>, s: sampler, position: vec3<f32>, normal: vec3<f32>, tile: f32, sharpness: f32) -> vec4<f32> {
+    let weights = pow(abs(normal), vec3<f32>(sharpness));
+    let w = weights / max(weights.x + weights.y + weights.z, 1.0e-5);
+    let x = textureSample(t, s, position.zy * tile);
+    let y = textureSample(t, s, position.xz * tile);
+    let z = textureSample(t, s, position.xy * tile);
+    return x * w.x + y * w.y + z * w.z;
+}";
+
+const PARALLAX_OFFSET_WGSL: &str = "fn parallax_offset(uv: vec2<f32>, height: f32, amplitude: f32, view_tangent: vec3<f32>) -> vec2<f32> {
+    let h = height * amplitude - amplitude * 0.5;
+    let v = normalize(view_tangent);
+    return uv + h * v.xy / (v.z + 0.42);
+}";
+
+/// Tangent frame of the fragment as WGSL arguments: normal, tangent, bitangent sign
+fn tangent_frame_wgsl() -> String {
+    format!(
+        "{}, {}, {}",
+        ShaderInput::WorldNormal.wgsl_expr(),
+        ShaderInput::WorldTangent.wgsl_expr(),
+        ShaderInput::TangentSign.wgsl_expr()
+    )
+}
+
+const TANGENT_FRAME_INPUTS: [ShaderInput; 3] = [
+    ShaderInput::WorldNormal,
+    ShaderInput::WorldTangent,
+    ShaderInput::TangentSign,
+];
+
+/// Tangent frame of the fragment described by `ctx`
+fn tangent_frame(ctx: &EvalContext) -> (Vec3, Vec3, f32) {
+    (
+        ctx.world_normal.normalize_or_zero(),
+        ctx.world_tangent.truncate().normalize_or_zero(),
+        ctx.world_tangent.w,
+    )
+}
+
+/// Decode a normal-map texel into a tangent-space normal
+pub fn unpack_normal(color: Vec4, strength: f32) -> Vec3 {
+    let n = color.truncate() * 2.0 - 1.0;
+    let z = 1.0 + (n.z - 1.0) * strength.clamp(0.0, 1.0);
+    (n.truncate() * strength).extend(z).normalize_or_zero()
+}
+
+pub fn tangent_to_world(v: Vec3, normal: Vec3, tangent: Vec3, tangent_sign: f32) -> Vec3 {
+    let bitangent = normal.cross(tangent) * tangent_sign;
+    (v.x * tangent + v.y * bitangent + v.z * normal).normalize_or_zero()
+}
+
+pub fn world_to_tangent(v: Vec3, normal: Vec3, tangent: Vec3, tangent_sign: f32) -> Vec3 {
+    let bitangent = normal.cross(tangent) * tangent_sign;
+    Vec3::new(v.dot(tangent), v.dot(bitangent), v.dot(normal))
+}
+
+pub fn blend_reoriented(a: Vec3, b: Vec3) -> Vec3 {
+    let t = a + Vec3::Z;
+    let u = b * Vec3::new(-1.0, -1.0, 1.0);
+    (t * t.dot(u) / t.z - u).normalize_or_zero()
+}
+
+pub fn blend_whiteout(a: Vec3, b: Vec3) -> Vec3 {
+    (a.truncate() + b.truncate())
+        .extend(a.z * b.z)
+        .normalize_or_zero()
+}
+
+pub fn parallax_offset(uv: Vec2, height: f32, amplitude: f32, view_tangent: Vec3) -> Vec2 {
+    let h = height * amplitude - amplitude * 0.5;
+    let v = view_tangent.normalize_or_zero();
+    uv + h * v.truncate() / (v.z + 0.42)
+}
+
+fn float_in(label: &str, default: f32) -> PinSpec {
+    PinSpec::input(label, PinType::Float, Value::Float(default))
+}
+
+fn flat_normal_in(label: &str) -> PinSpec {
+    PinSpec::input(label, PinType::Vec3, Value::Vec3(Vec3::Z))
+}
+
+impl ShaderNode for NormalNode {
+    fn title(&self) -> String {
+        match self {
+            NormalNode::FromTexture(_) => "Normal From Texture".to_string(),
+            NormalNode::FromHeight => "Normal From Height".to_string(),
+            NormalNode::Blend(mode) => format!("Normal Blend ({})", mode.label()),
+            NormalNode::Triplanar(_) => "Triplanar".to_string(),
+            NormalNode::ParallaxOffset => "Parallax Offset".to_string(),
+        }
+    }
+
+    fn signature(&self) -> NodeSignature {
+        let normal_outputs = vec![
+            PinSpec::output("Tangent", PinType::Vec3),
+            PinSpec::output("World", PinType::Vec3),
+        ];
+        match self {
+            NormalNode::FromTexture(_) => NodeSignature {
+                inputs: vec![
+                    PinSpec::input("Texture", PinType::Texture2D, Value::Texture),
+                    PinSpec::input_from("UV", ShaderInput::Uv),
+                    float_in("Strength", 1.0),
+                ],
+                outputs: normal_outputs,
+            },
+            NormalNode::FromHeight => NodeSignature {
+                inputs: vec![float_in("Height", 0.0), float_in("Strength", 1.0)],
+                outputs: normal_outputs,
+            },
+            NormalNode::Blend(_) => NodeSignature {
+                inputs: vec![flat_normal_in("A"), flat_normal_in("B")],
+                outputs: vec![PinSpec::output("Out", PinType::Vec3)],
+            },
+            NormalNode::Triplanar(_) => NodeSignature {
+                inputs: vec![
+                    PinSpec::input("Texture", PinType::Texture2D, Value::Texture),
+                    PinSpec::input_from("Position", ShaderInput::WorldPosition),
+                    PinSpec::input_from("Normal", ShaderInput::WorldNormal),
+                    float_in("Tile", 1.0),
+                    float_in("Sharpness", 4.0),
+                ],
+                outputs: vec![PinSpec::output("RGBA", PinType::Vec4)],
+            },
+            NormalNode::ParallaxOffset => NodeSignature {
+                inputs: vec![
+                    PinSpec::input_from("UV", ShaderInput::Uv),
+                    float_in("Height", 0.5),
+                    float_in("Amplitude", 0.05),
+                ],
+                outputs: vec![PinSpec::output("UV", PinType::Vec2)],
+            },
+        }
+    }
+
+    fn emit_wgsl(&self, inputs: &[String]) -> Vec<String> {
+        let frame = tangent_frame_wgsl();
+        match self {
+            NormalNode::FromTexture(sampler) => {
+                let tangent = format!(
+                    "normal_unpack(textureSample({}, {}, {}), {})",
+                    inputs[0],
+                    sampler.wgsl_name(),
+                    inputs[1],
+                    inputs[2]
+                );
+                let world = format!("normal_tangent_to_world({}, {})", tangent, frame);
+                vec![tangent, world]
+            }
+            NormalNode::FromHeight => {
+                let world = format!(
+                    "normal_from_height({}, {}, {}, {})",
+                    inputs[0],
+                    inputs[1],
+                    ShaderInput::WorldPosition.wgsl_expr(),
+                    ShaderInput::WorldNormal.wgsl_expr()
+                );
+                let tangent = format!("normal_world_to_tangent({}, {})", world, frame);
+                vec![tangent, world]
+            }
+            NormalNode::Blend(NormalBlendMode::Reoriented) => {
+                vec![format!("normal_blend_rnm({}, {})", inputs[0], inputs[1])]
+            }
+            NormalNode::Blend(NormalBlendMode::Whiteout) => {
+                vec![format!(
+                    "normal_blend_whiteout({}, {})",
+                    inputs[0], inputs[1]
+                )]
+            }
+            NormalNode::Triplanar(sampler) => vec![format!(
+                "triplanar_sample({}, {}, {}, {}, {}, {})",
+                inputs[0],
+                sampler.wgsl_name(),
+                inputs[1],
+                inputs[2],
+                inputs[3],
+                inputs[4]
+            )],
+            NormalNode::ParallaxOffset => vec![format!(
+                "parallax_offset({}, {}, {}, normal_world_to_tangent({}, {}))",
+                inputs[0],
+                inputs[1],
+                inputs[2],
+                ShaderInput::ViewDirection.wgsl_expr(),
+                frame
+            )],
+        }
+    }
+
+    fn wgsl_helpers(&self) -> Vec<WgslHelper> {
+        let to_world = WgslHelper::new("normal_tangent_to_world", NORMAL_TANGENT_TO_WORLD_WGSL);
+        let to_tangent = WgslHelper::new("normal_world_to_tangent", NORMAL_WORLD_TO_TANGENT_WGSL);
+        match self {
+            NormalNode::FromTexture(_) => {
+                vec![
+                    WgslHelper::new("normal_unpack", NORMAL_UNPACK_WGSL),
+                    to_world,
+                ]
+            }
+            NormalNode::FromHeight => vec![
+                WgslHelper::new("normal_from_height", NORMAL_FROM_HEIGHT_WGSL),
+                to_tangent,
+            ],
+            NormalNode::Blend(NormalBlendMode::Reoriented) => {
+                vec![WgslHelper::new("normal_blend_rnm", NORMAL_BLEND_RNM_WGSL)]
+            }
+            NormalNode::Blend(NormalBlendMode::Whiteout) => {
+                vec![WgslHelper::new(
+                    "normal_blend_whiteout",
+                    NORMAL_BLEND_WHITEOUT_WGSL,
+                )]
+            }
+            NormalNode::Triplanar(_) => {
+                vec![WgslHelper::new("triplanar_sample", TRIPLANAR_SAMPLE_WGSL)]
+            }
+            NormalNode::ParallaxOffset => vec![
+                to_tangent,
+                WgslHelper::new("parallax_offset", PARALLAX_OFFSET_WGSL),
+            ],
+        }
+    }
+
+    fn shader_inputs(&self) -> Vec<ShaderInput> {
+        match self {
+            NormalNode::FromTexture(_) => TANGENT_FRAME_INPUTS.to_vec(),
+            NormalNode::FromHeight => {
+                let mut inputs = TANGENT_FRAME_INPUTS.to_vec();
+                inputs.push(ShaderInput::WorldPosition);
+                inputs
+            }
+            NormalNode::ParallaxOffset => {
+                let mut inputs = TANGENT_FRAME_INPUTS.to_vec();
+                inputs.push(ShaderInput::ViewDirection);
+                inputs
+            }
+            NormalNode::Blend(_) | NormalNode::Triplanar(_) => Vec::new(),
+        }
+    }
+
+    fn samplers(&self) -> Vec<SamplerState> {
+        match self {
+            NormalNode::FromTexture(sampler) | NormalNode::Triplanar(sampler) => vec![*sampler],
+            _ => Vec::new(),
+        }
+    }
+
+    fn stages(&self) -> &'static [ShaderStage] {
+        match self {
+            NormalNode::FromTexture(_) | NormalNode::FromHeight | NormalNode::Triplanar(_) => {
+                &[ShaderStage::Fragment]
+            }
+            _ => &ShaderStage::ALL,
+        }
+    }
+
+    /// Textures sample white and height derivatives are zero on the CPU, so decoded and
+    /// height-derived normals follow from those
+    fn evaluate(&self, inputs: &[Value], ctx: &EvalContext) -> Vec<Value> {
+        let (normal, tangent, sign) = tangent_frame(ctx);
+        match self {
+            NormalNode::FromTexture(_) => {
+                let tangent_normal = unpack_normal(Vec4::ONE, inputs[2].as_f32());
+                vec![
+                    Value::Vec3(tangent_normal),
+                    Value::Vec3(tangent_to_world(tangent_normal, normal, tangent, sign)),
+                ]
+            }
+            NormalNode::FromHeight => vec![
+                Value::Vec3(world_to_tangent(normal, normal, tangent, sign)),
+                Value::Vec3(normal),
+            ],
+            NormalNode::Blend(mode) => {
+                let (a, b) = (inputs[0].as_vec3(), inputs[1].as_vec3());
+                let out = match mode {
+                    NormalBlendMode::Reoriented => blend_reoriented(a, b),
+                    NormalBlendMode::Whiteout => blend_whiteout(a, b),
+                };
+                vec![Value::Vec3(out)]
+            }
+            NormalNode::Triplanar(_) => vec![Value::Vec4(Vec4::ONE)],
+            NormalNode::ParallaxOffset => {
+                let view = (ctx.camera_position - ctx.world_position).normalize_or_zero();
+                let view_tangent = world_to_tangent(view, normal, tangent, sign);
+                vec![Value::Vec2(parallax_offset(
+                    inputs[0].as_vec2(),
+                    inputs[1].as_f32(),
+                    inputs[2].as_f32(),
+                    view_tangent,
+                ))]
+            }
+        }
+    }
+}
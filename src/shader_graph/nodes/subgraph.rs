@@ -0,0 +1,472 @@
+use crate::node_graph::model::{Connection, NodeGraph, NodeId, PinDirection, PinId};
+use crate::node_graph::node_factory::NodeFactory;
+use crate::shader_graph::evaluator::evaluate_subgraph;
+use crate::shader_graph::inputs::EvalContext;
+use crate::shader_graph::node_kind::{NodeKind, NodeSignature, PinSpec, ShaderNode};
+use crate::shader_graph::types::{PinType, Value};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Directory subgraphs are saved to as reusable assets
+pub const SUBGRAPH_DIR: &str = "assets/subgraphs";
+
+/// Extension of a saved subgraph
+pub const SUBGRAPH_EXTENSION: &str = "subgraph.ron";
+
+/// Canvas distance between the selection and the boundary nodes collapsing adds around it
+const BOUNDARY_MARGIN: f32 = 300.0;
+
+/// One pin on a subgraph's boundary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubgraphPort {
+    pub name: String,
+    pub ty: PinType,
+}
+
+impl SubgraphPort {
+    pub fn new(name: &str, ty: PinType) -> Self {
+        Self {
+            name: name.to_string(),
+            ty,
+        }
+    }
+}
+
+/// A graph used as one node. Its `Subgraph Inputs` node provides the node's inputs, its
+/// `Subgraph Outputs` node takes the node's outputs. Compiled into one WGSL function, emitted
+/// once however many nodes use it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subgraph {
+    pub name: String,
+    pub graph: NodeGraph,
+}
+
+impl Subgraph {
+    pub fn new(name: &str, graph: NodeGraph) -> Self {
+        Self {
+            name: name.to_string(),
+            graph,
+        }
+    }
+
+    /// Inputs of the node: the outputs of the `Subgraph Inputs` node
+    pub fn inputs(&self) -> &[SubgraphPort] {
+        self.graph
+            .nodes
+            .values()
+            .find_map(|node| match &node.kind {
+                NodeKind::SubgraphInputs(ports) => Some(ports.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Outputs of the node: the inputs of the `Subgraph Outputs` node
+    pub fn outputs(&self) -> &[SubgraphPort] {
+        self.graph
+            .nodes
+            .values()
+            .find_map(|node| match &node.kind {
+                NodeKind::SubgraphOutputs(ports) => Some(ports.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// The subgraph as the text saved in an asset file
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        ron::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// Write the subgraph to `dir`, named after it, returning the file's path
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, String> {
+        let text = self.to_ron()?;
+        let path = dir.join(asset_file_name(&self.name));
+        std::fs::create_dir_all(dir)
+            .and_then(|_| std::fs::write(&path, text))
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(path)
+    }
+}
+
+/// Name of the function argument carrying a subgraph input
+pub fn argument_name(index: usize) -> String {
+    format!("in{}", index)
+}
+
+impl ShaderNode for Subgraph {
+    fn title(&self) -> String {
+        self.name.clone()
+    }
+
+    fn signature(&self) -> NodeSignature {
+        NodeSignature {
+            inputs: self
+                .inputs()
+                .iter()
+                .map(|port| PinSpec::input(&port.name, port.ty, Value::Float(0.0)))
+                .collect(),
+            outputs: self
+                .outputs()
+                .iter()
+                .map(|port| PinSpec::output(&port.name, port.ty))
+                .collect(),
+        }
+    }
+
+    /// What the body needs (helpers, shader inputs, bindings) depends on the stage it is
+    /// compiled for, so the compiler compiles each subgraph node itself, once per stage, and
+    /// reports why the body doesn't compile rather than emitting a stand-in
+    fn emit_wgsl(&self, _inputs: &[String]) -> Vec<String> {
+        unreachable!("subgraph nodes are compiled by the compiler for the stage it emits")
+    }
+
+    fn evaluate(&self, inputs: &[Value], ctx: &EvalContext) -> Vec<Value> {
+        evaluate_subgraph(self, inputs, ctx).unwrap_or_else(|_| {
+            self.outputs()
+                .iter()
+                .map(|port| Value::zero(port.ty))
+                .collect()
+        })
+    }
+}
+
+/// Whether a node can move into a subgraph, and why not. The function a subgraph compiles
+/// into only returns values: output nodes, storage writes and texture bindings stay outside.
+pub fn check_collapsible(graph: &NodeGraph, node_id: NodeId) -> Result<(), String> {
+    let node = &graph.nodes[&node_id];
+    let reason = match &node.kind {
+        NodeKind::FragmentOutput(_)
+        | NodeKind::PbrOutput
+        | NodeKind::VertexOutput
+        | NodeKind::ComputeOutput(_) => Some("output nodes"),
+        NodeKind::SubgraphInputs(_) | NodeKind::SubgraphOutputs(_) => Some("subgraph boundaries"),
+        NodeKind::Compute(compute) if compute.is_write() => Some("storage writes"),
+        kind => {
+            let signature = kind.signature();
+            let mut pins = signature.inputs.iter().chain(signature.outputs.iter());
+            pins.any(|spec| spec.ty.is_texture())
+                .then_some("nodes with texture pins")
+        }
+    };
+    match reason {
+        Some(reason) => Err(format!(
+            "{} can't move into a subgraph: {} stay in the outer graph",
+            node.title, reason
+        )),
+        None => Ok(()),
+    }
+}
+
+/// `name`, or `name 2`, `name 3`... when a port already has it
+fn unique_name(ports: &[SubgraphPort], name: &str) -> String {
+    let taken = |candidate: &str| ports.iter().any(|port| port.name == candidate);
+    if !taken(name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|n| format!("{} {}", name, n))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_default()
+}
+
+/// Move `selection` into a new subgraph node, returning its ID.
+///
+/// Connections crossing the selection boundary become the node's pins: one input per outer
+/// output feeding the selection, typed like that output, and one output per selected output
+/// read outside. The nodes keep their IDs inside the subgraph.
+pub fn collapse_into_subgraph(
+    graph: &mut NodeGraph,
+    selection: &[NodeId],
+    name: &str,
+) -> Result<NodeId, String> {
+    let mut selection: Vec<NodeId> = selection
+        .iter()
+        .copied()
+        .filter(|id| graph.nodes.contains_key(id))
+        .collect();
+    selection.sort_by_key(|id| id.0);
+    selection.dedup();
+    if selection.is_empty() {
+        return Err("select the nodes to collapse first".to_string());
+    }
+    for &node_id in &selection {
+        check_collapsible(graph, node_id)?;
+    }
+
+    let owner = |graph: &NodeGraph, pin: PinId| graph.find_pin(pin).map(|(node_id, ..)| node_id);
+    let inside = |node_id: Option<NodeId>| node_id.is_some_and(|id| selection.contains(&id));
+
+    let mut internal = Vec::new();
+    // (outer output, inner inputs it feeds)
+    let mut incoming: Vec<(PinId, Vec<PinId>)> = Vec::new();
+    // (inner output, outer inputs it feeds)
+    let mut outgoing: Vec<(PinId, Vec<PinId>)> = Vec::new();
+    for connection in &graph.connections {
+        let from_inside = inside(owner(graph, connection.from_pin));
+        let to_inside = inside(owner(graph, connection.to_pin));
+        let (pin, target, crossing) = match (from_inside, to_inside) {
+            (true, true) => {
+                internal.push(connection.clone());
+                continue;
+            }
+            (false, true) => (connection.from_pin, connection.to_pin, &mut incoming),
+            (true, false) => (connection.from_pin, connection.to_pin, &mut outgoing),
+            (false, false) => continue,
+        };
+        match crossing.iter_mut().find(|(from, _)| *from == pin) {
+            Some((_, targets)) => targets.push(target),
+            None => crossing.push((pin, vec![target])),
+        }
+    }
+    // Pins in the order of the pins they stand for, top to bottom
+    let pin_order = |graph: &NodeGraph, pin: PinId| {
+        let (node_id, _, index) =
+            graph
+                .find_pin(pin)
+                .unwrap_or((NodeId(0), PinDirection::Input, 0));
+        let position = graph.nodes.get(&node_id).map_or(Vec2::ZERO, |n| n.position);
+        (
+            (position.y * 100.0) as i64,
+            (position.x * 100.0) as i64,
+            index,
+        )
+    };
+    incoming.sort_by_key(|(_, targets)| pin_order(graph, targets[0]));
+    outgoing.sort_by_key(|(pin, _)| pin_order(graph, *pin));
+
+    let label = |graph: &NodeGraph, pin: PinId| {
+        let (node_id, direction, index) = graph.find_pin(pin)?;
+        let node = &graph.nodes[&node_id];
+        Some(match direction {
+            PinDirection::Input => node.inputs[index].label.clone(),
+            PinDirection::Output => node.outputs[index].label.clone(),
+        })
+    };
+    let mut input_ports: Vec<SubgraphPort> = Vec::new();
+    for (from, targets) in &incoming {
+        let ty = graph.pin_type(*from).unwrap_or(PinType::Float);
+        let name = label(graph, targets[0]).unwrap_or_default();
+        input_ports.push(SubgraphPort::new(&unique_name(&input_ports, &name), ty));
+    }
+    let mut output_ports: Vec<SubgraphPort> = Vec::new();
+    for (from, _) in &outgoing {
+        let ty = graph.pin_type(*from).unwrap_or(PinType::Float);
+        let name = label(graph, *from).unwrap_or_default();
+        output_ports.push(SubgraphPort::new(&unique_name(&output_ports, &name), ty));
+    }
+
+    // Move the nodes and their internal wiring
+    let mut inner = NodeGraph::new();
+    inner.canvas_state = graph.canvas_state.clone();
+    for node_id in &selection {
+        if let Some(node) = graph.nodes.remove(node_id) {
+            inner.add_node(node);
+        }
+    }
+    inner.connections = internal;
+    // Before the boundaries add pins whose IDs may repeat outer ones
+    graph
+        .connections
+        .retain(|c| inner.find_pin(c.from_pin).is_none() && inner.find_pin(c.to_pin).is_none());
+    let positions: Vec<Vec2> = inner.nodes.values().map(|node| node.position).collect();
+    let min = positions
+        .iter()
+        .copied()
+        .reduce(Vec2::min)
+        .unwrap_or_default();
+    let max = positions
+        .iter()
+        .copied()
+        .reduce(Vec2::max)
+        .unwrap_or_default();
+    let center = (min + max) / 2.0;
+
+    let inputs_node = add_boundary(
+        &mut inner,
+        Vec2::new(min.x - BOUNDARY_MARGIN, center.y),
+        NodeKind::SubgraphInputs(input_ports),
+    );
+    for (index, (_, targets)) in incoming.iter().enumerate() {
+        let from_pin = inner.nodes[&inputs_node].outputs[index].pin_id;
+        for &to_pin in targets {
+            inner.add_connection(Connection { from_pin, to_pin });
+        }
+    }
+    let outputs_node = add_boundary(
+        &mut inner,
+        Vec2::new(max.x + BOUNDARY_MARGIN, center.y),
+        NodeKind::SubgraphOutputs(output_ports),
+    );
+    for (index, (from_pin, _)) in outgoing.iter().enumerate() {
+        let to_pin = inner.nodes[&outputs_node].inputs[index].pin_id;
+        inner.add_connection(Connection {
+            from_pin: *from_pin,
+            to_pin,
+        });
+    }
+
+    // Rewire the outer graph to the new node
+    let node_id = add_boundary(
+        graph,
+        center,
+        NodeKind::Subgraph(Subgraph::new(name, inner)),
+    );
+    let node = graph.nodes[&node_id].clone();
+    for (index, (from_pin, _)) in incoming.iter().enumerate() {
+        graph.add_connection(Connection {
+            from_pin: *from_pin,
+            to_pin: node.inputs[index].pin_id,
+        });
+    }
+    for (index, (_, targets)) in outgoing.iter().enumerate() {
+        for &to_pin in targets {
+            graph.add_connection(Connection {
+                from_pin: node.outputs[index].pin_id,
+                to_pin,
+            });
+        }
+    }
+    Ok(node_id)
+}
+
+/// Add a node with fresh IDs
+fn add_boundary(graph: &mut NodeGraph, position: Vec2, kind: NodeKind) -> NodeId {
+    let node_id = NodeFactory::get_next_node_id(graph);
+    let next_pin_id = NodeFactory::get_next_pin_id(graph);
+    let (node, _) = NodeFactory::create_node(node_id, position, kind, next_pin_id);
+    graph.add_node(node);
+    node_id
+}
+
+/// A subgraph node opened for editing, and the graph around it
+#[derive(Debug, Clone)]
+pub struct SubgraphLevel {
+    /// The graph the subgraph node lives in, minus the subgraph's contents
+    pub parent: NodeGraph,
+    pub node: NodeId,
+    pub name: String,
+}
+
+/// The subgraphs the editor has descended into, outermost first. The `NodeGraph` resource
+/// always holds the graph being edited; leaving a level puts it back into its node.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SubgraphStack {
+    pub levels: Vec<SubgraphLevel>,
+}
+
+impl SubgraphStack {
+    /// Open the subgraph node `node_id` of `graph`: `graph` becomes its contents
+    pub fn enter(&mut self, graph: &mut NodeGraph, node_id: NodeId) -> bool {
+        let Some(NodeKind::Subgraph(subgraph)) = graph.nodes.get_mut(&node_id).map(|n| &mut n.kind)
+        else {
+            return false;
+        };
+        let name = subgraph.name.clone();
+        let inner = std::mem::take(&mut subgraph.graph);
+        let parent = std::mem::replace(graph, inner);
+        self.levels.push(SubgraphLevel {
+            parent,
+            node: node_id,
+            name,
+        });
+        true
+    }
+
+    /// Close the innermost subgraph: its node takes the edited contents (and any new pins)
+    /// and `graph` becomes the parent again
+    pub fn exit(&mut self, graph: &mut NodeGraph) -> bool {
+        let Some(level) = self.levels.pop() else {
+            return false;
+        };
+        let inner = std::mem::replace(graph, level.parent);
+        if let Some(NodeKind::Subgraph(subgraph)) =
+            graph.nodes.get_mut(&level.node).map(|n| &mut n.kind)
+        {
+            subgraph.graph = inner;
+            subgraph.name = level.name;
+        }
+        NodeFactory::sync_pins(graph, level.node);
+        true
+    }
+
+    /// Close levels until `depth` remain; zero returns to the top-level graph
+    pub fn exit_to(&mut self, graph: &mut NodeGraph, depth: usize) {
+        while self.levels.len() > depth && self.exit(graph) {}
+    }
+
+    /// The top-level graph as it would be after leaving every level, for compiling the
+    /// preview while a subgraph is open
+    pub fn root_graph(&self, graph: &NodeGraph) -> NodeGraph {
+        let mut stack = self.clone();
+        let mut graph = graph.clone();
+        stack.exit_to(&mut graph, 0);
+        graph
+    }
+
+    /// Names from the top-level graph down to the open subgraph
+    pub fn breadcrumbs(&self) -> Vec<&str> {
+        self.levels
+            .iter()
+            .map(|level| level.name.as_str())
+            .collect()
+    }
+}
+
+/// Make a file name out of a subgraph name
+pub fn asset_file_name(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let stem = if stem.is_empty() { "subgraph" } else { &stem };
+    format!("{}.{}", stem, SUBGRAPH_EXTENSION)
+}
+
+/// Whether `path` is named like a saved subgraph, `<name>.subgraph.ron`
+fn is_subgraph_asset(path: &Path) -> bool {
+    let Some((inner, outer)) = SUBGRAPH_EXTENSION.rsplit_once('.') else {
+        return false;
+    };
+    path.extension().is_some_and(|ext| ext == outer)
+        && path
+            .file_stem()
+            .and_then(|stem| Path::new(stem).extension())
+            .is_some_and(|ext| ext == inner)
+}
+
+/// Saved subgraphs in `dir`, as (file name, subgraph). Files that don't load keep their
+/// error, so the subgraph panel can say why they are missing.
+pub fn load_subgraph_assets(dir: &Path) -> Vec<(String, Result<Subgraph, String>)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut assets: Vec<(String, Result<Subgraph, String>)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            is_subgraph_asset(&path).then(|| {
+                let file = entry.file_name().to_string_lossy().to_string();
+                let subgraph = std::fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| Subgraph::from_ron(&text));
+                (file, subgraph)
+            })
+        })
+        .collect();
+    assets.sort_by(|a, b| a.0.cmp(&b.0));
+    assets
+}
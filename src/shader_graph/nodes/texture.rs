@@ -0,0 +1,299 @@
+use crate::shader_graph::inputs::{EvalContext, ShaderInput};
+use crate::shader_graph::node_kind::{NodeSignature, PinSpec, ShaderNode, ShaderStage};
+use crate::shader_graph::types::{PinType, Value};
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Image asset the Texture 2D node picks by default
+pub const DEFAULT_TEXTURE_PATH: &str = "ducky.png";
+
+/// Directory images are loaded from, Bevy's default asset root
+pub const ASSETS_DIR: &str = "assets";
+
+/// Image formats the app can load (Bevy's `png` and `jpeg` features)
+pub const IMAGE_EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+/// Image files under `root`, as sorted `/`-separated paths relative to it
+pub fn find_images(root: &Path) -> Vec<String> {
+    fn visit(dir: &Path, prefix: &str, found: &mut Vec<String>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            let relative = format!("{}{}", prefix, name);
+            if path.is_dir() {
+                visit(&path, &format!("{}/", relative), found);
+            } else if path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+            {
+                found.push(relative);
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    visit(root, "", &mut found);
+    found.sort();
+    found
+}
+
+/// The images a Texture 2D node can pick. Listed on start and again whenever a picker opens,
+/// rather than on every frame one is open.
+#[derive(Resource, Debug, Clone)]
+pub struct ImageAssets {
+    pub root: PathBuf,
+    /// As `find_images` lists them
+    pub paths: Vec<String>,
+}
+
+impl Default for ImageAssets {
+    fn default() -> Self {
+        Self::load(ASSETS_DIR)
+    }
+}
+
+impl ImageAssets {
+    /// List the images under `root` now
+    pub fn load(root: impl Into<PathBuf>) -> Self {
+        let mut images = Self {
+            root: root.into(),
+            paths: Vec::new(),
+        };
+        images.refresh();
+        images
+    }
+
+    /// List the directory again, for images added or removed since
+    pub fn refresh(&mut self) {
+        self.paths = find_images(&self.root);
+    }
+}
+
+/// Minification and magnification filter of a sampler
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize,
+)]
+pub enum TextureFilter {
+    #[default]
+    Linear,
+    Nearest,
+}
+
+impl TextureFilter {
+    pub const ALL: [TextureFilter; 2] = [TextureFilter::Linear, TextureFilter::Nearest];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TextureFilter::Linear => "Linear",
+            TextureFilter::Nearest => "Nearest",
+        }
+    }
+}
+
+/// What a sampler does with UVs outside `0..=1`
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize,
+)]
+pub enum TextureWrap {
+    #[default]
+    Repeat,
+    Clamp,
+    Mirror,
+}
+
+impl TextureWrap {
+    pub const ALL: [TextureWrap; 3] =
+        [TextureWrap::Repeat, TextureWrap::Clamp, TextureWrap::Mirror];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TextureWrap::Repeat => "Repeat",
+            TextureWrap::Clamp => "Clamp",
+            TextureWrap::Mirror => "Mirror",
+        }
+    }
+}
+
+/// Sampler options of a Sample Texture 2D node. Every distinct state becomes one
+/// `sampler` binding, shared by all nodes using it.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize,
+)]
+pub struct SamplerState {
+    pub filter: TextureFilter,
+    pub wrap: TextureWrap,
+}
+
+impl SamplerState {
+    /// Every filter and wrap combination, in `index` order
+    pub const ALL: [SamplerState; 6] = [
+        SamplerState::new(TextureFilter::Linear, TextureWrap::Repeat),
+        SamplerState::new(TextureFilter::Linear, TextureWrap::Clamp),
+        SamplerState::new(TextureFilter::Linear, TextureWrap::Mirror),
+        SamplerState::new(TextureFilter::Nearest, TextureWrap::Repeat),
+        SamplerState::new(TextureFilter::Nearest, TextureWrap::Clamp),
+        SamplerState::new(TextureFilter::Nearest, TextureWrap::Mirror),
+    ];
+
+    pub const fn new(filter: TextureFilter, wrap: TextureWrap) -> Self {
+        Self { filter, wrap }
+    }
+
+    /// Position in `ALL`
+    pub fn index(self) -> usize {
+        let filter = match self.filter {
+            TextureFilter::Linear => 0,
+            TextureFilter::Nearest => 1,
+        };
+        let wrap = match self.wrap {
+            TextureWrap::Repeat => 0,
+            TextureWrap::Clamp => 1,
+            TextureWrap::Mirror => 2,
+        };
+        filter * TextureWrap::ALL.len() + wrap
+    }
+
+    /// Name of the `sampler` binding for this state, e.g. `sampler_linear_repeat`
+    pub fn wgsl_name(self) -> String {
+        format!(
+            "sampler_{}_{}",
+            self.filter.label().to_lowercase(),
+            self.wrap.label().to_lowercase()
+        )
+    }
+}
+
+/// Image assets and sampling. Texture outputs are bound by the compiler, one
+/// `texture_2d<f32>` per Texture 2D node.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TextureNode {
+    /// Image asset parameter, `path` relative to `assets/` - `None` binds plain white
+    Texture2D { path: Option<String> },
+    /// `textureSample` at a UV, which reads the mesh UVs when unconnected
+    Sample(SamplerState),
+    /// `textureSampleLevel` at an explicit mip level - works in the vertex stage too
+    SampleLevel(SamplerState),
+}
+
+impl TextureNode {
+    /// Texture 2D node showing the bundled sample image
+    pub fn default_texture() -> Self {
+        TextureNode::Texture2D {
+            path: Some(DEFAULT_TEXTURE_PATH.to_string()),
+        }
+    }
+}
+
+impl ShaderNode for TextureNode {
+    fn title(&self) -> String {
+        match self {
+            TextureNode::Texture2D { .. } => "Texture 2D".to_string(),
+            TextureNode::Sample(_) => "Sample Texture 2D".to_string(),
+            TextureNode::SampleLevel(_) => "Sample Texture 2D LOD".to_string(),
+        }
+    }
+
+    fn signature(&self) -> NodeSignature {
+        match self {
+            TextureNode::Texture2D { .. } => NodeSignature {
+                inputs: vec![],
+                outputs: vec![PinSpec::output("Texture", PinType::Texture2D)],
+            },
+            TextureNode::Sample(_) => NodeSignature {
+                inputs: vec![
+                    PinSpec::input("Texture", PinType::Texture2D, Value::Texture),
+                    PinSpec::input_from("UV", ShaderInput::Uv),
+                ],
+                outputs: sample_outputs(),
+            },
+            TextureNode::SampleLevel(_) => NodeSignature {
+                inputs: vec![
+                    PinSpec::input("Texture", PinType::Texture2D, Value::Texture),
+                    PinSpec::input_from("UV", ShaderInput::Uv),
+                    PinSpec::input("LOD", PinType::Float, Value::Float(0.0)),
+                ],
+                outputs: sample_outputs(),
+            },
+        }
+    }
+
+    fn emit_wgsl(&self, inputs: &[String]) -> Vec<String> {
+        match self {
+            // Declared as a binding by the compiler rather than computed
+            TextureNode::Texture2D { .. } => vec![String::new()],
+            TextureNode::Sample(sampler) => {
+                let sample = format!(
+                    "textureSample({}, {}, {})",
+                    inputs[0],
+                    sampler.wgsl_name(),
+                    inputs[1]
+                );
+                split_sample(sample)
+            }
+            TextureNode::SampleLevel(sampler) => split_sample(format!(
+                "textureSampleLevel({}, {}, {}, {})",
+                inputs[0],
+                sampler.wgsl_name(),
+                inputs[1],
+                inputs[2]
+            )),
+        }
+    }
+
+    fn samplers(&self) -> Vec<SamplerState> {
+        match self {
+            TextureNode::Sample(sampler) | TextureNode::SampleLevel(sampler) => vec![*sampler],
+            _ => Vec::new(),
+        }
+    }
+
+    fn stages(&self) -> &'static [ShaderStage] {
+        match self {
+            TextureNode::Sample(_) => &[ShaderStage::Fragment],
+            _ => &ShaderStage::ALL,
+        }
+    }
+
+    fn texture_image(&self) -> Option<String> {
+        match self {
+            TextureNode::Texture2D { path } => path.clone(),
+            _ => None,
+        }
+    }
+
+    /// The CPU evaluator has no image data, so every sample reads white - what an
+    /// unbound texture shows on the GPU
+    fn evaluate(&self, _inputs: &[Value], _ctx: &EvalContext) -> Vec<Value> {
+        match self {
+            TextureNode::Texture2D { .. } => vec![Value::Texture],
+            TextureNode::Sample(_) | TextureNode::SampleLevel(_) => vec![
+                Value::Vec4(bevy::prelude::Vec4::ONE),
+                Value::Vec3(bevy::prelude::Vec3::ONE),
+                Value::Float(1.0),
+            ],
+        }
+    }
+}
+
+/// RGBA, RGB and alpha outputs shared by both sampling nodes
+fn sample_outputs() -> Vec<PinSpec> {
+    vec![
+        PinSpec::output("RGBA", PinType::Vec4),
+        PinSpec::output("RGB", PinType::Vec3),
+        PinSpec::output("A", PinType::Float),
+    ]
+}
+
+/// A sample expression as the RGBA, RGB and alpha outputs
+fn split_sample(sample: String) -> Vec<String> {
+    vec![
+        sample.clone(),
+        format!("{}.rgb", sample),
+        format!("{}.a", sample),
+    ]
+}
@@ -0,0 +1,210 @@
+use crate::shader_graph::inputs::{EvalContext, ShaderInput};
+use crate::shader_graph::node_kind::{NodeSignature, PinSpec, ShaderNode, WgslHelper};
+use crate::shader_graph::types::{PinType, Value};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/// Periodic signal shapes, all in `-1..=1` and starting at zero like a sine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Waveform {
+    Sine,
+    Square,
+    Triangle,
+    Sawtooth,
+}
+
+impl Waveform {
+    pub const ALL: [Waveform; 4] = [
+        Waveform::Sine,
+        Waveform::Square,
+        Waveform::Triangle,
+        Waveform::Sawtooth,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Waveform::Sine => "Sine",
+            Waveform::Square => "Square",
+            Waveform::Triangle => "Triangle",
+            Waveform::Sawtooth => "Sawtooth",
+        }
+    }
+
+    fn wgsl_function_name(self) -> &'static str {
+        match self {
+            Waveform::Sine => "wave_sine",
+            Waveform::Square => "wave_square",
+            Waveform::Triangle => "wave_triangle",
+            Waveform::Sawtooth => "wave_sawtooth",
+        }
+    }
+
+    fn wgsl_function(self) -> &'static str {
+        match self {
+            Waveform::Sine => WAVE_SINE_WGSL,
+            Waveform::Square => WAVE_SQUARE_WGSL,
+            Waveform::Triangle => WAVE_TRIANGLE_WGSL,
+            Waveform::Sawtooth => WAVE_SAWTOOTH_WGSL,
+        }
+    }
+
+    /// Value at `cycles` periods in
+    pub fn sample(self, cycles: f32) -> f32 {
+        match self {
+            Waveform::Sine => (cycles * TAU).sin(),
+            Waveform::Square => {
+                if cycles.rem_euclid(1.0) < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Triangle => 1.0 - 4.0 * ((cycles + 0.25).rem_euclid(1.0) - 0.5).abs(),
+            Waveform::Sawtooth => 2.0 * (cycles + 0.5).rem_euclid(1.0) - 1.0,
+        }
+    }
+}
+
+const WAVE_SINE_WGSL: &str = "fn wave_sine(cycles: f32) -> f32 {
+    return sin(cycles * 6.2831855);
+}";
+
+const WAVE_SQUARE_WGSL: &str = "fn wave_square(cycles: f32) -> f32 {
+    return select(-1.0, 1.0, fract(cycles) < 0.5);
+}";
+
+const WAVE_TRIANGLE_WGSL: &str = "fn wave_triangle(cycles: f32) -> f32 {
+    return 1.0 - 4.0 * abs(fract(cycles + 0.25) - 0.5);
+}";
+
+const WAVE_SAWTOOTH_WGSL: &str = "fn wave_sawtooth(cycles: f32) -> f32 {
+    return 2.0 * fract(cycles + 0.5) - 1.0;
+}";
+
+/// Nodes driven by the preview clock, plus range remapping for their outputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TimeNode {
+    /// Seconds, sine and cosine of seconds, and the frame delta
+    Time,
+    /// `amplitude * wave(time * frequency + phase)`, phase in cycles
+    Oscillator(Waveform),
+    /// Linearly map `In` from `[In Min, In Max]` to `[Out Min, Out Max]`
+    Remap,
+}
+
+fn float_in(label: &str, default: f32) -> PinSpec {
+    PinSpec::input(label, PinType::Float, Value::Float(default))
+}
+
+impl ShaderNode for TimeNode {
+    fn title(&self) -> String {
+        match self {
+            TimeNode::Time => "Time".to_string(),
+            TimeNode::Oscillator(wave) => format!("Oscillator ({})", wave.label()),
+            TimeNode::Remap => "Remap".to_string(),
+        }
+    }
+
+    fn signature(&self) -> NodeSignature {
+        match self {
+            TimeNode::Time => NodeSignature {
+                inputs: vec![],
+                outputs: vec![
+                    PinSpec::output("Time", PinType::Float),
+                    PinSpec::output("Sin Time", PinType::Float),
+                    PinSpec::output("Cos Time", PinType::Float),
+                    PinSpec::output("Delta Time", PinType::Float),
+                ],
+            },
+            TimeNode::Oscillator(_) => NodeSignature {
+                inputs: vec![
+                    PinSpec::input_from("Time", ShaderInput::Time),
+                    float_in("Frequency", 1.0),
+                    float_in("Phase", 0.0),
+                    float_in("Amplitude", 1.0),
+                ],
+                outputs: vec![PinSpec::output("Out", PinType::Float)],
+            },
+            TimeNode::Remap => NodeSignature {
+                inputs: vec![
+                    float_in("In", 0.0),
+                    float_in("In Min", -1.0),
+                    float_in("In Max", 1.0),
+                    float_in("Out Min", 0.0),
+                    float_in("Out Max", 1.0),
+                ],
+                outputs: vec![PinSpec::output("Out", PinType::Float)],
+            },
+        }
+    }
+
+    fn emit_wgsl(&self, inputs: &[String]) -> Vec<String> {
+        match self {
+            TimeNode::Time => {
+                let time = ShaderInput::Time.wgsl_expr();
+                vec![
+                    time.clone(),
+                    format!("sin({})", time),
+                    format!("cos({})", time),
+                    ShaderInput::DeltaTime.wgsl_expr(),
+                ]
+            }
+            TimeNode::Oscillator(wave) => vec![format!(
+                "({}({} * {} + {}) * {})",
+                wave.wgsl_function_name(),
+                inputs[0],
+                inputs[1],
+                inputs[2],
+                inputs[3]
+            )],
+            TimeNode::Remap => vec![format!(
+                "({} + ({} - {}) * ({} - {}) / ({} - {}))",
+                inputs[3], inputs[0], inputs[1], inputs[4], inputs[3], inputs[2], inputs[1]
+            )],
+        }
+    }
+
+    fn wgsl_helpers(&self) -> Vec<WgslHelper> {
+        match self {
+            TimeNode::Oscillator(wave) => vec![WgslHelper::new(
+                wave.wgsl_function_name(),
+                wave.wgsl_function(),
+            )],
+            _ => Vec::new(),
+        }
+    }
+
+    fn shader_inputs(&self) -> Vec<ShaderInput> {
+        match self {
+            TimeNode::Time => vec![ShaderInput::Time, ShaderInput::DeltaTime],
+            _ => Vec::new(),
+        }
+    }
+
+    fn evaluate(&self, inputs: &[Value], ctx: &EvalContext) -> Vec<Value> {
+        let float = |i: usize| inputs[i].as_f32();
+        match self {
+            TimeNode::Time => vec![
+                Value::Float(ctx.time),
+                Value::Float(ctx.time.sin()),
+                Value::Float(ctx.time.cos()),
+                Value::Float(ctx.delta_time),
+            ],
+            TimeNode::Oscillator(wave) => {
+                let cycles = float(0) * float(1) + float(2);
+                vec![Value::Float(wave.sample(cycles) * float(3))]
+            }
+            TimeNode::Remap => vec![Value::Float(remap(
+                float(0),
+                float(1),
+                float(2),
+                float(3),
+                float(4),
+            ))],
+        }
+    }
+}
+
+pub fn remap(value: f32, in_min: f32, in_max: f32, out_min: f32, out_max: f32) -> f32 {
+    out_min + (value - in_min) * (out_max - out_min) / (in_max - in_min)
+}
@@ -0,0 +1,260 @@
+use crate::shader_graph::inputs::{EvalContext, ShaderInput};
+use crate::shader_graph::node_kind::{NodeSignature, PinSpec, ShaderNode, ShaderStage, WgslHelper};
+use crate::shader_graph::types::{PinType, Value};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/// Texture-coordinate sources and warps. UV inputs read the mesh UVs when unconnected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UvNode {
+    /// Mesh UV0
+    Uv,
+    TilingOffset,
+    /// Rotate around a pivot, angle in radians
+    Rotate,
+    /// Pick one cell of a `columns x rows` sprite sheet, left to right, top to bottom
+    Flipbook,
+    /// UV to (radius, angle) with the angle in `0..=1`
+    CartesianToPolar,
+    /// (radius, angle) back to UV
+    PolarToCartesian,
+    Twirl,
+    Spherize,
+    RadialShear,
+    /// Fragment position in framebuffer pixels
+    ScreenPosition,
+}
+
+const UV_ROTATE_WGSL: &str =
+    "fn uv_rotate(uv: vec2<f32>, center: vec2<f32>, rotation: f32) -> vec2<f32> {
+    let s = sin(rotation);
+    let c = cos(rotation);
+    let d = uv - center;
+    return vec2<f32>(d.x * c - d.y * s, d.x * s + d.y * c) + center;
+}";
+
+const UV_FLIPBOOK_WGSL: &str =
+    "fn uv_flipbook(uv: vec2<f32>, columns: f32, rows: f32, frame: f32) -> vec2<f32> {
+    let size = max(vec2<f32>(columns, rows), vec2<f32>(1.0));
+    let count = size.x * size.y;
+    let tile = floor(frame - count * floor(frame / count));
+    let cell = vec2<f32>(tile - size.x * floor(tile / size.x), floor(tile / size.x));
+    return (uv + cell) / size;
+}";
+
+const UV_TO_POLAR_WGSL: &str = "fn uv_to_polar(uv: vec2<f32>, center: vec2<f32>) -> vec2<f32> {
+    let d = uv - center;
+    return vec2<f32>(length(d), atan2(d.y, d.x) / 6.2831855 + 0.5);
+}";
+
+const UV_FROM_POLAR_WGSL: &str =
+    "fn uv_from_polar(polar: vec2<f32>, center: vec2<f32>) -> vec2<f32> {
+    let angle = (polar.y - 0.5) * 6.2831855;
+    return center + polar.x * vec2<f32>(cos(angle), sin(angle));
+}";
+
+const UV_TWIRL_WGSL: &str =
+    "fn uv_twirl(uv: vec2<f32>, center: vec2<f32>, strength: f32, offset: vec2<f32>) -> vec2<f32> {
+    let d = uv - center;
+    let angle = strength * length(d);
+    let s = sin(angle);
+    let c = cos(angle);
+    return vec2<f32>(c * d.x - s * d.y, s * d.x + c * d.y) + center + offset;
+}";
+
+const UV_SPHERIZE_WGSL: &str = "fn uv_spherize(uv: vec2<f32>, center: vec2<f32>, strength: vec2<f32>, offset: vec2<f32>) -> vec2<f32> {
+    let d = uv - center;
+    let d2 = dot(d, d);
+    return uv + d * (d2 * d2 * strength) + offset;
+}";
+
+const UV_RADIAL_SHEAR_WGSL: &str = "fn uv_radial_shear(uv: vec2<f32>, center: vec2<f32>, strength: vec2<f32>, offset: vec2<f32>) -> vec2<f32> {
+    let d = uv - center;
+    return uv + vec2<f32>(d.y, -d.x) * (dot(d, d) * strength) + offset;
+}";
+
+fn vec2_in(label: &str, default: Vec2) -> PinSpec {
+    PinSpec::input(label, PinType::Vec2, Value::Vec2(default))
+}
+
+fn float_in(label: &str, default: f32) -> PinSpec {
+    PinSpec::input(label, PinType::Float, Value::Float(default))
+}
+
+impl ShaderNode for UvNode {
+    fn title(&self) -> String {
+        match self {
+            UvNode::Uv => "UV",
+            UvNode::TilingOffset => "Tiling And Offset",
+            UvNode::Rotate => "Rotate UV",
+            UvNode::Flipbook => "Flipbook",
+            UvNode::CartesianToPolar => "Cartesian to Polar",
+            UvNode::PolarToCartesian => "Polar to Cartesian",
+            UvNode::Twirl => "Twirl",
+            UvNode::Spherize => "Spherize",
+            UvNode::RadialShear => "Radial Shear",
+            UvNode::ScreenPosition => "Screen Position",
+        }
+        .to_string()
+    }
+
+    fn signature(&self) -> NodeSignature {
+        let uv_in = PinSpec::input_from("UV", ShaderInput::Uv);
+        let center_in = vec2_in("Center", Vec2::splat(0.5));
+        let uv_out = vec![PinSpec::output("UV", PinType::Vec2)];
+
+        match self {
+            UvNode::Uv => NodeSignature {
+                inputs: vec![],
+                outputs: uv_out,
+            },
+            UvNode::ScreenPosition => NodeSignature {
+                inputs: vec![],
+                outputs: vec![PinSpec::output("Pixel", PinType::Vec2)],
+            },
+            UvNode::TilingOffset => NodeSignature {
+                inputs: vec![
+                    uv_in,
+                    vec2_in("Tiling", Vec2::ONE),
+                    vec2_in("Offset", Vec2::ZERO),
+                ],
+                outputs: uv_out,
+            },
+            UvNode::Rotate => NodeSignature {
+                inputs: vec![uv_in, center_in, float_in("Rotation", 0.0)],
+                outputs: uv_out,
+            },
+            UvNode::Flipbook => NodeSignature {
+                inputs: vec![
+                    uv_in,
+                    float_in("Columns", 4.0),
+                    float_in("Rows", 4.0),
+                    float_in("Frame", 0.0),
+                ],
+                outputs: uv_out,
+            },
+            UvNode::CartesianToPolar => NodeSignature {
+                inputs: vec![uv_in, center_in],
+                outputs: vec![PinSpec::output("Polar", PinType::Vec2)],
+            },
+            UvNode::PolarToCartesian => NodeSignature {
+                inputs: vec![vec2_in("Polar", Vec2::new(0.0, 0.5)), center_in],
+                outputs: uv_out,
+            },
+            UvNode::Twirl => NodeSignature {
+                inputs: vec![
+                    uv_in,
+                    center_in,
+                    float_in("Strength", 10.0),
+                    vec2_in("Offset", Vec2::ZERO),
+                ],
+                outputs: uv_out,
+            },
+            UvNode::Spherize | UvNode::RadialShear => NodeSignature {
+                inputs: vec![
+                    uv_in,
+                    center_in,
+                    vec2_in("Strength", Vec2::splat(10.0)),
+                    vec2_in("Offset", Vec2::ZERO),
+                ],
+                outputs: uv_out,
+            },
+        }
+    }
+
+    fn emit_wgsl(&self, inputs: &[String]) -> Vec<String> {
+        let expr = match self {
+            UvNode::Uv => ShaderInput::Uv.wgsl_expr(),
+            UvNode::ScreenPosition => ShaderInput::FragCoord.wgsl_expr(),
+            UvNode::TilingOffset => format!("({} * {} + {})", inputs[0], inputs[1], inputs[2]),
+            UvNode::Rotate => format!("uv_rotate({})", inputs.join(", ")),
+            UvNode::Flipbook => format!("uv_flipbook({})", inputs.join(", ")),
+            UvNode::CartesianToPolar => format!("uv_to_polar({})", inputs.join(", ")),
+            UvNode::PolarToCartesian => format!("uv_from_polar({})", inputs.join(", ")),
+            UvNode::Twirl => format!("uv_twirl({})", inputs.join(", ")),
+            UvNode::Spherize => format!("uv_spherize({})", inputs.join(", ")),
+            UvNode::RadialShear => format!("uv_radial_shear({})", inputs.join(", ")),
+        };
+        vec![expr]
+    }
+
+    fn wgsl_helpers(&self) -> Vec<WgslHelper> {
+        let (name, source) = match self {
+            UvNode::Rotate => ("uv_rotate", UV_ROTATE_WGSL),
+            UvNode::Flipbook => ("uv_flipbook", UV_FLIPBOOK_WGSL),
+            UvNode::CartesianToPolar => ("uv_to_polar", UV_TO_POLAR_WGSL),
+            UvNode::PolarToCartesian => ("uv_from_polar", UV_FROM_POLAR_WGSL),
+            UvNode::Twirl => ("uv_twirl", UV_TWIRL_WGSL),
+            UvNode::Spherize => ("uv_spherize", UV_SPHERIZE_WGSL),
+            UvNode::RadialShear => ("uv_radial_shear", UV_RADIAL_SHEAR_WGSL),
+            UvNode::Uv | UvNode::ScreenPosition | UvNode::TilingOffset => return Vec::new(),
+        };
+        vec![WgslHelper::new(name, source)]
+    }
+
+    fn shader_inputs(&self) -> Vec<ShaderInput> {
+        match self {
+            UvNode::Uv => vec![ShaderInput::Uv],
+            UvNode::ScreenPosition => vec![ShaderInput::FragCoord],
+            _ => Vec::new(),
+        }
+    }
+
+    fn stages(&self) -> &'static [ShaderStage] {
+        match self {
+            UvNode::ScreenPosition => &[ShaderStage::Fragment],
+            _ => &ShaderStage::ALL,
+        }
+    }
+
+    fn evaluate(&self, inputs: &[Value], ctx: &EvalContext) -> Vec<Value> {
+        let vec2 = |i: usize| inputs[i].as_vec2();
+        let float = |i: usize| inputs[i].as_f32();
+        let result = match self {
+            UvNode::Uv => ShaderInput::Uv.evaluate(ctx).as_vec2(),
+            UvNode::ScreenPosition => ShaderInput::FragCoord.evaluate(ctx).as_vec2(),
+            UvNode::TilingOffset => vec2(0) * vec2(1) + vec2(2),
+            UvNode::Rotate => rotate(vec2(0), vec2(1), float(2)),
+            UvNode::Flipbook => flipbook(vec2(0), float(1), float(2), float(3)),
+            UvNode::CartesianToPolar => to_polar(vec2(0), vec2(1)),
+            UvNode::PolarToCartesian => from_polar(vec2(0), vec2(1)),
+            UvNode::Twirl => {
+                let d = vec2(0) - vec2(1);
+                rotate(d, Vec2::ZERO, float(2) * d.length()) + vec2(1) + vec2(3)
+            }
+            UvNode::Spherize => {
+                let d = vec2(0) - vec2(1);
+                let d2 = d.dot(d);
+                vec2(0) + d * (d2 * d2 * vec2(2)) + vec2(3)
+            }
+            UvNode::RadialShear => {
+                let d = vec2(0) - vec2(1);
+                vec2(0) + Vec2::new(d.y, -d.x) * (d.dot(d) * vec2(2)) + vec2(3)
+            }
+        };
+        vec![Value::Vec2(result)]
+    }
+}
+
+pub fn rotate(uv: Vec2, center: Vec2, rotation: f32) -> Vec2 {
+    Vec2::from_angle(rotation).rotate(uv - center) + center
+}
+
+pub fn flipbook(uv: Vec2, columns: f32, rows: f32, frame: f32) -> Vec2 {
+    let size = Vec2::new(columns, rows).max(Vec2::ONE);
+    let tile = frame.rem_euclid(size.x * size.y).floor();
+    let cell = Vec2::new(tile.rem_euclid(size.x), (tile / size.x).floor());
+    (uv + cell) / size
+}
+
+/// (radius, angle) with the angle mapped from `-PI..=PI` to `0..=1`
+pub fn to_polar(uv: Vec2, center: Vec2) -> Vec2 {
+    let d = uv - center;
+    Vec2::new(d.length(), d.y.atan2(d.x) / TAU + 0.5)
+}
+
+pub fn from_polar(polar: Vec2, center: Vec2) -> Vec2 {
+    let angle = (polar.y - 0.5) * TAU;
+    center + polar.x * Vec2::new(angle.cos(), angle.sin())
+}
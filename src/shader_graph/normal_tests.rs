@@ -0,0 +1,173 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::NodeGraph;
+    use crate::node_graph::test_utils::{add_node, assert_near, connect};
+    use crate::shader_graph::compiler::{compile_and_validate, compile_fragment};
+    use crate::shader_graph::evaluator::GraphEvaluator;
+    use crate::shader_graph::inputs::EvalContext;
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget, ShaderNode};
+    use crate::shader_graph::nodes::normal::{
+        NormalBlendMode, NormalNode, blend_reoriented, blend_whiteout, parallax_offset,
+        tangent_to_world, unpack_normal, world_to_tangent,
+    };
+    use crate::shader_graph::nodes::texture::{
+        SamplerState, TextureFilter, TextureNode, TextureWrap,
+    };
+    use crate::shader_graph::types::Value;
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_flat_normal_map_texel_decodes_to_z() {
+        assert_near(unpack_normal(Vec4::new(0.5, 0.5, 1.0, 1.0), 1.0), Vec3::Z);
+    }
+
+    #[test]
+    fn test_zero_strength_flattens_normal() {
+        assert_near(unpack_normal(Vec4::new(1.0, 0.5, 0.5, 1.0), 0.0), Vec3::Z);
+        let tilted = unpack_normal(Vec4::new(1.0, 0.5, 0.5, 1.0), 1.0);
+        assert!(tilted.x > 0.9);
+    }
+
+    #[test]
+    fn test_tangent_frame_round_trip() {
+        let normal = Vec3::Y;
+        let tangent = Vec3::X;
+        let v = Vec3::new(0.3, -0.4, 0.866).normalize();
+        for sign in [1.0, -1.0] {
+            let world = tangent_to_world(v, normal, tangent, sign);
+            assert_near(world_to_tangent(world, normal, tangent, sign), v);
+        }
+        // Tangent-space +Z is the surface normal
+        assert_near(tangent_to_world(Vec3::Z, normal, tangent, 1.0), normal);
+    }
+
+    #[test]
+    fn test_blending_onto_flat_base_keeps_detail() {
+        let detail = Vec3::new(0.2, -0.3, 0.9).normalize();
+        assert_near(blend_reoriented(Vec3::Z, detail), detail);
+        assert_near(blend_whiteout(Vec3::Z, detail), detail);
+    }
+
+    #[test]
+    fn test_reoriented_blend_rotates_detail_with_base() {
+        let base = Vec3::new(0.6, 0.0, 0.8);
+        // A flat detail follows the base exactly
+        assert_near(blend_reoriented(base, Vec3::Z), base);
+        // Whiteout flattens the same case
+        assert!(blend_whiteout(base, Vec3::Z).x < base.x + 1e-4);
+    }
+
+    #[test]
+    fn test_parallax_is_zero_at_mid_height_and_head_on() {
+        let uv = Vec2::new(0.25, 0.75);
+        assert_eq!(parallax_offset(uv, 0.5, 0.1, Vec3::new(0.3, 0.2, 0.9)), uv);
+        assert_eq!(parallax_offset(uv, 1.0, 0.1, Vec3::Z), uv);
+        let shifted = parallax_offset(uv, 1.0, 0.1, Vec3::new(1.0, 0.0, 1.0));
+        assert!(shifted.x > uv.x);
+        assert_eq!(shifted.y, uv.y);
+    }
+
+    #[test]
+    fn test_height_normal_is_flat_on_cpu() {
+        let out = NormalNode::FromHeight.evaluate(
+            &[Value::Float(0.7), Value::Float(1.0)],
+            &EvalContext::default(),
+        );
+        assert_eq!(out[0], Value::Vec3(Vec3::Z));
+        assert_eq!(out[1], Value::Vec3(Vec3::Z));
+    }
+
+    #[test]
+    fn test_every_normal_node_validates() {
+        let mut nodes = vec![
+            NormalNode::FromTexture(SamplerState::default()),
+            NormalNode::FromHeight,
+            NormalNode::Triplanar(SamplerState::default()),
+            NormalNode::ParallaxOffset,
+        ];
+        nodes.extend(NormalBlendMode::ALL.map(NormalNode::Blend));
+        for node in nodes {
+            let mut graph = NodeGraph::new();
+            let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+            let normal = add_node(&mut graph, NodeKind::Normal(node));
+            connect(&mut graph, normal, 0, output, 0);
+
+            compile_and_validate(&graph)
+                .unwrap_or_else(|e| panic!("{:?} failed to compile: {}", node, e));
+        }
+    }
+
+    #[test]
+    fn test_normal_map_reads_tangent_frame() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let texture = add_node(
+            &mut graph,
+            NodeKind::Texture(TextureNode::default_texture()),
+        );
+        let normal = add_node(
+            &mut graph,
+            NodeKind::Normal(NormalNode::FromTexture(SamplerState::default())),
+        );
+        connect(&mut graph, texture, 0, normal, 0);
+        connect(&mut graph, normal, 1, output, 0);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert!(source.contains("@location(4) tangent: vec4<f32>"));
+        assert!(source.contains("input.world_tangent.w"));
+        assert!(source.contains("var sampler_linear_repeat: sampler;"));
+        assert_eq!(source.matches("fn normal_tangent_to_world").count(), 1);
+    }
+
+    #[test]
+    fn test_texture_normals_use_their_sampler() {
+        let sampler = SamplerState::new(TextureFilter::Nearest, TextureWrap::Clamp);
+        for node in [
+            NormalNode::FromTexture(sampler),
+            NormalNode::Triplanar(sampler),
+        ] {
+            let mut graph = NodeGraph::new();
+            let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+            let texture = add_node(
+                &mut graph,
+                NodeKind::Texture(TextureNode::default_texture()),
+            );
+            let normal = add_node(&mut graph, NodeKind::Normal(node));
+            connect(&mut graph, texture, 0, normal, 0);
+            connect(&mut graph, normal, 0, output, 0);
+
+            let source = compile_and_validate(&graph).expect("graph should compile");
+            assert!(source.contains("var sampler_nearest_clamp: sampler;"));
+            assert!(
+                !source.contains("sampler_linear_repeat"),
+                "{:?} kept the default sampler",
+                node
+            );
+        }
+    }
+
+    #[test]
+    fn test_height_normal_feeds_blend() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let height = add_node(&mut graph, NodeKind::Normal(NormalNode::FromHeight));
+        let parallax = add_node(&mut graph, NodeKind::Normal(NormalNode::ParallaxOffset));
+        let blend = add_node(
+            &mut graph,
+            NodeKind::Normal(NormalNode::Blend(NormalBlendMode::Reoriented)),
+        );
+        connect(&mut graph, parallax, 0, height, 0);
+        connect(&mut graph, height, 0, blend, 1);
+        connect(&mut graph, blend, 0, output, 0);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        // Both nodes use the world-to-tangent helper; it is emitted once
+        assert_eq!(source.matches("fn normal_world_to_tangent").count(), 1);
+        assert!(compile_fragment(&graph).unwrap().contains("dpdx(position)"));
+
+        let color = GraphEvaluator::new(&graph)
+            .evaluate_fragment()
+            .expect("graph should evaluate");
+        assert!(color.truncate().abs_diff_eq(Vec3::Z, 1e-4));
+    }
+}
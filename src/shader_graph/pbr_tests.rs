@@ -0,0 +1,242 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::NodeGraph;
+    use crate::node_graph::test_utils::{
+        BEVY_MESH_STUBS, add_node, connect, validate_with_bevy_stubs,
+    };
+    use crate::shader_graph::compiler::{
+        CompileError, compile_and_validate, compile_pbr, compile_preview,
+    };
+    use crate::shader_graph::evaluator::GraphEvaluator;
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget};
+    use crate::shader_graph::nodes::geometry::{GeometryNode, Space};
+    use crate::shader_graph::nodes::lighting::LightingNode;
+    use crate::shader_graph::nodes::normal::NormalNode;
+    use crate::shader_graph::nodes::texture::TextureNode;
+    use crate::shader_graph::scaffold::PBR_EXTENSION_BINDING_BASE;
+    use bevy::prelude::*;
+
+    /// Stand-ins for Bevy's PBR declarations, added to the mesh ones
+    const BEVY_PBR_STUBS: &str = "struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+};
+
+struct StandardMaterial {
+    base_color: vec4<f32>,
+    emissive: vec4<f32>,
+    perceptual_roughness: f32,
+    metallic: f32,
+};
+
+struct PbrInput {
+    material: StandardMaterial,
+    diffuse_occlusion: vec3<f32>,
+    world_normal: vec3<f32>,
+    N: vec3<f32>,
+};
+
+fn pbr_input_from_standard_material(in: VertexOutput, is_front: bool) -> PbrInput {
+    var pbr_input: PbrInput;
+    pbr_input.N = in.world_normal;
+    return pbr_input;
+}
+
+fn alpha_discard(material: StandardMaterial, color: vec4<f32>) -> vec4<f32> {
+    return color;
+}
+
+fn apply_pbr_lighting(in: PbrInput) -> vec4<f32> {
+    return in.material.base_color;
+}
+
+fn main_pass_post_lighting_processing(in: PbrInput, color: vec4<f32>) -> vec4<f32> {
+    return color;
+}
+";
+
+    fn validate_pbr(source: &str) -> Result<(), CompileError> {
+        let stubs = format!("{}{}", BEVY_MESH_STUBS, BEVY_PBR_STUBS);
+        validate_with_bevy_stubs(&stubs, source)
+    }
+
+    #[test]
+    fn test_pbr_output_has_surface_pins() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::PbrOutput);
+        let labels: Vec<&str> = graph.nodes[&output]
+            .inputs
+            .iter()
+            .map(|pin| pin.label.as_str())
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                "Base Color",
+                "Metallic",
+                "Roughness",
+                "Normal",
+                "Emissive",
+                "Occlusion",
+                "Alpha"
+            ]
+        );
+        assert!(graph.nodes[&output].outputs.is_empty());
+    }
+
+    #[test]
+    fn test_pbr_graph_feeds_bevy_lighting() {
+        let mut graph = NodeGraph::new();
+        add_node(&mut graph, NodeKind::PbrOutput);
+
+        let source = compile_pbr(&graph).expect("graph should compile").source;
+        assert!(
+            source.contains("#import bevy_pbr::pbr_fragment::pbr_input_from_standard_material")
+        );
+        assert!(source.contains("fn graph_surface(input: GraphInput) -> GraphSurface"));
+        assert!(source.contains("pbr_input.material.perceptual_roughness = surface.roughness;"));
+        assert!(source.contains("out.color = apply_pbr_lighting(pbr_input);"));
+        // Unconnected pins use their defaults, the normal the interpolated one
+        assert!(source.contains(
+            "return GraphSurface(vec4<f32>(vec3<f32>(0.4, 0.7, 1.0), 1.0), 0.0, 0.5, normalize(input.world_normal), vec3<f32>(0.0, 0.0, 0.0), 1.0);"
+        ));
+        validate_pbr(&source).expect("stubbed PBR shader should validate");
+    }
+
+    #[test]
+    fn test_pbr_runs_the_vertex_graph() {
+        let mut graph = NodeGraph::new();
+        add_node(&mut graph, NodeKind::PbrOutput);
+        let vertex = add_node(&mut graph, NodeKind::VertexOutput);
+        let position = add_node(
+            &mut graph,
+            NodeKind::Geometry(GeometryNode::Position(Space::Object)),
+        );
+        connect(&mut graph, position, 0, vertex, 0);
+
+        let source = compile_pbr(&graph).expect("graph should compile").source;
+        assert!(
+            source.contains("#import bevy_pbr::forward_io::{Vertex, VertexOutput, FragmentOutput}")
+        );
+        assert!(source.contains(&format!(
+            "    displaced.position = vertex.position + node{}_out0;",
+            position.0
+        )));
+        assert!(source.contains("    return mesh_vertex(displaced);"));
+        validate_pbr(&source).expect("stubbed PBR shader should validate");
+    }
+
+    #[test]
+    fn test_pbr_reads_custom_interpolants() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::PbrOutput);
+        let vertex = add_node(&mut graph, NodeKind::VertexOutput);
+        let position = add_node(
+            &mut graph,
+            NodeKind::Geometry(GeometryNode::Position(Space::Object)),
+        );
+        let custom = add_node(&mut graph, NodeKind::Geometry(GeometryNode::Custom(0)));
+        connect(&mut graph, position, 0, vertex, 2);
+        connect(&mut graph, custom, 0, output, 0);
+
+        let source = compile_pbr(&graph).expect("graph should compile").source;
+        assert!(source.contains("    @location(8) custom0: vec4<f32>,"));
+        assert!(source.contains(&format!(
+            "    out.custom0 = vec4<f32>(node{}_out0, 1.0);",
+            position.0
+        )));
+        assert!(source.contains(
+            "fn fragment(graph: GraphVertexOutput, @builtin(front_facing) is_front: bool) -> FragmentOutput {"
+        ));
+        assert!(source.contains("pbr_input_from_standard_material(mesh, is_front)"));
+        validate_pbr(&source).expect("stubbed PBR shader should validate");
+    }
+
+    #[test]
+    fn test_pbr_bindings_follow_the_base_material() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::PbrOutput);
+        let texture = add_node(
+            &mut graph,
+            NodeKind::Texture(TextureNode::default_texture()),
+        );
+        let sample = add_node(
+            &mut graph,
+            NodeKind::Texture(TextureNode::Sample(Default::default())),
+        );
+        connect(&mut graph, texture, 0, sample, 0);
+        connect(&mut graph, sample, 1, output, 0);
+
+        let preview = compile_pbr(&graph).expect("graph should compile");
+        let base = PBR_EXTENSION_BINDING_BASE;
+        for binding in [base, base + 1, base + 5, base + 17] {
+            assert!(
+                preview.source.contains(&format!(
+                    "@group(#{{MATERIAL_BIND_GROUP}}) @binding({})",
+                    binding
+                )),
+                "missing binding {}",
+                binding
+            );
+        }
+        assert!(!preview.source.contains("@binding(0)"));
+        assert_eq!(preview.images, vec![Some("ducky.png".to_string())]);
+        validate_pbr(&preview.source).expect("stubbed PBR shader should validate");
+    }
+
+    #[test]
+    fn test_surface_inputs_take_graph_nodes() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::PbrOutput);
+        let fresnel = add_node(&mut graph, NodeKind::Lighting(LightingNode::Fresnel));
+        let rim = add_node(&mut graph, NodeKind::Lighting(LightingNode::Rim));
+        let normal = add_node(&mut graph, NodeKind::Normal(NormalNode::FromHeight));
+        let position = add_node(
+            &mut graph,
+            NodeKind::Geometry(GeometryNode::Position(Space::Object)),
+        );
+        connect(&mut graph, fresnel, 0, output, 2);
+        connect(&mut graph, normal, 1, output, 3);
+        connect(&mut graph, rim, 0, output, 4);
+        connect(&mut graph, position, 0, normal, 0);
+
+        let source = compile_pbr(&graph).expect("graph should compile").source;
+        assert!(source.contains("input.object_position = mesh.world_position.xyz"));
+        validate_pbr(&source).expect("stubbed PBR shader should validate");
+    }
+
+    #[test]
+    fn test_fragment_output_graph_is_not_pbr() {
+        let mut graph = NodeGraph::new();
+        add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        assert_eq!(compile_pbr(&graph), Err(CompileError::NotPbr));
+    }
+
+    #[test]
+    fn test_pbr_and_fragment_outputs_are_exclusive() {
+        let mut graph = NodeGraph::new();
+        let a = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let b = add_node(&mut graph, NodeKind::PbrOutput);
+        assert_eq!(
+            compile_pbr(&graph),
+            Err(CompileError::MultipleOutputs(vec![a, b]))
+        );
+    }
+
+    #[test]
+    fn test_other_targets_show_the_base_color() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::PbrOutput);
+        let constant = add_node(&mut graph, NodeKind::Constant(0.25));
+        connect(&mut graph, constant, 0, output, 0);
+        connect(&mut graph, constant, 0, output, 6);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert!(source.contains("out.color = vec4<f32>(vec3<f32>(node"));
+        assert!(compile_preview(&graph).is_ok());
+
+        let color = GraphEvaluator::new(&graph)
+            .evaluate_fragment()
+            .expect("graph should evaluate");
+        assert_eq!(color, Vec4::splat(0.25));
+    }
+}
@@ -0,0 +1,179 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::NodeGraph;
+    use crate::node_graph::test_utils::{
+        BEVY_MESH_STUBS, add_node, connect, validate_with_bevy_stubs,
+    };
+    use crate::shader_graph::compiler::{CompileError, compile_preview};
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget};
+    use crate::shader_graph::nodes::geometry::{GeometryNode, Space};
+    use crate::shader_graph::nodes::texture::{
+        SamplerState, TextureFilter, TextureNode, TextureWrap,
+    };
+    use crate::shader_graph::nodes::time::{TimeNode, Waveform};
+    use crate::shader_graph::nodes::uv::UvNode;
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_preview_needs_an_output_node() {
+        assert_eq!(
+            compile_preview(&NodeGraph::new()),
+            Err(CompileError::MissingOutput)
+        );
+    }
+
+    #[test]
+    fn test_preview_uses_bevy_entry_point_and_material_group() {
+        let mut graph = NodeGraph::new();
+        add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+
+        let source = compile_preview(&graph)
+            .expect("graph should compile")
+            .source;
+        assert!(source.starts_with("#import bevy_pbr::forward_io::{Vertex, VertexOutput}"));
+        assert!(source.contains("@group(#{MATERIAL_BIND_GROUP}) @binding(0)"));
+        assert!(source.contains("fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32>"));
+        assert!(!source.contains("fn vs_main"));
+        // Without a vertex graph the material's vertex stage is Bevy's
+        assert!(source.contains(
+            "@vertex\nfn vertex(vertex: Vertex) -> VertexOutput {\n    return mesh_vertex(vertex);\n}"
+        ));
+        validate_with_bevy_stubs(BEVY_MESH_STUBS, &source).expect("preview should validate");
+    }
+
+    #[test]
+    fn test_preview_runs_the_vertex_graph() {
+        let mut graph = NodeGraph::new();
+        add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let vertex = add_node(&mut graph, NodeKind::VertexOutput);
+        let time = add_node(&mut graph, NodeKind::Time(TimeNode::Time));
+        let normal = add_node(
+            &mut graph,
+            NodeKind::Geometry(GeometryNode::Normal(Space::Object)),
+        );
+        connect(&mut graph, time, 1, vertex, 0);
+        connect(&mut graph, normal, 0, vertex, 1);
+
+        let source = compile_preview(&graph)
+            .expect("graph should compile")
+            .source;
+        assert!(source.contains("    let mesh = mesh_vertex(vertex);"));
+        assert!(source.contains("    input.object_normal = mesh.world_normal;"));
+        assert!(source.contains(&format!(
+            "    displaced.position = vertex.position + vec3<f32>(node{}_out1);",
+            time.0
+        )));
+        assert!(source.contains(&format!("    displaced.normal = node{}_out0;", normal.0)));
+        assert!(source.contains("    return mesh_vertex(displaced);"));
+        validate_with_bevy_stubs(BEVY_MESH_STUBS, &source).expect("preview should validate");
+    }
+
+    #[test]
+    fn test_preview_carries_custom_interpolants() {
+        let mut graph = NodeGraph::new();
+        let fragment = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let custom = add_node(&mut graph, NodeKind::Geometry(GeometryNode::Custom(1)));
+        connect(&mut graph, custom, 0, fragment, 0);
+
+        // Nothing writes it yet, so it stays zero
+        let source = compile_preview(&graph)
+            .expect("graph should compile")
+            .source;
+        assert!(source.contains("    @location(9) custom1: vec4<f32>,\n};"));
+        assert!(!source.contains("custom0"));
+        assert!(source.contains("    var out = graph_vertex_output(mesh_vertex(vertex));"));
+        assert!(source.contains("    out.custom1 = vec4<f32>(0.0);"));
+        assert!(source.contains(
+            "fn fragment(graph: GraphVertexOutput) -> @location(0) vec4<f32> {\n    let mesh = mesh_vertex_output(graph);"
+        ));
+        assert!(source.contains("    input.custom1 = graph.custom1;"));
+        validate_with_bevy_stubs(BEVY_MESH_STUBS, &source).expect("preview should validate");
+
+        let vertex = add_node(&mut graph, NodeKind::VertexOutput);
+        let position = add_node(
+            &mut graph,
+            NodeKind::Geometry(GeometryNode::Position(Space::Object)),
+        );
+        connect(&mut graph, position, 0, vertex, 3);
+        let source = compile_preview(&graph)
+            .expect("graph should compile")
+            .source;
+        assert!(source.contains("    var out = graph_vertex_output(mesh_vertex(displaced));"));
+        assert!(source.contains(&format!(
+            "    out.custom1 = vec4<f32>(node{}_out0, 1.0);",
+            position.0
+        )));
+        validate_with_bevy_stubs(BEVY_MESH_STUBS, &source).expect("preview should validate");
+    }
+
+    #[test]
+    fn test_preview_fills_interpolants_from_mesh() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let color = add_node(&mut graph, NodeKind::Geometry(GeometryNode::VertexColor));
+        let screen = add_node(&mut graph, NodeKind::Geometry(GeometryNode::ScreenPosition));
+        connect(&mut graph, color, 0, output, 0);
+        connect(&mut graph, screen, 0, output, 1);
+
+        let source = compile_preview(&graph)
+            .expect("graph should compile")
+            .source;
+        assert!(source.contains("input.color = mesh.color;"));
+        assert!(
+            source.contains("input.screen_position = view.clip_from_world * mesh.world_position;")
+        );
+        validate_with_bevy_stubs(BEVY_MESH_STUBS, &source).expect("preview should validate");
+    }
+
+    #[test]
+    fn test_preview_validates_for_every_input_kind() {
+        let kinds = [
+            NodeKind::Uv(UvNode::Twirl),
+            NodeKind::Uv(UvNode::ScreenPosition),
+            NodeKind::Geometry(GeometryNode::Position(Space::Object)),
+            NodeKind::Geometry(GeometryNode::Normal(Space::View)),
+            NodeKind::Geometry(GeometryNode::Tangent),
+            NodeKind::Geometry(GeometryNode::ViewDirection),
+            NodeKind::Time(TimeNode::Time),
+            NodeKind::Time(TimeNode::Oscillator(Waveform::Triangle)),
+        ];
+        for kind in kinds {
+            let mut graph = NodeGraph::new();
+            let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+            let node = add_node(&mut graph, kind.clone());
+            connect(&mut graph, node, 0, output, 0);
+
+            let source = compile_preview(&graph)
+                .expect("graph should compile")
+                .source;
+            validate_with_bevy_stubs(BEVY_MESH_STUBS, &source)
+                .unwrap_or_else(|e| panic!("{:?} preview failed: {}", kind, e));
+        }
+    }
+
+    #[test]
+    fn test_preview_binds_textures_to_material_slots() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let texture = add_node(
+            &mut graph,
+            NodeKind::Texture(TextureNode::default_texture()),
+        );
+        let nearest = SamplerState::new(TextureFilter::Nearest, TextureWrap::Repeat);
+        let sample = add_node(&mut graph, NodeKind::Texture(TextureNode::Sample(nearest)));
+        connect(&mut graph, texture, 0, sample, 0);
+        connect(&mut graph, sample, 1, output, 0);
+
+        let preview = compile_preview(&graph).expect("graph should compile");
+        assert_eq!(preview.images, vec![Some("ducky.png".to_string())]);
+        assert!(preview.source.contains(&format!(
+            "@group(#{{MATERIAL_BIND_GROUP}}) @binding(1)\nvar node{}_out0: texture_2d<f32>;",
+            texture.0
+        )));
+        assert!(preview.source.contains(
+            "@group(#{MATERIAL_BIND_GROUP}) @binding(8)\nvar sampler_nearest_repeat: sampler;"
+        ));
+        validate_with_bevy_stubs(BEVY_MESH_STUBS, &preview.source)
+            .expect("preview should validate");
+    }
+}
@@ -0,0 +1,660 @@
+use crate::shader_graph::inputs::{Interpolant, VertexAttribute};
+use crate::shader_graph::node_kind::OutputTarget;
+use crate::shader_graph::nodes::compute::{STORAGE_TEXTURE_FORMAT, StorageAccess, StorageResource};
+use crate::shader_graph::nodes::texture::SamplerState;
+
+/// First `VertexOutput` location free for interpolants - 0..=2 are world position, normal and UV
+const FIRST_INTERPOLANT_LOCATION: usize = 3;
+
+/// Location of the preview's first custom interpolant, past the last of Bevy's mesh
+/// `VertexOutput` (`visibility_range_dither` at 7)
+const FIRST_PREVIEW_CUSTOM_LOCATION: usize = 8;
+
+/// Bind group of texture and sampler bindings in standalone modules - group 0 holds the uniforms
+const RESOURCE_GROUP: u32 = 1;
+
+/// First binding of `GraphPreviewMaterial`'s texture slots - binding 0 is `globals`
+pub const PREVIEW_TEXTURE_BINDING: u32 = 1;
+/// Number of texture slots `GraphPreviewMaterial` declares
+pub const PREVIEW_TEXTURE_SLOTS: usize = 4;
+/// Binding of `SamplerState::ALL[0]` in `GraphPreviewMaterial`, the others follow in order
+pub const PREVIEW_SAMPLER_BINDING: u32 = PREVIEW_TEXTURE_BINDING + PREVIEW_TEXTURE_SLOTS as u32;
+/// Binding of the `lights` uniform in `GraphPreviewMaterial`, after the sampler carriers
+pub const PREVIEW_LIGHTS_BINDING: u32 = 17;
+/// Offset of the PBR extension's bindings - Bevy keeps 0..100 for the base `StandardMaterial`
+pub const PBR_EXTENSION_BINDING_BASE: u32 = 100;
+
+/// Offset of the fullscreen pass's bindings - 0 and 1 hold the screen texture and its sampler
+pub const FULLSCREEN_BINDING_BASE: u32 = 2;
+
+/// Bevy pipeline a preview shader is compiled for. All share one bind group layout,
+/// shifted by `binding_base` within `bind_group`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewTarget {
+    /// `GraphPreviewMaterial`: the graph's colour is the fragment colour
+    Unlit,
+    /// `ExtendedMaterial<StandardMaterial, GraphPbrExtension>`: the graph fills Bevy's
+    /// `PbrInput` and Bevy lights, shadows and fogs it
+    Pbr,
+    /// `Material2d`, reading the 2D mesh pipeline's `VertexOutput`
+    Sprite,
+    /// Post-process pass after Bevy's fullscreen vertex shader, with the screen in group 0
+    Fullscreen,
+    /// `UiMaterial`, whose bind group Bevy fixes at 1
+    Ui,
+}
+
+impl From<OutputTarget> for PreviewTarget {
+    fn from(target: OutputTarget) -> Self {
+        match target {
+            OutputTarget::Mesh => PreviewTarget::Unlit,
+            OutputTarget::Sprite => PreviewTarget::Sprite,
+            OutputTarget::Fullscreen => PreviewTarget::Fullscreen,
+            OutputTarget::Ui => PreviewTarget::Ui,
+        }
+    }
+}
+
+impl PreviewTarget {
+    pub fn binding_base(self) -> u32 {
+        match self {
+            PreviewTarget::Unlit | PreviewTarget::Sprite | PreviewTarget::Ui => 0,
+            PreviewTarget::Pbr => PBR_EXTENSION_BINDING_BASE,
+            PreviewTarget::Fullscreen => FULLSCREEN_BINDING_BASE,
+        }
+    }
+
+    /// Bind group the graph's uniforms, textures and samplers live in
+    pub fn bind_group(self) -> &'static str {
+        match self {
+            PreviewTarget::Unlit | PreviewTarget::Pbr | PreviewTarget::Sprite => {
+                "#{MATERIAL_BIND_GROUP}"
+            }
+            PreviewTarget::Fullscreen => "0",
+            PreviewTarget::Ui => "1",
+        }
+    }
+
+    /// Whether the entry point reads a mesh `VertexOutput` - the other targets draw a flat quad
+    /// and only know its UV
+    pub fn has_mesh(self) -> bool {
+        matches!(
+            self,
+            PreviewTarget::Unlit | PreviewTarget::Pbr | PreviewTarget::Sprite
+        )
+    }
+
+    /// Whether the module also carries the material's `vertex` entry point, which runs the
+    /// vertex graph - the 3D mesh pipelines, whose materials take it as their vertex shader
+    pub fn has_vertex_stage(self) -> bool {
+        matches!(self, PreviewTarget::Unlit | PreviewTarget::Pbr)
+    }
+
+    fn imports(self) -> &'static str {
+        match self {
+            PreviewTarget::Unlit => {
+                "#import bevy_pbr::forward_io::{Vertex, VertexOutput}
+#import bevy_pbr::mesh_view_bindings::view
+#import bevy_pbr::mesh_functions::{get_world_from_local, mesh_normal_local_to_world, mesh_position_local_to_world, mesh_tangent_local_to_world}
+#import bevy_pbr::view_transformations::position_world_to_clip
+"
+            }
+            PreviewTarget::Pbr => {
+                "#import bevy_pbr::forward_io::{Vertex, VertexOutput, FragmentOutput}
+#import bevy_pbr::mesh_view_bindings::view
+#import bevy_pbr::mesh_functions::{get_world_from_local, mesh_normal_local_to_world, mesh_position_local_to_world, mesh_tangent_local_to_world}
+#import bevy_pbr::view_transformations::position_world_to_clip
+#import bevy_pbr::pbr_fragment::pbr_input_from_standard_material
+#import bevy_pbr::pbr_functions::{alpha_discard, apply_pbr_lighting, main_pass_post_lighting_processing}
+"
+            }
+            PreviewTarget::Sprite => {
+                "#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+#import bevy_sprite::mesh2d_view_bindings::view
+"
+            }
+            PreviewTarget::Fullscreen => {
+                "#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+"
+            }
+            PreviewTarget::Ui => {
+                "#import bevy_ui::ui_vertex_output::UiVertexOutput
+"
+            }
+        }
+    }
+
+    /// Signature of the `fragment` entry point, up to its opening brace. `mesh_input` is the
+    /// parameter the mesh targets take their vertex output as.
+    fn entry_signature(self, mesh_input: &str) -> String {
+        match self {
+            PreviewTarget::Unlit | PreviewTarget::Sprite => format!(
+                "@fragment\nfn fragment({}) -> @location(0) vec4<f32> {{",
+                mesh_input
+            ),
+            PreviewTarget::Pbr => format!(
+                "@fragment\nfn fragment({}, @builtin(front_facing) is_front: bool) -> FragmentOutput {{",
+                mesh_input
+            ),
+            PreviewTarget::Fullscreen => {
+                "@fragment\nfn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {"
+                    .to_string()
+            }
+            PreviewTarget::Ui => {
+                "@fragment\nfn fragment(in: UiVertexOutput) -> @location(0) vec4<f32> {".to_string()
+            }
+        }
+    }
+}
+
+/// What a PBR graph hands to Bevy, filled by `fn graph_surface`
+const GRAPH_SURFACE_WGSL: &str = "struct GraphSurface {
+    base_color: vec4<f32>,
+    metallic: f32,
+    roughness: f32,
+    normal: vec3<f32>,
+    emissive: vec3<f32>,
+    occlusion: f32,
+};";
+
+/// Uniform structs every graph body may read, whichever stage feeds them
+const SHARED_STRUCTS_WGSL: &str = "struct CameraUniforms {
+    view_proj: mat4x4<f32>,
+    view: mat4x4<f32>,
+    position: vec3<f32>,
+};
+
+struct Globals {
+    time: f32,
+    delta_time: f32,
+};
+
+struct Lights {
+    direction: vec4<f32>,
+    color: vec4<f32>,
+    point_position: vec4<f32>,
+    point_color: vec4<f32>,
+    ambient: vec4<f32>,
+};";
+
+/// Bevy's mesh `vertex` (`bevy_pbr/src/render/mesh.wgsl`) as a plain function, so the vertex
+/// graph can run it on the displaced vertex. The preview meshes have every attribute and no
+/// morph targets or skin, which leaves only the pipeline's own flags conditional.
+const MESH_VERTEX_WGSL: &str = "fn mesh_vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+    let world_from_local = get_world_from_local(vertex.instance_index);
+    out.world_normal = mesh_normal_local_to_world(vertex.normal, vertex.instance_index);
+    out.world_position = mesh_position_local_to_world(world_from_local, vec4<f32>(vertex.position, 1.0));
+    out.position = position_world_to_clip(out.world_position.xyz);
+    out.uv = vertex.uv;
+    out.world_tangent = mesh_tangent_local_to_world(world_from_local, vertex.tangent, vertex.instance_index);
+    out.color = vertex.color;
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = vertex.instance_index;
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+    out.visibility_range_dither = bevy_pbr::mesh_functions::get_visibility_range_dither_level(
+        vertex.instance_index,
+        world_from_local[3],
+    );
+#endif
+    return out;
+}";
+
+/// Bevy's mesh `VertexOutput` at the same locations, up to the custom interpolants
+const GRAPH_VERTEX_OUTPUT_WGSL: &str = "struct GraphVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec4<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(4) world_tangent: vec4<f32>,
+    @location(5) color: vec4<f32>,
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    @location(6) @interpolate(flat) instance_index: u32,
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+    @location(7) @interpolate(flat) visibility_range_dither: i32,
+#endif
+";
+
+/// Copies between Bevy's `VertexOutput` and `GraphVertexOutput`, leaving the custom fields out
+const GRAPH_VERTEX_OUTPUT_COPIES_WGSL: &str =
+    "fn graph_vertex_output(mesh: VertexOutput) -> GraphVertexOutput {
+    var out: GraphVertexOutput;
+    out.position = mesh.position;
+    out.world_position = mesh.world_position;
+    out.world_normal = mesh.world_normal;
+    out.uv = mesh.uv;
+    out.world_tangent = mesh.world_tangent;
+    out.color = mesh.color;
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = mesh.instance_index;
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+    out.visibility_range_dither = mesh.visibility_range_dither;
+#endif
+    return out;
+}
+
+fn mesh_vertex_output(graph: GraphVertexOutput) -> VertexOutput {
+    var mesh: VertexOutput;
+    mesh.position = graph.position;
+    mesh.world_position = graph.world_position;
+    mesh.world_normal = graph.world_normal;
+    mesh.uv = graph.uv;
+    mesh.world_tangent = graph.world_tangent;
+    mesh.color = graph.color;
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    mesh.instance_index = graph.instance_index;
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+    mesh.visibility_range_dither = graph.visibility_range_dither;
+#endif
+    return mesh;
+}";
+
+/// Fills the graph's `camera` and `input` from Bevy's view and a mesh `VertexOutput` called `mesh`
+const MESH_GRAPH_INPUT_WGSL: &str = "
+    camera = CameraUniforms(view.clip_from_world, view.view_from_world, view.world_position);
+    var input: GraphInput;
+    input.clip_position = mesh.position;
+    input.world_position = mesh.world_position.xyz;
+    input.world_normal = mesh.world_normal;
+    input.uv = mesh.uv;
+";
+
+/// Sort and dedup interpolants so locations are stable for a given set
+fn normalized(interpolants: &[Interpolant]) -> Vec<Interpolant> {
+    let mut interpolants = interpolants.to_vec();
+    interpolants.sort();
+    interpolants.dedup();
+    interpolants
+}
+
+/// Structs, bindings and `vs_main` shared by every compiled graph.
+///
+/// Mirrors `default.wgsl`, plus whichever interpolants (and the mesh attributes feeding them)
+/// the graph's nodes read. With a vertex graph, `default.wgsl`'s `vs_main` becomes
+/// `fn mesh_vertex` and `vs_main` runs `graph_main` instead.
+pub fn vertex_stage(interpolants: &[Interpolant], graph_main: Option<&str>) -> String {
+    let interpolants = normalized(interpolants);
+
+    let mut attributes: Vec<VertexAttribute> =
+        interpolants.iter().filter_map(|i| i.attribute()).collect();
+    attributes.sort();
+    attributes.dedup();
+
+    let mut source = String::from(
+        "struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+",
+    );
+    for attribute in &attributes {
+        source.push_str(&format!(
+            "    @location({}) {}: {},\n",
+            attribute.location(),
+            attribute.field(),
+            attribute.pin_type().wgsl_type()
+        ));
+    }
+    source.push_str(
+        "};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+",
+    );
+    for (index, interpolant) in interpolants.iter().enumerate() {
+        source.push_str(&format!(
+            "    @location({}) {}: {},\n",
+            FIRST_INTERPOLANT_LOCATION + index,
+            interpolant.field(),
+            interpolant.pin_type().wgsl_type()
+        ));
+    }
+    source.push_str(
+        "};
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+};
+
+",
+    );
+    source.push_str(SHARED_STRUCTS_WGSL);
+    source.push_str(
+        "
+
+struct ModelUniforms {
+    model: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniforms;
+
+@group(0) @binding(1)
+var<uniform> model: ModelUniforms;
+
+@group(0) @binding(2)
+var<uniform> globals: Globals;
+
+@group(0) @binding(3)
+var<uniform> lights: Lights;
+
+",
+    );
+    source.push_str(match graph_main {
+        Some(_) => "fn mesh_vertex(vertex: VertexInput) -> VertexOutput {",
+        None => "@vertex\nfn vs_main(vertex: VertexInput) -> VertexOutput {",
+    });
+    source.push_str(
+        "
+    var out: VertexOutput;
+    let world_pos = model.model * vec4<f32>(vertex.position, 1.0);
+    out.world_position = world_pos.xyz;
+    out.world_normal = (model.model * vec4<f32>(vertex.normal, 0.0)).xyz;
+    out.clip_position = camera.view_proj * world_pos;
+    out.uv = vertex.uv;
+",
+    );
+    for interpolant in &interpolants {
+        source.push_str(&format!(
+            "    out.{} = {};\n",
+            interpolant.field(),
+            interpolant.vertex_expr()
+        ));
+    }
+    source.push_str("    return out;\n}");
+    if let Some(graph_main) = graph_main {
+        source.push_str("\n\n@vertex\nfn vs_main(vertex: VertexInput) -> VertexOutput {\n");
+        source.push_str(graph_main);
+        source.push('}');
+    }
+    source
+}
+
+/// Declarations for the preview material, shared by its `vertex` and `fragment` entry points.
+///
+/// The graph body reads a `VertexOutput`-shaped `GraphInput`, the `camera` and the material's
+/// `globals`; `preview_entry_point` fills them in from Bevy's bindings.
+pub fn preview_prelude(interpolants: &[Interpolant], target: PreviewTarget) -> String {
+    let mut source = String::from(target.imports());
+    source.push_str(
+        "
+struct GraphInput {
+    clip_position: vec4<f32>,
+    world_position: vec3<f32>,
+    world_normal: vec3<f32>,
+    uv: vec2<f32>,
+",
+    );
+    for interpolant in normalized(interpolants) {
+        source.push_str(&format!(
+            "    {}: {},\n",
+            interpolant.field(),
+            interpolant.pin_type().wgsl_type()
+        ));
+    }
+    source.push_str("};\n\n");
+    source.push_str(SHARED_STRUCTS_WGSL);
+    if target == PreviewTarget::Pbr {
+        source.push_str("\n\n");
+        source.push_str(GRAPH_SURFACE_WGSL);
+    }
+    if target == PreviewTarget::Fullscreen {
+        source.push_str(
+            "
+
+@group(0) @binding(0)
+var screen_texture: texture_2d<f32>;
+
+@group(0) @binding(1)
+var screen_sampler: sampler;",
+        );
+    }
+    let base = target.binding_base();
+    source.push_str(&format!(
+        "
+
+@group({group}) @binding({})
+var<uniform> globals: Globals;
+
+@group({group}) @binding({})
+var<uniform> lights: Lights;
+
+var<private> camera: CameraUniforms;",
+        base,
+        base + PREVIEW_LIGHTS_BINDING,
+        group = target.bind_group()
+    ));
+    source
+}
+
+/// Bevy `fragment` entry point adapting the target's vertex output for the graph function:
+/// `graph_fragment` returns the colour, `graph_surface` the `PbrInput` overrides.
+///
+/// Fullscreen and UI targets only know the UV, so the graph sees a flat 2x2 quad facing +Z
+/// with an identity camera one unit in front of it. A graph reading custom interpolants takes
+/// the `GraphVertexOutput` of `preview_vertex_entry_point` and unpacks Bevy's part as `mesh`.
+pub fn preview_entry_point(interpolants: &[Interpolant], target: PreviewTarget) -> String {
+    let reads_custom = interpolants
+        .iter()
+        .any(|interpolant| matches!(interpolant, Interpolant::Custom(_)));
+    let mut source = if reads_custom {
+        target.entry_signature("graph: GraphVertexOutput")
+            + "\n    let mesh = mesh_vertex_output(graph);"
+    } else {
+        target.entry_signature("mesh: VertexOutput")
+    };
+    if target.has_mesh() {
+        source.push_str(MESH_GRAPH_INPUT_WGSL);
+    } else {
+        source.push_str(
+            "
+    let identity = mat4x4<f32>(
+        vec4<f32>(1.0, 0.0, 0.0, 0.0),
+        vec4<f32>(0.0, 1.0, 0.0, 0.0),
+        vec4<f32>(0.0, 0.0, 1.0, 0.0),
+        vec4<f32>(0.0, 0.0, 0.0, 1.0),
+    );
+    camera = CameraUniforms(identity, identity, vec3<f32>(0.0, 0.0, 1.0));
+    var input: GraphInput;
+    input.clip_position = in.position;
+    input.world_position = vec3<f32>(in.uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0);
+    input.world_normal = vec3<f32>(0.0, 0.0, 1.0);
+    input.uv = in.uv;
+",
+        );
+    }
+    for interpolant in normalized(interpolants) {
+        let expr = if target.has_mesh() {
+            interpolant.preview_expr()
+        } else {
+            interpolant.flat_expr()
+        };
+        source.push_str(&format!("    input.{} = {};\n", interpolant.field(), expr));
+    }
+    match target {
+        PreviewTarget::Pbr => source.push_str(
+            "    let surface = graph_surface(input);
+    var pbr_input = pbr_input_from_standard_material(mesh, is_front);
+    pbr_input.material.base_color = surface.base_color;
+    pbr_input.material.metallic = surface.metallic;
+    pbr_input.material.perceptual_roughness = surface.roughness;
+    pbr_input.material.emissive = vec4<f32>(surface.emissive, 1.0);
+    pbr_input.diffuse_occlusion = vec3<f32>(surface.occlusion);
+    pbr_input.N = normalize(surface.normal);
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+    return out;
+}
+",
+        ),
+        _ => source.push_str("    return graph_fragment(input);\n}\n"),
+    }
+    source
+}
+
+/// Bevy `vertex` entry point of the mesh targets, whose materials use the preview module as
+/// their vertex shader too. `graph_main` is `VertexBody::displace_wgsl`: it reads the undisplaced
+/// vertex as `input`, whose `interpolants` are filled here, and leaves `displaced` for
+/// `mesh_vertex` to run again. Without a vertex graph the mesh vertex stage runs as Bevy's would.
+///
+/// `custom` holds the index and expression of each custom interpolant the fragment graph
+/// reads. With any, the entry point returns a `GraphVertexOutput`: Bevy's `VertexOutput`
+/// with the custom interpolants after it, from `FIRST_PREVIEW_CUSTOM_LOCATION` on.
+pub fn preview_vertex_entry_point(
+    interpolants: &[Interpolant],
+    graph_main: Option<&str>,
+    custom: &[(usize, String)],
+) -> String {
+    let mut source = String::from(MESH_VERTEX_WGSL);
+    if !custom.is_empty() {
+        source.push_str("\n\n");
+        source.push_str(GRAPH_VERTEX_OUTPUT_WGSL);
+        for (index, _) in custom {
+            source.push_str(&format!(
+                "    @location({}) {}: vec4<f32>,\n",
+                FIRST_PREVIEW_CUSTOM_LOCATION + index,
+                Interpolant::Custom(*index).field()
+            ));
+        }
+        source.push_str("};\n\n");
+        source.push_str(GRAPH_VERTEX_OUTPUT_COPIES_WGSL);
+    }
+    source.push_str(&format!(
+        "\n\n@vertex\nfn vertex(vertex: Vertex) -> {} {{\n",
+        if custom.is_empty() {
+            "VertexOutput"
+        } else {
+            "GraphVertexOutput"
+        }
+    ));
+    let mesh_vertex = match graph_main {
+        Some(graph_main) => {
+            source.push_str("    let mesh = mesh_vertex(vertex);");
+            source.push_str(MESH_GRAPH_INPUT_WGSL);
+            for interpolant in normalized(interpolants) {
+                source.push_str(&format!(
+                    "    input.{} = {};\n",
+                    interpolant.field(),
+                    interpolant.preview_expr()
+                ));
+            }
+            source.push_str(graph_main);
+            "mesh_vertex(displaced)"
+        }
+        None => "mesh_vertex(vertex)",
+    };
+    if custom.is_empty() {
+        source.push_str(&format!("    return {};\n}}\n\n", mesh_vertex));
+        return source;
+    }
+    source.push_str(&format!(
+        "    var out = graph_vertex_output({});\n",
+        mesh_vertex
+    ));
+    for (index, expr) in custom {
+        source.push_str(&format!(
+            "    out.{} = {};\n",
+            Interpolant::Custom(*index).field(),
+            expr
+        ));
+    }
+    source.push_str("    return out;\n}\n\n");
+    source
+}
+
+/// Uniforms and storage bindings of a compute module, all in group 0: `globals` at binding 0,
+/// each storage binding at its `StorageResource::binding` with the access the graph needs
+pub fn compute_prelude(storage: &[(StorageResource, StorageAccess)]) -> String {
+    let mut source = String::from(SHARED_STRUCTS_WGSL);
+    source.push_str(
+        "
+
+@group(0) @binding(0)
+var<uniform> globals: Globals;
+
+",
+    );
+    for &(resource, access) in storage {
+        let ty = match resource {
+            StorageResource::Buffer(_) => format!(
+                "<storage, {}> {}: array<vec4<f32>>",
+                access.wgsl_mode(resource),
+                resource.wgsl_name()
+            ),
+            StorageResource::Texture(_) => format!(
+                " {}: texture_storage_2d<{}, {}>",
+                resource.wgsl_name(),
+                STORAGE_TEXTURE_FORMAT,
+                access.wgsl_mode(resource)
+            ),
+        };
+        source.push_str(&format!(
+            "@group(0) @binding({})\nvar{};\n\n",
+            resource.binding(),
+            ty
+        ));
+    }
+    source
+}
+
+/// `@group(1)` declarations for a standalone module: textures in order, then samplers
+pub fn resource_bindings(textures: &[String], samplers: &[SamplerState]) -> String {
+    let mut source = String::new();
+    let mut binding = 0;
+    for name in textures {
+        source.push_str(&format!(
+            "@group({}) @binding({})\nvar {}: texture_2d<f32>;\n\n",
+            RESOURCE_GROUP, binding, name
+        ));
+        binding += 1;
+    }
+    for sampler in samplers {
+        source.push_str(&format!(
+            "@group({}) @binding({})\nvar {}: sampler;\n\n",
+            RESOURCE_GROUP,
+            binding,
+            sampler.wgsl_name()
+        ));
+        binding += 1;
+    }
+    source
+}
+
+/// Bind group declarations matching `GraphPreviewMaterial`'s fixed layout: one texture slot
+/// per name, and each sampler at its `SamplerState::index`
+pub fn preview_resource_bindings(
+    textures: &[String],
+    samplers: &[SamplerState],
+    target: PreviewTarget,
+) -> String {
+    let base = target.binding_base();
+    let group = target.bind_group();
+    let mut source = String::new();
+    for (slot, name) in textures.iter().enumerate() {
+        source.push_str(&format!(
+            "@group({}) @binding({})\nvar {}: texture_2d<f32>;\n\n",
+            group,
+            base + PREVIEW_TEXTURE_BINDING + slot as u32,
+            name
+        ));
+    }
+    for sampler in samplers {
+        source.push_str(&format!(
+            "@group({}) @binding({})\nvar {}: sampler;\n\n",
+            group,
+            base + PREVIEW_SAMPLER_BINDING + sampler.index() as u32,
+            sampler.wgsl_name()
+        ));
+    }
+    source
+}
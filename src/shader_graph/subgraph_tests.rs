@@ -0,0 +1,269 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::{NodeGraph, NodeId};
+    use crate::node_graph::node_factory::MathOperation;
+    use crate::node_graph::test_utils::{add_node, connect};
+    use crate::shader_graph::compiler::{CompileError, compile_and_validate};
+    use crate::shader_graph::evaluator::GraphEvaluator;
+    use crate::shader_graph::inputs::EvalContext;
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget, ShaderNode};
+    use crate::shader_graph::nodes::geometry::GeometryNode;
+    use crate::shader_graph::nodes::subgraph::{
+        Subgraph, SubgraphStack, asset_file_name, collapse_into_subgraph, load_subgraph_assets,
+    };
+    use crate::shader_graph::nodes::texture::{SamplerState, TextureNode};
+    use crate::shader_graph::nodes::uv::UvNode;
+    use crate::shader_graph::types::PinType;
+    use bevy::prelude::*;
+
+    /// `(a * b) + b` into the colour and `a * b` into the alpha, returning the graph and the
+    /// multiply and add nodes
+    fn math_graph() -> (NodeGraph, NodeId, NodeId) {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let a = add_node(&mut graph, NodeKind::Constant(0.5));
+        let b = add_node(&mut graph, NodeKind::Constant(0.25));
+        let mul = add_node(&mut graph, NodeKind::Math(MathOperation::Multiply));
+        let add = add_node(&mut graph, NodeKind::Math(MathOperation::Add));
+        connect(&mut graph, a, 0, mul, 0);
+        connect(&mut graph, b, 0, mul, 1);
+        connect(&mut graph, mul, 0, add, 0);
+        connect(&mut graph, b, 0, add, 1);
+        connect(&mut graph, add, 0, output, 0);
+        connect(&mut graph, mul, 0, output, 1);
+        (graph, mul, add)
+    }
+
+    fn subgraph(graph: &NodeGraph, node_id: NodeId) -> &Subgraph {
+        match &graph.nodes[&node_id].kind {
+            NodeKind::Subgraph(subgraph) => subgraph,
+            kind => panic!("{:?} is no subgraph", kind),
+        }
+    }
+
+    #[test]
+    fn test_boundary_connections_become_pins() {
+        let (mut graph, mul, add) = math_graph();
+        let before = GraphEvaluator::new(&graph).evaluate_fragment().unwrap();
+
+        let node_id = collapse_into_subgraph(&mut graph, &[add, mul], "Scale").unwrap();
+        // The two nodes are replaced by one
+        assert_eq!(graph.nodes.len(), 4);
+        let node = &graph.nodes[&node_id];
+        // `b` feeds both nodes but is one input
+        let labels: Vec<&str> = node.inputs.iter().map(|pin| pin.label.as_str()).collect();
+        assert_eq!(labels, ["A", "B"]);
+        assert_eq!(node.outputs.len(), 2);
+        // Two constants in, colour and alpha out
+        assert_eq!(graph.connections.len(), 4);
+
+        let inner = subgraph(&graph, node_id);
+        assert!(inner.graph.nodes.contains_key(&mul) && inner.graph.nodes.contains_key(&add));
+        assert_eq!(inner.inputs().len(), 2);
+        assert!(inner.outputs().iter().all(|port| port.ty == PinType::Float));
+
+        let after = GraphEvaluator::new(&graph).evaluate_fragment().unwrap();
+        assert_eq!(after, before);
+        assert_eq!(after, Vec4::new(0.375, 0.375, 0.375, 0.125));
+    }
+
+    #[test]
+    fn test_function_is_emitted_once_per_subgraph() {
+        let (mut graph, mul, add) = math_graph();
+        let node_id = collapse_into_subgraph(&mut graph, &[mul, add], "Scale").unwrap();
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert_eq!(source.matches("struct subgraph_scale_").count(), 1);
+        assert_eq!(source.matches("fn subgraph_scale_").count(), 1);
+        // Called once, both outputs read off the result
+        assert_eq!(source.matches("_result = subgraph_scale_").count(), 1);
+        assert!(source.contains(&format!("node{}_result.out1", node_id.0)));
+
+        // A second use of the same subgraph calls the same function
+        let kind = graph.nodes[&node_id].kind.clone();
+        let copy = add_node(&mut graph, kind);
+        let output = graph
+            .nodes
+            .values()
+            .find(|node| node.kind.is_output())
+            .unwrap()
+            .node_id;
+        let alpha = graph.nodes[&output].inputs[1].pin_id;
+        graph.connections.retain(|c| c.to_pin != alpha);
+        connect(&mut graph, copy, 1, output, 1);
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert_eq!(source.matches("fn subgraph_scale_").count(), 1);
+        assert_eq!(source.matches("_result = subgraph_scale_").count(), 2);
+    }
+
+    #[test]
+    fn test_different_subgraphs_with_one_name_get_their_own_functions() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let a = add_node(&mut graph, NodeKind::Constant(0.5));
+        let b = add_node(&mut graph, NodeKind::Constant(0.25));
+        let mul = add_node(&mut graph, NodeKind::Math(MathOperation::Multiply));
+        let add = add_node(&mut graph, NodeKind::Math(MathOperation::Add));
+        connect(&mut graph, a, 0, mul, 0);
+        connect(&mut graph, b, 0, mul, 1);
+        connect(&mut graph, a, 0, add, 0);
+        connect(&mut graph, b, 0, add, 1);
+        let sum = add_node(&mut graph, NodeKind::Math(MathOperation::Add));
+        connect(&mut graph, mul, 0, sum, 0);
+        connect(&mut graph, add, 0, sum, 1);
+        connect(&mut graph, sum, 0, output, 0);
+        collapse_into_subgraph(&mut graph, &[mul], "Scale").unwrap();
+        collapse_into_subgraph(&mut graph, &[add], "Scale").unwrap();
+        let source = compile_and_validate(&graph).expect("graph should compile");
+
+        let names: Vec<&str> = source
+            .match_indices("fn subgraph_scale_")
+            .map(|(start, _)| &source[start + 3..])
+            .map(|rest| &rest[..rest.find('(').unwrap()])
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert_ne!(names[0], names[1]);
+        // The whole 64-bit hash of the body
+        for name in names {
+            let hash = name.trim_start_matches("subgraph_scale_");
+            assert_eq!(hash.len(), 16);
+            assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+        }
+    }
+
+    #[test]
+    fn test_fragment_inputs_are_passed_in() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let tiling = add_node(&mut graph, NodeKind::Uv(UvNode::TilingOffset));
+        connect(&mut graph, tiling, 0, output, 0);
+        let ctx = EvalContext {
+            uv: Vec2::new(0.25, 0.75),
+            ..default()
+        };
+        let before = GraphEvaluator::with_context(&graph, ctx)
+            .evaluate_fragment()
+            .unwrap();
+
+        let node_id = collapse_into_subgraph(&mut graph, &[tiling], "Tile").unwrap();
+        assert_eq!(graph.nodes[&node_id].inputs.len(), 0);
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        // The body reads `input.uv` from a struct of just the fields it needs
+        assert!(
+            source.contains("_input {\n    uv: vec2<f32>,\n}"),
+            "{}",
+            source
+        );
+        assert!(source.contains("_input(input.uv))"), "{}", source);
+        let after = GraphEvaluator::with_context(&graph, ctx)
+            .evaluate_fragment()
+            .unwrap();
+        assert_eq!(after, before);
+    }
+
+    #[test]
+    fn test_nested_subgraphs_pass_fragment_inputs_along() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let view = add_node(&mut graph, NodeKind::Geometry(GeometryNode::ViewDirection));
+        connect(&mut graph, view, 0, output, 0);
+
+        let inner = collapse_into_subgraph(&mut graph, &[view], "View").unwrap();
+        collapse_into_subgraph(&mut graph, &[inner], "Outer").unwrap();
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        // The outer function hands its own `input` on to the inner one
+        assert_eq!(source.matches("_input(input.world_position)").count(), 2);
+        assert!(source.contains("normalize(camera.position - input.world_position)"));
+    }
+
+    #[test]
+    fn test_broken_subgraph_reports_why() {
+        let (mut graph, mul, add) = math_graph();
+        let node_id = collapse_into_subgraph(&mut graph, &[mul, add], "Scale").unwrap();
+        let NodeKind::Subgraph(subgraph) = &mut graph.nodes.get_mut(&node_id).unwrap().kind else {
+            unreachable!()
+        };
+        subgraph
+            .graph
+            .nodes
+            .retain(|_, node| !matches!(node.kind, NodeKind::SubgraphOutputs(_)));
+
+        assert_eq!(
+            compile_and_validate(&graph).unwrap_err(),
+            CompileError::InSubgraph {
+                node: node_id,
+                error: Box::new(CompileError::MissingOutput),
+            }
+        );
+    }
+
+    #[test]
+    fn test_outputs_and_textures_stay_outside() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let sample = add_node(
+            &mut graph,
+            NodeKind::Texture(TextureNode::Sample(SamplerState::default())),
+        );
+        connect(&mut graph, sample, 0, output, 0);
+        let snapshot = graph.clone();
+
+        assert!(collapse_into_subgraph(&mut graph, &[output], "Out").is_err());
+        let error = collapse_into_subgraph(&mut graph, &[sample], "Sample").unwrap_err();
+        assert!(error.contains("texture"), "{}", error);
+        assert!(collapse_into_subgraph(&mut graph, &[], "Nothing").is_err());
+        assert_eq!(graph, snapshot);
+    }
+
+    #[test]
+    fn test_entering_and_leaving_keeps_edits() {
+        let (mut graph, mul, add) = math_graph();
+        let node_id = collapse_into_subgraph(&mut graph, &[mul, add], "Scale").unwrap();
+        let top = graph.clone();
+
+        let mut stack = SubgraphStack::default();
+        let output = graph
+            .nodes
+            .values()
+            .find(|n| n.kind.is_output())
+            .unwrap()
+            .node_id;
+        assert!(!stack.enter(&mut graph, output));
+        assert!(stack.enter(&mut graph, node_id));
+        assert_eq!(stack.breadcrumbs(), ["Scale"]);
+        assert!(graph.nodes.contains_key(&mul));
+        // Leaving without edits gives back the same graph
+        assert_eq!(stack.root_graph(&graph), top);
+
+        // Turn the multiply into a divide and rename
+        graph.nodes.get_mut(&mul).unwrap().kind = NodeKind::Math(MathOperation::Divide);
+        stack.levels[0].name = "Ratio".to_string();
+        stack.exit_to(&mut graph, 0);
+        assert!(stack.levels.is_empty());
+        assert_eq!(graph.nodes[&node_id].title, format!("Ratio {}", node_id.0));
+        let color = GraphEvaluator::new(&graph).evaluate_fragment().unwrap();
+        assert_eq!(color, Vec4::new(2.25, 2.25, 2.25, 2.0));
+    }
+
+    #[test]
+    fn test_saved_subgraph_loads_back() {
+        let (mut graph, mul, add) = math_graph();
+        let node_id = collapse_into_subgraph(&mut graph, &[mul, add], "Scale Bias").unwrap();
+        let saved = subgraph(&graph, node_id).clone();
+
+        let dir = std::env::temp_dir().join(format!("subgraphs_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = saved.save(&dir).expect("subgraph should save");
+        assert_eq!(path, dir.join(asset_file_name("Scale Bias")));
+        assert_eq!(asset_file_name("Scale Bias"), "scale_bias.subgraph.ron");
+        std::fs::write(dir.join("broken.subgraph.ron"), "(name: ").unwrap();
+        std::fs::write(dir.join("notasubgraph.ron"), "(name: ").unwrap();
+
+        let assets = load_subgraph_assets(&dir);
+        assert_eq!(assets.len(), 2);
+        assert!(assets[0].1.is_err());
+        let loaded = assets[1].1.as_ref().expect("saved subgraph should load");
+        assert_eq!(loaded, &saved);
+        assert_eq!(loaded.signature(), saved.signature());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
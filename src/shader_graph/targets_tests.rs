@@ -0,0 +1,191 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::NodeGraph;
+    use crate::node_graph::test_utils::{add_node, connect};
+    use crate::shader_graph::compiler::{
+        CompileError, compile_pbr, compile_preview, compile_target, validate_wgsl,
+    };
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget};
+    use crate::shader_graph::nodes::geometry::{GeometryNode, Space};
+    use crate::shader_graph::nodes::texture::TextureNode;
+    use crate::shader_graph::nodes::time::TimeNode;
+    use crate::shader_graph::scaffold::{FULLSCREEN_BINDING_BASE, PreviewTarget};
+    use bevy::prelude::*;
+
+    /// Bevy's 2D mesh `VertexOutput` and view, as `mesh2d_vertex_output` and
+    /// `mesh2d_view_bindings` declare them
+    const SPRITE_STUBS: &str = "struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec4<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) world_tangent: vec4<f32>,
+    @location(4) color: vec4<f32>,
+};
+
+struct View {
+    clip_from_world: mat4x4<f32>,
+    view_from_world: mat4x4<f32>,
+    world_position: vec3<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> view: View;
+";
+
+    const FULLSCREEN_STUBS: &str = "struct FullscreenVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+";
+
+    const UI_STUBS: &str = "struct UiVertexOutput {
+    @location(0) uv: vec2<f32>,
+    @location(1) @interpolate(flat) size: vec2<f32>,
+    @location(2) @interpolate(flat) border_widths: vec4<f32>,
+    @location(3) @interpolate(flat) border_radius: vec4<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+";
+
+    fn validate_for(target: OutputTarget, source: &str) -> Result<(), CompileError> {
+        let stubs = match target {
+            OutputTarget::Sprite => SPRITE_STUBS,
+            OutputTarget::Fullscreen => FULLSCREEN_STUBS,
+            OutputTarget::Ui => UI_STUBS,
+            OutputTarget::Mesh => unreachable!("covered by preview_tests"),
+        };
+        let body: String = source
+            .lines()
+            .filter(|line| !line.starts_with("#import"))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .replace("#{MATERIAL_BIND_GROUP}", "2");
+        validate_wgsl(&format!("{}\n{}", stubs, body))
+    }
+
+    /// Output of the given target fed by a texture sampled at the object position, with the
+    /// clock as alpha, so every binding kind and a mesh-only interpolant show up
+    fn busy_graph(target: OutputTarget) -> NodeGraph {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(target));
+        let texture = add_node(
+            &mut graph,
+            NodeKind::Texture(TextureNode::default_texture()),
+        );
+        let sample = add_node(
+            &mut graph,
+            NodeKind::Texture(TextureNode::Sample(Default::default())),
+        );
+        let time = add_node(&mut graph, NodeKind::Time(TimeNode::Time));
+        let position = add_node(
+            &mut graph,
+            NodeKind::Geometry(GeometryNode::Position(Space::Object)),
+        );
+        connect(&mut graph, texture, 0, sample, 0);
+        connect(&mut graph, position, 0, sample, 1);
+        connect(&mut graph, sample, 1, output, 0);
+        connect(&mut graph, time, 0, output, 1);
+        graph
+    }
+
+    #[test]
+    fn test_every_target_validates() {
+        for target in [
+            OutputTarget::Sprite,
+            OutputTarget::Fullscreen,
+            OutputTarget::Ui,
+        ] {
+            let source = compile_target(&busy_graph(target))
+                .unwrap_or_else(|e| panic!("{:?} failed to compile: {}", target, e))
+                .source;
+            validate_for(target, &source)
+                .unwrap_or_else(|e| panic!("{:?} failed to validate: {}", target, e));
+        }
+    }
+
+    #[test]
+    fn test_mesh_and_pbr_targets_match_their_preview() {
+        let graph = busy_graph(OutputTarget::Mesh);
+        assert_eq!(compile_target(&graph), compile_preview(&graph));
+
+        let mut graph = NodeGraph::new();
+        add_node(&mut graph, NodeKind::PbrOutput);
+        assert_eq!(compile_target(&graph), compile_pbr(&graph));
+    }
+
+    #[test]
+    fn test_sprite_reads_the_2d_mesh_pipeline() {
+        let source = compile_target(&busy_graph(OutputTarget::Sprite))
+            .expect("graph should compile")
+            .source;
+        assert!(source.starts_with("#import bevy_sprite::mesh2d_vertex_output::VertexOutput"));
+        assert!(source.contains("#import bevy_sprite::mesh2d_view_bindings::view"));
+        assert!(
+            source.contains("@group(#{MATERIAL_BIND_GROUP}) @binding(0)\nvar<uniform> globals")
+        );
+        assert!(source.contains("fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32>"));
+        assert!(source.contains("input.object_position = mesh.world_position.xyz;"));
+    }
+
+    #[test]
+    fn test_fullscreen_binds_the_screen_first() {
+        let source = compile_target(&busy_graph(OutputTarget::Fullscreen))
+            .expect("graph should compile")
+            .source;
+        assert!(
+            source.contains("fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32>")
+        );
+        assert!(source.contains("@group(0) @binding(0)\nvar screen_texture: texture_2d<f32>;"));
+        assert!(source.contains("@group(0) @binding(1)\nvar screen_sampler: sampler;"));
+        assert!(source.contains(&format!(
+            "@group(0) @binding({})\nvar<uniform> globals: Globals;",
+            FULLSCREEN_BINDING_BASE
+        )));
+        assert!(!source.contains("MATERIAL_BIND_GROUP"));
+        assert!(!source.contains("view."));
+        assert!(source.contains("input.object_position = input.world_position;"));
+    }
+
+    #[test]
+    fn test_ui_uses_the_ui_material_group() {
+        let source = compile_target(&busy_graph(OutputTarget::Ui))
+            .expect("graph should compile")
+            .source;
+        assert!(source.starts_with("#import bevy_ui::ui_vertex_output::UiVertexOutput"));
+        assert!(source.contains("fn fragment(in: UiVertexOutput) -> @location(0) vec4<f32>"));
+        assert!(source.contains("@group(1) @binding(0)\nvar<uniform> globals: Globals;"));
+        assert!(source.contains("@group(1) @binding(1)\nvar node"));
+        assert!(!source.contains("MATERIAL_BIND_GROUP"));
+    }
+
+    #[test]
+    fn test_flat_targets_fill_mesh_only_inputs() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Ui));
+        let color = add_node(&mut graph, NodeKind::Geometry(GeometryNode::VertexColor));
+        connect(&mut graph, color, 0, output, 0);
+
+        let source = compile_target(&graph).expect("graph should compile").source;
+        assert!(source.contains("input.color = vec4<f32>(1.0);"));
+        validate_for(OutputTarget::Ui, &source).expect("UI shader should validate");
+    }
+
+    #[test]
+    fn test_output_target_maps_to_preview_target() {
+        let targets: Vec<PreviewTarget> = OutputTarget::ALL
+            .into_iter()
+            .map(PreviewTarget::from)
+            .collect();
+        assert_eq!(
+            targets,
+            [
+                PreviewTarget::Unlit,
+                PreviewTarget::Sprite,
+                PreviewTarget::Fullscreen,
+                PreviewTarget::Ui
+            ]
+        );
+        assert_eq!(OutputTarget::default(), OutputTarget::Mesh);
+    }
+}
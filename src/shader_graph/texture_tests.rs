@@ -0,0 +1,248 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::{NodeGraph, NodeId};
+    use crate::node_graph::pin_manager::PinPositionManager;
+    use crate::node_graph::test_utils::{add_node, connect};
+    use crate::shader_graph::compiler::{
+        CompileError, compile_and_validate, compile_fragment, compile_preview, output_var_name,
+    };
+    use crate::shader_graph::evaluator::GraphEvaluator;
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget};
+    use crate::shader_graph::nodes::texture::{
+        ImageAssets, SamplerState, TextureFilter, TextureNode, TextureWrap, find_images,
+    };
+    use crate::shader_graph::types::PinType;
+    use bevy::prelude::*;
+    use std::path::Path;
+
+    fn texture(graph: &mut NodeGraph, path: &str) -> NodeId {
+        add_node(
+            graph,
+            NodeKind::Texture(TextureNode::Texture2D {
+                path: Some(path.to_string()),
+            }),
+        )
+    }
+
+    fn sample(graph: &mut NodeGraph, sampler: SamplerState) -> NodeId {
+        add_node(graph, NodeKind::Texture(TextureNode::Sample(sampler)))
+    }
+
+    /// Texture 2D -> Sample Texture 2D -> Fragment Output
+    fn sampled_texture_graph() -> (NodeGraph, NodeId, NodeId) {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let texture = texture(&mut graph, "ducky.png");
+        let sample = sample(&mut graph, SamplerState::default());
+        connect(&mut graph, texture, 0, sample, 0);
+        connect(&mut graph, sample, 1, output, 0);
+        connect(&mut graph, sample, 2, output, 1);
+        (graph, texture, sample)
+    }
+
+    #[test]
+    fn test_sampler_states_are_indexed_in_order() {
+        for (index, state) in SamplerState::ALL.iter().enumerate() {
+            assert_eq!(state.index(), index);
+        }
+        assert_eq!(
+            SamplerState::new(TextureFilter::Nearest, TextureWrap::Mirror).wgsl_name(),
+            "sampler_nearest_mirror"
+        );
+    }
+
+    #[test]
+    fn test_find_images_lists_bundled_assets() {
+        assert!(find_images(Path::new("assets")).contains(&"ducky.png".to_string()));
+        assert!(find_images(Path::new("does-not-exist")).is_empty());
+    }
+
+    #[test]
+    fn test_image_list_changes_only_on_refresh() {
+        let dir = std::env::temp_dir().join(format!("image_assets_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("scratch dir should be writable");
+
+        let mut images = ImageAssets::load(&dir);
+        assert!(images.paths.is_empty());
+        std::fs::write(dir.join("noise.png"), []).unwrap();
+        assert!(images.paths.is_empty());
+        images.refresh();
+        assert_eq!(images.paths, ["noise.png"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_texture_output_becomes_binding() {
+        let (graph, texture, _) = sampled_texture_graph();
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        let name = output_var_name(texture, 0);
+        assert!(source.contains(&format!(
+            "@group(1) @binding(0)\nvar {}: texture_2d<f32>;",
+            name
+        )));
+        assert!(source.contains("@group(1) @binding(1)\nvar sampler_linear_repeat: sampler;"));
+        assert!(!source.contains(&format!("let {}", name)));
+        assert!(source.contains(&format!(
+            "textureSample({}, sampler_linear_repeat, input.uv)",
+            name
+        )));
+    }
+
+    #[test]
+    fn test_unconnected_texture_samples_default() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let sample = sample(&mut graph, SamplerState::default());
+        connect(&mut graph, sample, 1, output, 0);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert!(source.contains("var default_texture: texture_2d<f32>;"));
+
+        let preview = compile_preview(&graph).expect("graph should compile");
+        assert_eq!(preview.images, vec![None]);
+    }
+
+    #[test]
+    fn test_bindings_are_allocated_once_per_texture_and_sampler() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let blend = add_node(
+            &mut graph,
+            NodeKind::Math(crate::node_graph::node_factory::MathOperation::Add),
+        );
+        let ducky = texture(&mut graph, "ducky.png");
+        let other = texture(&mut graph, "other.png");
+        let nearest = SamplerState::new(TextureFilter::Nearest, TextureWrap::Clamp);
+        let a = sample(&mut graph, SamplerState::default());
+        let b = sample(&mut graph, nearest);
+        let c = sample(&mut graph, nearest);
+        connect(&mut graph, ducky, 0, a, 0);
+        connect(&mut graph, other, 0, b, 0);
+        connect(&mut graph, ducky, 0, c, 0);
+        connect(&mut graph, a, 2, blend, 0);
+        connect(&mut graph, b, 2, blend, 1);
+        connect(&mut graph, blend, 0, output, 1);
+        connect(&mut graph, c, 1, output, 0);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert_eq!(source.matches(": texture_2d<f32>;").count(), 2);
+        assert_eq!(source.matches(": sampler;").count(), 2);
+        assert!(source.contains("@group(1) @binding(3)"));
+        assert!(!source.contains("@group(1) @binding(4)"));
+
+        let preview = compile_preview(&graph).expect("graph should compile");
+        assert_eq!(preview.images.len(), 2);
+        assert!(preview.images.contains(&Some("ducky.png".to_string())));
+        assert!(preview.images.contains(&Some("other.png".to_string())));
+    }
+
+    #[test]
+    fn test_every_sampler_state_validates() {
+        for state in SamplerState::ALL {
+            let mut graph = NodeGraph::new();
+            let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+            let texture = texture(&mut graph, "ducky.png");
+            let sample = sample(&mut graph, state);
+            connect(&mut graph, texture, 0, sample, 0);
+            connect(&mut graph, sample, 0, output, 0);
+
+            compile_and_validate(&graph)
+                .unwrap_or_else(|e| panic!("{:?} failed to compile: {}", state, e));
+        }
+    }
+
+    #[test]
+    fn test_preview_rejects_more_textures_than_slots() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let mut previous = None;
+        for i in 0..5 {
+            let texture = texture(&mut graph, &format!("image{}.png", i));
+            let sample = sample(&mut graph, SamplerState::default());
+            connect(&mut graph, texture, 0, sample, 0);
+            let sum = add_node(
+                &mut graph,
+                NodeKind::Math(crate::node_graph::node_factory::MathOperation::Add),
+            );
+            connect(&mut graph, sample, 2, sum, 0);
+            if let Some(previous) = previous {
+                connect(&mut graph, previous, 0, sum, 1);
+            }
+            previous = Some(sum);
+        }
+        connect(&mut graph, previous.unwrap(), 0, output, 1);
+
+        assert!(compile_fragment(&graph).is_ok());
+        assert_eq!(
+            compile_preview(&graph).map(|p| p.images),
+            Err(CompileError::TooManyTextures(5))
+        );
+    }
+
+    #[test]
+    fn test_texture_cannot_feed_a_value_input() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let texture = texture(&mut graph, "ducky.png");
+        connect(&mut graph, texture, 0, output, 0);
+        let to_pin = graph.nodes[&output].inputs[0].pin_id;
+
+        let expected = CompileError::IncompatibleTypes {
+            pin: to_pin,
+            from: PinType::Texture2D,
+            to: PinType::Vec3,
+        };
+        assert_eq!(compile_fragment(&graph), Err(expected.clone()));
+        assert_eq!(
+            GraphEvaluator::new(&graph).evaluate_fragment(),
+            Err(expected)
+        );
+    }
+
+    #[test]
+    fn test_pin_manager_refuses_texture_to_value_connections() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let texture = texture(&mut graph, "ducky.png");
+        let sample = sample(&mut graph, SamplerState::default());
+
+        let texture_out = graph.nodes[&texture].outputs[0].pin_id;
+        let color_in = graph.nodes[&output].inputs[0].pin_id;
+        let sample_texture_in = graph.nodes[&sample].inputs[0].pin_id;
+        let sample_uv_in = graph.nodes[&sample].inputs[1].pin_id;
+        let rgb_out = graph.nodes[&sample].outputs[1].pin_id;
+
+        for (from, to, compatible) in [
+            (texture_out, sample_texture_in, true),
+            (texture_out, color_in, false),
+            (texture_out, sample_uv_in, false),
+            (rgb_out, sample_texture_in, false),
+            (rgb_out, color_in, true),
+        ] {
+            assert_eq!(
+                PinPositionManager::pin_types_compatible(from, to, &graph),
+                compatible
+            );
+        }
+    }
+
+    #[test]
+    fn test_cpu_samples_read_white() {
+        let (graph, _, sample) = sampled_texture_graph();
+        let mut evaluator = GraphEvaluator::new(&graph);
+        assert_eq!(
+            evaluator
+                .evaluate_fragment()
+                .expect("graph should evaluate"),
+            Vec4::ONE
+        );
+        assert_eq!(
+            evaluator
+                .evaluate_node(sample)
+                .expect("graph should evaluate")[0],
+            crate::shader_graph::types::Value::Vec4(Vec4::ONE)
+        );
+    }
+}
@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::NodeGraph;
+    use crate::node_graph::test_utils::{add_node, assert_near, connect};
+    use crate::shader_graph::compiler::{compile_and_validate, compile_fragment};
+    use crate::shader_graph::evaluator::GraphEvaluator;
+    use crate::shader_graph::inputs::EvalContext;
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget, ShaderNode};
+    use crate::shader_graph::nodes::time::{TimeNode, Waveform, remap};
+    use crate::shader_graph::types::Value;
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_waveforms_start_at_zero_and_peak_at_quarter() {
+        for wave in [Waveform::Sine, Waveform::Triangle] {
+            assert_near(wave.sample(0.0), 0.0);
+            assert_near(wave.sample(0.25), 1.0);
+            assert_near(wave.sample(0.75), -1.0);
+        }
+    }
+
+    #[test]
+    fn test_square_wave_halves() {
+        assert_eq!(Waveform::Square.sample(0.1), 1.0);
+        assert_eq!(Waveform::Square.sample(0.6), -1.0);
+        assert_eq!(Waveform::Square.sample(-0.6), 1.0);
+    }
+
+    #[test]
+    fn test_sawtooth_ramps_up_and_wraps() {
+        assert_near(Waveform::Sawtooth.sample(0.0), 0.0);
+        assert_near(Waveform::Sawtooth.sample(0.25), 0.5);
+        assert_near(Waveform::Sawtooth.sample(0.75), -0.5);
+    }
+
+    #[test]
+    fn test_waveforms_are_periodic() {
+        for wave in Waveform::ALL {
+            assert_near(wave.sample(0.3), wave.sample(2.3));
+        }
+    }
+
+    #[test]
+    fn test_oscillator_uses_frequency_phase_and_amplitude() {
+        let node = TimeNode::Oscillator(Waveform::Sine);
+        let out = node.evaluate(
+            &[
+                Value::Float(1.0),
+                Value::Float(0.5),
+                Value::Float(-0.25),
+                Value::Float(3.0),
+            ],
+            &EvalContext::default(),
+        );
+        // 1s * 0.5Hz - 0.25 cycles = a quarter cycle
+        assert_near(out[0].as_f32(), 3.0);
+    }
+
+    #[test]
+    fn test_remap() {
+        assert_near(remap(0.0, -1.0, 1.0, 0.0, 1.0), 0.5);
+        assert_near(remap(5.0, 0.0, 10.0, 100.0, 200.0), 150.0);
+        assert_near(remap(2.0, 0.0, 1.0, 1.0, 0.0), -1.0);
+    }
+
+    #[test]
+    fn test_time_node_outputs() {
+        let ctx = EvalContext {
+            time: 2.0,
+            delta_time: 0.25,
+            ..Default::default()
+        };
+        let out = TimeNode::Time.evaluate(&[], &ctx);
+        assert_eq!(out[0], Value::Float(2.0));
+        assert_near(out[1].as_f32(), 2.0_f32.sin());
+        assert_near(out[2].as_f32(), 2.0_f32.cos());
+        assert_eq!(out[3], Value::Float(0.25));
+    }
+
+    #[test]
+    fn test_time_reads_globals_uniform() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let time = add_node(&mut graph, NodeKind::Time(TimeNode::Time));
+        connect(&mut graph, time, 1, output, 0);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert!(source.contains("var<uniform> globals: Globals;"));
+        assert!(source.contains("sin(globals.time)"));
+    }
+
+    #[test]
+    fn test_unconnected_oscillator_time_defaults_to_clock() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let osc = add_node(
+            &mut graph,
+            NodeKind::Time(TimeNode::Oscillator(Waveform::Square)),
+        );
+        connect(&mut graph, osc, 0, output, 0);
+
+        let source = compile_fragment(&graph).expect("graph should compile");
+        assert!(source.contains("wave_square(globals.time * 1.0 + 0.0)"));
+
+        let at = |time: f32| {
+            GraphEvaluator::with_context(&graph, EvalContext::at_time(time))
+                .evaluate_fragment()
+                .expect("graph should evaluate")
+                .x
+        };
+        assert_eq!(at(0.25), 1.0);
+        assert_eq!(at(0.75), -1.0);
+    }
+
+    #[test]
+    fn test_every_time_node_validates() {
+        let mut nodes = vec![TimeNode::Time, TimeNode::Remap];
+        nodes.extend(Waveform::ALL.map(TimeNode::Oscillator));
+        for node in nodes {
+            let mut graph = NodeGraph::new();
+            let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+            let time = add_node(&mut graph, NodeKind::Time(node));
+            connect(&mut graph, time, 0, output, 1);
+
+            compile_and_validate(&graph)
+                .unwrap_or_else(|e| panic!("{:?} failed to compile: {}", node, e));
+        }
+    }
+
+    #[test]
+    fn test_oscillator_feeding_remap() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let osc = add_node(
+            &mut graph,
+            NodeKind::Time(TimeNode::Oscillator(Waveform::Sine)),
+        );
+        let remap = add_node(&mut graph, NodeKind::Time(TimeNode::Remap));
+        connect(&mut graph, osc, 0, remap, 0);
+        connect(&mut graph, remap, 0, output, 0);
+
+        compile_and_validate(&graph).expect("graph should compile");
+        let color = GraphEvaluator::with_context(&graph, EvalContext::at_time(0.25))
+            .evaluate_fragment()
+            .expect("graph should evaluate");
+        // sin peaks at +1, remapped from [-1, 1] to [0, 1]
+        assert_near(color.x, 1.0);
+    }
+}
@@ -0,0 +1,334 @@
+use bevy::math::{BVec2, BVec3, BVec4};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Data type carried by a pin - maps one-to-one onto a WGSL type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PinType {
+    Bool,
+    Float,
+    Vec2,
+    Vec3,
+    Vec4,
+    /// `vecN<bool>` masks, as component-wise comparisons produce
+    BVec2,
+    BVec3,
+    BVec4,
+    /// A `texture_2d<f32>` binding - flows between nodes by name, never through a `let`
+    Texture2D,
+}
+
+impl PinType {
+    /// WGSL spelling of this type
+    pub fn wgsl_type(self) -> &'static str {
+        match self {
+            PinType::Bool => "bool",
+            PinType::Float => "f32",
+            PinType::Vec2 => "vec2<f32>",
+            PinType::Vec3 => "vec3<f32>",
+            PinType::Vec4 => "vec4<f32>",
+            PinType::BVec2 => "vec2<bool>",
+            PinType::BVec3 => "vec3<bool>",
+            PinType::BVec4 => "vec4<bool>",
+            PinType::Texture2D => "texture_2d<f32>",
+        }
+    }
+
+    /// `bool` or `vecN<bool>`
+    pub fn is_boolean(self) -> bool {
+        matches!(
+            self,
+            PinType::Bool | PinType::BVec2 | PinType::BVec3 | PinType::BVec4
+        )
+    }
+
+    /// Float type of the same width: `vec3<bool>` is `vec3<f32>`
+    pub fn float_type(self) -> PinType {
+        match self {
+            PinType::Bool => PinType::Float,
+            PinType::BVec2 => PinType::Vec2,
+            PinType::BVec3 => PinType::Vec3,
+            PinType::BVec4 => PinType::Vec4,
+            other => other,
+        }
+    }
+
+    /// Boolean type of the same width: what comparing two values of this type gives
+    pub fn bool_type(self) -> PinType {
+        match self {
+            PinType::Float => PinType::Bool,
+            PinType::Vec2 => PinType::BVec2,
+            PinType::Vec3 => PinType::BVec3,
+            PinType::Vec4 => PinType::BVec4,
+            other => other,
+        }
+    }
+
+    /// Textures are resources, not values: they only connect to texture pins
+    pub fn is_texture(self) -> bool {
+        self == PinType::Texture2D
+    }
+
+    /// Whether an output of this type can feed an input of type `to`
+    pub fn converts_to(self, to: PinType) -> bool {
+        self == to || (!self.is_texture() && !to.is_texture())
+    }
+
+    /// Number of scalar components
+    pub fn component_count(self) -> usize {
+        match self {
+            PinType::Bool | PinType::Float => 1,
+            PinType::Vec2 | PinType::BVec2 => 2,
+            PinType::Vec3 | PinType::BVec3 => 3,
+            PinType::Vec4 | PinType::BVec4 => 4,
+            PinType::Texture2D => 0,
+        }
+    }
+
+    /// Wrap a WGSL expression of this type so it evaluates to `to`.
+    ///
+    /// Scalars splat, wider vectors truncate and narrower vectors are padded
+    /// with zeros (alpha pads with one). Booleans convert through the float type of their
+    /// width, `true` being one and any non-zero component `true`.
+    pub fn convert_expr(self, expr: &str, to: PinType) -> String {
+        if self == to {
+            return expr.to_string();
+        }
+
+        match (self, to) {
+            (PinType::Bool, PinType::Float) => format!("select(0.0, 1.0, {})", expr),
+            (PinType::BVec2 | PinType::BVec3 | PinType::BVec4, _) | (PinType::Bool, _) => {
+                let float = self.float_type();
+                let as_float = format!(
+                    "select({}, {}, {})",
+                    Value::Float(0.0).convert(float).to_wgsl(),
+                    Value::Float(1.0).convert(float).to_wgsl(),
+                    expr
+                );
+                float.convert_expr(&as_float, to)
+            }
+            (_, PinType::Bool) => format!("({} != 0.0)", self.convert_expr(expr, PinType::Float)),
+            (_, PinType::BVec2 | PinType::BVec3 | PinType::BVec4) => {
+                let float = to.float_type();
+                format!(
+                    "({} != {})",
+                    self.convert_expr(expr, float),
+                    Value::Float(0.0).convert(float).to_wgsl()
+                )
+            }
+            (PinType::Float, _) => format!("{}({})", to.wgsl_type(), expr),
+            (_, PinType::Float) => format!("({}).x", expr),
+            (PinType::Vec3 | PinType::Vec4, PinType::Vec2) => format!("({}).xy", expr),
+            (PinType::Vec4, PinType::Vec3) => format!("({}).xyz", expr),
+            (PinType::Vec2, PinType::Vec3) => format!("vec3<f32>({}, 0.0)", expr),
+            (PinType::Vec2, PinType::Vec4) => format!("vec4<f32>({}, 0.0, 1.0)", expr),
+            (PinType::Vec3, PinType::Vec4) => format!("vec4<f32>({}, 1.0)", expr),
+            _ => expr.to_string(),
+        }
+    }
+}
+
+/// A concrete value flowing through the graph on the CPU
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Value {
+    Bool(bool),
+    Float(f32),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Vec4(Vec4),
+    BVec2(BVec2),
+    BVec3(BVec3),
+    BVec4(BVec4),
+    /// Stand-in for a texture binding - the CPU evaluator has no image data
+    Texture,
+}
+
+impl Value {
+    /// Zero value for the given type (alpha of Vec4 is one)
+    pub fn zero(ty: PinType) -> Self {
+        match ty {
+            PinType::Bool => Value::Bool(false),
+            PinType::Float => Value::Float(0.0),
+            PinType::Vec2 => Value::Vec2(Vec2::ZERO),
+            PinType::Vec3 => Value::Vec3(Vec3::ZERO),
+            PinType::Vec4 => Value::Vec4(Vec4::new(0.0, 0.0, 0.0, 1.0)),
+            PinType::BVec2 => Value::BVec2(BVec2::FALSE),
+            PinType::BVec3 => Value::BVec3(BVec3::FALSE),
+            PinType::BVec4 => Value::BVec4(BVec4::FALSE),
+            PinType::Texture2D => Value::Texture,
+        }
+    }
+
+    pub fn pin_type(&self) -> PinType {
+        match self {
+            Value::Bool(_) => PinType::Bool,
+            Value::Float(_) => PinType::Float,
+            Value::Vec2(_) => PinType::Vec2,
+            Value::Vec3(_) => PinType::Vec3,
+            Value::Vec4(_) => PinType::Vec4,
+            Value::BVec2(_) => PinType::BVec2,
+            Value::BVec3(_) => PinType::BVec3,
+            Value::BVec4(_) => PinType::BVec4,
+            Value::Texture => PinType::Texture2D,
+        }
+    }
+
+    /// Components as floats, `true` being one - empty for textures
+    pub fn components(&self) -> Vec<f32> {
+        let bit = |b: bool| if b { 1.0 } else { 0.0 };
+        match *self {
+            Value::Bool(b) => vec![bit(b)],
+            Value::Float(f) => vec![f],
+            Value::Vec2(v) => v.to_array().to_vec(),
+            Value::Vec3(v) => v.to_array().to_vec(),
+            Value::Vec4(v) => v.to_array().to_vec(),
+            Value::BVec2(v) => <[bool; 2]>::from(v).map(bit).to_vec(),
+            Value::BVec3(v) => <[bool; 3]>::from(v).map(bit).to_vec(),
+            Value::BVec4(v) => <[bool; 4]>::from(v).map(bit).to_vec(),
+            Value::Texture => Vec::new(),
+        }
+    }
+
+    /// Value of type `ty` from its components, missing ones zero; boolean components are
+    /// `true` when non-zero
+    pub fn from_components(ty: PinType, components: &[f32]) -> Value {
+        let c = |i: usize| components.get(i).copied().unwrap_or(0.0);
+        let b = |i: usize| c(i) != 0.0;
+        match ty {
+            PinType::Bool => Value::Bool(b(0)),
+            PinType::Float => Value::Float(c(0)),
+            PinType::Vec2 => Value::Vec2(Vec2::new(c(0), c(1))),
+            PinType::Vec3 => Value::Vec3(Vec3::new(c(0), c(1), c(2))),
+            PinType::Vec4 => Value::Vec4(Vec4::new(c(0), c(1), c(2), c(3))),
+            PinType::BVec2 => Value::BVec2(BVec2::new(b(0), b(1))),
+            PinType::BVec3 => Value::BVec3(BVec3::new(b(0), b(1), b(2))),
+            PinType::BVec4 => Value::BVec4(BVec4::new(b(0), b(1), b(2), b(3))),
+            PinType::Texture2D => Value::Texture,
+        }
+    }
+
+    /// Convert to another type using the same rules as `PinType::convert_expr`
+    pub fn convert(self, to: PinType) -> Value {
+        match (self, to) {
+            (v, t) if v.pin_type() == t => v,
+            (Value::Texture, t) | (_, t @ PinType::Texture2D) => Value::zero(t),
+            (Value::Bool(b), t) => Value::Float(if b { 1.0 } else { 0.0 }).convert(t),
+            (v @ (Value::BVec2(_) | Value::BVec3(_) | Value::BVec4(_)), t) => {
+                let float = v.pin_type().float_type();
+                Value::from_components(float, &v.components()).convert(t)
+            }
+            (v, PinType::Bool) => Value::Bool(v.as_f32() != 0.0),
+            (v, t @ (PinType::BVec2 | PinType::BVec3 | PinType::BVec4)) => {
+                let float = v.convert(t.float_type());
+                Value::from_components(t, &float.components())
+            }
+            (Value::Float(f), PinType::Vec2) => Value::Vec2(Vec2::splat(f)),
+            (Value::Float(f), PinType::Vec3) => Value::Vec3(Vec3::splat(f)),
+            (Value::Float(f), PinType::Vec4) => Value::Vec4(Vec4::splat(f)),
+            (v, PinType::Float) => Value::Float(v.as_f32()),
+            (v, PinType::Vec2) => Value::Vec2(v.as_vec2()),
+            (Value::Vec2(v), PinType::Vec3) => Value::Vec3(v.extend(0.0)),
+            (Value::Vec4(v), PinType::Vec3) => Value::Vec3(v.truncate()),
+            (Value::Vec2(v), PinType::Vec4) => Value::Vec4(Vec4::new(v.x, v.y, 0.0, 1.0)),
+            (Value::Vec3(v), PinType::Vec4) => Value::Vec4(v.extend(1.0)),
+            (v, _) => v,
+        }
+    }
+
+    /// First component as a scalar
+    pub fn as_f32(&self) -> f32 {
+        match *self {
+            Value::Bool(b) => {
+                if b {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Value::Float(f) => f,
+            Value::Vec2(v) => v.x,
+            Value::Vec3(v) => v.x,
+            Value::Vec4(v) => v.x,
+            Value::BVec2(_) | Value::BVec3(_) | Value::BVec4(_) => self.components()[0],
+            Value::Texture => 0.0,
+        }
+    }
+
+    pub fn as_vec2(&self) -> Vec2 {
+        match *self {
+            Value::Vec2(v) => v,
+            Value::Vec3(v) => v.truncate(),
+            Value::Vec4(v) => v.truncate().truncate(),
+            Value::BVec2(_) | Value::BVec3(_) | Value::BVec4(_) => {
+                self.convert(PinType::Vec2).as_vec2()
+            }
+            other => Vec2::splat(other.as_f32()),
+        }
+    }
+
+    pub fn as_vec3(&self) -> Vec3 {
+        match self.convert(PinType::Vec3) {
+            Value::Vec3(v) => v,
+            _ => Vec3::ZERO,
+        }
+    }
+
+    pub fn as_vec4(&self) -> Vec4 {
+        match self.convert(PinType::Vec4) {
+            Value::Vec4(v) => v,
+            _ => Vec4::ZERO,
+        }
+    }
+
+    /// WGSL literal for this value
+    pub fn to_wgsl(self) -> String {
+        match self {
+            Value::Bool(b) => b.to_string(),
+            Value::Float(f) => wgsl_float(f),
+            Value::Vec2(v) => format!("vec2<f32>({}, {})", wgsl_float(v.x), wgsl_float(v.y)),
+            Value::Vec3(v) => format!(
+                "vec3<f32>({}, {}, {})",
+                wgsl_float(v.x),
+                wgsl_float(v.y),
+                wgsl_float(v.z)
+            ),
+            Value::Vec4(v) => format!(
+                "vec4<f32>({}, {}, {}, {})",
+                wgsl_float(v.x),
+                wgsl_float(v.y),
+                wgsl_float(v.z),
+                wgsl_float(v.w)
+            ),
+            Value::BVec2(_) | Value::BVec3(_) | Value::BVec4(_) => format!(
+                "{}({})",
+                self.pin_type().wgsl_type(),
+                self.components()
+                    .iter()
+                    .map(|&c| (c != 0.0).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Value::Texture => DEFAULT_TEXTURE.to_string(),
+        }
+    }
+}
+
+/// Binding an unconnected texture input reads - bound to a plain white image
+pub const DEFAULT_TEXTURE: &str = "default_texture";
+
+/// Format an f32 as a WGSL float literal (always carries a decimal point or exponent).
+/// WGSL has no literal for infinity or NaN, so infinities clamp to the largest finite f32
+/// and NaN becomes zero
+pub fn wgsl_float(value: f32) -> String {
+    let value = if value.is_nan() {
+        0.0
+    } else {
+        value.clamp(f32::MIN, f32::MAX)
+    };
+    let text = format!("{:?}", value);
+    if text.contains('.') || text.contains('e') {
+        text
+    } else {
+        format!("{}.0", text)
+    }
+}
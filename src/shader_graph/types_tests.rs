@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod tests {
+    use crate::shader_graph::types::{PinType, Value, wgsl_float};
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_wgsl_type_names() {
+        assert_eq!(PinType::Bool.wgsl_type(), "bool");
+        assert_eq!(PinType::Float.wgsl_type(), "f32");
+        assert_eq!(PinType::Vec2.wgsl_type(), "vec2<f32>");
+        assert_eq!(PinType::Vec3.wgsl_type(), "vec3<f32>");
+        assert_eq!(PinType::Vec4.wgsl_type(), "vec4<f32>");
+    }
+
+    #[test]
+    fn test_component_count() {
+        assert_eq!(PinType::Float.component_count(), 1);
+        assert_eq!(PinType::Vec2.component_count(), 2);
+        assert_eq!(PinType::Vec3.component_count(), 3);
+        assert_eq!(PinType::Vec4.component_count(), 4);
+    }
+
+    #[test]
+    fn test_convert_expr_same_type_is_identity() {
+        assert_eq!(PinType::Vec3.convert_expr("c", PinType::Vec3), "c");
+    }
+
+    #[test]
+    fn test_convert_expr_splats_scalars() {
+        assert_eq!(
+            PinType::Float.convert_expr("x", PinType::Vec3),
+            "vec3<f32>(x)"
+        );
+    }
+
+    #[test]
+    fn test_convert_expr_truncates_and_pads() {
+        assert_eq!(PinType::Vec4.convert_expr("c", PinType::Vec3), "(c).xyz");
+        assert_eq!(PinType::Vec3.convert_expr("c", PinType::Float), "(c).x");
+        assert_eq!(
+            PinType::Vec3.convert_expr("c", PinType::Vec4),
+            "vec4<f32>(c, 1.0)"
+        );
+        assert_eq!(
+            PinType::Vec2.convert_expr("uv", PinType::Vec3),
+            "vec3<f32>(uv, 0.0)"
+        );
+    }
+
+    #[test]
+    fn test_convert_expr_bool() {
+        assert_eq!(
+            PinType::Bool.convert_expr("b", PinType::Float),
+            "select(0.0, 1.0, b)"
+        );
+        assert_eq!(
+            PinType::Float.convert_expr("x", PinType::Bool),
+            "(x != 0.0)"
+        );
+    }
+
+    #[test]
+    fn test_value_convert_matches_expr_rules() {
+        assert_eq!(
+            Value::Float(0.5).convert(PinType::Vec3),
+            Value::Vec3(Vec3::splat(0.5))
+        );
+        assert_eq!(
+            Value::Vec4(Vec4::new(1.0, 2.0, 3.0, 4.0)).convert(PinType::Vec3),
+            Value::Vec3(Vec3::new(1.0, 2.0, 3.0))
+        );
+        assert_eq!(
+            Value::Vec3(Vec3::new(1.0, 2.0, 3.0)).convert(PinType::Vec4),
+            Value::Vec4(Vec4::new(1.0, 2.0, 3.0, 1.0))
+        );
+        assert_eq!(
+            Value::Vec2(Vec2::new(1.0, 2.0)).convert(PinType::Vec3),
+            Value::Vec3(Vec3::new(1.0, 2.0, 0.0))
+        );
+        assert_eq!(Value::Bool(true).convert(PinType::Float), Value::Float(1.0));
+        assert_eq!(Value::Float(0.0).convert(PinType::Bool), Value::Bool(false));
+    }
+
+    #[test]
+    fn test_value_zero() {
+        assert_eq!(Value::zero(PinType::Float), Value::Float(0.0));
+        assert_eq!(
+            Value::zero(PinType::Vec4),
+            Value::Vec4(Vec4::new(0.0, 0.0, 0.0, 1.0))
+        );
+        assert_eq!(Value::zero(PinType::Vec3).pin_type(), PinType::Vec3);
+    }
+
+    #[test]
+    fn test_wgsl_float_always_has_decimal_point() {
+        assert_eq!(wgsl_float(1.0), "1.0");
+        assert_eq!(wgsl_float(0.25), "0.25");
+        assert_eq!(wgsl_float(-3.0), "-3.0");
+    }
+
+    #[test]
+    fn test_wgsl_float_never_emits_inf_or_nan() {
+        assert_eq!(wgsl_float(f32::INFINITY), "3.4028235e38");
+        assert_eq!(wgsl_float(f32::NEG_INFINITY), "-3.4028235e38");
+        assert_eq!(wgsl_float(f32::NAN), "0.0");
+        assert_eq!(
+            Value::Vec2(Vec2::new(1.0 / 0.0, 0.5)).to_wgsl(),
+            "vec2<f32>(3.4028235e38, 0.5)"
+        );
+    }
+
+    #[test]
+    fn test_value_to_wgsl() {
+        assert_eq!(Value::Float(2.0).to_wgsl(), "2.0");
+        assert_eq!(Value::Bool(true).to_wgsl(), "true");
+        assert_eq!(
+            Value::Vec3(Vec3::new(1.0, 0.5, 0.0)).to_wgsl(),
+            "vec3<f32>(1.0, 0.5, 0.0)"
+        );
+    }
+
+    #[test]
+    fn test_mask_conversions() {
+        assert_eq!(
+            PinType::BVec3.convert_expr("m", PinType::Vec3),
+            "select(vec3<f32>(0.0, 0.0, 0.0), vec3<f32>(1.0, 1.0, 1.0), m)"
+        );
+        assert_eq!(
+            PinType::Vec3.convert_expr("v", PinType::BVec3),
+            "(v != vec3<f32>(0.0, 0.0, 0.0))"
+        );
+        assert_eq!(
+            PinType::Bool.convert_expr("b", PinType::Vec2),
+            "vec2<f32>(select(0.0, 1.0, b))"
+        );
+        assert_eq!(
+            Value::Vec3(Vec3::new(0.0, 2.0, -1.0)).convert(PinType::BVec3),
+            Value::BVec3(BVec3::new(false, true, true))
+        );
+        assert_eq!(
+            Value::BVec2(BVec2::new(true, false)).convert(PinType::Vec4),
+            Value::Vec4(Vec4::new(1.0, 0.0, 0.0, 1.0))
+        );
+        assert_eq!(
+            Value::Bool(true).convert(PinType::BVec2),
+            Value::BVec2(BVec2::TRUE)
+        );
+        assert_eq!(
+            Value::BVec4(BVec4::new(true, false, true, false)).to_wgsl(),
+            "vec4<bool>(true, false, true, false)"
+        );
+        assert_eq!(PinType::Vec3.bool_type(), PinType::BVec3);
+        assert_eq!(PinType::BVec2.float_type(), PinType::Vec2);
+        assert!(PinType::BVec4.is_boolean() && !PinType::Vec4.is_boolean());
+    }
+}
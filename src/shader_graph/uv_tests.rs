@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::NodeGraph;
+    use crate::node_graph::test_utils::{add_node, assert_near, connect};
+    use crate::shader_graph::compiler::{compile_and_validate, compile_fragment};
+    use crate::shader_graph::evaluator::GraphEvaluator;
+    use crate::shader_graph::inputs::{EvalContext, ShaderInput};
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget, ShaderNode};
+    use crate::shader_graph::nodes::uv::{UvNode, flipbook, from_polar, rotate, to_polar};
+    use crate::shader_graph::types::Value;
+    use bevy::prelude::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    const ALL_UV_NODES: [UvNode; 10] = [
+        UvNode::Uv,
+        UvNode::TilingOffset,
+        UvNode::Rotate,
+        UvNode::Flipbook,
+        UvNode::CartesianToPolar,
+        UvNode::PolarToCartesian,
+        UvNode::Twirl,
+        UvNode::Spherize,
+        UvNode::RadialShear,
+        UvNode::ScreenPosition,
+    ];
+
+    fn evaluate(node: UvNode, inputs: &[Value], uv: Vec2) -> Vec2 {
+        node.evaluate(inputs, &EvalContext::at_uv(uv))[0].as_vec2()
+    }
+
+    #[test]
+    fn test_uv_input_reads_vertex_output() {
+        assert_eq!(UvNode::Uv.emit_wgsl(&[]), vec!["input.uv".to_string()]);
+        assert_eq!(
+            evaluate(UvNode::Uv, &[], Vec2::new(0.25, 0.75)),
+            Vec2::new(0.25, 0.75)
+        );
+    }
+
+    #[test]
+    fn test_unconnected_uv_pins_default_to_mesh_uv() {
+        let signature = UvNode::TilingOffset.signature();
+        assert_eq!(signature.inputs[0].implicit, Some(ShaderInput::Uv));
+        assert_eq!(signature.inputs[0].default_wgsl(), "input.uv");
+        assert_eq!(signature.inputs[1].default_wgsl(), "vec2<f32>(1.0, 1.0)");
+    }
+
+    #[test]
+    fn test_tiling_and_offset() {
+        let result = evaluate(
+            UvNode::TilingOffset,
+            &[
+                Value::Vec2(Vec2::new(0.5, 0.25)),
+                Value::Vec2(Vec2::new(2.0, 4.0)),
+                Value::Vec2(Vec2::new(0.1, 0.0)),
+            ],
+            Vec2::ZERO,
+        );
+        assert_near(result, Vec2::new(1.1, 1.0));
+    }
+
+    #[test]
+    fn test_rotate_quarter_turn_around_center() {
+        assert_near(
+            rotate(Vec2::new(1.0, 0.5), Vec2::splat(0.5), FRAC_PI_2),
+            Vec2::new(0.5, 1.0),
+        );
+    }
+
+    #[test]
+    fn test_flipbook_picks_cells_left_to_right_top_to_bottom() {
+        assert_near(flipbook(Vec2::ZERO, 2.0, 2.0, 0.0), Vec2::ZERO);
+        assert_near(flipbook(Vec2::ZERO, 2.0, 2.0, 1.0), Vec2::new(0.5, 0.0));
+        assert_near(flipbook(Vec2::ZERO, 2.0, 2.0, 2.0), Vec2::new(0.0, 0.5));
+        assert_near(flipbook(Vec2::ONE, 2.0, 2.0, 3.0), Vec2::ONE);
+    }
+
+    #[test]
+    fn test_flipbook_wraps_frames() {
+        assert_near(
+            flipbook(Vec2::ZERO, 2.0, 2.0, 5.0),
+            flipbook(Vec2::ZERO, 2.0, 2.0, 1.0),
+        );
+        assert_near(
+            flipbook(Vec2::ZERO, 2.0, 2.0, -1.0),
+            flipbook(Vec2::ZERO, 2.0, 2.0, 3.0),
+        );
+    }
+
+    #[test]
+    fn test_polar_roundtrip() {
+        let center = Vec2::splat(0.5);
+        for uv in [
+            Vec2::new(0.9, 0.5),
+            Vec2::new(0.2, 0.8),
+            Vec2::new(0.1, 0.1),
+        ] {
+            assert_near(from_polar(to_polar(uv, center), center), uv);
+        }
+    }
+
+    #[test]
+    fn test_to_polar_radius_and_angle() {
+        let polar = to_polar(Vec2::new(1.0, 0.5), Vec2::splat(0.5));
+        assert_near(polar, Vec2::new(0.5, 0.5));
+    }
+
+    #[test]
+    fn test_warps_leave_center_untouched() {
+        let center = Value::Vec2(Vec2::splat(0.5));
+        for node in [UvNode::Twirl, UvNode::Spherize, UvNode::RadialShear] {
+            let strength = node.signature().inputs[2].default;
+            let result = evaluate(
+                node,
+                &[center, center, strength, Value::Vec2(Vec2::ZERO)],
+                Vec2::ZERO,
+            );
+            assert_near(result, Vec2::splat(0.5));
+        }
+    }
+
+    #[test]
+    fn test_zero_strength_warps_are_identity() {
+        let uv = Value::Vec2(Vec2::new(0.8, 0.3));
+        let center = Value::Vec2(Vec2::splat(0.5));
+        let offset = Value::Vec2(Vec2::ZERO);
+        assert_near(
+            evaluate(
+                UvNode::Twirl,
+                &[uv, center, Value::Float(0.0), offset],
+                Vec2::ZERO,
+            ),
+            uv.as_vec2(),
+        );
+        for node in [UvNode::Spherize, UvNode::RadialShear] {
+            assert_near(
+                evaluate(
+                    node,
+                    &[uv, center, Value::Vec2(Vec2::ZERO), offset],
+                    Vec2::ZERO,
+                ),
+                uv.as_vec2(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_screen_position_reads_frag_coord() {
+        let ctx = EvalContext {
+            frag_coord: Vec2::new(320.0, 240.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            UvNode::ScreenPosition.evaluate(&[], &ctx)[0],
+            Value::Vec2(Vec2::new(320.0, 240.0))
+        );
+    }
+
+    #[test]
+    fn test_every_uv_node_validates() {
+        for node in ALL_UV_NODES {
+            let mut graph = NodeGraph::new();
+            let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+            let uv = add_node(&mut graph, NodeKind::Uv(node));
+            connect(&mut graph, uv, 0, output, 0);
+
+            compile_and_validate(&graph)
+                .unwrap_or_else(|e| panic!("{:?} failed to compile: {}", node, e));
+        }
+    }
+
+    #[test]
+    fn test_unconnected_uv_input_compiles_to_vertex_uv() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let tiling = add_node(&mut graph, NodeKind::Uv(UvNode::TilingOffset));
+        connect(&mut graph, tiling, 0, output, 0);
+
+        let source = compile_fragment(&graph).expect("graph should compile");
+        assert!(source.contains("(input.uv * vec2<f32>(1.0, 1.0) + vec2<f32>(0.0, 0.0))"));
+    }
+
+    #[test]
+    fn test_graph_evaluator_uses_context_uv() {
+        let mut graph = NodeGraph::new();
+        let output = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let rotate = add_node(&mut graph, NodeKind::Uv(UvNode::Rotate));
+        connect(&mut graph, rotate, 0, output, 0);
+
+        let color = GraphEvaluator::with_context(&graph, EvalContext::at_uv(Vec2::new(0.2, 0.7)))
+            .evaluate_fragment()
+            .expect("graph should evaluate");
+        assert_near(color.truncate().truncate(), Vec2::new(0.2, 0.7));
+    }
+}
@@ -0,0 +1,291 @@
+#[cfg(test)]
+mod tests {
+    use crate::node_graph::model::{NodeGraph, NodeId};
+    use crate::node_graph::test_utils::{add_node, connect};
+    use crate::shader_graph::compiler::{
+        CompileError, compile_and_validate, compile_preview, compile_target, find_vertex_output,
+    };
+    use crate::shader_graph::evaluator::GraphEvaluator;
+    use crate::shader_graph::inputs::{CUSTOM_INTERPOLANTS, EvalContext};
+    use crate::shader_graph::node_kind::{NodeKind, OutputTarget, ShaderNode, ShaderStage};
+    use crate::shader_graph::nodes::geometry::{GeometryNode, Space};
+    use crate::shader_graph::nodes::normal::NormalNode;
+    use crate::shader_graph::nodes::texture::TextureNode;
+    use crate::shader_graph::nodes::time::TimeNode;
+    use crate::shader_graph::nodes::uv::UvNode;
+    use crate::shader_graph::types::Value;
+    use bevy::prelude::*;
+
+    /// Fragment Output plus an unconnected Vertex Output
+    fn vertex_graph() -> (NodeGraph, NodeId, NodeId) {
+        let mut graph = NodeGraph::new();
+        let fragment = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let vertex = add_node(&mut graph, NodeKind::VertexOutput);
+        (graph, fragment, vertex)
+    }
+
+    #[test]
+    fn test_vertex_output_pins() {
+        let (graph, _, vertex) = vertex_graph();
+        let labels: Vec<&str> = graph.nodes[&vertex]
+            .inputs
+            .iter()
+            .map(|pin| pin.label.as_str())
+            .collect();
+        assert_eq!(
+            labels,
+            ["Position Offset", "Normal", "Custom 0", "Custom 1"]
+        );
+        assert!(!NodeKind::VertexOutput.is_output());
+        assert_eq!(find_vertex_output(&graph), Ok(Some(vertex)));
+    }
+
+    #[test]
+    fn test_graph_without_vertex_output_keeps_default_vs_main() {
+        let mut graph = NodeGraph::new();
+        add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert!(source.contains(
+            "@vertex\nfn vs_main(vertex: VertexInput) -> VertexOutput {\n    var out: VertexOutput;"
+        ));
+        assert!(!source.contains("fn mesh_vertex"));
+    }
+
+    #[test]
+    fn test_displacement_runs_the_mesh_vertex_twice() {
+        let (mut graph, _, vertex) = vertex_graph();
+        let time = add_node(&mut graph, NodeKind::Time(TimeNode::Time));
+        let normal = add_node(
+            &mut graph,
+            NodeKind::Geometry(GeometryNode::Normal(Space::Object)),
+        );
+        connect(&mut graph, time, 1, vertex, 0);
+        connect(&mut graph, normal, 0, vertex, 1);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert!(source.contains("fn mesh_vertex(vertex: VertexInput) -> VertexOutput {"));
+        assert!(source.contains("    let input = mesh_vertex(vertex);"));
+        assert!(source.contains(&format!(
+            "    displaced.position = vertex.position + vec3<f32>(node{}_out1);",
+            time.0
+        )));
+        assert!(source.contains(&format!("    displaced.normal = node{}_out0;", normal.0)));
+        assert!(source.contains("    var out = mesh_vertex(displaced);"));
+        // The vertex graph's object normal needs the interpolant to exist
+        assert!(source.contains("out.object_normal = vertex.normal;"));
+    }
+
+    #[test]
+    fn test_custom_interpolants_reach_the_fragment() {
+        let (mut graph, fragment, vertex) = vertex_graph();
+        let position = add_node(
+            &mut graph,
+            NodeKind::Geometry(GeometryNode::Position(Space::Object)),
+        );
+        let custom = add_node(&mut graph, NodeKind::Geometry(GeometryNode::Custom(1)));
+        connect(&mut graph, position, 0, vertex, 3);
+        connect(&mut graph, custom, 0, fragment, 0);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert!(source.contains("custom1: vec4<f32>,"));
+        assert!(source.contains(&format!(
+            "    out.custom1 = vec4<f32>(node{}_out0, 1.0);",
+            position.0
+        )));
+        assert!(!source.contains("custom0"));
+        assert!(source.contains("input.custom1"));
+    }
+
+    #[test]
+    fn test_unread_custom_interpolants_are_dropped() {
+        let (mut graph, _, vertex) = vertex_graph();
+        let time = add_node(&mut graph, NodeKind::Time(TimeNode::Time));
+        connect(&mut graph, time, 0, vertex, 2);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert!(!source.contains("custom0"));
+    }
+
+    #[test]
+    fn test_fragment_only_nodes_are_rejected_in_the_vertex_stage() {
+        let cases = [
+            (
+                NodeKind::Texture(TextureNode::Sample(Default::default())),
+                0,
+            ),
+            (NodeKind::Normal(NormalNode::FromHeight), 0),
+            (NodeKind::Geometry(GeometryNode::ScreenPosition), 0),
+            (NodeKind::Uv(UvNode::ScreenPosition), 0),
+        ];
+        for (kind, output) in cases {
+            let (mut graph, _, vertex) = vertex_graph();
+            let node = add_node(&mut graph, kind.clone());
+            connect(&mut graph, node, output, vertex, 0);
+            assert_eq!(
+                compile_and_validate(&graph),
+                Err(CompileError::WrongStage {
+                    node,
+                    stage: ShaderStage::Vertex
+                }),
+                "{:?} should be fragment-only",
+                kind
+            );
+        }
+    }
+
+    #[test]
+    fn test_sample_level_works_in_the_vertex_stage() {
+        let (mut graph, _, vertex) = vertex_graph();
+        let texture = add_node(
+            &mut graph,
+            NodeKind::Texture(TextureNode::default_texture()),
+        );
+        let sample = add_node(
+            &mut graph,
+            NodeKind::Texture(TextureNode::SampleLevel(Default::default())),
+        );
+        connect(&mut graph, texture, 0, sample, 0);
+        connect(&mut graph, sample, 2, vertex, 0);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert!(source.contains("textureSampleLevel("));
+        assert!(source.contains("var sampler_linear_repeat: sampler;"));
+        assert_eq!(
+            TextureNode::SampleLevel(Default::default()).stages(),
+            &ShaderStage::ALL
+        );
+        assert_eq!(
+            TextureNode::Sample(Default::default()).stages(),
+            &[ShaderStage::Fragment]
+        );
+    }
+
+    #[test]
+    fn test_nodes_shared_by_both_stages_are_emitted_in_each() {
+        let (mut graph, fragment, vertex) = vertex_graph();
+        let time = add_node(&mut graph, NodeKind::Time(TimeNode::Time));
+        connect(&mut graph, time, 0, vertex, 0);
+        connect(&mut graph, time, 0, fragment, 1);
+
+        let source = compile_and_validate(&graph).expect("graph should compile");
+        assert_eq!(
+            source
+                .matches(&format!("let node{}_out0: f32 = globals.time;", time.0))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_only_one_vertex_output() {
+        let (mut graph, _, vertex) = vertex_graph();
+        let second = add_node(&mut graph, NodeKind::VertexOutput);
+        assert_eq!(
+            compile_and_validate(&graph),
+            Err(CompileError::MultipleOutputs(vec![vertex, second]))
+        );
+    }
+
+    #[test]
+    fn test_preview_reports_vertex_stage_errors() {
+        let (mut graph, _, vertex) = vertex_graph();
+        let height = add_node(&mut graph, NodeKind::Normal(NormalNode::FromHeight));
+        connect(&mut graph, height, 0, vertex, 1);
+        assert_eq!(
+            compile_preview(&graph),
+            Err(CompileError::WrongStage {
+                node: height,
+                stage: ShaderStage::Vertex
+            })
+        );
+    }
+
+    #[test]
+    fn test_targets_without_a_vertex_stage_reject_custom_interpolants() {
+        for target in [
+            OutputTarget::Sprite,
+            OutputTarget::Fullscreen,
+            OutputTarget::Ui,
+        ] {
+            let mut graph = NodeGraph::new();
+            let fragment = add_node(&mut graph, NodeKind::FragmentOutput(target));
+            let vertex = add_node(&mut graph, NodeKind::VertexOutput);
+            let time = add_node(&mut graph, NodeKind::Time(TimeNode::Time));
+            let custom = add_node(&mut graph, NodeKind::Geometry(GeometryNode::Custom(1)));
+            connect(&mut graph, time, 0, vertex, 3);
+            connect(&mut graph, custom, 0, fragment, 0);
+
+            assert_eq!(
+                compile_target(&graph),
+                Err(CompileError::CustomInterpolantInPreview(1)),
+                "{:?} should reject the read",
+                target
+            );
+            assert!(compile_and_validate(&graph).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_evaluator_runs_the_vertex_graph() {
+        let (mut graph, _, vertex) = vertex_graph();
+        assert_eq!(
+            GraphEvaluator::new(&graph).evaluate_vertex(),
+            Ok(Some(vec![
+                Value::Vec3(Vec3::ZERO),
+                Value::Vec3(Vec3::Z),
+                Value::Vec4(Vec4::ZERO),
+                Value::Vec4(Vec4::ZERO),
+            ]))
+        );
+
+        let time = add_node(&mut graph, NodeKind::Time(TimeNode::Time));
+        connect(&mut graph, time, 0, vertex, 0);
+        let offset = GraphEvaluator::with_context(&graph, EvalContext::at_time(2.0))
+            .evaluate_vertex()
+            .expect("graph should evaluate")
+            .expect("graph has a vertex stage");
+        assert_eq!(offset[0], Value::Vec3(Vec3::splat(2.0)));
+
+        graph.remove_node(vertex);
+        assert_eq!(GraphEvaluator::new(&graph).evaluate_vertex(), Ok(None));
+    }
+
+    #[test]
+    fn test_fragment_reads_custom_interpolants_from_the_context() {
+        let mut graph = NodeGraph::new();
+        let fragment = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let custom = add_node(&mut graph, NodeKind::Geometry(GeometryNode::Custom(0)));
+        connect(&mut graph, custom, 0, fragment, 0);
+
+        let ctx = EvalContext {
+            custom: [Vec4::new(0.25, 0.5, 0.75, 1.0), Vec4::ZERO],
+            ..Default::default()
+        };
+        let color = GraphEvaluator::with_context(&graph, ctx)
+            .evaluate_fragment()
+            .expect("graph should evaluate");
+        assert_eq!(color, Vec4::new(0.25, 0.5, 0.75, 1.0));
+    }
+    #[test]
+    fn test_custom_interpolant_past_the_last_is_rejected() {
+        let mut graph = NodeGraph::new();
+        let fragment = add_node(&mut graph, NodeKind::FragmentOutput(OutputTarget::Mesh));
+        let custom = add_node(
+            &mut graph,
+            NodeKind::Geometry(GeometryNode::Custom(CUSTOM_INTERPOLANTS)),
+        );
+        connect(&mut graph, custom, 0, fragment, 0);
+
+        assert_eq!(
+            compile_and_validate(&graph),
+            Err(CompileError::NoSuchInterpolant {
+                node: custom,
+                index: CUSTOM_INTERPOLANTS
+            })
+        );
+        let color = GraphEvaluator::new(&graph)
+            .evaluate_fragment()
+            .expect("graph should evaluate");
+        assert_eq!(color, Vec4::W);
+    }
+}
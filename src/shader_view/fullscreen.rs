@@ -0,0 +1,194 @@
+use crate::shader_view::{GRAPH_FULLSCREEN_SHADER, GraphFullscreenMaterial};
+use bevy::core_pipeline::FullscreenShader;
+use bevy::core_pipeline::core_3d::graph::{Core3d, Node3d};
+use bevy::ecs::query::QueryItem;
+use bevy::ecs::system::StaticSystemParam;
+use bevy::prelude::*;
+use bevy::render::extract_component::{ExtractComponent, ExtractComponentPlugin};
+use bevy::render::extract_resource::ExtractResourcePlugin;
+use bevy::render::render_graph::{
+    NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode, ViewNodeRunner,
+};
+use bevy::render::render_resource::binding_types::{sampler, texture_2d};
+use bevy::render::render_resource::*;
+use bevy::render::renderer::{RenderContext, RenderDevice};
+use bevy::render::view::ViewTarget;
+use bevy::render::{Render, RenderApp, RenderStartup, RenderSystems};
+
+/// Marks the camera a `Fullscreen` graph runs on as a post-process pass
+#[derive(Component, ExtractComponent, Debug, Clone, Copy, Default)]
+pub struct GraphFullscreenPass;
+
+/// Runs a `Fullscreen` graph over the cameras carrying `GraphFullscreenPass`, after tonemapping
+pub struct GraphFullscreenPlugin;
+
+impl Plugin for GraphFullscreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GraphFullscreenMaterial>().add_plugins((
+            ExtractResourcePlugin::<GraphFullscreenMaterial>::default(),
+            ExtractComponentPlugin::<GraphFullscreenPass>::default(),
+        ));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .init_resource::<GraphFullscreenBindings>()
+            .add_systems(RenderStartup, init_graph_fullscreen_pipeline)
+            .add_systems(
+                Render,
+                prepare_graph_fullscreen.in_set(RenderSystems::PrepareBindGroups),
+            )
+            .add_render_graph_node::<ViewNodeRunner<GraphFullscreenNode>>(
+                Core3d,
+                GraphFullscreenLabel,
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::Tonemapping,
+                    GraphFullscreenLabel,
+                    Node3d::EndMainPassPostProcessing,
+                ),
+            );
+    }
+}
+
+/// The pass's pipeline: the screen and its sampler at 0 and 1, then `GraphFullscreenMaterial`.
+/// The shader is replaced in place as the graph changes, and the pipeline cache rebuilds it.
+#[derive(Resource)]
+struct GraphFullscreenPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    pipeline: CachedRenderPipelineId,
+}
+
+fn init_graph_fullscreen_pipeline(
+    mut commands: Commands,
+    device: Res<RenderDevice>,
+    fullscreen_shader: Res<FullscreenShader>,
+    pipeline_cache: Res<PipelineCache>,
+) {
+    let mut entries = vec![
+        texture_2d(TextureSampleType::Float { filterable: true }).build(0, ShaderStages::FRAGMENT),
+        sampler(SamplerBindingType::Filtering).build(1, ShaderStages::FRAGMENT),
+    ];
+    entries.extend(GraphFullscreenMaterial::bind_group_layout_entries(
+        &device, false,
+    ));
+    let layout = device.create_bind_group_layout("graph_fullscreen", &entries);
+
+    // The preview camera isn't HDR, so its post-process targets have the default format
+    let pipeline = pipeline_cache.queue_render_pipeline(RenderPipelineDescriptor {
+        label: Some("graph_fullscreen".into()),
+        layout: vec![layout.clone()],
+        vertex: fullscreen_shader.to_vertex_state(),
+        fragment: Some(FragmentState {
+            shader: GRAPH_FULLSCREEN_SHADER,
+            targets: vec![Some(ColorTargetState {
+                format: TextureFormat::bevy_default(),
+                blend: None,
+                write_mask: ColorWrites::ALL,
+            })],
+            ..default()
+        }),
+        ..default()
+    });
+    commands.insert_resource(GraphFullscreenPipeline {
+        layout,
+        sampler: device.create_sampler(&SamplerDescriptor::default()),
+        pipeline,
+    });
+}
+
+/// `GraphFullscreenMaterial`'s GPU resources. The screen changes every frame, so the node makes
+/// the bind group itself; this keeps the rest, rebuilt when the material changes.
+#[derive(Resource, Default)]
+struct GraphFullscreenBindings(Option<Vec<(u32, OwnedBindingResource)>>);
+
+/// Prepare the material's bindings, retrying each frame until its images are on the GPU
+fn prepare_graph_fullscreen(
+    material: Option<Res<GraphFullscreenMaterial>>,
+    pipeline: Res<GraphFullscreenPipeline>,
+    mut bindings: ResMut<GraphFullscreenBindings>,
+    device: Res<RenderDevice>,
+    mut param: StaticSystemParam<<GraphFullscreenMaterial as AsBindGroup>::Param>,
+) {
+    let Some(material) = material else {
+        return;
+    };
+    if bindings.0.is_some() && !material.is_changed() {
+        return;
+    }
+    bindings.0 = material
+        .unprepared_bind_group(&pipeline.layout, &device, &mut param, false)
+        .ok()
+        .map(|bind_group| bind_group.bindings.0);
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct GraphFullscreenLabel;
+
+#[derive(Default)]
+struct GraphFullscreenNode;
+
+impl ViewNode for GraphFullscreenNode {
+    type ViewQuery = (&'static ViewTarget, &'static GraphFullscreenPass);
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, _): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let pipeline = world.resource::<GraphFullscreenPipeline>();
+        let Some(render_pipeline) = world
+            .resource::<PipelineCache>()
+            .get_render_pipeline(pipeline.pipeline)
+        else {
+            return Ok(());
+        };
+        let Some(bindings) = &world.resource::<GraphFullscreenBindings>().0 else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+        let mut entries = vec![
+            BindGroupEntry {
+                binding: 0,
+                resource: post_process.source.into_binding(),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: pipeline.sampler.into_binding(),
+            },
+        ];
+        entries.extend(bindings.iter().map(|(binding, resource)| BindGroupEntry {
+            binding: *binding,
+            resource: resource.get_binding(),
+        }));
+        let bind_group = render_context.render_device().create_bind_group(
+            "graph_fullscreen",
+            &pipeline.layout,
+            &entries,
+        );
+
+        let mut pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("graph_fullscreen"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                depth_slice: None,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_render_pipeline(render_pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..3, 0..1);
+        Ok(())
+    }
+}
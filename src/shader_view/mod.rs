@@ -1,5 +1,14 @@
+pub mod fullscreen;
+pub mod preview_material;
 pub mod shader_view;
 pub mod systems;
+pub mod transport;
 
+#[cfg(test)]
+mod transport_tests;
+
+pub use fullscreen::*;
+pub use preview_material::*;
 pub use shader_view::*;
 pub use systems::*;
+pub use transport::*;
@@ -0,0 +1,673 @@
+use crate::node_graph::model::NodeGraph;
+use crate::shader_graph::compiler::{compile_target, find_output_node};
+use crate::shader_graph::node_kind::NodeKind;
+use crate::shader_graph::nodes::subgraph::SubgraphStack;
+use crate::shader_graph::nodes::texture::{SamplerState, TextureFilter, TextureWrap};
+use crate::shader_graph::scaffold::PreviewTarget;
+use crate::shader_view::{GraphFullscreenPass, PreviewTime, ShaderView};
+use bevy::asset::uuid_handle;
+use bevy::image::{ImageAddressMode, ImageFilterMode, ImageSampler, ImageSamplerDescriptor};
+use bevy::pbr::{ExtendedMaterial, Material, MaterialExtension};
+use bevy::prelude::*;
+use bevy::render::extract_resource::ExtractResource;
+use bevy::render::render_resource::{AsBindGroup, ShaderType};
+use bevy::shader::ShaderRef;
+use bevy::sprite_render::{AlphaMode2d, Material2d};
+use bevy::ui_render::prelude::UiMaterial;
+use bevy_egui::{EguiContexts, egui};
+
+/// Compiled graph shader, replaced in place whenever the graph changes
+pub const GRAPH_PREVIEW_SHADER: Handle<Shader> =
+    uuid_handle!("6b2f8f5e-0c3a-4d71-9a8e-3f1d5c7b2e90");
+
+/// Compiled `PBR Output` graph, the vertex and fragment shader of `GraphPbrExtension`
+pub const GRAPH_PBR_SHADER: Handle<Shader> = uuid_handle!("c41d7a92-5e83-4f06-b1a7-8d2e6f09a3c5");
+
+/// Compiled `Sprite (2D)` graph, the fragment shader of `GraphSpriteMaterial`
+pub const GRAPH_SPRITE_SHADER: Handle<Shader> =
+    uuid_handle!("2e7c4a18-93f5-4b6d-a0c2-71d8e5f3b946");
+
+/// Compiled `UI` graph, the fragment shader of `GraphUiMaterial`
+pub const GRAPH_UI_SHADER: Handle<Shader> = uuid_handle!("f05b9c3e-6d12-4a87-b3e9-2c4f8a7d1e60");
+
+/// Compiled `Fullscreen` graph, the fragment stage of the preview camera's post-process pass
+pub const GRAPH_FULLSCREEN_SHADER: Handle<Shader> =
+    uuid_handle!("8a41d6f2-c7e0-4395-9b58-e3a2f10c6d7b");
+
+/// Mirrors the WGSL `Globals` struct every compiled graph reads
+#[derive(Debug, Clone, Copy, Default, PartialEq, ShaderType)]
+pub struct GraphGlobals {
+    pub time: f32,
+    pub delta_time: f32,
+}
+
+/// Mirrors the WGSL `Lights` struct the lighting nodes read. Vectors are padded to `vec4`;
+/// `direction` points from the surface towards the directional light.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ShaderType)]
+pub struct GraphLights {
+    pub direction: Vec4,
+    pub color: Vec4,
+    pub point_position: Vec4,
+    pub point_color: Vec4,
+    pub ambient: Vec4,
+}
+
+impl GraphLights {
+    /// Light data from the preview scene. Direct lights pass their colour only, since the
+    /// graph is shaded unlit and physical intensities would blow it out; ambient light is
+    /// scaled by its brightness.
+    pub fn from_scene(
+        directional: Option<(&DirectionalLight, &GlobalTransform)>,
+        point: Option<(&PointLight, &GlobalTransform)>,
+        ambient: Option<&AmbientLight>,
+    ) -> Self {
+        let mut lights = Self::default();
+        if let Some((light, transform)) = directional {
+            lights.direction = transform.back().as_vec3().extend(0.0);
+            lights.color = light.color.to_linear().to_vec4().with_w(1.0);
+        }
+        if let Some((light, transform)) = point {
+            lights.point_position = transform.translation().extend(1.0);
+            lights.point_color = light.color.to_linear().to_vec4().with_w(1.0);
+        }
+        if let Some(ambient) = ambient {
+            let color = ambient.color.to_linear().to_vec3() * ambient.brightness;
+            lights.ambient = color.extend(1.0);
+        }
+        lights
+    }
+}
+
+/// Material running the node graph's vertex and fragment stages on the preview mesh.
+///
+/// The layout is fixed, so the compiler targets it (`scaffold::PREVIEW_*_BINDING`): texture
+/// slots at 1..=4, unset slots binding white, then one sampler per `SamplerState` at 5..=10.
+/// Bevy takes a sampler from an image, so each sampler comes from a 1x1 carrier image whose
+/// texture fills the otherwise unused bindings 11..=16. The scene's lights follow at 17.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone, Default)]
+pub struct GraphPreviewMaterial {
+    #[uniform(0)]
+    pub globals: GraphGlobals,
+    #[texture(1)]
+    pub texture_0: Option<Handle<Image>>,
+    #[texture(2)]
+    pub texture_1: Option<Handle<Image>>,
+    #[texture(3)]
+    pub texture_2: Option<Handle<Image>>,
+    #[texture(4)]
+    pub texture_3: Option<Handle<Image>>,
+    #[texture(11)]
+    #[sampler(5)]
+    pub sampler_linear_repeat: Option<Handle<Image>>,
+    #[texture(12)]
+    #[sampler(6)]
+    pub sampler_linear_clamp: Option<Handle<Image>>,
+    #[texture(13)]
+    #[sampler(7)]
+    pub sampler_linear_mirror: Option<Handle<Image>>,
+    #[texture(14)]
+    #[sampler(8)]
+    pub sampler_nearest_repeat: Option<Handle<Image>>,
+    #[texture(15)]
+    #[sampler(9)]
+    pub sampler_nearest_clamp: Option<Handle<Image>>,
+    #[texture(16)]
+    #[sampler(10)]
+    pub sampler_nearest_mirror: Option<Handle<Image>>,
+    #[uniform(17)]
+    pub lights: GraphLights,
+}
+
+/// `GraphPbrExtension`, the same slots placed after `StandardMaterial`'s own bindings
+/// (`scaffold::PBR_EXTENSION_BINDING_BASE`) so `compile_pbr` can light the graph with Bevy's PBR
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone, Default)]
+pub struct GraphPbrExtension {
+    #[uniform(100)]
+    pub globals: GraphGlobals,
+    #[texture(101)]
+    pub texture_0: Option<Handle<Image>>,
+    #[texture(102)]
+    pub texture_1: Option<Handle<Image>>,
+    #[texture(103)]
+    pub texture_2: Option<Handle<Image>>,
+    #[texture(104)]
+    pub texture_3: Option<Handle<Image>>,
+    #[texture(111)]
+    #[sampler(105)]
+    pub sampler_linear_repeat: Option<Handle<Image>>,
+    #[texture(112)]
+    #[sampler(106)]
+    pub sampler_linear_clamp: Option<Handle<Image>>,
+    #[texture(113)]
+    #[sampler(107)]
+    pub sampler_linear_mirror: Option<Handle<Image>>,
+    #[texture(114)]
+    #[sampler(108)]
+    pub sampler_nearest_repeat: Option<Handle<Image>>,
+    #[texture(115)]
+    #[sampler(109)]
+    pub sampler_nearest_clamp: Option<Handle<Image>>,
+    #[texture(116)]
+    #[sampler(110)]
+    pub sampler_nearest_mirror: Option<Handle<Image>>,
+    #[uniform(117)]
+    pub lights: GraphLights,
+}
+
+/// Graph preview material running a `PBR Output` graph on top of `StandardMaterial`
+pub type GraphPbrMaterial = ExtendedMaterial<StandardMaterial, GraphPbrExtension>;
+
+/// `Material2d` running a `Sprite (2D)` graph on the preview quad, laid out like
+/// `GraphPreviewMaterial` in the 2D material group
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone, Default)]
+pub struct GraphSpriteMaterial {
+    #[uniform(0)]
+    pub globals: GraphGlobals,
+    #[texture(1)]
+    pub texture_0: Option<Handle<Image>>,
+    #[texture(2)]
+    pub texture_1: Option<Handle<Image>>,
+    #[texture(3)]
+    pub texture_2: Option<Handle<Image>>,
+    #[texture(4)]
+    pub texture_3: Option<Handle<Image>>,
+    #[texture(11)]
+    #[sampler(5)]
+    pub sampler_linear_repeat: Option<Handle<Image>>,
+    #[texture(12)]
+    #[sampler(6)]
+    pub sampler_linear_clamp: Option<Handle<Image>>,
+    #[texture(13)]
+    #[sampler(7)]
+    pub sampler_linear_mirror: Option<Handle<Image>>,
+    #[texture(14)]
+    #[sampler(8)]
+    pub sampler_nearest_repeat: Option<Handle<Image>>,
+    #[texture(15)]
+    #[sampler(9)]
+    pub sampler_nearest_clamp: Option<Handle<Image>>,
+    #[texture(16)]
+    #[sampler(10)]
+    pub sampler_nearest_mirror: Option<Handle<Image>>,
+    #[uniform(17)]
+    pub lights: GraphLights,
+}
+
+/// `UiMaterial` running a `UI` graph on the preview node, laid out like `GraphPreviewMaterial`
+/// in group 1
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone, Default)]
+pub struct GraphUiMaterial {
+    #[uniform(0)]
+    pub globals: GraphGlobals,
+    #[texture(1)]
+    pub texture_0: Option<Handle<Image>>,
+    #[texture(2)]
+    pub texture_1: Option<Handle<Image>>,
+    #[texture(3)]
+    pub texture_2: Option<Handle<Image>>,
+    #[texture(4)]
+    pub texture_3: Option<Handle<Image>>,
+    #[texture(11)]
+    #[sampler(5)]
+    pub sampler_linear_repeat: Option<Handle<Image>>,
+    #[texture(12)]
+    #[sampler(6)]
+    pub sampler_linear_clamp: Option<Handle<Image>>,
+    #[texture(13)]
+    #[sampler(7)]
+    pub sampler_linear_mirror: Option<Handle<Image>>,
+    #[texture(14)]
+    #[sampler(8)]
+    pub sampler_nearest_repeat: Option<Handle<Image>>,
+    #[texture(15)]
+    #[sampler(9)]
+    pub sampler_nearest_clamp: Option<Handle<Image>>,
+    #[texture(16)]
+    #[sampler(10)]
+    pub sampler_nearest_mirror: Option<Handle<Image>>,
+    #[uniform(17)]
+    pub lights: GraphLights,
+}
+
+/// Bindings of a `Fullscreen` graph, placed after the screen texture and its sampler
+/// (`scaffold::FULLSCREEN_BINDING_BASE`). The post-process pass binds it itself rather than
+/// through a material, so it is a resource, extracted to the render world each time it changes.
+#[derive(Resource, ExtractResource, AsBindGroup, Debug, Clone, Default)]
+pub struct GraphFullscreenMaterial {
+    #[uniform(2)]
+    pub globals: GraphGlobals,
+    #[texture(3)]
+    pub texture_0: Option<Handle<Image>>,
+    #[texture(4)]
+    pub texture_1: Option<Handle<Image>>,
+    #[texture(5)]
+    pub texture_2: Option<Handle<Image>>,
+    #[texture(6)]
+    pub texture_3: Option<Handle<Image>>,
+    #[texture(13)]
+    #[sampler(7)]
+    pub sampler_linear_repeat: Option<Handle<Image>>,
+    #[texture(14)]
+    #[sampler(8)]
+    pub sampler_linear_clamp: Option<Handle<Image>>,
+    #[texture(15)]
+    #[sampler(9)]
+    pub sampler_linear_mirror: Option<Handle<Image>>,
+    #[texture(16)]
+    #[sampler(10)]
+    pub sampler_nearest_repeat: Option<Handle<Image>>,
+    #[texture(17)]
+    #[sampler(11)]
+    pub sampler_nearest_clamp: Option<Handle<Image>>,
+    #[texture(18)]
+    #[sampler(12)]
+    pub sampler_nearest_mirror: Option<Handle<Image>>,
+    #[uniform(19)]
+    pub lights: GraphLights,
+}
+
+/// Slots shared by the graph materials, so one system can fill whichever is in use
+pub trait GraphBindings: Default {
+    fn globals_mut(&mut self) -> &mut GraphGlobals;
+
+    fn lights_mut(&mut self) -> &mut GraphLights;
+
+    /// Texture slots in binding order
+    fn texture_slots_mut(&mut self) -> [&mut Option<Handle<Image>>; 4];
+
+    /// Sampler carriers in `SamplerState::ALL` order
+    fn sampler_slots_mut(&mut self) -> [&mut Option<Handle<Image>>; 6];
+
+    /// Material with every sampler carrier created, and no textures bound
+    fn with_sampler_carriers(images: &mut Assets<Image>) -> Self {
+        let mut material = Self::default();
+        for (slot, state) in material
+            .sampler_slots_mut()
+            .into_iter()
+            .zip(SamplerState::ALL)
+        {
+            *slot = Some(images.add(Image {
+                sampler: ImageSampler::Descriptor(sampler_descriptor(state)),
+                ..default()
+            }));
+        }
+        material
+    }
+
+    /// Bind the image of each slot, loading it through the asset server
+    fn bind_images(&mut self, images: &[Option<String>], asset_server: &AssetServer) {
+        let mut images = images.iter();
+        for slot in self.texture_slots_mut() {
+            *slot = images
+                .next()
+                .and_then(|image| image.as_deref())
+                .map(|path| asset_server.load(path.to_string()));
+        }
+    }
+}
+
+/// Implements `GraphBindings` for a material with the shared field names
+macro_rules! impl_graph_bindings {
+    ($material:ty) => {
+        impl GraphBindings for $material {
+            fn globals_mut(&mut self) -> &mut GraphGlobals {
+                &mut self.globals
+            }
+
+            fn lights_mut(&mut self) -> &mut GraphLights {
+                &mut self.lights
+            }
+
+            fn texture_slots_mut(&mut self) -> [&mut Option<Handle<Image>>; 4] {
+                [
+                    &mut self.texture_0,
+                    &mut self.texture_1,
+                    &mut self.texture_2,
+                    &mut self.texture_3,
+                ]
+            }
+
+            fn sampler_slots_mut(&mut self) -> [&mut Option<Handle<Image>>; 6] {
+                [
+                    &mut self.sampler_linear_repeat,
+                    &mut self.sampler_linear_clamp,
+                    &mut self.sampler_linear_mirror,
+                    &mut self.sampler_nearest_repeat,
+                    &mut self.sampler_nearest_clamp,
+                    &mut self.sampler_nearest_mirror,
+                ]
+            }
+        }
+    };
+}
+
+impl_graph_bindings!(GraphPreviewMaterial);
+impl_graph_bindings!(GraphPbrExtension);
+impl_graph_bindings!(GraphSpriteMaterial);
+impl_graph_bindings!(GraphUiMaterial);
+impl_graph_bindings!(GraphFullscreenMaterial);
+
+impl GraphPreviewMaterial {
+    /// Material with every sampler carrier created, and no textures bound
+    pub fn new(images: &mut Assets<Image>) -> Self {
+        Self::with_sampler_carriers(images)
+    }
+}
+
+/// Bevy sampler matching a Sample Texture 2D node's options
+pub fn sampler_descriptor(state: SamplerState) -> ImageSamplerDescriptor {
+    let address_mode = match state.wrap {
+        TextureWrap::Repeat => ImageAddressMode::Repeat,
+        TextureWrap::Clamp => ImageAddressMode::ClampToEdge,
+        TextureWrap::Mirror => ImageAddressMode::MirrorRepeat,
+    };
+    let filter = match state.filter {
+        TextureFilter::Linear => ImageFilterMode::Linear,
+        TextureFilter::Nearest => ImageFilterMode::Nearest,
+    };
+    ImageSamplerDescriptor {
+        address_mode_u: address_mode,
+        address_mode_v: address_mode,
+        address_mode_w: address_mode,
+        mag_filter: filter,
+        min_filter: filter,
+        mipmap_filter: filter,
+        ..default()
+    }
+}
+
+impl Material for GraphPreviewMaterial {
+    /// The compiled module carries a `vertex` entry point running the vertex graph
+    fn vertex_shader() -> ShaderRef {
+        GRAPH_PREVIEW_SHADER.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        GRAPH_PREVIEW_SHADER.into()
+    }
+}
+
+impl MaterialExtension for GraphPbrExtension {
+    fn vertex_shader() -> ShaderRef {
+        GRAPH_PBR_SHADER.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        GRAPH_PBR_SHADER.into()
+    }
+}
+
+impl Material2d for GraphSpriteMaterial {
+    fn fragment_shader() -> ShaderRef {
+        GRAPH_SPRITE_SHADER.into()
+    }
+
+    /// The graph's Alpha blends, as it does on the PBR material
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+impl UiMaterial for GraphUiMaterial {
+    fn fragment_shader() -> ShaderRef {
+        GRAPH_UI_SHADER.into()
+    }
+}
+
+/// Recompile the preview shader when the graph changes, bind the images it samples, and show
+/// it on whatever runs its target: the sphere with the unlit graph material or the PBR
+/// extension, the sprite, the UI node, or the preview camera's fullscreen pass. A graph that
+/// doesn't compile puts the plain `StandardMaterial` on the sphere. While a subgraph is open
+/// the whole top-level graph is compiled, edits inside it included. Nothing is touched when
+/// the graph compiles to the same result as last time.
+#[allow(clippy::too_many_arguments)]
+pub fn update_graph_preview_system(
+    mut commands: Commands,
+    node_graph: Res<NodeGraph>,
+    subgraphs: Res<SubgraphStack>,
+    mut shader_view: ResMut<ShaderView>,
+    mut shaders: ResMut<Assets<Shader>>,
+    mut materials: ResMut<Assets<GraphPreviewMaterial>>,
+    mut pbr_materials: ResMut<Assets<GraphPbrMaterial>>,
+    mut sprite_materials: ResMut<Assets<GraphSpriteMaterial>>,
+    mut ui_materials: ResMut<Assets<GraphUiMaterial>>,
+    mut fullscreen_material: ResMut<GraphFullscreenMaterial>,
+    mut cameras: Query<&mut Camera>,
+    asset_server: Res<AssetServer>,
+) {
+    if !node_graph.is_changed() {
+        return;
+    }
+    let Some(mesh_entity) = shader_view.mesh_entity else {
+        return;
+    };
+
+    let root_graph;
+    let node_graph = if subgraphs.levels.is_empty() {
+        &*node_graph
+    } else {
+        root_graph = subgraphs.root_graph(&node_graph);
+        &root_graph
+    };
+
+    let target = match find_output_node(node_graph)
+        .ok()
+        .map(|output| &node_graph.nodes[&output].kind)
+    {
+        Some(NodeKind::PbrOutput) => PreviewTarget::Pbr,
+        Some(NodeKind::FragmentOutput(target)) => PreviewTarget::from(*target),
+        _ => PreviewTarget::Unlit,
+    };
+
+    // Dragging nodes or annotations changes the graph without changing its shader
+    let compiled = (
+        target,
+        compile_target(node_graph).map_err(|e| e.to_string()),
+    );
+    if shader_view.last_compiled.as_ref() == Some(&compiled) {
+        return;
+    }
+    shader_view.last_compiled = Some(compiled.clone());
+
+    // The target actually shown, `None` for the standard material fallback
+    let shown = match compiled.1 {
+        Ok(preview) => {
+            let images = &preview.images;
+            match target {
+                PreviewTarget::Unlit => {
+                    if let Some(material) = materials.get_mut(&shader_view.graph_material) {
+                        material.bind_images(images, &asset_server);
+                    }
+                }
+                PreviewTarget::Pbr => {
+                    if let Some(material) = pbr_materials.get_mut(&shader_view.pbr_material) {
+                        material.extension.bind_images(images, &asset_server);
+                    }
+                }
+                PreviewTarget::Sprite => {
+                    if let Some(material) = sprite_materials.get_mut(&shader_view.sprite_material) {
+                        material.bind_images(images, &asset_server);
+                    }
+                }
+                PreviewTarget::Ui => {
+                    if let Some(material) = ui_materials.get_mut(&shader_view.ui_material) {
+                        material.bind_images(images, &asset_server);
+                    }
+                }
+                PreviewTarget::Fullscreen => fullscreen_material.bind_images(images, &asset_server),
+            }
+            let (shader, path) = match target {
+                PreviewTarget::Unlit => (GRAPH_PREVIEW_SHADER, "graph_preview.wgsl"),
+                PreviewTarget::Pbr => (GRAPH_PBR_SHADER, "graph_pbr.wgsl"),
+                PreviewTarget::Sprite => (GRAPH_SPRITE_SHADER, "graph_sprite.wgsl"),
+                PreviewTarget::Ui => (GRAPH_UI_SHADER, "graph_ui.wgsl"),
+                PreviewTarget::Fullscreen => (GRAPH_FULLSCREEN_SHADER, "graph_fullscreen.wgsl"),
+            };
+            if let Err(e) = shaders.insert(shader.id(), Shader::from_wgsl(preview.source, path)) {
+                warn!("Failed to store graph preview shader: {}", e);
+                shader_view.last_compiled = None;
+                return;
+            }
+            shader_view.preview_error = None;
+            Some(target)
+        }
+        Err(e) => {
+            if shader_view.preview_error.is_none() {
+                debug!("Graph preview falls back to the standard material: {}", e);
+            }
+            shader_view.preview_error = Some(e);
+            None
+        }
+    };
+
+    let visibility = |visible: bool| {
+        if visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        }
+    };
+    let mut entity = commands.entity(mesh_entity);
+    entity
+        .remove::<MeshMaterial3d<StandardMaterial>>()
+        .remove::<MeshMaterial3d<GraphPreviewMaterial>>()
+        .remove::<MeshMaterial3d<GraphPbrMaterial>>()
+        .insert(visibility(matches!(
+            shown,
+            None | Some(PreviewTarget::Unlit | PreviewTarget::Pbr)
+        )));
+    match shown {
+        Some(PreviewTarget::Pbr) => {
+            entity.insert(MeshMaterial3d(shader_view.pbr_material.clone()));
+        }
+        Some(PreviewTarget::Unlit) => {
+            entity.insert(MeshMaterial3d(shader_view.graph_material.clone()));
+        }
+        _ => {
+            entity.insert(MeshMaterial3d(shader_view.standard_material.clone()));
+        }
+    }
+
+    let sprite = shown == Some(PreviewTarget::Sprite);
+    if let Some(sprite_entity) = shader_view.sprite_entity {
+        commands.entity(sprite_entity).insert(visibility(sprite));
+    }
+    if let Some(camera_entity) = shader_view.sprite_camera_entity
+        && let Ok(mut camera) = cameras.get_mut(camera_entity)
+        && camera.is_active != sprite
+    {
+        camera.is_active = sprite;
+    }
+    if let Some(ui_entity) = shader_view.ui_entity {
+        commands
+            .entity(ui_entity)
+            .insert(visibility(shown == Some(PreviewTarget::Ui)));
+    }
+    if let Some(camera_entity) = shader_view.camera_entity {
+        let mut camera = commands.entity(camera_entity);
+        if shown == Some(PreviewTarget::Fullscreen) {
+            camera.insert(GraphFullscreenPass);
+        } else {
+            camera.remove::<GraphFullscreenPass>();
+        }
+    }
+}
+
+/// Why the graph isn't previewed, over the preview above the transport bar
+pub fn preview_error_ui_system(shader_view: Res<ShaderView>, mut egui_contexts: EguiContexts) {
+    let Some(error) = &shader_view.preview_error else {
+        return;
+    };
+    let ctx = egui_contexts.ctx_mut().expect("Failed to get egui context");
+
+    egui::Area::new(egui::Id::new("preview_error"))
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -64.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new(format!("Preview: {}", error))
+                        .color(egui::Color32::from_rgb(230, 120, 90)),
+                );
+            });
+        });
+}
+
+/// Copy the transport clock into the graph materials' `globals` uniform
+pub fn sync_preview_globals_system(
+    preview_time: Res<PreviewTime>,
+    shader_view: Res<ShaderView>,
+    mut materials: ResMut<Assets<GraphPreviewMaterial>>,
+    mut pbr_materials: ResMut<Assets<GraphPbrMaterial>>,
+    mut sprite_materials: ResMut<Assets<GraphSpriteMaterial>>,
+    mut ui_materials: ResMut<Assets<GraphUiMaterial>>,
+    mut fullscreen_material: ResMut<GraphFullscreenMaterial>,
+) {
+    if !preview_time.is_changed() {
+        return;
+    }
+    if let Some(material) = materials.get_mut(&shader_view.graph_material) {
+        *material.globals_mut() = preview_time.globals();
+    }
+    if let Some(material) = pbr_materials.get_mut(&shader_view.pbr_material) {
+        *material.extension.globals_mut() = preview_time.globals();
+    }
+    if let Some(material) = sprite_materials.get_mut(&shader_view.sprite_material) {
+        *material.globals_mut() = preview_time.globals();
+    }
+    if let Some(material) = ui_materials.get_mut(&shader_view.ui_material) {
+        *material.globals_mut() = preview_time.globals();
+    }
+    *fullscreen_material.globals_mut() = preview_time.globals();
+}
+
+/// Copy the preview scene's lights into the graph materials' `lights` uniform
+#[allow(clippy::too_many_arguments)]
+pub fn sync_preview_lights_system(
+    directional: Query<(&DirectionalLight, &GlobalTransform)>,
+    point: Query<(&PointLight, &GlobalTransform)>,
+    ambient: Option<Res<AmbientLight>>,
+    shader_view: Res<ShaderView>,
+    mut materials: ResMut<Assets<GraphPreviewMaterial>>,
+    mut pbr_materials: ResMut<Assets<GraphPbrMaterial>>,
+    mut sprite_materials: ResMut<Assets<GraphSpriteMaterial>>,
+    mut ui_materials: ResMut<Assets<GraphUiMaterial>>,
+    mut fullscreen_material: ResMut<GraphFullscreenMaterial>,
+) {
+    let lights = GraphLights::from_scene(
+        directional.iter().next(),
+        point.iter().next(),
+        ambient.as_deref(),
+    );
+    // Only touch the assets when something moved, so the bind groups aren't rebuilt every frame
+    if materials
+        .get(&shader_view.graph_material)
+        .is_some_and(|material| material.lights != lights)
+        && let Some(material) = materials.get_mut(&shader_view.graph_material)
+    {
+        *material.lights_mut() = lights;
+    }
+    if pbr_materials
+        .get(&shader_view.pbr_material)
+        .is_some_and(|material| material.extension.lights != lights)
+        && let Some(material) = pbr_materials.get_mut(&shader_view.pbr_material)
+    {
+        *material.extension.lights_mut() = lights;
+    }
+    if sprite_materials
+        .get(&shader_view.sprite_material)
+        .is_some_and(|material| material.lights != lights)
+        && let Some(material) = sprite_materials.get_mut(&shader_view.sprite_material)
+    {
+        *material.lights_mut() = lights;
+    }
+    if ui_materials
+        .get(&shader_view.ui_material)
+        .is_some_and(|material| material.lights != lights)
+        && let Some(material) = ui_materials.get_mut(&shader_view.ui_material)
+    {
+        *material.lights_mut() = lights;
+    }
+    if fullscreen_material.lights != lights {
+        *fullscreen_material.lights_mut() = lights;
+    }
+}
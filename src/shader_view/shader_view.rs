@@ -1,3 +1,8 @@
+use crate::shader_graph::compiler::PreviewShader;
+use crate::shader_graph::scaffold::PreviewTarget;
+use crate::shader_view::{
+    GraphPbrMaterial, GraphPreviewMaterial, GraphSpriteMaterial, GraphUiMaterial,
+};
 use bevy::prelude::*;
 
 /// Resource that stores the shader view state
@@ -6,6 +11,27 @@ pub struct ShaderView {
     pub shader_handle: Handle<Shader>,
     pub mesh_entity: Option<Entity>,
     pub camera_entity: Option<Entity>,
+    /// 2D mesh a `Sprite (2D)` graph is drawn on, hidden for other graphs
+    pub sprite_entity: Option<Entity>,
+    /// Camera drawing the sprite over the 3D scene, active only for `Sprite (2D)` graphs
+    pub sprite_camera_entity: Option<Entity>,
+    /// UI node a `UI` graph fills, hidden for other graphs
+    pub ui_entity: Option<Entity>,
+    /// Shown while the graph cannot be compiled
+    pub standard_material: Handle<StandardMaterial>,
+    /// Runs the compiled graph; swapped onto the mesh once the graph compiles
+    pub graph_material: Handle<GraphPreviewMaterial>,
+    /// Runs a `PBR Output` graph through Bevy's lighting instead
+    pub pbr_material: Handle<GraphPbrMaterial>,
+    /// Runs a `Sprite (2D)` graph on the sprite
+    pub sprite_material: Handle<GraphSpriteMaterial>,
+    /// Runs a `UI` graph on the UI node
+    pub ui_material: Handle<GraphUiMaterial>,
+    /// Why the graph material is not in use, if it isn't
+    pub preview_error: Option<String>,
+    /// Target and shader the preview was last updated with, or the error it failed with.
+    /// A graph edit that compiles to the same result leaves the shaders and materials alone.
+    pub last_compiled: Option<(PreviewTarget, Result<PreviewShader, String>)>,
 }
 
 impl Default for ShaderView {
@@ -14,6 +40,16 @@ impl Default for ShaderView {
             shader_handle: Handle::default(),
             mesh_entity: None,
             camera_entity: None,
+            sprite_entity: None,
+            sprite_camera_entity: None,
+            ui_entity: None,
+            standard_material: Handle::default(),
+            graph_material: Handle::default(),
+            pbr_material: Handle::default(),
+            sprite_material: Handle::default(),
+            ui_material: Handle::default(),
+            preview_error: None,
+            last_compiled: None,
         }
     }
 }
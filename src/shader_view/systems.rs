@@ -1,14 +1,41 @@
-use crate::shader_view::{ShaderView, ShaderViewCamera, ShaderViewEntity};
+use crate::shader_view::{
+    GraphBindings, GraphFullscreenMaterial, GraphPbrExtension, GraphPbrMaterial,
+    GraphPreviewMaterial, GraphSpriteMaterial, GraphUiMaterial, ShaderView, ShaderViewCamera,
+    ShaderViewEntity,
+};
 use bevy::camera::visibility::RenderLayers;
 use bevy::prelude::*;
 use bevy::render::render_resource::*;
-use bevy_egui::egui;
+
+/// Where the shader view camera sits, looking at the origin
+pub const PREVIEW_CAMERA_POSITION: Vec3 = Vec3::new(0.0, 1.5, 6.0);
+
+/// Side in pixels of the sprite and UI previews, about the sphere's size in a 1080p window
+pub const PREVIEW_QUAD_SIZE: f32 = 400.0;
+
+/// Add the tangents and white vertex colour graph nodes may read
+fn with_graph_attributes(mesh: Mesh) -> Mesh {
+    let mut mesh = mesh
+        .with_generated_tangents()
+        .expect("Preview meshes have normals and UVs");
+    let vertex_count = mesh.count_vertices();
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_COLOR,
+        vec![[1.0, 1.0, 1.0, 1.0]; vertex_count],
+    );
+    mesh
+}
 
 /// System to set up the shader view 3D scene
+#[allow(clippy::too_many_arguments)]
 pub fn setup_shader_view(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut graph_materials: ResMut<Assets<GraphPreviewMaterial>>,
+    mut pbr_materials: ResMut<Assets<GraphPbrMaterial>>,
+    mut sprite_materials: ResMut<Assets<GraphSpriteMaterial>>,
+    mut ui_materials: ResMut<Assets<GraphUiMaterial>>,
     mut shaders: ResMut<Assets<Shader>>,
     mut images: ResMut<Assets<Image>>,
     _asset_server: Res<AssetServer>,
@@ -16,13 +43,16 @@ pub fn setup_shader_view(
     info!("Setting up shader view 3D scene");
 
     // Create a render target image for the shader preview
-    let size = Extent3d {
+    let _size = Extent3d {
         width: 512,
         height: 512,
         ..default()
     };
-    // Create a simple sphere mesh using basic shape
-    let sphere_mesh = meshes.add(Sphere::new(1.0).mesh().ico(5).unwrap());
+    // Create a simple sphere mesh using basic shape, and the quad sprite graphs are shown on
+    let sphere_mesh = meshes.add(with_graph_attributes(
+        Sphere::new(1.0).mesh().ico(5).unwrap(),
+    ));
+    let quad_mesh = meshes.add(with_graph_attributes(Rectangle::new(2.0, 2.0).into()));
 
     // Create a basic material for the sphere
     let material = materials.add(StandardMaterial {
@@ -35,8 +65,8 @@ pub fn setup_shader_view(
     // Create a proper 3D entity with PBR material
     let sphere_entity = commands
         .spawn((
-            Mesh3d(sphere_mesh),
-            MeshMaterial3d(material),
+            Mesh3d(sphere_mesh.clone()),
+            MeshMaterial3d(material.clone()),
             Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
             ShaderViewEntity,
         ))
@@ -68,12 +98,60 @@ pub fn setup_shader_view(
                 clear_color: Color::srgb(0.1, 0.1, 0.15).into(),
                 ..default()
             },
-            Transform::from_translation(Vec3::new(0.0, 1.5, 6.0))
+            Transform::from_translation(PREVIEW_CAMERA_POSITION)
                 .looking_at(Vec3::new(0.0, 0.0, 0.0), Vec3::Y),
             ShaderViewCamera,
         ))
         .id();
 
+    // The sprite and UI targets' previews, shown in place of the sphere when a graph targets them
+    let sprite_material =
+        sprite_materials.add(GraphSpriteMaterial::with_sampler_carriers(&mut images));
+    let sprite_entity = commands
+        .spawn((
+            Mesh2d(quad_mesh.clone()),
+            MeshMaterial2d(sprite_material.clone()),
+            Transform::from_scale(Vec3::splat(PREVIEW_QUAD_SIZE / 2.0)),
+            Visibility::Hidden,
+            ShaderViewEntity,
+        ))
+        .id();
+    let sprite_camera_entity = commands
+        .spawn((
+            Camera2d,
+            Camera {
+                // drawn over the 3D preview, which it keeps
+                order: 1,
+                clear_color: ClearColorConfig::None,
+                is_active: false,
+                ..default()
+            },
+        ))
+        .id();
+    let ui_material = ui_materials.add(GraphUiMaterial::with_sampler_carriers(&mut images));
+    let ui_entity = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(50.0),
+                top: Val::Percent(50.0),
+                width: Val::Px(PREVIEW_QUAD_SIZE),
+                height: Val::Px(PREVIEW_QUAD_SIZE),
+                margin: UiRect {
+                    left: Val::Px(-PREVIEW_QUAD_SIZE / 2.0),
+                    top: Val::Px(-PREVIEW_QUAD_SIZE / 2.0),
+                    ..default()
+                },
+                ..default()
+            },
+            MaterialNode(ui_material.clone()),
+            UiTargetCamera(camera_entity),
+            Visibility::Hidden,
+            ShaderViewEntity,
+        ))
+        .id();
+    commands.insert_resource(GraphFullscreenMaterial::with_sampler_carriers(&mut images));
+
     // Add lighting for the render-to-texture scene
     commands.spawn((
         DirectionalLight {
@@ -106,6 +184,23 @@ pub fn setup_shader_view(
         shader_handle: default_shader.clone(),
         mesh_entity: Some(sphere_entity),
         camera_entity: Some(camera_entity),
+        sprite_entity: Some(sprite_entity),
+        sprite_camera_entity: Some(sprite_camera_entity),
+        ui_entity: Some(ui_entity),
+        standard_material: material,
+        graph_material: graph_materials.add(GraphPreviewMaterial::new(&mut images)),
+        // The graph overrides the base material's surface; blending lets its Alpha show
+        pbr_material: pbr_materials.add(GraphPbrMaterial {
+            base: StandardMaterial {
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            },
+            extension: GraphPbrExtension::with_sampler_carriers(&mut images),
+        }),
+        sprite_material,
+        ui_material,
+        preview_error: None,
+        last_compiled: None,
     });
 
     info!("Shader view setup complete with 3D sphere and render target");
@@ -0,0 +1,143 @@
+use crate::shader_view::GraphGlobals;
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+
+/// Length of one step of the frame buttons
+pub const FRAME_DURATION: f32 = 1.0 / 60.0;
+
+/// Shortest range the scrub slider covers, in seconds
+const SCRUB_WINDOW: f32 = 10.0;
+
+/// Clock driving `globals.time` in the preview, independent of the app's own `Time`
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct PreviewTime {
+    /// Seconds on the preview clock
+    pub elapsed: f32,
+    /// How far the clock moved on its last update
+    pub delta: f32,
+    /// Playback rate, 1.0 is real time
+    pub speed: f32,
+    pub playing: bool,
+}
+
+impl Default for PreviewTime {
+    fn default() -> Self {
+        Self {
+            elapsed: 0.0,
+            delta: 0.0,
+            speed: 1.0,
+            playing: true,
+        }
+    }
+}
+
+impl PreviewTime {
+    /// Advance by a frame of real time, scaled by `speed`, if playing
+    pub fn advance(&mut self, real_delta: f32) {
+        self.delta = if self.playing {
+            real_delta * self.speed
+        } else {
+            0.0
+        };
+        self.elapsed = (self.elapsed + self.delta).max(0.0);
+    }
+
+    /// Pause and move the clock by whole frames, backwards for negative `frames`
+    pub fn step(&mut self, frames: i32) {
+        self.playing = false;
+        let before = self.elapsed;
+        self.elapsed = (self.elapsed + frames as f32 * FRAME_DURATION).max(0.0);
+        self.delta = (self.elapsed - before).abs();
+    }
+
+    /// Jump to `seconds` without advancing the simulation
+    pub fn scrub(&mut self, seconds: f32) {
+        self.elapsed = seconds.max(0.0);
+        self.delta = 0.0;
+    }
+
+    pub fn toggle_playing(&mut self) {
+        self.playing = !self.playing;
+    }
+
+    /// Upper end of the scrub slider - whole windows of `SCRUB_WINDOW` seconds, so the
+    /// slider keeps its scale while time runs on
+    pub fn scrub_range(&self) -> f32 {
+        ((self.elapsed / SCRUB_WINDOW).floor() + 1.0) * SCRUB_WINDOW
+    }
+
+    pub fn globals(&self) -> GraphGlobals {
+        GraphGlobals {
+            time: self.elapsed,
+            delta_time: self.delta,
+        }
+    }
+}
+
+pub fn advance_preview_time_system(time: Res<Time>, mut preview_time: ResMut<PreviewTime>) {
+    // Leave a paused clock untouched so the material isn't re-uploaded every frame
+    if !preview_time.playing && preview_time.delta == 0.0 {
+        return;
+    }
+    preview_time.advance(time.delta_secs());
+}
+
+/// Play/pause, frame stepping, scrubbing and speed for the preview clock
+pub fn transport_bar_ui_system(
+    mut preview_time: ResMut<PreviewTime>,
+    mut egui_contexts: EguiContexts,
+) {
+    let ctx = egui_contexts.ctx_mut().expect("Failed to get egui context");
+
+    // Edit a copy so only actual interaction flags the clock as changed
+    let mut edited = preview_time.clone();
+
+    egui::Area::new(egui::Id::new("preview_transport"))
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -16.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("⏮").on_hover_text("Previous frame").clicked() {
+                        edited.step(-1);
+                    }
+                    let play_label = if edited.playing { "⏸" } else { "▶" };
+                    if ui
+                        .button(play_label)
+                        .on_hover_text("Play / pause")
+                        .clicked()
+                    {
+                        edited.toggle_playing();
+                    }
+                    if ui.button("⏭").on_hover_text("Next frame").clicked() {
+                        edited.step(1);
+                    }
+
+                    let mut seconds = edited.elapsed;
+                    let range = 0.0..=edited.scrub_range();
+                    let slider = ui.add(
+                        egui::Slider::new(&mut seconds, range)
+                            .suffix(" s")
+                            .fixed_decimals(2),
+                    );
+                    if slider.changed() {
+                        edited.scrub(seconds);
+                    }
+
+                    ui.label("Speed");
+                    ui.add(
+                        egui::DragValue::new(&mut edited.speed)
+                            .range(0.0..=4.0)
+                            .speed(0.05)
+                            .suffix("x"),
+                    );
+                    if ui.button("⟲").on_hover_text("Reset to 0 s").clicked() {
+                        edited.scrub(0.0);
+                    }
+                });
+            });
+        });
+
+    if edited != *preview_time {
+        *preview_time = edited;
+    }
+}
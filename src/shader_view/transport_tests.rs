@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use crate::shader_view::transport::{FRAME_DURATION, PreviewTime};
+
+    #[test]
+    fn test_advance_scales_by_speed() {
+        let mut time = PreviewTime {
+            speed: 2.0,
+            ..Default::default()
+        };
+        time.advance(0.5);
+        assert_eq!(time.elapsed, 1.0);
+        assert_eq!(time.delta, 1.0);
+    }
+
+    #[test]
+    fn test_paused_clock_does_not_move() {
+        let mut time = PreviewTime::default();
+        time.advance(0.5);
+        time.toggle_playing();
+        time.advance(0.5);
+        assert_eq!(time.elapsed, 0.5);
+        assert_eq!(time.delta, 0.0);
+    }
+
+    #[test]
+    fn test_step_pauses_and_moves_one_frame() {
+        let mut time = PreviewTime::default();
+        time.step(2);
+        assert!(!time.playing);
+        assert!((time.elapsed - 2.0 * FRAME_DURATION).abs() < 1e-6);
+
+        time.step(-1);
+        assert!((time.elapsed - FRAME_DURATION).abs() < 1e-6);
+        assert!((time.delta - FRAME_DURATION).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_clock_never_goes_negative() {
+        let mut time = PreviewTime::default();
+        time.step(-5);
+        assert_eq!(time.elapsed, 0.0);
+        time.scrub(-3.0);
+        assert_eq!(time.elapsed, 0.0);
+    }
+
+    #[test]
+    fn test_scrub_keeps_play_state() {
+        let mut time = PreviewTime::default();
+        time.scrub(4.0);
+        assert!(time.playing);
+        assert_eq!(time.elapsed, 4.0);
+        assert_eq!(time.delta, 0.0);
+    }
+
+    #[test]
+    fn test_scrub_range_grows_in_windows() {
+        let mut time = PreviewTime::default();
+        assert_eq!(time.scrub_range(), 10.0);
+        time.scrub(12.5);
+        assert_eq!(time.scrub_range(), 20.0);
+    }
+
+    #[test]
+    fn test_globals_follow_clock() {
+        let mut time = PreviewTime::default();
+        time.advance(0.25);
+        let globals = time.globals();
+        assert_eq!(globals.time, 0.25);
+        assert_eq!(globals.delta_time, 0.25);
+    }
+}
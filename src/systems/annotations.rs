@@ -0,0 +1,139 @@
+use crate::node_graph::annotations::{
+    ANNOTATION_COLORS, DEFAULT_FRAME_SIZE, add_frame, add_note, frame_around, remove_annotation,
+};
+use crate::node_graph::canvas::screen_to_canvas;
+use crate::node_graph::model::NodeGraph;
+use crate::node_graph::ui_state::GraphUiState;
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+
+/// Comment frames and sticky notes: Ctrl+K puts a frame around the selection, or an empty
+/// one mid-screen, and Ctrl+Shift+K drops a note at the cursor. The Comments window edits the
+/// frame or note last clicked: its title or text, its colour, or deleting it.
+pub fn annotations_ui_system(
+    mut node_graph: ResMut<NodeGraph>,
+    mut ui_state: ResMut<GraphUiState>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    mut egui_contexts: EguiContexts,
+) {
+    let ctx = egui_contexts.ctx_mut().expect("Failed to get egui context");
+
+    let mut add_comment = false;
+    let mut add_sticky_note = None;
+    if !ctx.wants_keyboard_input() {
+        let ctrl =
+            key_input.pressed(KeyCode::ControlLeft) || key_input.pressed(KeyCode::ControlRight);
+        let shift = key_input.pressed(KeyCode::ShiftLeft) || key_input.pressed(KeyCode::ShiftRight);
+        if ctrl && key_input.just_pressed(KeyCode::KeyK) {
+            if shift {
+                add_sticky_note = ctx.input(|i| i.pointer.latest_pos());
+            } else {
+                add_comment = true;
+            }
+        }
+    }
+
+    let centre = ctx.viewport_rect().center();
+    let mut delete = false;
+    egui::Window::new("Comments")
+        .default_pos(egui::pos2(16.0, 540.0))
+        .default_open(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let label = if ui_state.selection.is_empty() {
+                    "Add Comment"
+                } else {
+                    "Comment Selection"
+                };
+                add_comment |= ui.button(label).on_hover_text("Ctrl+K").clicked();
+                if ui
+                    .button("Sticky Note")
+                    .on_hover_text("Ctrl+Shift+K")
+                    .clicked()
+                {
+                    add_sticky_note = Some(centre);
+                }
+            });
+
+            let Some(id) = ui_state.active_annotation else {
+                ui.label("Click a comment's title bar or a note to edit it");
+                return;
+            };
+            // Edit copies so the graph only counts as changed when something was
+            let frame = node_graph.frames.iter().find(|frame| frame.id == id);
+            let note = node_graph.notes.iter().find(|note| note.id == id);
+            let (mut text, mut color) = match (frame, note) {
+                (Some(frame), _) => (frame.title.clone(), frame.color),
+                (None, Some(note)) => (note.text.clone(), note.color),
+                (None, None) => return,
+            };
+            let is_note = note.is_some();
+            ui.separator();
+            let edited = if is_note {
+                ui.add(
+                    egui::TextEdit::multiline(&mut text)
+                        .desired_rows(6)
+                        .hint_text("# Heading\n- bullet, **bold**, *italics*, `code`"),
+                )
+            } else {
+                ui.add(egui::TextEdit::singleline(&mut text).hint_text("Title"))
+            };
+            let mut recolored = false;
+            ui.horizontal(|ui| {
+                for rgb in ANNOTATION_COLORS {
+                    let [r, g, b] = rgb;
+                    let (rect, response) =
+                        ui.allocate_exact_size(egui::vec2(18.0, 18.0), egui::Sense::click());
+                    let stroke = if color == rgb {
+                        egui::Stroke::new(2.0, egui::Color32::WHITE)
+                    } else {
+                        egui::Stroke::NONE
+                    };
+                    ui.painter().rect(
+                        rect,
+                        3.0,
+                        egui::Color32::from_rgb(r, g, b),
+                        stroke,
+                        egui::StrokeKind::Inside,
+                    );
+                    if response.clicked() {
+                        color = rgb;
+                        recolored = true;
+                    }
+                }
+            });
+            if edited.changed() || recolored {
+                if let Some(frame) = node_graph.frames.iter_mut().find(|frame| frame.id == id) {
+                    frame.title = text;
+                    frame.color = color;
+                } else if let Some(note) = node_graph.notes.iter_mut().find(|note| note.id == id) {
+                    note.text = text;
+                    note.color = color;
+                }
+            }
+            delete = ui.button("Delete").clicked();
+        });
+
+    if delete && let Some(id) = ui_state.active_annotation.take() {
+        info!("ANNOTATION: Deleted {:?}", id);
+        remove_annotation(&mut node_graph, id);
+    }
+    if add_comment {
+        let rect = frame_around(&node_graph, &ui_state.selection).unwrap_or_else(|| {
+            let centre = screen_to_canvas(Vec2::new(centre.x, centre.y), &node_graph.canvas_state);
+            Rect::from_center_size(centre, DEFAULT_FRAME_SIZE)
+        });
+        let id = add_frame(&mut node_graph, "Comment", rect);
+        info!("ANNOTATION: Added comment frame {:?} at {:?}", id, rect);
+        ui_state.active_annotation = Some(id);
+    }
+    if let Some(screen_pos) = add_sticky_note {
+        let position = screen_to_canvas(
+            Vec2::new(screen_pos.x, screen_pos.y),
+            &node_graph.canvas_state,
+        );
+        let id = add_note(&mut node_graph, position, "");
+        info!("ANNOTATION: Added sticky note {:?} at {:?}", id, position);
+        ui_state.active_annotation = Some(id);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::node_graph::canvas::{CanvasAnimation, CanvasSettings, frame_bounds, nodes_bounds};
+use crate::node_graph::minimap::Minimap;
+use crate::node_graph::model::NodeGraph;
+use crate::node_graph::ui_state::GraphUiState;
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+
+/// Frame All with F and Frame Selection with Shift+F, both animated, and the View window
+/// showing the zoom and its limits, the minimap toggle and grid snapping
+pub fn canvas_view_system(
+    node_graph: Res<NodeGraph>,
+    ui_state: Res<GraphUiState>,
+    mut settings: ResMut<CanvasSettings>,
+    mut animation: ResMut<CanvasAnimation>,
+    mut minimap: ResMut<Minimap>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    mut egui_contexts: EguiContexts,
+) {
+    let ctx = egui_contexts.ctx_mut().expect("Failed to get egui context");
+
+    let mut frame_all = false;
+    let mut frame_selection = false;
+    if !ctx.wants_keyboard_input() && key_input.just_pressed(KeyCode::KeyF) {
+        let shift = key_input.pressed(KeyCode::ShiftLeft) || key_input.pressed(KeyCode::ShiftRight);
+        frame_selection = shift;
+        frame_all = !shift;
+    }
+
+    egui::Window::new("View")
+        .default_pos(egui::pos2(16.0, 420.0))
+        .default_open(false)
+        .show(ctx, |ui| {
+            ui.label(format!("Zoom {:.0}%", node_graph.canvas_state.zoom * 100.0));
+            ui.horizontal(|ui| {
+                frame_all |= ui.button("Frame All").on_hover_text("F").clicked();
+                frame_selection |= ui
+                    .add_enabled(
+                        !ui_state.selection.is_empty(),
+                        egui::Button::new("Frame Selection"),
+                    )
+                    .on_hover_text("Shift+F")
+                    .clicked();
+            });
+            let mut visible = minimap.visible;
+            if ui.checkbox(&mut visible, "Minimap (B)").changed() {
+                minimap.visible = visible;
+            }
+            ui.checkbox(&mut settings.snap_to_grid, "Snap to grid");
+            ui.horizontal(|ui| {
+                ui.label("Zoom limits");
+                let max = settings.max_zoom;
+                ui.add(
+                    egui::DragValue::new(&mut settings.min_zoom)
+                        .speed(0.01)
+                        .range(0.01..=max),
+                );
+                let min = settings.min_zoom;
+                ui.add(
+                    egui::DragValue::new(&mut settings.max_zoom)
+                        .speed(0.01)
+                        .range(min..=16.0),
+                );
+            });
+        });
+
+    let bounds = if frame_selection {
+        nodes_bounds(
+            ui_state
+                .selection
+                .iter()
+                .filter_map(|id| node_graph.nodes.get(id)),
+        )
+    } else if frame_all {
+        nodes_bounds(node_graph.nodes.values())
+    } else {
+        return;
+    };
+    let Some(bounds) = bounds else {
+        return;
+    };
+    let viewport = ctx.viewport_rect();
+    let viewport = Rect::new(
+        viewport.min.x,
+        viewport.min.y,
+        viewport.max.x,
+        viewport.max.y,
+    );
+    let target = frame_bounds(bounds, viewport, &settings);
+    info!(
+        "CANVAS: Framing {:?}, zoom {} offset {:?}",
+        bounds, target.zoom, target.offset
+    );
+    animation.target = Some(target);
+}
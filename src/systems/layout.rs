@@ -0,0 +1,134 @@
+use crate::node_graph::history::{NodeMoves, UndoHistory};
+use crate::node_graph::layout::{LayoutSettings, NodeAnimation, layered_layout};
+use crate::node_graph::model::NodeGraph;
+use crate::node_graph::selection::all_nodes;
+use crate::node_graph::ui_state::GraphUiState;
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+
+enum LayoutAction {
+    /// Every node, or only the selected ones
+    Arrange {
+        selection: bool,
+    },
+    Undo,
+    Redo,
+}
+
+/// Auto layout and undo: Ctrl+L arranges the whole graph in layers from inputs to outputs
+/// and Ctrl+Shift+L only the selected nodes, both animated. Ctrl+Z takes back a layout, a
+/// Selection window arrangement or a node drag and Ctrl+Shift+Z or Ctrl+Y redoes it. The
+/// Layout window has the same commands and the spacing.
+pub fn auto_layout_system(
+    mut node_graph: ResMut<NodeGraph>,
+    ui_state: Res<GraphUiState>,
+    mut settings: ResMut<LayoutSettings>,
+    mut history: ResMut<UndoHistory>,
+    mut animation: ResMut<NodeAnimation>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    mut egui_contexts: EguiContexts,
+) {
+    let ctx = egui_contexts.ctx_mut().expect("Failed to get egui context");
+
+    let mut action = None;
+    if !ctx.wants_keyboard_input() {
+        let ctrl =
+            key_input.pressed(KeyCode::ControlLeft) || key_input.pressed(KeyCode::ControlRight);
+        let shift = key_input.pressed(KeyCode::ShiftLeft) || key_input.pressed(KeyCode::ShiftRight);
+        if ctrl && key_input.just_pressed(KeyCode::KeyL) {
+            action = Some(LayoutAction::Arrange { selection: shift });
+        } else if ctrl && shift && key_input.just_pressed(KeyCode::KeyZ)
+            || ctrl && key_input.just_pressed(KeyCode::KeyY)
+        {
+            action = Some(LayoutAction::Redo);
+        } else if ctrl && key_input.just_pressed(KeyCode::KeyZ) {
+            action = Some(LayoutAction::Undo);
+        }
+    }
+
+    egui::Window::new("Layout")
+        .default_pos(egui::pos2(16.0, 480.0))
+        .default_open(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Arrange All").on_hover_text("Ctrl+L").clicked() {
+                    action = Some(LayoutAction::Arrange { selection: false });
+                }
+                let arrange_selection = ui
+                    .add_enabled(
+                        ui_state.selection.len() > 1,
+                        egui::Button::new("Arrange Selection"),
+                    )
+                    .on_hover_text("Ctrl+Shift+L");
+                if arrange_selection.clicked() {
+                    action = Some(LayoutAction::Arrange { selection: true });
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Layer gap");
+                ui.add(egui::DragValue::new(&mut settings.layer_gap).range(0.0..=1000.0));
+                ui.label("Node gap");
+                ui.add(egui::DragValue::new(&mut settings.node_gap).range(0.0..=1000.0));
+            });
+            ui.horizontal(|ui| {
+                let undo = history.undo_label().map(|label| format!("Undo {}", label));
+                let undo = ui
+                    .add_enabled(
+                        undo.is_some(),
+                        egui::Button::new(undo.unwrap_or_else(|| "Undo".to_string())),
+                    )
+                    .on_hover_text("Ctrl+Z");
+                if undo.clicked() {
+                    action = Some(LayoutAction::Undo);
+                }
+                let redo = history.redo_label().map(|label| format!("Redo {}", label));
+                let redo = ui
+                    .add_enabled(
+                        redo.is_some(),
+                        egui::Button::new(redo.unwrap_or_else(|| "Redo".to_string())),
+                    )
+                    .on_hover_text("Ctrl+Shift+Z");
+                if redo.clicked() {
+                    action = Some(LayoutAction::Redo);
+                }
+            });
+        });
+
+    let Some(action) = action else {
+        return;
+    };
+    // Moves still under way land first, so the step starts from where they were heading
+    animation.finish(&mut node_graph);
+    let targets = match action {
+        LayoutAction::Arrange { selection } => {
+            let nodes = if selection {
+                ui_state.selection.clone()
+            } else {
+                all_nodes(&node_graph)
+            };
+            if nodes.len() < 2 {
+                return;
+            }
+            let targets = layered_layout(&node_graph, &nodes, &settings);
+            let step = NodeMoves::new("Auto Layout", &node_graph, &targets);
+            info!("LAYOUT: Arranging {} nodes", nodes.len());
+            history.record(step);
+            targets
+        }
+        LayoutAction::Undo => match history.undo() {
+            Some(step) => {
+                info!("LAYOUT: Undo {}", step.label);
+                step.before()
+            }
+            None => return,
+        },
+        LayoutAction::Redo => match history.redo() {
+            Some(step) => {
+                info!("LAYOUT: Redo {}", step.label);
+                step.after()
+            }
+            None => return,
+        },
+    };
+    animation.start(targets);
+}
@@ -1 +1,8 @@
+pub mod annotations;
+pub mod canvas_view;
+pub mod layout;
+pub mod node_palette;
+pub mod selection;
 pub mod spawn_node;
+pub mod subgraph;
+pub mod wgsl_library;
@@ -0,0 +1,184 @@
+use crate::node_graph::canvas::screen_to_canvas;
+use crate::node_graph::model::NodeGraph;
+use crate::node_graph::ui_state::{GraphUiState, PaletteRequest};
+use crate::shader_graph::catalog::{NodeTemplate, node_catalog, search};
+use crate::shader_graph::nodes::library::WgslLibrary;
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+
+/// Height of the result list before it scrolls
+const RESULTS_HEIGHT: f32 = 320.0;
+
+/// The open palette, kept between frames
+#[derive(Default)]
+pub struct PaletteState {
+    request: Option<PaletteRequest>,
+    query: String,
+    /// Index into the current results, moved with the arrow keys
+    highlighted: usize,
+    /// Catalog read when the palette opens, so library changes show up the next time
+    templates: Vec<NodeTemplate>,
+    /// Focus the search field on the first frame
+    focus: bool,
+}
+
+/// Node search palette: Space or Tab over the canvas, a right-click on empty canvas, or a wire
+/// dropped on empty canvas opens it at the cursor. Typing filters every node type, the arrow
+/// keys move through the results, Enter or a click adds the node there.
+pub fn node_palette_system(
+    mut node_graph: ResMut<NodeGraph>,
+    mut ui_state: ResMut<GraphUiState>,
+    library: Res<WgslLibrary>,
+    mut egui_contexts: EguiContexts,
+    mut state: Local<PaletteState>,
+) {
+    let ctx = egui_contexts.ctx_mut().expect("Failed to get egui context");
+    let pointer_pos = ctx.input(|i| i.pointer.latest_pos());
+
+    let mut request = ui_state.palette.take();
+    if request.is_none() && state.request.is_none() && !ctx.wants_keyboard_input() {
+        let key = ctx.input_mut(|i| {
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Space)
+                | i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)
+        });
+        if key {
+            // Don't type the space that opened the palette into its search field
+            ctx.input_mut(|i| {
+                i.events
+                    .retain(|event| !matches!(event, egui::Event::Text(text) if text == " "))
+            });
+        }
+        let right_click =
+            ctx.input(|i| i.pointer.secondary_clicked()) && !ctx.is_pointer_over_area();
+        if key || right_click {
+            request = Some(PaletteRequest {
+                screen_pos: pointer_pos.unwrap_or_else(|| ctx.viewport_rect().center()),
+                from_pin: None,
+            });
+        }
+    }
+    if let Some(request) = request {
+        info!("PALETTE: Opened at {:?}", request.screen_pos);
+        *state = PaletteState {
+            request: Some(request),
+            templates: node_catalog(&library),
+            focus: true,
+            ..default()
+        };
+    }
+    let Some(request) = state.request.clone() else {
+        return;
+    };
+
+    let accepts = request
+        .from_pin
+        .and_then(|from_pin| node_graph.pin_type(from_pin));
+    let results = search(&state.templates, &state.query, accepts);
+
+    // The search field keeps focus; the keys that move through the results never reach it
+    let (up, down, enter, escape) = ctx.input_mut(|i| {
+        (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+        )
+    });
+    let moved = up || down;
+    if down && state.highlighted + 1 < results.len() {
+        state.highlighted += 1;
+    }
+    if up {
+        state.highlighted = state.highlighted.saturating_sub(1);
+    }
+    state.highlighted = state.highlighted.min(results.len().saturating_sub(1));
+
+    let mut picked = enter
+        .then(|| results.get(state.highlighted).copied())
+        .flatten();
+    let mut query_changed = false;
+
+    let area = egui::Area::new(egui::Id::new("node_palette"))
+        .order(egui::Order::Foreground)
+        .fixed_pos(request.screen_pos)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_width(260.0);
+                let field = ui.add(
+                    egui::TextEdit::singleline(&mut state.query)
+                        .hint_text("Search nodes")
+                        .desired_width(f32::INFINITY),
+                );
+                if state.focus {
+                    field.request_focus();
+                    state.focus = false;
+                }
+                query_changed = field.changed();
+                if let Some(ty) = accepts {
+                    ui.label(
+                        egui::RichText::new(format!("Nodes taking {:?}", ty))
+                            .small()
+                            .weak(),
+                    );
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(RESULTS_HEIGHT)
+                    .show(ui, |ui| {
+                        let mut category = None;
+                        for (position, &index) in results.iter().enumerate() {
+                            let template = &state.templates[index];
+                            if category != Some(template.category.as_str()) {
+                                category = Some(template.category.as_str());
+                                ui.label(egui::RichText::new(&template.category).small().strong());
+                            }
+                            let highlighted = position == state.highlighted;
+                            let row = ui
+                                .selectable_label(highlighted, &template.title)
+                                .on_hover_text(&template.description);
+                            if highlighted && moved {
+                                row.scroll_to_me(None);
+                            }
+                            if row.clicked() {
+                                picked = Some(index);
+                            }
+                        }
+                        if results.is_empty() {
+                            ui.label("No matching nodes");
+                        }
+                    });
+
+                if let Some(&index) = results.get(state.highlighted) {
+                    ui.separator();
+                    ui.label(
+                        egui::RichText::new(&state.templates[index].description)
+                            .small()
+                            .weak(),
+                    );
+                }
+            });
+        });
+
+    if query_changed {
+        state.highlighted = 0;
+    }
+
+    // Clicking anywhere else closes the palette
+    let clicked_outside = ctx.input(|i| i.pointer.any_pressed())
+        && pointer_pos.is_some_and(|pos| !area.response.rect.contains(pos));
+
+    if let Some(index) = picked {
+        let template = &state.templates[index];
+        let canvas_state = &node_graph.canvas_state;
+        let screen_pos = Vec2::new(request.screen_pos.x, request.screen_pos.y);
+        let position = screen_to_canvas(screen_pos, canvas_state);
+        let node_id = template.spawn(&mut node_graph, position, request.from_pin);
+        info!(
+            "SPAWN: Added {} node {:?} from the palette at {:?}",
+            template.title, node_id, position
+        );
+        state.request = None;
+    } else if escape || clicked_outside {
+        state.request = None;
+    }
+}
@@ -0,0 +1,255 @@
+use crate::node_graph::canvas::screen_to_canvas;
+use crate::node_graph::clipboard::GraphFragment;
+use crate::node_graph::history::{NodeMoves, UndoHistory};
+use crate::node_graph::layout::{LayoutSettings, NodeAnimation};
+use crate::node_graph::model::{NodeGraph, NodeId};
+use crate::node_graph::selection::{
+    Align, Distribute, align_nodes, all_nodes, delete_nodes, distribute_nodes, duplicate_nodes,
+    invert_selection, stack_nodes,
+};
+use crate::node_graph::ui_state::GraphUiState;
+use bevy::prelude::*;
+use bevy_egui::{EguiClipboard, EguiContexts, egui};
+
+/// Something to do with the selection, from a shortcut or a button
+enum SelectionAction {
+    SelectAll,
+    Invert,
+    Delete,
+    Duplicate,
+    Copy,
+    Cut,
+    /// At the cursor for the shortcut, mid-screen for the button
+    Paste {
+        at_cursor: bool,
+    },
+    Align(Align),
+    Distribute(Distribute),
+    /// One column, `LayoutSettings::node_gap` apart
+    Stack,
+}
+
+/// Selection shortcuts and the Selection window: Ctrl+A selects every node, Ctrl+I inverts
+/// the selection, Delete or Backspace removes the selected nodes and Ctrl+D duplicates them in
+/// place. Ctrl+C and Ctrl+X put the selection on the system clipboard as text, Ctrl+V pastes it
+/// at the cursor. The window lines the selection up on an edge or centre, spaces it out
+/// evenly or stacks it into a column; Ctrl+Z takes each of those back.
+#[allow(clippy::too_many_arguments)]
+pub fn selection_ui_system(
+    mut node_graph: ResMut<NodeGraph>,
+    mut ui_state: ResMut<GraphUiState>,
+    layout_settings: Res<LayoutSettings>,
+    mut history: ResMut<UndoHistory>,
+    mut animation: ResMut<NodeAnimation>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    mut clipboard: ResMut<EguiClipboard>,
+    mut egui_contexts: EguiContexts,
+) {
+    let ctx = egui_contexts.ctx_mut().expect("Failed to get egui context");
+
+    // Nodes removed elsewhere, e.g. collapsed into a subgraph, drop out of the selection
+    if ui_state
+        .selection
+        .iter()
+        .any(|id| !node_graph.nodes.contains_key(id))
+    {
+        ui_state
+            .selection
+            .retain(|id| node_graph.nodes.contains_key(id));
+    }
+
+    let mut action = None;
+    if !ctx.wants_keyboard_input() {
+        let ctrl =
+            key_input.pressed(KeyCode::ControlLeft) || key_input.pressed(KeyCode::ControlRight);
+        if ctrl && key_input.just_pressed(KeyCode::KeyA) {
+            action = Some(SelectionAction::SelectAll);
+        } else if ctrl && key_input.just_pressed(KeyCode::KeyI) {
+            action = Some(SelectionAction::Invert);
+        } else if ctrl && key_input.just_pressed(KeyCode::KeyD) {
+            action = Some(SelectionAction::Duplicate);
+        } else if ctrl && key_input.just_pressed(KeyCode::KeyC) {
+            action = Some(SelectionAction::Copy);
+        } else if ctrl && key_input.just_pressed(KeyCode::KeyX) {
+            action = Some(SelectionAction::Cut);
+        } else if ctrl && key_input.just_pressed(KeyCode::KeyV) {
+            action = Some(SelectionAction::Paste { at_cursor: true });
+        } else if key_input.any_just_pressed([KeyCode::Delete, KeyCode::Backspace]) {
+            action = Some(SelectionAction::Delete);
+        }
+    }
+
+    let count = ui_state.selection.len();
+    egui::Window::new("Selection")
+        .default_pos(egui::pos2(16.0, 260.0))
+        .show(ctx, |ui| {
+            ui.label(match count {
+                0 => "Nothing selected".to_string(),
+                1 => "1 node selected".to_string(),
+                n => format!("{} nodes selected", n),
+            });
+            ui.horizontal(|ui| {
+                if ui.button("All").clicked() {
+                    action = Some(SelectionAction::SelectAll);
+                }
+                if ui.button("Invert").clicked() {
+                    action = Some(SelectionAction::Invert);
+                }
+                ui.add_enabled_ui(count > 0, |ui| {
+                    if ui.button("Duplicate").clicked() {
+                        action = Some(SelectionAction::Duplicate);
+                    }
+                    if ui.button("Delete").clicked() {
+                        action = Some(SelectionAction::Delete);
+                    }
+                });
+            });
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(count > 0, |ui| {
+                    if ui.button("Copy").clicked() {
+                        action = Some(SelectionAction::Copy);
+                    }
+                    if ui.button("Cut").clicked() {
+                        action = Some(SelectionAction::Cut);
+                    }
+                });
+                if ui.button("Paste").clicked() {
+                    action = Some(SelectionAction::Paste { at_cursor: false });
+                }
+            });
+            ui.add_enabled_ui(count > 1, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("Align");
+                    for align in Align::ALL {
+                        if ui.small_button(align.label()).clicked() {
+                            action = Some(SelectionAction::Align(align));
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Distribute");
+                    // Evening out the gaps takes a node between the outermost two
+                    ui.add_enabled_ui(count > 2, |ui| {
+                        for distribute in [Distribute::Horizontally, Distribute::Vertically] {
+                            if ui.small_button(distribute.label()).clicked() {
+                                action = Some(SelectionAction::Distribute(distribute));
+                            }
+                        }
+                    });
+                });
+                if ui.small_button("Stack in a Column").clicked() {
+                    action = Some(SelectionAction::Stack);
+                }
+            });
+        });
+
+    let Some(action) = action else {
+        return;
+    };
+    match action {
+        SelectionAction::SelectAll => {
+            ui_state.selection = all_nodes(&node_graph);
+        }
+        SelectionAction::Invert => {
+            ui_state.selection = invert_selection(&node_graph, &ui_state.selection);
+        }
+        SelectionAction::Delete => {
+            if ui_state.selection.is_empty() {
+                return;
+            }
+            info!("SELECT: Deleting {} nodes", ui_state.selection.len());
+            delete_selection(&mut node_graph, &mut ui_state, &mut history, &mut animation);
+        }
+        SelectionAction::Duplicate => {
+            if ui_state.selection.is_empty() {
+                return;
+            }
+            // The copies land on the originals, selected to be dragged off
+            let copies = duplicate_nodes(&mut node_graph, &ui_state.selection, Vec2::ZERO);
+            info!("SPAWN: Duplicated {} nodes as {:?}", copies.len(), copies);
+            ui_state.selection = copies;
+        }
+        SelectionAction::Copy | SelectionAction::Cut => {
+            let fragment = GraphFragment::copy(&node_graph, &ui_state.selection);
+            if fragment.is_empty() {
+                return;
+            }
+            match fragment.to_text() {
+                Ok(text) => {
+                    clipboard.set_text(&text);
+                    info!("CLIPBOARD: Copied {} nodes", fragment.nodes.len());
+                }
+                Err(e) => {
+                    warn!("CLIPBOARD: Could not copy the selection: {}", e);
+                    return;
+                }
+            }
+            if matches!(action, SelectionAction::Cut) {
+                delete_selection(&mut node_graph, &mut ui_state, &mut history, &mut animation);
+            }
+        }
+        SelectionAction::Paste { at_cursor } => {
+            let Some(text) = clipboard.get_text() else {
+                return;
+            };
+            let fragment = match GraphFragment::from_text(&text) {
+                Ok(fragment) => fragment,
+                Err(e) => {
+                    info!("CLIPBOARD: Nothing to paste: {}", e);
+                    return;
+                }
+            };
+            let screen_pos = ctx
+                .input(|i| i.pointer.latest_pos())
+                .filter(|_| at_cursor)
+                .unwrap_or_else(|| ctx.viewport_rect().center());
+            let position = screen_to_canvas(
+                Vec2::new(screen_pos.x, screen_pos.y),
+                &node_graph.canvas_state,
+            );
+            let pasted = fragment.paste(&mut node_graph, position);
+            info!("SPAWN: Pasted {} nodes at {:?}", pasted.len(), position);
+            ui_state.selection = pasted;
+        }
+        SelectionAction::Align(align) => {
+            let before = positions(&node_graph, &ui_state.selection);
+            align_nodes(&mut node_graph, &ui_state.selection, align);
+            history.record(NodeMoves::since("Align", &before, &node_graph));
+        }
+        SelectionAction::Distribute(distribute) => {
+            let before = positions(&node_graph, &ui_state.selection);
+            distribute_nodes(&mut node_graph, &ui_state.selection, distribute);
+            history.record(NodeMoves::since("Distribute", &before, &node_graph));
+        }
+        SelectionAction::Stack => {
+            let before = positions(&node_graph, &ui_state.selection);
+            stack_nodes(
+                &mut node_graph,
+                &ui_state.selection,
+                layout_settings.node_gap,
+            );
+            history.record(NodeMoves::since("Stack", &before, &node_graph));
+        }
+    }
+}
+
+/// Remove the selected nodes, along with their undo steps and moves under way
+fn delete_selection(
+    graph: &mut NodeGraph,
+    ui_state: &mut GraphUiState,
+    history: &mut UndoHistory,
+    animation: &mut NodeAnimation,
+) {
+    delete_nodes(graph, &ui_state.selection);
+    history.forget(&ui_state.selection);
+    animation.forget(&ui_state.selection);
+    ui_state.selection.clear();
+    ui_state.clear_drag_state();
+}
+
+fn positions(graph: &NodeGraph, node_ids: &[NodeId]) -> Vec<(NodeId, Vec2)> {
+    node_ids
+        .iter()
+        .filter_map(|id| Some((*id, graph.nodes.get(id)?.position)))
+        .collect()
+}
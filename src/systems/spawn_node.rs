@@ -1,9 +1,33 @@
-use crate::node_graph::model::NodeGraph;
+use crate::node_graph::model::{Connection, NodeGraph};
 use crate::node_graph::node_factory::{MathOperation, NodeFactory};
+use crate::shader_graph::node_kind::{NodeKind, OutputTarget};
+use crate::shader_graph::nodes::compute::{ComputeNode, WorkgroupSize};
+use crate::shader_graph::nodes::custom::CustomFunction;
+use crate::shader_graph::nodes::gradient::ColorRamp;
+use crate::shader_graph::nodes::logic::{CompareOp, LogicNode};
+use crate::shader_graph::nodes::texture::{SamplerState, TextureNode};
+use crate::shader_graph::types::PinType;
 use bevy::prelude::*;
+use bevy_egui::EguiContexts;
 
 /// Centralized node spawning system - single source of truth for all node creation
-pub fn spawn_test_node_system(mut node_graph: ResMut<NodeGraph>, input: Res<ButtonInput<KeyCode>>) {
+pub fn spawn_test_node_system(
+    mut node_graph: ResMut<NodeGraph>,
+    input: Res<ButtonInput<KeyCode>>,
+    mut egui_contexts: EguiContexts,
+) {
+    // Keys typed into a text field (e.g. a Custom Function's code box) are not shortcuts
+    if egui_contexts
+        .ctx_mut()
+        .is_ok_and(|ctx| ctx.wants_keyboard_input())
+    {
+        return;
+    }
+    // Ctrl+C, Ctrl+V and the other editing shortcuts share these keys
+    if input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
     // Check if 'N' key was pressed - spawn test node
     if input.just_pressed(KeyCode::KeyN) {
         info!("SPAWN: Creating test node");
@@ -81,4 +105,291 @@ pub fn spawn_test_node_system(mut node_graph: ResMut<NodeGraph>, input: Res<Butt
             node_graph.nodes.len()
         );
     }
+
+    // Check if 'R' key was pressed - spawn color ramp node
+    if input.just_pressed(KeyCode::KeyR) {
+        info!("SPAWN: Creating color ramp node");
+
+        let node_id = NodeFactory::get_next_node_id(&node_graph);
+        let next_pin_id = NodeFactory::get_next_pin_id(&node_graph);
+
+        // Spawn in canvas space with offset to prevent stacking
+        let spawn_pos = Vec2::new(
+            (node_id.0 as f32) * 40.0 + 600.0,
+            (node_id.0 as f32) * 20.0 + 100.0,
+        );
+
+        let (node_instance, _next_pin_id) = NodeFactory::create_node(
+            node_id,
+            spawn_pos,
+            NodeKind::ColorRamp(ColorRamp::default()),
+            next_pin_id,
+        );
+
+        info!(
+            "SPAWN: Added color ramp node {:?} at {:?}",
+            node_instance.node_id, node_instance.position
+        );
+        node_graph.add_node(node_instance);
+        info!(
+            "SPAWN: Color ramp node added successfully, total nodes: {}",
+            node_graph.nodes.len()
+        );
+    }
+
+    // Check if 'P' key was pressed - spawn a PBR output node
+    if input.just_pressed(KeyCode::KeyP) {
+        info!("SPAWN: Creating PBR output node");
+
+        let node_id = NodeFactory::get_next_node_id(&node_graph);
+        let next_pin_id = NodeFactory::get_next_pin_id(&node_graph);
+
+        // Spawn in canvas space with offset to prevent stacking
+        let spawn_pos = Vec2::new(
+            (node_id.0 as f32) * 40.0 + 800.0,
+            (node_id.0 as f32) * 20.0 + 200.0,
+        );
+
+        let (node_instance, _next_pin_id) =
+            NodeFactory::create_node(node_id, spawn_pos, NodeKind::PbrOutput, next_pin_id);
+
+        info!(
+            "SPAWN: Added PBR output node {:?} at {:?}",
+            node_instance.node_id, node_instance.position
+        );
+        node_graph.add_node(node_instance);
+        info!(
+            "SPAWN: PBR output node added successfully, total nodes: {}",
+            node_graph.nodes.len()
+        );
+    }
+
+    // Check if 'O' key was pressed - spawn a fragment output node
+    if input.just_pressed(KeyCode::KeyO) {
+        info!("SPAWN: Creating fragment output node");
+
+        let node_id = NodeFactory::get_next_node_id(&node_graph);
+        let next_pin_id = NodeFactory::get_next_pin_id(&node_graph);
+
+        // Spawn in canvas space with offset to prevent stacking
+        let spawn_pos = Vec2::new(
+            (node_id.0 as f32) * 40.0 + 800.0,
+            (node_id.0 as f32) * 20.0 + 400.0,
+        );
+
+        let (node_instance, _next_pin_id) = NodeFactory::create_node(
+            node_id,
+            spawn_pos,
+            NodeKind::FragmentOutput(OutputTarget::default()),
+            next_pin_id,
+        );
+
+        info!(
+            "SPAWN: Added fragment output node {:?} at {:?}",
+            node_instance.node_id, node_instance.position
+        );
+        node_graph.add_node(node_instance);
+        info!(
+            "SPAWN: Fragment output node added successfully, total nodes: {}",
+            node_graph.nodes.len()
+        );
+    }
+
+    // Check if 'V' key was pressed - spawn a vertex output node
+    if input.just_pressed(KeyCode::KeyV) {
+        info!("SPAWN: Creating vertex output node");
+
+        let node_id = NodeFactory::get_next_node_id(&node_graph);
+        let next_pin_id = NodeFactory::get_next_pin_id(&node_graph);
+
+        // Spawn in canvas space with offset to prevent stacking
+        let spawn_pos = Vec2::new((node_id.0 as f32) * 40.0 + 800.0, (node_id.0 as f32) * 20.0);
+
+        let (node_instance, _next_pin_id) =
+            NodeFactory::create_node(node_id, spawn_pos, NodeKind::VertexOutput, next_pin_id);
+
+        info!(
+            "SPAWN: Added vertex output node {:?} at {:?}",
+            node_instance.node_id, node_instance.position
+        );
+        node_graph.add_node(node_instance);
+        info!(
+            "SPAWN: Vertex output node added successfully, total nodes: {}",
+            node_graph.nodes.len()
+        );
+    }
+
+    // Check if 'K' key was pressed - spawn a compute kernel copying buffer 0 into buffer 1
+    if input.just_pressed(KeyCode::KeyK) {
+        info!("SPAWN: Creating compute kernel nodes");
+
+        let output_id = NodeFactory::get_next_node_id(&node_graph);
+        let next_pin_id = NodeFactory::get_next_pin_id(&node_graph);
+
+        // Spawn in canvas space with offset to prevent stacking
+        let spawn_pos = Vec2::new(
+            (output_id.0 as f32) * 40.0 + 200.0,
+            (output_id.0 as f32) * 20.0 + 500.0,
+        );
+
+        let (output, next_pin_id) = NodeFactory::create_node(
+            output_id,
+            spawn_pos,
+            NodeKind::ComputeOutput(WorkgroupSize::default()),
+            next_pin_id,
+        );
+        node_graph.add_node(output);
+
+        let read_id = NodeFactory::get_next_node_id(&node_graph);
+        let (read, next_pin_id) = NodeFactory::create_node(
+            read_id,
+            spawn_pos + Vec2::new(220.0, 0.0),
+            NodeKind::Compute(ComputeNode::ReadBuffer(0)),
+            next_pin_id,
+        );
+        let read_output = read.outputs[0].pin_id;
+        node_graph.add_node(read);
+
+        let write_id = NodeFactory::get_next_node_id(&node_graph);
+        let (write, _next_pin_id) = NodeFactory::create_node(
+            write_id,
+            spawn_pos + Vec2::new(440.0, 0.0),
+            NodeKind::Compute(ComputeNode::WriteBuffer(1)),
+            next_pin_id,
+        );
+        let write_value_input = write.inputs[1].pin_id;
+        node_graph.add_node(write);
+        node_graph.add_connection(Connection {
+            from_pin: read_output,
+            to_pin: write_value_input,
+        });
+
+        info!(
+            "SPAWN: Compute kernel nodes added successfully, total nodes: {}",
+            node_graph.nodes.len()
+        );
+    }
+
+    // Check if 'T' key was pressed - spawn a texture asset and a sampler reading it
+    if input.just_pressed(KeyCode::KeyT) {
+        info!("SPAWN: Creating texture nodes");
+
+        let texture_id = NodeFactory::get_next_node_id(&node_graph);
+        let next_pin_id = NodeFactory::get_next_pin_id(&node_graph);
+
+        // Spawn in canvas space with offset to prevent stacking
+        let spawn_pos = Vec2::new(
+            (texture_id.0 as f32) * 40.0 + 200.0,
+            (texture_id.0 as f32) * 20.0 + 300.0,
+        );
+
+        let (texture, next_pin_id) = NodeFactory::create_node(
+            texture_id,
+            spawn_pos,
+            NodeKind::Texture(TextureNode::default_texture()),
+            next_pin_id,
+        );
+        let texture_output = texture.outputs[0].pin_id;
+        node_graph.add_node(texture);
+
+        let sample_id = NodeFactory::get_next_node_id(&node_graph);
+        let (sample, _next_pin_id) = NodeFactory::create_node(
+            sample_id,
+            spawn_pos + Vec2::new(220.0, 0.0),
+            NodeKind::Texture(TextureNode::Sample(SamplerState::default())),
+            next_pin_id,
+        );
+        let sample_texture_input = sample.inputs[0].pin_id;
+        node_graph.add_node(sample);
+        node_graph.add_connection(Connection {
+            from_pin: texture_output,
+            to_pin: sample_texture_input,
+        });
+
+        info!(
+            "SPAWN: Texture nodes added successfully, total nodes: {}",
+            node_graph.nodes.len()
+        );
+    }
+
+    // Check if 'L' key was pressed - spawn a comparison driving a Select, and a Switch
+    if input.just_pressed(KeyCode::KeyL) {
+        info!("SPAWN: Creating logic nodes");
+
+        let compare_id = NodeFactory::get_next_node_id(&node_graph);
+        let next_pin_id = NodeFactory::get_next_pin_id(&node_graph);
+
+        // Spawn in canvas space with offset to prevent stacking
+        let spawn_pos = Vec2::new(
+            (compare_id.0 as f32) * 40.0 + 200.0,
+            (compare_id.0 as f32) * 20.0 + 700.0,
+        );
+
+        let (compare, next_pin_id) = NodeFactory::create_node(
+            compare_id,
+            spawn_pos,
+            NodeKind::Logic(LogicNode::Compare(CompareOp::Less, PinType::Float)),
+            next_pin_id,
+        );
+        let compare_output = compare.outputs[0].pin_id;
+        node_graph.add_node(compare);
+
+        let select_id = NodeFactory::get_next_node_id(&node_graph);
+        let (select, next_pin_id) = NodeFactory::create_node(
+            select_id,
+            spawn_pos + Vec2::new(220.0, 0.0),
+            NodeKind::Logic(LogicNode::Select(PinType::Float)),
+            next_pin_id,
+        );
+        let select_condition_input = select.inputs[2].pin_id;
+        node_graph.add_node(select);
+        node_graph.add_connection(Connection {
+            from_pin: compare_output,
+            to_pin: select_condition_input,
+        });
+
+        let switch_id = NodeFactory::get_next_node_id(&node_graph);
+        let (switch, _next_pin_id) = NodeFactory::create_node(
+            switch_id,
+            spawn_pos + Vec2::new(0.0, 160.0),
+            NodeKind::Logic(LogicNode::Switch {
+                cases: 3,
+                ty: PinType::Vec3,
+            }),
+            next_pin_id,
+        );
+        node_graph.add_node(switch);
+
+        info!(
+            "SPAWN: Logic nodes added successfully, total nodes: {}",
+            node_graph.nodes.len()
+        );
+    }
+
+    // Check if 'W' key was pressed - spawn a Custom Function node with the default WGSL
+    if input.just_pressed(KeyCode::KeyW) {
+        info!("SPAWN: Creating custom function node");
+
+        let node_id = NodeFactory::get_next_node_id(&node_graph);
+        let next_pin_id = NodeFactory::get_next_pin_id(&node_graph);
+
+        // Spawn in canvas space with offset to prevent stacking
+        let spawn_pos = Vec2::new(
+            (node_id.0 as f32) * 40.0 + 400.0,
+            (node_id.0 as f32) * 20.0 + 300.0,
+        );
+
+        let (node_instance, _next_pin_id) = NodeFactory::create_node(
+            node_id,
+            spawn_pos,
+            NodeKind::Custom(CustomFunction::default()),
+            next_pin_id,
+        );
+
+        info!(
+            "SPAWN: Added custom function node {:?} at {:?}",
+            node_instance.node_id, node_instance.position
+        );
+        node_graph.add_node(node_instance);
+    }
 }
@@ -0,0 +1,192 @@
+use crate::node_graph::history::UndoHistory;
+use crate::node_graph::layout::NodeAnimation;
+use crate::node_graph::model::NodeGraph;
+use crate::node_graph::node_factory::NodeFactory;
+use crate::node_graph::ui_state::GraphUiState;
+use crate::shader_graph::node_kind::NodeKind;
+use crate::shader_graph::nodes::subgraph::{
+    SUBGRAPH_DIR, Subgraph, SubgraphStack, collapse_into_subgraph, load_subgraph_assets,
+};
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use std::path::Path;
+
+/// What the Subgraphs window remembers between frames
+#[derive(Default)]
+pub struct SubgraphPanel {
+    /// Result of the last collapse or save
+    status: Option<Result<String, String>>,
+    /// Saved subgraphs, read when the window first shows them and after each save
+    assets: Option<Vec<(String, Result<Subgraph, String>)>>,
+}
+
+/// Collapse the selection with Ctrl+G, open double-clicked subgraph nodes, and show where in
+/// the nesting the editor is with a breadcrumb back out of every level. Entering or leaving a
+/// level drops the undo steps and node animations, whose node ids mean other nodes there.
+#[allow(clippy::too_many_arguments)]
+pub fn subgraph_ui_system(
+    mut node_graph: ResMut<NodeGraph>,
+    mut stack: ResMut<SubgraphStack>,
+    mut ui_state: ResMut<GraphUiState>,
+    mut history: ResMut<UndoHistory>,
+    mut animation: ResMut<NodeAnimation>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    mut egui_contexts: EguiContexts,
+    mut panel: Local<SubgraphPanel>,
+) {
+    let ctx = egui_contexts.ctx_mut().expect("Failed to get egui context");
+
+    let ctrl = key_input.pressed(KeyCode::ControlLeft) || key_input.pressed(KeyCode::ControlRight);
+    if ctrl && key_input.just_pressed(KeyCode::KeyG) && !ctx.wants_keyboard_input() {
+        let count = ui_state.selection.len();
+        panel.status = Some(
+            match collapse_into_subgraph(&mut node_graph, &ui_state.selection, "Subgraph") {
+                Ok(node_id) => {
+                    info!("SUBGRAPH: Collapsed {} nodes into {:?}", count, node_id);
+                    history.forget(&ui_state.selection);
+                    animation.forget(&ui_state.selection);
+                    ui_state.selection = vec![node_id];
+                    Ok(format!("Collapsed {} nodes", count))
+                }
+                Err(e) => Err(e),
+            },
+        );
+    }
+
+    if let Some(node_id) = ui_state.open_subgraph.take()
+        && stack.enter(&mut node_graph, node_id)
+    {
+        info!("SUBGRAPH: Entered {:?}", node_id);
+        history.clear();
+        animation.targets.clear();
+        ui_state.selection.clear();
+        ui_state.clear_drag_state();
+        ui_state.clear_pending_connection();
+    }
+
+    let mut leave_to: Option<usize> = None;
+    let mut save = false;
+    let mut picked: Option<Subgraph> = None;
+
+    egui::Window::new("Subgraphs")
+        .default_pos(egui::pos2(16.0, 60.0))
+        .show(ctx, |ui| {
+            // Breadcrumbs: the top-level graph, then every open subgraph
+            ui.horizontal_wrapped(|ui| {
+                let depth = stack.levels.len();
+                if depth == 0 {
+                    ui.strong("Graph");
+                } else if ui.link("Graph").clicked() {
+                    leave_to = Some(0);
+                }
+                for (index, name) in stack.breadcrumbs().into_iter().enumerate() {
+                    ui.label("›");
+                    if index + 1 == depth {
+                        ui.strong(name);
+                    } else if ui.link(name).clicked() {
+                        leave_to = Some(index + 1);
+                    }
+                }
+            });
+
+            if let Some(level) = stack.levels.last_mut() {
+                ui.horizontal(|ui| {
+                    ui.label("Name");
+                    ui.text_edit_singleline(&mut level.name);
+                });
+                save = ui.button("Save as asset").clicked();
+            } else {
+                ui.label(
+                    egui::RichText::new("Select nodes, then Ctrl+G collapses them into a subgraph")
+                        .small()
+                        .weak(),
+                );
+            }
+
+            match &panel.status {
+                Some(Ok(message)) => {
+                    ui.label(egui::RichText::new(message).small());
+                }
+                Some(Err(error)) => {
+                    ui.label(
+                        egui::RichText::new(error)
+                            .small()
+                            .color(egui::Color32::from_rgb(230, 120, 90)),
+                    );
+                }
+                None => {}
+            }
+
+            egui::CollapsingHeader::new("Saved subgraphs").show(ui, |ui| {
+                let assets = panel
+                    .assets
+                    .get_or_insert_with(|| load_subgraph_assets(Path::new(SUBGRAPH_DIR)));
+                if assets.is_empty() {
+                    ui.label(format!("Nothing saved in {}", SUBGRAPH_DIR));
+                }
+                for (file, subgraph) in assets.iter() {
+                    match subgraph {
+                        Ok(subgraph) => {
+                            if ui.button(&subgraph.name).on_hover_text(file).clicked() {
+                                picked = Some(subgraph.clone());
+                            }
+                        }
+                        Err(e) => {
+                            ui.label(
+                                egui::RichText::new(format!("{}: {}", file, e))
+                                    .small()
+                                    .color(egui::Color32::from_rgb(230, 120, 90)),
+                            );
+                        }
+                    }
+                }
+                if ui.small_button("Refresh").clicked() {
+                    panel.assets = None;
+                }
+            });
+        });
+
+    if save && let Some(level) = stack.levels.last() {
+        let subgraph = Subgraph::new(&level.name, node_graph.clone());
+        panel.status = Some(match subgraph.save(Path::new(SUBGRAPH_DIR)) {
+            Ok(path) => {
+                info!("SUBGRAPH: Saved {}", path.display());
+                Ok(format!("Saved {}", path.display()))
+            }
+            Err(e) => Err(e),
+        });
+        panel.assets = None;
+    }
+
+    if let Some(depth) = leave_to {
+        stack.exit_to(&mut node_graph, depth);
+        history.clear();
+        animation.targets.clear();
+        ui_state.selection.clear();
+        ui_state.clear_drag_state();
+        ui_state.clear_pending_connection();
+    }
+
+    if let Some(subgraph) = picked {
+        let node_id = NodeFactory::get_next_node_id(&node_graph);
+        let next_pin_id = NodeFactory::get_next_pin_id(&node_graph);
+
+        // Spawn in canvas space with offset to prevent stacking
+        let spawn_pos = Vec2::new(
+            (node_id.0 as f32) * 40.0 + 400.0,
+            (node_id.0 as f32) * 20.0 + 300.0,
+        );
+
+        let (node_instance, _next_pin_id) = NodeFactory::create_node(
+            node_id,
+            spawn_pos,
+            NodeKind::Subgraph(subgraph),
+            next_pin_id,
+        );
+        info!(
+            "SPAWN: Added subgraph node {:?} at {:?}",
+            node_instance.node_id, node_instance.position
+        );
+        node_graph.add_node(node_instance);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::node_graph::model::NodeGraph;
+use crate::node_graph::node_factory::NodeFactory;
+use crate::shader_graph::node_kind::NodeKind;
+use crate::shader_graph::nodes::library::{LibraryFunction, WgslLibrary};
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+
+/// Seconds between checks of the library directory for changed files
+const RESCAN_INTERVAL: f32 = 1.0;
+
+/// Re-read the library directory when a file changes and refresh the Library nodes using it
+pub fn sync_wgsl_library_system(
+    mut library: ResMut<WgslLibrary>,
+    mut node_graph: ResMut<NodeGraph>,
+    time: Res<Time>,
+    mut since_scan: Local<f32>,
+) {
+    *since_scan += time.delta_secs();
+    if *since_scan < RESCAN_INTERVAL {
+        return;
+    }
+    *since_scan = 0.0;
+
+    // Only touch the graph (and so recompile it) when a file actually changed
+    if !library.bypass_change_detection().rescan() {
+        return;
+    }
+    library.set_changed();
+    info!(
+        "WGSL LIBRARY: {} functions, {} files with errors",
+        library.functions.len(),
+        library.errors.len()
+    );
+    for (file, error) in &library.errors {
+        warn!("WGSL LIBRARY: {}: {}", file, error);
+    }
+
+    let refreshed = library.sync_graph(node_graph.bypass_change_detection());
+    if !refreshed.is_empty() {
+        info!("WGSL LIBRARY: refreshed nodes {:?}", refreshed);
+        node_graph.set_changed();
+    }
+}
+
+/// Window listing the library's functions by category; clicking one adds it as a node
+pub fn wgsl_library_ui_system(
+    library: Res<WgslLibrary>,
+    mut node_graph: ResMut<NodeGraph>,
+    mut egui_contexts: EguiContexts,
+) {
+    let ctx = egui_contexts.ctx_mut().expect("Failed to get egui context");
+    let mut picked: Option<LibraryFunction> = None;
+
+    egui::Window::new("WGSL Library")
+        .default_open(false)
+        .default_pos(egui::pos2(16.0, 120.0))
+        .show(ctx, |ui| {
+            ui.label(
+                egui::RichText::new(library.root.display().to_string())
+                    .small()
+                    .weak(),
+            );
+            if library.functions.is_empty() && library.errors.is_empty() {
+                ui.label("No .wgsl files found");
+            }
+            for (category, functions) in library.categories() {
+                egui::CollapsingHeader::new(category)
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        for function in functions {
+                            let mut hover =
+                                format!("{}::{}", function.file, function.signature.name);
+                            if !function.description.is_empty() {
+                                hover = format!("{}\n{}", function.description, hover);
+                            }
+                            if ui.button(&function.title).on_hover_text(hover).clicked() {
+                                picked = Some(function.clone());
+                            }
+                        }
+                    });
+            }
+            for (file, error) in &library.errors {
+                ui.label(
+                    egui::RichText::new(format!("{}: {}", file, error))
+                        .small()
+                        .color(egui::Color32::from_rgb(230, 120, 90)),
+                );
+            }
+        });
+
+    if let Some(function) = picked {
+        let node_id = NodeFactory::get_next_node_id(&node_graph);
+        let next_pin_id = NodeFactory::get_next_pin_id(&node_graph);
+
+        // Spawn in canvas space with offset to prevent stacking
+        let spawn_pos = Vec2::new(
+            (node_id.0 as f32) * 40.0 + 400.0,
+            (node_id.0 as f32) * 20.0 + 300.0,
+        );
+
+        let (node_instance, _next_pin_id) =
+            NodeFactory::create_node(node_id, spawn_pos, NodeKind::Library(function), next_pin_id);
+
+        info!(
+            "SPAWN: Added library node {:?} at {:?}",
+            node_instance.node_id, node_instance.position
+        );
+        node_graph.add_node(node_instance);
+    }
+}